@@ -32,8 +32,7 @@ fn main() -> io::Result<()> {
         println!("Listening on 127.0.0.1:7878");
 
         while let Some(stream) = incoming.next().await {
-            let stream = stream?;
-            let addr = stream.peer_addr()?;
+            let (stream, addr) = stream?;
 
             threadpool
                 .spawn(async move {
@@ -0,0 +1,206 @@
+use std::io;
+use std::pin::Pin;
+use std::task::Context;
+
+use bytes::BytesMut;
+use futures::io::{AsyncRead, AsyncWrite};
+use futures::sink::Sink;
+use futures::stream::Stream;
+use futures::{ready, Poll};
+
+use super::{Decoder, Encoder};
+
+const INITIAL_CAPACITY: usize = 8 * 1024;
+/// Once this many bytes are buffered for writing, the sink flushes before it
+/// accepts another frame.
+const BACKPRESSURE_BOUNDARY: usize = INITIAL_CAPACITY;
+
+/// A unified `Stream` and `Sink` interface to a byte stream, such as a
+/// `TcpStream`, using a codec to decode and encode frames.
+///
+/// Bytes read from the stream are buffered until the codec can decode a frame
+/// from them. Once the stream reaches its end, the remaining bytes are handed
+/// to the codec's [`decode_eof`], and the `Stream` ends after the last frame.
+/// Frames passed to the sink are encoded into a write buffer, which is
+/// written out when the sink is flushed, or once it grows large enough.
+///
+/// This type requires the `bytes` feature.
+///
+/// [`decode_eof`]: trait.Decoder.html#method.decode_eof
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// #![feature(async_await)]
+/// use futures::prelude::*;
+/// use romio::codec::{Framed, LineCodec};
+/// use romio::TcpStream;
+///
+/// # async fn run() -> Result<(), Box<dyn std::error::Error + 'static>> {
+/// let stream = TcpStream::connect(&"127.0.0.1:7878".parse()?).await?;
+/// let mut framed = Framed::new(stream, LineCodec::new());
+///
+/// framed.send("PING".to_string()).await?;
+/// while let Some(line) = framed.next().await {
+///     println!("{}", line?);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct Framed<S, C> {
+    io: S,
+    codec: C,
+    rd: BytesMut,
+    /// Whether `rd` may hold a frame that hasn't been decoded yet.
+    is_readable: bool,
+    /// Whether the end of the stream has been reached.
+    eof: bool,
+    wr: BytesMut,
+}
+
+impl<S, C> Framed<S, C> {
+    /// Creates a new `Framed` backed by the given stream and codec.
+    pub fn new(io: S, codec: C) -> Framed<S, C> {
+        Framed {
+            io,
+            codec,
+            rd: BytesMut::with_capacity(INITIAL_CAPACITY),
+            is_readable: false,
+            eof: false,
+            wr: BytesMut::with_capacity(INITIAL_CAPACITY),
+        }
+    }
+
+    /// Returns a reference to the underlying stream.
+    pub fn get_ref(&self) -> &S {
+        &self.io
+    }
+
+    /// Returns a mutable reference to the underlying stream.
+    ///
+    /// Care should be taken not to read from or write to the stream directly,
+    /// as that would interleave with the frames of this `Framed`.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.io
+    }
+
+    /// Returns a reference to the codec.
+    pub fn codec(&self) -> &C {
+        &self.codec
+    }
+
+    /// Returns a mutable reference to the codec.
+    pub fn codec_mut(&mut self) -> &mut C {
+        &mut self.codec
+    }
+
+    /// Consumes the `Framed`, returning the underlying stream and the codec.
+    ///
+    /// Bytes that were read but not yet decoded, and frames that were encoded
+    /// but not yet flushed, are lost.
+    pub fn into_inner(self) -> (S, C) {
+        (self.io, self.codec)
+    }
+}
+
+impl<S, C> Stream for Framed<S, C>
+where
+    S: AsyncRead + Unpin,
+    C: Decoder + Unpin,
+{
+    type Item = Result<C::Item, C::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            if this.is_readable {
+                if this.eof {
+                    // Whatever the codec leaves behind can never be decoded.
+                    let frame = this.codec.decode_eof(&mut this.rd);
+                    match frame {
+                        Ok(Some(_)) => {}
+                        _ => this.rd.clear(),
+                    }
+                    return Poll::Ready(frame.transpose());
+                }
+
+                if let Some(frame) = this.codec.decode(&mut this.rd)? {
+                    return Poll::Ready(Some(Ok(frame)));
+                }
+                this.is_readable = false;
+            }
+
+            // Read into zeroed spare room, which is given back unless the read
+            // fills it.
+            let len = this.rd.len();
+            this.rd.resize(len + INITIAL_CAPACITY, 0);
+
+            let n = match Pin::new(&mut this.io).poll_read(cx, &mut this.rd[len..]) {
+                Poll::Ready(Ok(n)) => n,
+                Poll::Ready(Err(e)) => {
+                    this.rd.truncate(len);
+                    return Poll::Ready(Some(Err(e.into())));
+                }
+                Poll::Pending => {
+                    this.rd.truncate(len);
+                    return Poll::Pending;
+                }
+            };
+            this.rd.truncate(len + n);
+
+            if n == 0 {
+                this.eof = true;
+            }
+            this.is_readable = true;
+        }
+    }
+}
+
+impl<S, C> Sink<C::Item> for Framed<S, C>
+where
+    S: AsyncWrite + Unpin,
+    C: Encoder + Unpin,
+{
+    type Error = C::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), C::Error>> {
+        if self.wr.len() >= BACKPRESSURE_BOUNDARY {
+            self.poll_flush(cx)
+        } else {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: C::Item) -> Result<(), C::Error> {
+        let this = self.get_mut();
+        this.codec.encode(item, &mut this.wr)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), C::Error>> {
+        let this = self.get_mut();
+
+        while !this.wr.is_empty() {
+            let n = ready!(Pin::new(&mut this.io).poll_write(cx, &this.wr))?;
+            if n == 0 {
+                return Poll::Ready(Err(io::Error::new(
+                    io::ErrorKind::WriteZero,
+                    "failed to write frame to stream",
+                )
+                .into()));
+            }
+            this.wr.advance(n);
+        }
+
+        ready!(Pin::new(&mut this.io).poll_flush(cx))?;
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), C::Error>> {
+        ready!(self.as_mut().poll_flush(cx))?;
+        ready!(Pin::new(&mut self.io).poll_close(cx))?;
+        Poll::Ready(Ok(()))
+    }
+}
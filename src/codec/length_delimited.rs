@@ -0,0 +1,282 @@
+use std::io;
+
+use bytes::{BigEndian, BufMut, ByteOrder, Bytes, BytesMut, LittleEndian};
+
+use super::{Decoder, Encoder};
+
+const DEFAULT_MAX_FRAME_LENGTH: usize = 8 * 1024 * 1024;
+
+/// A codec for frames prefixed with their length.
+///
+/// By default every frame starts with a 4-byte big-endian length field,
+/// followed by as many bytes of payload. Frames are decoded into their
+/// payload, and payloads are encoded with the header prepended. The size,
+/// byte order and position of the length field can be changed through a
+/// [`LengthDelimitedCodecBuilder`].
+///
+/// Frames with a payload longer than the maximum frame length fail to decode
+/// or encode with an `InvalidData` or `InvalidInput` error respectively, so a
+/// peer can't make the decoder allocate arbitrary amounts of memory. The
+/// maximum is 8 MiB by default.
+///
+/// This type requires the `bytes` feature.
+///
+/// [`LengthDelimitedCodecBuilder`]: struct.LengthDelimitedCodecBuilder.html
+///
+/// # Examples
+///
+/// ```rust
+/// use bytes::{Bytes, BytesMut};
+/// use romio::codec::{Decoder, Encoder, LengthDelimitedCodec};
+///
+/// # fn main() -> std::io::Result<()> {
+/// let mut codec = LengthDelimitedCodec::new();
+/// let mut buf = BytesMut::new();
+///
+/// codec.encode(Bytes::from_static(b"Mercutio"), &mut buf)?;
+/// assert_eq!(&buf[..], b"\0\0\0\x08Mercutio");
+///
+/// let frame = codec.decode(&mut buf)?.unwrap();
+/// assert_eq!(&frame[..], b"Mercutio");
+/// # Ok(()) }
+/// ```
+#[derive(Clone, Debug)]
+pub struct LengthDelimitedCodec {
+    builder: LengthDelimitedCodecBuilder,
+    /// The payload length of the frame being decoded, once its header has
+    /// been read.
+    frame_length: Option<usize>,
+}
+
+impl LengthDelimitedCodec {
+    /// Creates a new `LengthDelimitedCodec` with a 4-byte big-endian length
+    /// field and a maximum frame length of 8 MiB.
+    pub fn new() -> LengthDelimitedCodec {
+        LengthDelimitedCodec::builder().new_codec()
+    }
+
+    /// Creates a new builder, for configuring the header of a
+    /// `LengthDelimitedCodec`.
+    pub fn builder() -> LengthDelimitedCodecBuilder {
+        LengthDelimitedCodecBuilder::new()
+    }
+
+    /// Returns the maximum payload length of a frame.
+    pub fn max_frame_length(&self) -> usize {
+        self.builder.max_frame_length
+    }
+
+    /// Sets the maximum payload length of a frame.
+    pub fn set_max_frame_length(&mut self, max_frame_length: usize) {
+        self.builder.max_frame_length = max_frame_length;
+    }
+
+    fn decode_head(&mut self, src: &mut BytesMut) -> io::Result<Option<usize>> {
+        let builder = &self.builder;
+        let header_len = builder.header_len();
+        if src.len() < header_len {
+            return Ok(None);
+        }
+
+        let field = &src[builder.length_field_offset..header_len];
+        let len = if builder.big_endian {
+            BigEndian::read_uint(field, field.len())
+        } else {
+            LittleEndian::read_uint(field, field.len())
+        };
+        if len > builder.max_frame_length as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "frame exceeds the maximum length",
+            ));
+        }
+        let len = len as usize;
+        src.advance(header_len);
+
+        // Make room for the rest of the frame right away.
+        src.reserve(len);
+        Ok(Some(len))
+    }
+}
+
+impl Default for LengthDelimitedCodec {
+    fn default() -> LengthDelimitedCodec {
+        LengthDelimitedCodec::new()
+    }
+}
+
+impl Decoder for LengthDelimitedCodec {
+    type Item = BytesMut;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<BytesMut>> {
+        let len = match self.frame_length {
+            Some(len) => len,
+            None => match self.decode_head(src)? {
+                Some(len) => len,
+                None => return Ok(None),
+            },
+        };
+
+        if src.len() < len {
+            self.frame_length = Some(len);
+            return Ok(None);
+        }
+
+        self.frame_length = None;
+        Ok(Some(src.split_to(len)))
+    }
+}
+
+impl Encoder for LengthDelimitedCodec {
+    type Item = Bytes;
+    type Error = io::Error;
+
+    fn encode(&mut self, data: Bytes, dst: &mut BytesMut) -> io::Result<()> {
+        let builder = &self.builder;
+        let len = data.len();
+        if len > builder.max_frame_length || len as u64 > builder.max_field_value() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "frame exceeds the maximum length",
+            ));
+        }
+
+        dst.reserve(builder.header_len() + len);
+        for _ in 0..builder.length_field_offset {
+            dst.put_u8(0);
+        }
+        if builder.big_endian {
+            dst.put_uint_be(len as u64, builder.length_field_length);
+        } else {
+            dst.put_uint_le(len as u64, builder.length_field_length);
+        }
+        dst.put(data);
+        Ok(())
+    }
+}
+
+/// A builder for a `LengthDelimitedCodec`, for configuring the header that
+/// precedes every frame.
+///
+/// The header consists of `length_field_offset` bytes which the codec doesn't
+/// interpret, followed by the length field. The whole header is stripped from
+/// decoded frames. When encoding, the bytes before the length field are
+/// written as zeroes.
+///
+/// # Examples
+///
+/// A codec for frames with a 1-byte tag and a 2-byte little-endian length:
+///
+/// ```rust
+/// use bytes::{Bytes, BytesMut};
+/// use romio::codec::{Decoder, Encoder, LengthDelimitedCodec};
+///
+/// # fn main() -> std::io::Result<()> {
+/// let mut codec = LengthDelimitedCodec::builder()
+///     .length_field_offset(1)
+///     .length_field_length(2)
+///     .little_endian()
+///     .new_codec();
+///
+/// let mut buf = BytesMut::from(&b"\x07\x05\0Romeo"[..]);
+/// assert_eq!(&codec.decode(&mut buf)?.unwrap()[..], b"Romeo");
+///
+/// codec.encode(Bytes::from_static(b"Juliet"), &mut buf)?;
+/// assert_eq!(&buf[..], b"\0\x06\0Juliet");
+/// # Ok(()) }
+/// ```
+#[derive(Clone, Debug)]
+pub struct LengthDelimitedCodecBuilder {
+    length_field_offset: usize,
+    length_field_length: usize,
+    big_endian: bool,
+    max_frame_length: usize,
+}
+
+impl LengthDelimitedCodecBuilder {
+    /// Creates a new builder for a 4-byte big-endian length field at the
+    /// start of the frame, and a maximum frame length of 8 MiB.
+    pub fn new() -> LengthDelimitedCodecBuilder {
+        LengthDelimitedCodecBuilder {
+            length_field_offset: 0,
+            length_field_length: 4,
+            big_endian: true,
+            max_frame_length: DEFAULT_MAX_FRAME_LENGTH,
+        }
+    }
+
+    /// Sets the number of bytes in the header before the length field.
+    ///
+    /// Defaults to 0.
+    pub fn length_field_offset(&mut self, offset: usize) -> &mut LengthDelimitedCodecBuilder {
+        self.length_field_offset = offset;
+        self
+    }
+
+    /// Sets the size of the length field in bytes.
+    ///
+    /// Defaults to 4.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `length` is not 1, 2, 4 or 8.
+    pub fn length_field_length(&mut self, length: usize) -> &mut LengthDelimitedCodecBuilder {
+        assert!(
+            [1, 2, 4, 8].contains(&length),
+            "length field must be 1, 2, 4 or 8 bytes"
+        );
+        self.length_field_length = length;
+        self
+    }
+
+    /// Reads and writes the length field in big-endian byte order.
+    ///
+    /// This is the default.
+    pub fn big_endian(&mut self) -> &mut LengthDelimitedCodecBuilder {
+        self.big_endian = true;
+        self
+    }
+
+    /// Reads and writes the length field in little-endian byte order.
+    pub fn little_endian(&mut self) -> &mut LengthDelimitedCodecBuilder {
+        self.big_endian = false;
+        self
+    }
+
+    /// Sets the maximum payload length of a frame.
+    ///
+    /// Defaults to 8 MiB.
+    pub fn max_frame_length(
+        &mut self,
+        max_frame_length: usize,
+    ) -> &mut LengthDelimitedCodecBuilder {
+        self.max_frame_length = max_frame_length;
+        self
+    }
+
+    /// Creates a new `LengthDelimitedCodec` with the configured options.
+    pub fn new_codec(&self) -> LengthDelimitedCodec {
+        LengthDelimitedCodec {
+            builder: self.clone(),
+            frame_length: None,
+        }
+    }
+
+    fn header_len(&self) -> usize {
+        self.length_field_offset + self.length_field_length
+    }
+
+    fn max_field_value(&self) -> u64 {
+        match self.length_field_length {
+            8 => u64::MAX,
+            n => (1 << (n * 8)) - 1,
+        }
+    }
+}
+
+impl Default for LengthDelimitedCodecBuilder {
+    fn default() -> LengthDelimitedCodecBuilder {
+        LengthDelimitedCodecBuilder::new()
+    }
+}
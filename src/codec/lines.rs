@@ -0,0 +1,168 @@
+use std::io;
+
+use bytes::{BufMut, BytesMut};
+
+use super::{Decoder, Encoder};
+
+const DEFAULT_MAX_LENGTH: usize = 64 * 1024;
+
+/// A codec for newline-delimited text.
+///
+/// Lines are decoded into `String`s without their terminating `\n`, or
+/// `\r\n`, and encoded with a `\n` appended. A line which is not valid UTF-8,
+/// or which is longer than the maximum length, fails to decode with an
+/// `InvalidData` error. The decoder skips over the rest of such a line, and
+/// can be used again afterwards.
+///
+/// The maximum length is 64 KiB by default, which bounds how much memory a
+/// peer that never sends a newline can make the decoder buffer.
+///
+/// This type requires the `bytes` feature.
+///
+/// # Examples
+///
+/// ```rust
+/// use bytes::BytesMut;
+/// use romio::codec::{Decoder, LineCodec};
+///
+/// # fn main() -> std::io::Result<()> {
+/// let mut codec = LineCodec::new();
+/// let mut buf = BytesMut::from(&b"Romeo\r\nJuliet\nTyb"[..]);
+///
+/// assert_eq!(codec.decode(&mut buf)?, Some("Romeo".to_string()));
+/// assert_eq!(codec.decode(&mut buf)?, Some("Juliet".to_string()));
+/// assert_eq!(codec.decode(&mut buf)?, None);
+/// assert_eq!(codec.decode_eof(&mut buf)?, Some("Tyb".to_string()));
+/// # Ok(()) }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LineCodec {
+    max_length: usize,
+    /// How far the buffer has been searched for a newline already.
+    next_index: usize,
+    /// Whether the rest of a line that is too long is being dropped.
+    discarding: bool,
+}
+
+impl LineCodec {
+    /// Creates a new `LineCodec` with a maximum line length of 64 KiB.
+    pub fn new() -> LineCodec {
+        LineCodec::with_max_length(DEFAULT_MAX_LENGTH)
+    }
+
+    /// Creates a new `LineCodec` with a maximum line length of `max_length`
+    /// bytes, not counting the line terminator.
+    pub fn with_max_length(max_length: usize) -> LineCodec {
+        LineCodec {
+            max_length,
+            next_index: 0,
+            discarding: false,
+        }
+    }
+
+    /// Returns the maximum line length.
+    pub fn max_length(&self) -> usize {
+        self.max_length
+    }
+
+    fn decode_line(&mut self, mut line: BytesMut) -> io::Result<String> {
+        self.next_index = 0;
+        if line.ends_with(b"\n") {
+            line.truncate(line.len() - 1);
+            if line.ends_with(b"\r") {
+                line.truncate(line.len() - 1);
+            }
+        }
+        if line.len() > self.max_length {
+            return Err(line_too_long());
+        }
+        String::from_utf8(line.to_vec())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "line is not valid UTF-8"))
+    }
+}
+
+impl Default for LineCodec {
+    fn default() -> LineCodec {
+        LineCodec::new()
+    }
+}
+
+impl Decoder for LineCodec {
+    type Item = String;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<String>> {
+        loop {
+            let newline = src[self.next_index..]
+                .iter()
+                .position(|&b| b == b'\n')
+                .map(|i| self.next_index + i);
+
+            if self.discarding {
+                self.next_index = 0;
+                match newline {
+                    Some(end) => {
+                        src.advance(end + 1);
+                        self.discarding = false;
+                        continue;
+                    }
+                    None => {
+                        src.clear();
+                        return Ok(None);
+                    }
+                }
+            }
+
+            return match newline {
+                Some(end) => {
+                    let line = src.split_to(end + 1);
+                    self.decode_line(line).map(Some)
+                }
+                None => {
+                    // A terminating `\r` doesn't count towards the length.
+                    let len = src.len() - src.ends_with(b"\r") as usize;
+                    if len > self.max_length {
+                        // The rest of the line is dropped as it arrives.
+                        self.discarding = true;
+                        self.next_index = 0;
+                        src.clear();
+                        Err(line_too_long())
+                    } else {
+                        self.next_index = src.len();
+                        Ok(None)
+                    }
+                }
+            };
+        }
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> io::Result<Option<String>> {
+        match self.decode(buf)? {
+            Some(line) => Ok(Some(line)),
+            None if buf.is_empty() => Ok(None),
+            None => {
+                let len = buf.len();
+                self.decode_line(buf.split_to(len)).map(Some)
+            }
+        }
+    }
+}
+
+impl Encoder for LineCodec {
+    type Item = String;
+    type Error = io::Error;
+
+    fn encode(&mut self, line: String, dst: &mut BytesMut) -> io::Result<()> {
+        dst.reserve(line.len() + 1);
+        dst.put(line.as_bytes());
+        dst.put_u8(b'\n');
+        Ok(())
+    }
+}
+
+fn line_too_long() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        "line exceeds the maximum length",
+    )
+}
@@ -0,0 +1,133 @@
+//! Traits for encoding and decoding frames.
+//!
+//! A codec turns a stream of bytes into discrete frames and back, keeping the
+//! framing rules of a protocol separate from the I/O that carries it. The
+//! [`Decoder`] and [`Encoder`] traits follow the same shape as the codec traits
+//! found elsewhere in the async ecosystem, so existing codecs port over with
+//! little more than a change of imports.
+//!
+//! Codecs are driven by adapters such as [`Framed`] for byte streams and
+//! [`UdpFramed`] for datagrams, which read into and write out of a `BytesMut`
+//! buffer on the codec's behalf. Two codecs for common framing rules are
+//! included: [`LineCodec`] for newline-delimited text, and
+//! [`LengthDelimitedCodec`] for frames with a length prefix.
+//!
+//! This module requires the `bytes` feature.
+//!
+//! [`Decoder`]: trait.Decoder.html
+//! [`Encoder`]: trait.Encoder.html
+//! [`Framed`]: struct.Framed.html
+//! [`LengthDelimitedCodec`]: struct.LengthDelimitedCodec.html
+//! [`LineCodec`]: struct.LineCodec.html
+//! [`UdpFramed`]: ../udp/struct.UdpFramed.html
+
+use std::io;
+
+use bytes::BytesMut;
+
+mod framed;
+mod length_delimited;
+mod lines;
+
+pub use self::framed::Framed;
+pub use self::length_delimited::{LengthDelimitedCodec, LengthDelimitedCodecBuilder};
+pub use self::lines::LineCodec;
+
+/// Decoding of frames from a buffer of bytes.
+pub trait Decoder {
+    /// The type of decoded frames.
+    type Item;
+
+    /// The type of unrecoverable decoding errors.
+    ///
+    /// Errors from the underlying I/O are converted into this type, so it
+    /// must implement `From<io::Error>`.
+    type Error: From<io::Error>;
+
+    /// Attempts to decode a frame from the provided buffer of bytes.
+    ///
+    /// If the buffer holds a complete frame, the bytes making it up should be
+    /// removed from `src` and the frame returned as `Ok(Some(frame))`. If more
+    /// bytes are needed, `Ok(None)` should be returned and the buffer left
+    /// untouched.
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error>;
+
+    /// Attempts to decode a frame from the provided buffer of bytes, when no
+    /// more bytes will follow.
+    ///
+    /// This is called when the input ends, or, for datagram transports, once
+    /// for every datagram received. By default it calls [`decode`], and fails
+    /// if the bytes left over don't form a complete frame.
+    ///
+    /// [`decode`]: #tymethod.decode
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        match self.decode(buf)? {
+            Some(frame) => Ok(Some(frame)),
+            None if buf.is_empty() => Ok(None),
+            None => {
+                Err(io::Error::new(io::ErrorKind::InvalidData, "bytes remaining on stream").into())
+            }
+        }
+    }
+}
+
+/// Encoding of frames into a buffer of bytes.
+pub trait Encoder {
+    /// The type of frames to be encoded.
+    type Item;
+
+    /// The type of encoding errors.
+    ///
+    /// Errors from the underlying I/O are converted into this type, so it
+    /// must implement `From<io::Error>`.
+    type Error: From<io::Error>;
+
+    /// Encodes a frame into the provided buffer of bytes.
+    ///
+    /// The encoded bytes should be appended to `dst`.
+    fn encode(&mut self, item: Self::Item, dst: &mut BytesMut) -> Result<(), Self::Error>;
+}
+
+impl<D: Decoder + ?Sized> Decoder for &mut D {
+    type Item = D::Item;
+    type Error = D::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        (**self).decode(src)
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        (**self).decode_eof(buf)
+    }
+}
+
+impl<D: Decoder + ?Sized> Decoder for Box<D> {
+    type Item = D::Item;
+    type Error = D::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        (**self).decode(src)
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        (**self).decode_eof(buf)
+    }
+}
+
+impl<E: Encoder + ?Sized> Encoder for &mut E {
+    type Item = E::Item;
+    type Error = E::Error;
+
+    fn encode(&mut self, item: Self::Item, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        (**self).encode(item, dst)
+    }
+}
+
+impl<E: Encoder + ?Sized> Encoder for Box<E> {
+    type Item = E::Item;
+    type Error = E::Error;
+
+    fn encode(&mut self, item: Self::Item, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        (**self).encode(item, dst)
+    }
+}
@@ -0,0 +1,173 @@
+//! Runtime detection of optional kernel features.
+//!
+//! Some socket options are only available on recent kernels, and setting them
+//! elsewhere fails with `ENOPROTOOPT` or `EINVAL`. The functions in this module
+//! probe the running kernel once, by trying the option on a throwaway socket,
+//! and cache the result for the lifetime of the process.
+//!
+//! All probes report `false` on platforms other than Linux.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use romio::features;
+//!
+//! if features::supports_tcp_fastopen() {
+//!     println!("TCP Fast Open is available");
+//! }
+//!
+//! println!("{:#?}", features::Features::all());
+//! ```
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
+
+/// The capabilities of the running kernel, as reported by the probes in this
+/// module.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Features {
+    /// Whether `TCP_FASTOPEN` is supported, see [`supports_tcp_fastopen`].
+    ///
+    /// [`supports_tcp_fastopen`]: fn.supports_tcp_fastopen.html
+    pub tcp_fastopen: bool,
+    /// Whether Multipath TCP is supported, see [`supports_mptcp`].
+    ///
+    /// [`supports_mptcp`]: fn.supports_mptcp.html
+    pub mptcp: bool,
+    /// Whether `UDP_SEGMENT` is supported, see [`supports_udp_gso`].
+    ///
+    /// [`supports_udp_gso`]: fn.supports_udp_gso.html
+    pub udp_gso: bool,
+    /// Whether `SOF_TIMESTAMPING_OPT_ID` is supported, see
+    /// [`supports_timestamping_opt_id`].
+    ///
+    /// [`supports_timestamping_opt_id`]: fn.supports_timestamping_opt_id.html
+    pub timestamping_opt_id: bool,
+}
+
+impl Features {
+    /// Probes all features at once.
+    pub fn all() -> Features {
+        Features {
+            tcp_fastopen: supports_tcp_fastopen(),
+            mptcp: supports_mptcp(),
+            udp_gso: supports_udp_gso(),
+            timestamping_opt_id: supports_timestamping_opt_id(),
+        }
+    }
+}
+
+/// Returns whether the kernel supports TCP Fast Open with the `TCP_FASTOPEN`
+/// option.
+pub fn supports_tcp_fastopen() -> bool {
+    static PROBE: Probe = Probe::new();
+    PROBE.get(sys::tcp_fastopen)
+}
+
+/// Returns whether the kernel supports creating Multipath TCP sockets with
+/// `IPPROTO_MPTCP`.
+pub fn supports_mptcp() -> bool {
+    static PROBE: Probe = Probe::new();
+    PROBE.get(sys::mptcp)
+}
+
+/// Returns whether the kernel supports UDP generic segmentation offload with
+/// the `UDP_SEGMENT` option.
+pub fn supports_udp_gso() -> bool {
+    static PROBE: Probe = Probe::new();
+    PROBE.get(sys::udp_gso)
+}
+
+/// Returns whether the kernel supports the `SOF_TIMESTAMPING_OPT_ID` flag of
+/// the `SO_TIMESTAMPING` option.
+pub fn supports_timestamping_opt_id() -> bool {
+    static PROBE: Probe = Probe::new();
+    PROBE.get(sys::timestamping_opt_id)
+}
+
+/// The cached result of a probe.
+struct Probe {
+    once: Once,
+    supported: AtomicBool,
+}
+
+impl Probe {
+    const fn new() -> Probe {
+        Probe {
+            once: Once::new(),
+            supported: AtomicBool::new(false),
+        }
+    }
+
+    fn get(&self, probe: fn() -> bool) -> bool {
+        self.once
+            .call_once(|| self.supported.store(probe(), Ordering::Relaxed));
+        self.supported.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod sys {
+    use libc::c_int;
+
+    use crate::socket;
+
+    // Not yet exported by libc for all targets.
+    const UDP_SEGMENT: c_int = 103;
+
+    /// Creates a socket and optionally sets an option on it, returning whether
+    /// both succeeded.
+    fn probe(domain: c_int, ty: c_int, proto: c_int, opt: Option<(c_int, c_int, c_int)>) -> bool {
+        unsafe {
+            let fd = libc::socket(domain, ty | libc::SOCK_CLOEXEC, proto);
+            if fd == -1 {
+                return false;
+            }
+            let res = match opt {
+                Some((level, name, val)) => socket::setsockopt(fd, level, name, val).is_ok(),
+                None => true,
+            };
+            libc::close(fd);
+            res
+        }
+    }
+
+    pub(super) fn tcp_fastopen() -> bool {
+        let opt = (libc::IPPROTO_TCP, libc::TCP_FASTOPEN, 5);
+        probe(libc::AF_INET, libc::SOCK_STREAM, 0, Some(opt))
+    }
+
+    pub(super) fn mptcp() -> bool {
+        probe(libc::AF_INET, libc::SOCK_STREAM, libc::IPPROTO_MPTCP, None)
+    }
+
+    pub(super) fn udp_gso() -> bool {
+        let opt = (libc::SOL_UDP, UDP_SEGMENT, 1200);
+        probe(libc::AF_INET, libc::SOCK_DGRAM, 0, Some(opt))
+    }
+
+    pub(super) fn timestamping_opt_id() -> bool {
+        let flags = libc::SOF_TIMESTAMPING_SOFTWARE | libc::SOF_TIMESTAMPING_OPT_ID;
+        let opt = (libc::SOL_SOCKET, libc::SO_TIMESTAMPING, flags as c_int);
+        probe(libc::AF_INET, libc::SOCK_DGRAM, 0, Some(opt))
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod sys {
+    pub(super) fn tcp_fastopen() -> bool {
+        false
+    }
+
+    pub(super) fn mptcp() -> bool {
+        false
+    }
+
+    pub(super) fn udp_gso() -> bool {
+        false
+    }
+
+    pub(super) fn timestamping_opt_id() -> bool {
+        false
+    }
+}
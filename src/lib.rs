@@ -33,7 +33,8 @@
 //!
 //!     // accept connections and process them serially
 //!     while let Some(stream) = incoming.next().await {
-//!         say_hello(stream?).await;
+//!         let (stream, _) = stream?;
+//!         say_hello(stream).await;
 //!     }
 //!     Ok(())
 //! }
@@ -59,15 +60,25 @@
 #![deny(missing_docs, missing_debug_implementations)]
 #![cfg_attr(test, deny(warnings))]
 
+#[cfg(feature = "bytes")]
+pub mod codec;
+pub mod features;
+pub mod net;
 pub mod tcp;
+pub mod timer;
 pub mod udp;
 
 #[cfg(unix)]
 pub mod uds;
 
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+pub mod uring;
+
 pub mod raw;
 
 mod reactor;
+#[cfg(unix)]
+mod socket;
 
 #[doc(inline)]
 pub use crate::tcp::{TcpListener, TcpStream};
@@ -0,0 +1,51 @@
+//! Networking utilities.
+//!
+//! This module contains helpers for setting up sockets that need more than a
+//! plain `bind`, such as multicast receivers.
+
+use std::convert::TryFrom;
+use std::io;
+use std::net::Ipv4Addr;
+
+use net2::UdpBuilder;
+
+use crate::udp::UdpSocket;
+
+/// Creates a UDP socket that receives datagrams sent to the multicast `group`
+/// on the given `port`.
+///
+/// The socket is created with `SO_REUSEADDR`, so that several processes on the
+/// same host can join the group, and is bound to `0.0.0.0:port`. Multicast
+/// loopback is enabled, so datagrams sent to the group from this host are
+/// received as well. The group is joined on the interface with address
+/// `iface`, or on the default interface if it is `Ipv4Addr::UNSPECIFIED`.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// #![feature(async_await)]
+/// # use std::error::Error;
+/// use romio::net::join_multicast_group;
+/// use std::net::Ipv4Addr;
+///
+/// # async fn listen() -> Result<(), Box<dyn Error + 'static>> {
+/// let group = Ipv4Addr::new(239, 255, 42, 99);
+/// let mut socket = join_multicast_group(group, 7878, Ipv4Addr::UNSPECIFIED)?;
+///
+/// let mut buf = vec![0; 1024];
+/// let (n, sender) = socket.recv_from(&mut buf).await?;
+/// println!("{} bytes from {}", n, sender);
+/// # Ok(())
+/// # }
+/// ```
+pub fn join_multicast_group(group: Ipv4Addr, port: u16, iface: Ipv4Addr) -> io::Result<UdpSocket> {
+    let socket = UdpBuilder::new_v4()?
+        .reuse_address(true)?
+        .bind((Ipv4Addr::UNSPECIFIED, port))?;
+    socket.set_multicast_loop_v4(true)?;
+    // Join through the romio socket, so that `leave_all_multicast_v4` knows
+    // about the group.
+    let socket = UdpSocket::try_from(socket)?;
+    socket.join_multicast_v4(&group, &iface)?;
+    Ok(socket)
+}
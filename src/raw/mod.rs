@@ -9,3 +9,5 @@ pub use async_ready::*;
 
 mod poll_evented;
 pub use poll_evented::*;
+
+pub use crate::reactor::Handle;
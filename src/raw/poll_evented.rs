@@ -2,6 +2,7 @@
 
 use crate::reactor::platform;
 use crate::reactor::registration::Registration;
+use crate::reactor::Handle;
 
 use futures::io::{AsyncRead, AsyncWrite};
 use futures::{ready, Poll};
@@ -11,8 +12,8 @@ use mio::event::Evented;
 use std::fmt;
 use std::io::{self, Read, Write};
 use std::pin::Pin;
-use std::sync::atomic::AtomicUsize;
-use std::sync::atomic::Ordering::Relaxed;
+use std::sync::atomic::Ordering::{Relaxed, SeqCst};
+use std::sync::atomic::{AtomicBool, AtomicUsize};
 use std::task::Context;
 
 /// Associates an I/O resource that implements the [`std::io::Read`] and/or
@@ -79,6 +80,9 @@ use std::task::Context;
 /// These events are included as part of the read readiness event stream. The
 /// write readiness event stream is only for `Ready::writable()` events.
 ///
+/// The exception is `UnixReady::error()`, which has a readiness stream of its
+/// own, accessed through [`poll_error_ready`] and [`clear_error_ready`].
+///
 /// [`std::io::Read`]: https://doc.rust-lang.org/std/io/trait.Read.html
 /// [`std::io::Write`]: https://doc.rust-lang.org/std/io/trait.Write.html
 /// [`AsyncRead`]: ../io/trait.AsyncRead.html
@@ -86,8 +90,10 @@ use std::task::Context;
 /// [`mio::Evented`]: https://docs.rs/mio/0.6/mio/trait.Evented.html
 /// [`Registration`]: struct.Registration.html
 /// [`TcpListener`]: ../net/struct.TcpListener.html
+/// [`clear_error_ready`]: #method.clear_error_ready
 /// [`clear_read_ready`]: #method.clear_read_ready
 /// [`clear_write_ready`]: #method.clear_write_ready
+/// [`poll_error_ready`]: #method.poll_error_ready
 /// [`poll_read_ready`]: #method.poll_read_ready
 /// [`poll_write_ready`]: #method.poll_write_ready
 pub struct PollEvented<E: Evented> {
@@ -105,6 +111,12 @@ struct Inner {
 
     /// Currently visible write readiness
     write_readiness: AtomicUsize,
+
+    /// Currently visible error readiness
+    error_readiness: AtomicUsize,
+
+    /// Set once the I/O resource has been deregistered
+    deregistered: AtomicBool,
 }
 
 // ===== impl PollEvented =====
@@ -114,13 +126,92 @@ where
     E: Evented,
 {
     /// Creates a new `PollEvented` associated with the default reactor.
+    ///
+    /// The I/O resource is registered for all readiness events, see
+    /// [`new_with_interest`] to only receive some of them.
+    ///
+    /// [`new_with_interest`]: #method.new_with_interest
     pub fn new(io: E) -> PollEvented<E> {
+        PollEvented::with_registration(io, Registration::new())
+    }
+
+    /// Creates a new `PollEvented` associated with the default reactor, which
+    /// only receives readiness events matching `interest`.
+    ///
+    /// This is useful for resources that are only ever read from or written
+    /// to, such as a timer or signal descriptor, to avoid spurious wakeups
+    /// for the other direction. Polling for a readiness that is not part of
+    /// `interest` never completes.
+    ///
+    /// An error is returned if `interest` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use romio::raw::PollEvented;
+    ///
+    /// # fn main() -> std::io::Result<()> {
+    /// let listener = mio::net::TcpListener::bind(&"127.0.0.1:0".parse().unwrap())?;
+    ///
+    /// // a listener is only ever read from
+    /// let io = PollEvented::new_with_interest(listener, mio::Ready::readable())?;
+    /// # Ok(()) }
+    /// ```
+    pub fn new_with_interest(io: E, interest: mio::Ready) -> io::Result<PollEvented<E>> {
+        if interest.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "interest must not be empty",
+            ));
+        }
+        let registration = Registration::new_with_interest(interest);
+        Ok(PollEvented::with_registration(io, registration))
+    }
+
+    /// Creates a new `PollEvented` associated with the reactor `handle` refers
+    /// to.
+    ///
+    /// Unlike the other constructors, this registers the I/O resource with
+    /// the reactor right away, so the handle can be obtained on one thread
+    /// with [`Handle::current`] and used to register resources from another
+    /// thread. An error is returned if the registration fails, for example
+    /// because the reactor is gone.
+    ///
+    /// [`Handle::current`]: struct.Handle.html#method.current
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use romio::raw::{Handle, PollEvented};
+    /// use std::thread;
+    ///
+    /// # fn main() -> std::io::Result<()> {
+    /// let handle = Handle::current();
+    ///
+    /// thread::spawn(move || -> std::io::Result<()> {
+    ///     let listener = mio::net::TcpListener::bind(&"127.0.0.1:0".parse().unwrap())?;
+    ///     let io = PollEvented::new_with_handle(listener, &handle)?;
+    ///     Ok(())
+    /// })
+    /// .join()
+    /// .unwrap()?;
+    /// # Ok(()) }
+    /// ```
+    pub fn new_with_handle(io: E, handle: &Handle) -> io::Result<PollEvented<E>> {
+        let io = PollEvented::with_registration(io, Registration::new());
+        io.inner.registration.register_with(io.get_ref(), handle)?;
+        Ok(io)
+    }
+
+    fn with_registration(io: E, registration: Registration) -> PollEvented<E> {
         PollEvented {
             io: Some(io),
             inner: Inner {
-                registration: Registration::new(),
+                registration,
                 read_readiness: AtomicUsize::new(0),
                 write_readiness: AtomicUsize::new(0),
+                error_readiness: AtomicUsize::new(0),
+                deregistered: AtomicBool::new(false),
             },
         }
     }
@@ -137,20 +228,96 @@ where
         self.io.as_mut().unwrap()
     }
 
-    // TODO: restore this once we make reactor::poll_evented public
-    // /// Consumes self, returning the inner I/O object
-    // ///
-    // /// This function will deregister the I/O resource from the reactor before
-    // /// returning. If the deregistration operation fails, an error is returned.
-    // ///
-    // /// Note that deregistering does not guarantee that the I/O resource can be
-    // /// registered with a different reactor. Some I/O resource types can only be
-    // /// associated with a single reactor instance for their lifetime.
-    // pub fn into_inner(mut self) -> io::Result<E> {
-    //     let io = self.io.take().unwrap();
-    //     self.inner.registration.deregister(&io)?;
-    //     Ok(io)
-    // }
+    /// Consumes self, returning the inner I/O object
+    ///
+    /// This function will deregister the I/O resource from the reactor before
+    /// returning. If the deregistration operation fails, an error is returned.
+    ///
+    /// Note that deregistering does not guarantee that the I/O resource can be
+    /// registered with a different reactor. Some I/O resource types can only be
+    /// associated with a single reactor instance for their lifetime.
+    pub fn into_inner(mut self) -> io::Result<E> {
+        self.deregister()?;
+        Ok(self.io.take().unwrap())
+    }
+
+    /// Deregisters the I/O resource from the reactor.
+    ///
+    /// No readiness events are received for the resource afterwards, and
+    /// polling for readiness returns an error. This is useful to stop
+    /// receiving events for a resource before handing it off, for example to
+    /// another thread, while keeping it wrapped. Calling `deregister` again
+    /// does nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #![feature(async_await)]
+    /// use futures::future;
+    /// use romio::raw::PollEvented;
+    ///
+    /// # async fn run() -> std::io::Result<()> {
+    /// let listener = mio::net::TcpListener::bind(&"127.0.0.1:0".parse().unwrap())?;
+    /// let io = PollEvented::new(listener);
+    ///
+    /// io.deregister()?;
+    /// assert!(future::poll_fn(|cx| io.poll_read_ready(cx)).await.is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn deregister(&self) -> io::Result<()> {
+        if self.inner.deregistered.swap(true, SeqCst) {
+            return Ok(());
+        }
+        let res = self.inner.registration.deregister(self.get_ref());
+        if res.is_err() {
+            self.inner.deregistered.store(false, SeqCst);
+        }
+        res
+    }
+
+    /// Changes the readiness events the I/O resource is registered for.
+    ///
+    /// This allows subscribing to readiness events at runtime, for example to
+    /// stop watching for writability once there is nothing left to send, and
+    /// to watch for it again when there is. Polling for a readiness that is
+    /// not part of `interest` doesn't complete until it is registered for
+    /// again.
+    ///
+    /// The resource is registered with the reactor first if it hasn't been
+    /// yet. `opts` is passed on to [`mio::Poll::reregister`]; the readiness
+    /// tracking of `PollEvented` expects `mio::PollOpt::edge()`, which is
+    /// what the resource is initially registered with.
+    ///
+    /// An error is returned if `interest` is empty.
+    ///
+    /// [`mio::Poll::reregister`]: https://docs.rs/mio/0.6/mio/struct.Poll.html#method.reregister
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use romio::raw::PollEvented;
+    ///
+    /// # fn main() -> std::io::Result<()> {
+    /// let socket = mio::net::UdpSocket::bind(&"127.0.0.1:0".parse().unwrap())?;
+    /// let mut io = PollEvented::new_with_interest(socket, mio::Ready::readable())?;
+    ///
+    /// // there is something to send now
+    /// let interest = mio::Ready::readable() | mio::Ready::writable();
+    /// io.reregister(interest, mio::PollOpt::edge())?;
+    /// # Ok(()) }
+    /// ```
+    pub fn reregister(&mut self, interest: mio::Ready, opts: mio::PollOpt) -> io::Result<()> {
+        if interest.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "interest must not be empty",
+            ));
+        }
+        self.register()?;
+        let io = self.io.as_ref().unwrap();
+        self.inner.registration.reregister(io, interest, opts)
+    }
 
     /// Check the I/O resource's read readiness state.
     ///
@@ -166,10 +333,7 @@ where
     /// cleared by calling [`clear_read_ready`].
     ///
     /// [`clear_read_ready`]: #method.clear_read_ready
-    pub fn poll_read_ready(
-        self: Pin<&mut Self>,
-        cx: &mut Context<'_>,
-    ) -> Poll<io::Result<mio::Ready>> {
+    pub fn poll_read_ready(&self, cx: &mut Context<'_>) -> Poll<io::Result<mio::Ready>> {
         self.register()?;
 
         // Load cached & encoded readiness.
@@ -216,7 +380,7 @@ where
     ///
     /// The `mask` argument specifies the readiness bits to clear. This may not
     /// include `writable` or `hup`.
-    pub fn clear_read_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> io::Result<()> {
+    pub fn clear_read_ready(&self, cx: &mut Context<'_>) -> io::Result<()> {
         self.inner
             .read_readiness
             .fetch_and(!mio::Ready::readable().as_usize(), Relaxed);
@@ -229,6 +393,65 @@ where
         Ok(())
     }
 
+    /// Check the I/O resource's priority readiness state.
+    ///
+    /// Priority readiness is signaled through the read readiness stream, for
+    /// example when TCP urgent data is pending on a socket. The HUP bit is
+    /// checked as well, so that a closed resource does not wait forever.
+    ///
+    /// The I/O resource will remain in a priority-ready state until readiness
+    /// is cleared by calling [`clear_priority_ready`]. This leaves the regular
+    /// read readiness untouched.
+    ///
+    /// [`clear_priority_ready`]: #method.clear_priority_ready
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub(crate) fn poll_priority_ready(&self, cx: &mut Context<'_>) -> Poll<io::Result<mio::Ready>> {
+        self.register()?;
+
+        let mut cached = self.inner.read_readiness.load(Relaxed);
+        let mask = platform::priority() | platform::hup();
+
+        let mut ret = mio::Ready::from_usize(cached) & mask;
+
+        if ret.is_empty() {
+            loop {
+                let ready = ready!(self.inner.registration.poll_read_ready(cx)?);
+                cached |= ready.as_usize();
+                self.inner.read_readiness.store(cached, Relaxed);
+
+                ret |= ready & mask;
+
+                if !ret.is_empty() {
+                    return Poll::Ready(Ok(ret));
+                }
+            }
+        } else {
+            if let Some(ready) = self.inner.registration.take_read_ready()? {
+                cached |= ready.as_usize();
+                self.inner.read_readiness.store(cached, Relaxed);
+            }
+
+            Poll::Ready(Ok(mio::Ready::from_usize(cached)))
+        }
+    }
+
+    /// Clears the I/O resource's priority readiness state and registers the
+    /// current task to be notified once a priority readiness event is
+    /// received.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub(crate) fn clear_priority_ready(&self, cx: &mut Context<'_>) -> io::Result<()> {
+        self.inner
+            .read_readiness
+            .fetch_and(!platform::priority().as_usize(), Relaxed);
+
+        if self.poll_priority_ready(cx)?.is_ready() {
+            // Notify the current task
+            cx.waker().wake_by_ref();
+        }
+
+        Ok(())
+    }
+
     /// Check the I/O resource's write readiness state.
     ///
     /// This always checks for writable readiness and also checks for HUP
@@ -299,7 +522,7 @@ where
     /// # Panics
     ///
     /// This function will panic if called from outside of a task context.
-    pub fn clear_write_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> io::Result<()> {
+    pub fn clear_write_ready(&self, cx: &mut Context<'_>) -> io::Result<()> {
         self.inner
             .write_readiness
             .fetch_and(!mio::Ready::writable().as_usize(), Relaxed);
@@ -312,8 +535,196 @@ where
         Ok(())
     }
 
+    /// Check the I/O resource's error readiness state.
+    ///
+    /// On Unix platforms this reports the `error` readiness of
+    /// [`mio::unix::UnixReady`], which is signaled when an error is pending on
+    /// the socket. For example, Linux queues ICMP errors received by a
+    /// connected UDP socket and signals them this way. Error readiness is not
+    /// part of the read readiness stream. On other platforms no error
+    /// readiness is ever signaled, and this never completes.
+    ///
+    /// If the resource has no pending error then `Poll::Pending` is returned
+    /// and the current task is notified once a new event is received.
+    ///
+    /// The I/O resource will remain in an error-ready state until readiness is
+    /// cleared by calling [`clear_error_ready`].
+    ///
+    /// [`mio::unix::UnixReady`]: https://docs.rs/mio/0.6/mio/unix/struct.UnixReady.html
+    /// [`clear_error_ready`]: #method.clear_error_ready
+    pub fn poll_error_ready(&self, cx: &mut Context<'_>) -> Poll<io::Result<mio::Ready>> {
+        self.register()?;
+
+        // Load cached & encoded readiness.
+        let mut cached = self.inner.error_readiness.load(Relaxed);
+        let mask = platform::error();
+
+        // See if the current readiness matches any bits.
+        let mut ret = mio::Ready::from_usize(cached) & mask;
+
+        if ret.is_empty() {
+            // Readiness does not match, consume the registration's readiness
+            // stream. This happens in a loop to ensure that the stream gets
+            // drained.
+            loop {
+                let ready = ready!(self.inner.registration.poll_error_ready(cx)?);
+                cached |= ready.as_usize();
+
+                // Update the cache store
+                self.inner.error_readiness.store(cached, Relaxed);
+
+                ret |= ready & mask;
+
+                if !ret.is_empty() {
+                    return Poll::Ready(Ok(ret));
+                }
+            }
+        } else {
+            // Check what's new with the registration stream. This will not
+            // request to be notified
+            if let Some(ready) = self.inner.registration.take_error_ready()? {
+                cached |= ready.as_usize();
+                self.inner.error_readiness.store(cached, Relaxed);
+            }
+
+            Poll::Ready(Ok(mio::Ready::from_usize(cached)))
+        }
+    }
+
+    /// Clears the I/O resource's error readiness state and registers the
+    /// current task to be notified once an error readiness event is received.
+    ///
+    /// This should be called once the pending error has been consumed, for
+    /// example with `take_error` or by reading the socket's error queue.
+    /// After calling this function, `poll_error_ready` will return `Pending`
+    /// until a new error readiness event has been received.
+    pub fn clear_error_ready(&self, cx: &mut Context<'_>) -> io::Result<()> {
+        self.inner
+            .error_readiness
+            .fetch_and(!platform::error().as_usize(), Relaxed);
+
+        if self.poll_error_ready(cx)?.is_ready() {
+            // Notify the current task
+            cx.waker().wake_by_ref();
+        }
+
+        Ok(())
+    }
+
+    /// Attempts a read-like operation on the I/O resource once it is ready
+    /// for reading.
+    ///
+    /// This polls for read readiness and then calls `f` with the resource. If
+    /// `f` fails with `WouldBlock`, the read readiness is cleared and
+    /// `Poll::Pending` is returned, so the current task is notified once the
+    /// resource becomes readable again. Any other result of `f` is returned
+    /// as is.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use futures::Poll;
+    /// use romio::raw::PollEvented;
+    /// use std::io;
+    /// use std::net::SocketAddr;
+    /// use std::task::Context;
+    ///
+    /// fn poll_recv_from(
+    ///     io: &PollEvented<mio::net::UdpSocket>,
+    ///     cx: &mut Context<'_>,
+    ///     buf: &mut [u8],
+    /// ) -> Poll<io::Result<(usize, SocketAddr)>> {
+    ///     io.read_with(cx, |socket| socket.recv_from(buf))
+    /// }
+    /// ```
+    pub fn read_with<F, R>(&self, cx: &mut Context<'_>, f: F) -> Poll<io::Result<R>>
+    where
+        F: FnOnce(&E) -> io::Result<R>,
+    {
+        ready!(self.poll_read_ready(cx)?);
+
+        match f(self.get_ref()) {
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                self.clear_read_ready(cx)?;
+                Poll::Pending
+            }
+            r => Poll::Ready(r),
+        }
+    }
+
+    /// Attempts a write-like operation on the I/O resource once it is ready
+    /// for writing.
+    ///
+    /// This polls for write readiness and then calls `f` with the resource.
+    /// If `f` fails with `WouldBlock`, the write readiness is cleared and
+    /// `Poll::Pending` is returned, so the current task is notified once the
+    /// resource becomes writable again. Any other result of `f` is returned
+    /// as is.
+    pub fn write_with<F, R>(&self, cx: &mut Context<'_>, f: F) -> Poll<io::Result<R>>
+    where
+        F: FnOnce(&E) -> io::Result<R>,
+    {
+        ready!(self.poll_write_ready(cx)?);
+
+        match f(self.get_ref()) {
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                self.clear_write_ready(cx)?;
+                Poll::Pending
+            }
+            r => Poll::Ready(r),
+        }
+    }
+
+    /// Attempts a read-like operation on the I/O resource without waiting for
+    /// it to become ready.
+    ///
+    /// If `f` fails with `WouldBlock`, the cached read readiness is cleared,
+    /// so that the next `poll_read_ready` waits for a new readiness event.
+    /// Unlike `clear_read_ready`, no task is registered for wakeup.
+    pub(crate) fn try_read_with<F, R>(&self, f: F) -> io::Result<R>
+    where
+        F: FnOnce(&E) -> io::Result<R>,
+    {
+        let res = f(self.get_ref());
+        if let Err(ref e) = res {
+            if e.kind() == io::ErrorKind::WouldBlock {
+                self.inner
+                    .read_readiness
+                    .fetch_and(!mio::Ready::readable().as_usize(), Relaxed);
+            }
+        }
+        res
+    }
+
+    /// Attempts a write-like operation on the I/O resource without waiting
+    /// for it to become ready.
+    ///
+    /// If `f` fails with `WouldBlock`, the cached write readiness is cleared,
+    /// so that the next `poll_write_ready` waits for a new readiness event.
+    /// Unlike `clear_write_ready`, no task is registered for wakeup.
+    pub(crate) fn try_write_with<F, R>(&self, f: F) -> io::Result<R>
+    where
+        F: FnOnce(&E) -> io::Result<R>,
+    {
+        let res = f(self.get_ref());
+        if let Err(ref e) = res {
+            if e.kind() == io::ErrorKind::WouldBlock {
+                self.inner
+                    .write_readiness
+                    .fetch_and(!mio::Ready::writable().as_usize(), Relaxed);
+            }
+        }
+        res
+    }
+
     /// Ensure that the I/O resource is registered with the reactor.
     fn register(&self) -> io::Result<()> {
+        if self.inner.deregistered.load(SeqCst) {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "I/O resource has been deregistered",
+            ));
+        }
         self.inner
             .registration
             .register(self.io.as_ref().unwrap())?;
@@ -399,9 +810,9 @@ impl<E: Evented + fmt::Debug> fmt::Debug for PollEvented<E> {
 
 impl<E: Evented> Drop for PollEvented<E> {
     fn drop(&mut self) {
-        if let Some(io) = self.io.take() {
+        if self.io.is_some() {
             // Ignore errors
-            let _ = self.inner.registration.deregister(&io);
+            let _ = self.deregister();
         }
     }
 }
@@ -49,8 +49,14 @@ struct Reactor {
 ///
 /// By default, most components bind lazily to reactors.
 /// To get this behavior when manually passing a `Handle`, use `default()`.
+///
+/// A `Handle` can be sent to other threads, to register I/O resources there
+/// with the reactor of the thread it was obtained on, see
+/// [`PollEvented::new_with_handle`].
+///
+/// [`PollEvented::new_with_handle`]: struct.PollEvented.html#method.new_with_handle
 #[derive(Clone)]
-struct Handle {
+pub struct Handle {
     inner: Option<HandlePriv>,
 }
 
@@ -94,12 +100,14 @@ struct ScheduledIo {
     readiness: AtomicUsize,
     reader: AtomicWaker,
     writer: AtomicWaker,
+    error: AtomicWaker,
 }
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy)]
 pub(crate) enum Direction {
     Read,
     Write,
+    Error,
 }
 
 /// The global fallback reactor.
@@ -261,6 +269,7 @@ impl Reactor {
 
         let mut rd = None;
         let mut wr = None;
+        let mut err = None;
 
         // Create a scope to ensure that notifying the tasks stays out of the
         // lock's critical section.
@@ -282,9 +291,13 @@ impl Reactor {
                 wr = io.writer.take();
             }
 
-            if !(ready & (!mio::Ready::writable())).is_empty() {
+            if !(ready & Direction::Read.mask()).is_empty() {
                 rd = io.reader.take();
             }
+
+            if platform::is_error(&ready) {
+                err = io.error.take();
+            }
         }
 
         if let Some(task) = rd {
@@ -294,6 +307,10 @@ impl Reactor {
         if let Some(task) = wr {
             task.wake();
         }
+
+        if let Some(task) = err {
+            task.wake();
+        }
     }
 }
 
@@ -306,6 +323,26 @@ impl fmt::Debug for Reactor {
 // ===== impl Handle =====
 
 impl Handle {
+    /// Returns a handle to the reactor of the current execution context.
+    ///
+    /// If no reactor is associated with the current execution context, the
+    /// handle refers to the global fallback reactor, which is started on a
+    /// background thread the first time it is needed. If that fails, the
+    /// returned handle binds lazily, like `Handle::default()`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use romio::raw::Handle;
+    ///
+    /// let handle = Handle::current();
+    /// ```
+    pub fn current() -> Handle {
+        Handle {
+            inner: HandlePriv::try_current().ok(),
+        }
+    }
+
     fn as_priv(&self) -> Option<&HandlePriv> {
         self.inner.as_ref()
     }
@@ -459,10 +496,11 @@ impl fmt::Debug for HandlePriv {
 // ===== impl Inner =====
 
 impl Inner {
-    /// Register an I/O resource with the reactor.
+    /// Register an I/O resource with the reactor, for events matching
+    /// `interest`.
     ///
     /// The registration token is returned.
-    fn add_source(&self, source: &dyn Evented) -> io::Result<usize> {
+    fn add_source(&self, source: &dyn Evented, interest: mio::Ready) -> io::Result<usize> {
         // Get an ABA guard value
         let aba_guard = self.next_aba_guard.fetch_add(1 << TOKEN_SHIFT, Relaxed);
 
@@ -482,18 +520,31 @@ impl Inner {
             readiness: AtomicUsize::new(0),
             reader: AtomicWaker::new(),
             writer: AtomicWaker::new(),
+            error: AtomicWaker::new(),
         });
 
         self.io.register(
             source,
             mio::Token(aba_guard | key),
-            mio::Ready::all(),
+            interest,
             mio::PollOpt::edge(),
         )?;
 
         Ok(key)
     }
 
+    /// Changes the readiness an I/O resource is registered for.
+    fn reregister_source(
+        &self,
+        source: &dyn Evented,
+        token: usize,
+        interest: mio::Ready,
+        opts: mio::PollOpt,
+    ) -> io::Result<()> {
+        let aba_guard = self.io_dispatch.read()[token].aba_guard;
+        self.io.reregister(source, mio::Token(aba_guard | token), interest, opts)
+    }
+
     /// Deregisters an I/O resource from the reactor.
     fn deregister_source(&self, source: &dyn Evented) -> io::Result<()> {
         self.io.deregister(source)
@@ -511,8 +562,9 @@ impl Inner {
         let sched = io_dispatch.get(token).unwrap();
 
         let (atomic_waker, ready) = match dir {
-            Direction::Read => (&sched.reader, !mio::Ready::writable()),
+            Direction::Read => (&sched.reader, Direction::Read.mask()),
             Direction::Write => (&sched.writer, mio::Ready::writable()),
+            Direction::Error => (&sched.error, platform::error()),
         };
 
         atomic_waker.register(&cx.waker());
@@ -532,6 +584,7 @@ impl Drop for Inner {
         for (_, io) in io.iter() {
             io.writer.wake();
             io.reader.wake();
+            io.error.wake();
         }
     }
 }
@@ -540,10 +593,12 @@ impl Direction {
     fn mask(&self) -> mio::Ready {
         match *self {
             Direction::Read => {
-                // Everything except writable is signaled through read.
-                mio::Ready::all() - mio::Ready::writable()
+                // Everything except writable and error is signaled through
+                // read.
+                mio::Ready::all() - mio::Ready::writable() - platform::error()
             }
             Direction::Write => mio::Ready::writable() | platform::hup(),
+            Direction::Error => platform::error(),
         }
     }
 }
@@ -560,6 +615,19 @@ pub(crate) mod platform {
     pub fn is_hup(ready: &Ready) -> bool {
         UnixReady::from(*ready).is_hup()
     }
+
+    pub fn error() -> Ready {
+        UnixReady::error().into()
+    }
+
+    pub fn is_error(ready: &Ready) -> bool {
+        UnixReady::from(*ready).is_error()
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn priority() -> Ready {
+        UnixReady::priority().into()
+    }
 }
 
 #[cfg(windows)]
@@ -573,4 +641,12 @@ pub(crate) mod platform {
     pub fn is_hup(_: &Ready) -> bool {
         false
     }
+
+    pub fn error() -> Ready {
+        Ready::empty()
+    }
+
+    pub fn is_error(_: &Ready) -> bool {
+        false
+    }
 }
@@ -1,4 +1,4 @@
-use super::{Direction, HandlePriv};
+use super::{Direction, Handle, HandlePriv};
 
 use mio::{self, Evented};
 
@@ -52,6 +52,9 @@ pub(crate) struct Registration {
     /// registration. The rest of the `state` variable is a pointer to tasks
     /// that must be notified once the lock is released.
     state: AtomicUsize,
+
+    /// The readiness the I/O resource is registered for.
+    interest: mio::Ready,
 }
 
 #[derive(Debug)]
@@ -77,6 +80,10 @@ const LOCKED: usize = 1;
 /// A handle has been associated with the registration.
 const READY: usize = 2;
 
+/// The registration was deregistered before a handle was associated, and can
+/// no longer be registered.
+const DEREGISTERED: usize = 3;
+
 /// Masks the lifecycle state
 const LIFECYCLE_MASK: usize = 0b11;
 
@@ -91,9 +98,19 @@ impl Registration {
     /// This registration is not associated with a Reactor instance. Call
     /// `register` to establish the association.
     pub fn new() -> Registration {
+        Registration::new_with_interest(mio::Ready::all())
+    }
+
+    /// Create a new `Registration` that only receives events for the given
+    /// readiness.
+    ///
+    /// This registration is not associated with a Reactor instance. Call
+    /// `register` to establish the association.
+    pub fn new_with_interest(interest: mio::Ready) -> Registration {
         Registration {
             inner: UnsafeCell::new(None),
             state: AtomicUsize::new(INIT),
+            interest,
         }
     }
 
@@ -115,6 +132,18 @@ impl Registration {
         self.register2(io, || HandlePriv::try_current())
     }
 
+    /// Register the I/O resource with the reactor `handle` refers to.
+    ///
+    /// A default handle binds to the reactor of the current execution
+    /// context, just like `register`. Otherwise, this behaves the same as
+    /// `register`.
+    pub fn register_with(&self, io: &impl Evented, handle: &Handle) -> io::Result<bool> {
+        self.register2(io, || match handle.as_priv() {
+            Some(handle) => Ok(handle.clone()),
+            None => HandlePriv::try_current(),
+        })
+    }
+
     /// Deregister the I/O resource from the reactor it is associated with.
     ///
     /// This function must be called before the I/O resource associated with the
@@ -131,14 +160,71 @@ impl Registration {
     /// no longer result in notifications getting sent for this registration.
     ///
     /// `Err` is returned if an error is encountered.
-    pub fn deregister(&mut self, io: &impl Evented) -> io::Result<()> {
-        // The state does not need to be checked and coordination is not
-        // necessary as this function takes `&mut self`. This guarantees a
-        // single thread is accessing the instance.
-        if let Some(inner) = unsafe { (*self.inner.get()).as_ref() } {
-            inner.deregister(io)?;
+    ///
+    /// If the I/O resource is being registered concurrently, the registering
+    /// thread undoes the registration once it completes. If it hasn't been
+    /// registered at all, it can't be registered afterwards.
+    pub fn deregister(&self, io: &impl Evented) -> io::Result<()> {
+        let mut state = self.state.load(SeqCst);
+
+        loop {
+            match state & LIFECYCLE_MASK {
+                INIT => {
+                    match self
+                        .state
+                        .compare_exchange(INIT, DEREGISTERED, SeqCst, SeqCst)
+                    {
+                        Ok(_) => return Ok(()),
+                        Err(actual) => state = actual,
+                    }
+                }
+                LOCKED => {
+                    // Another thread is associating a handle. Hand the
+                    // deregistration off to it, keeping any queued nodes for
+                    // it to consume.
+                    let next = (state & !LIFECYCLE_MASK) | DEREGISTERED;
+                    match self.state.compare_exchange(state, next, SeqCst, SeqCst) {
+                        Ok(_) => return Ok(()),
+                        Err(actual) => state = actual,
+                    }
+                }
+                READY => {
+                    // Once the state is `READY`, `inner` is set and never
+                    // modified again.
+                    let inner = unsafe { (*self.inner.get()).as_ref().unwrap() };
+                    return inner.deregister(io);
+                }
+                _ => return Ok(()),
+            }
         }
+    }
 
+    /// Change the readiness the I/O resource is registered for.
+    ///
+    /// The I/O resource must have been registered with [`register`] first.
+    /// From then on, only events matching `interest` are received.
+    ///
+    /// [`register`]: #method.register
+    pub fn reregister(
+        &mut self,
+        io: &impl Evented,
+        interest: mio::Ready,
+        opts: mio::PollOpt,
+    ) -> io::Result<()> {
+        // As with `deregister`, taking `&mut self` guarantees a single thread
+        // is accessing the instance.
+        let inner = match unsafe { (*self.inner.get()).as_ref() } {
+            Some(inner) => inner,
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    "must call `register` before reregister",
+                ));
+            }
+        };
+
+        inner.reregister(io, interest, opts)?;
+        self.interest = interest;
         Ok(())
     }
 
@@ -150,7 +236,7 @@ impl Registration {
         let mut state = self.state.load(SeqCst);
 
         loop {
-            match state {
+            match state & LIFECYCLE_MASK {
                 INIT => {
                     // Registration is currently not associated with a handle.
                     // Get a handle then attempt to lock the state.
@@ -164,7 +250,7 @@ impl Registration {
                     }
 
                     // Create the actual registration
-                    let (inner, res) = Inner::new(io, handle);
+                    let (inner, res) = Inner::new(io, handle, self.interest);
 
                     unsafe {
                         *self.inner.get() = Some(inner);
@@ -172,13 +258,25 @@ impl Registration {
 
                     // Transition out of the locked state. This acquires the
                     // current value, potentially having a list of tasks that
-                    // are pending readiness notifications.
-                    let actual = self.state.swap(READY, SeqCst);
+                    // are pending readiness notifications. If the resource was
+                    // deregistered in the meantime, stay deregistered.
+                    let mut actual = self.state.load(SeqCst);
+                    let deregistered = loop {
+                        let next = match actual & LIFECYCLE_MASK {
+                            DEREGISTERED => DEREGISTERED,
+                            _ => READY,
+                        };
+                        match self.state.compare_exchange(actual, next, SeqCst, SeqCst) {
+                            Ok(_) => break next == DEREGISTERED,
+                            Err(a) => actual = a,
+                        }
+                    };
 
                     // Consume the stack of nodes
 
                     let mut read = false;
                     let mut write = false;
+                    let mut error = false;
                     let mut ptr = (actual & !LIFECYCLE_MASK) as *mut Node;
 
                     let inner = unsafe { (*self.inner.get()).as_ref().unwrap() };
@@ -195,21 +293,43 @@ impl Registration {
                         let flag = match direction {
                             Direction::Read => &mut read,
                             Direction::Write => &mut write,
+                            Direction::Error => &mut error,
                         };
 
                         if !*flag {
                             *flag = true;
 
                             let waker = unsafe { &*waker };
-                            let mut cx = Context::from_waker(&waker);
-                            inner.register(&mut cx, direction);
+                            if deregistered {
+                                // The task observes the deregistration when
+                                // it polls again.
+                                waker.wake_by_ref();
+                            } else {
+                                let mut cx = Context::from_waker(&waker);
+                                inner.register(&mut cx, direction);
+                            }
                         }
 
                         ptr = next;
                     }
 
+                    if deregistered {
+                        res?;
+                        inner.deregister(io)?;
+                        return Err(io::Error::new(
+                            io::ErrorKind::Other,
+                            "I/O resource has been deregistered",
+                        ));
+                    }
+
                     return res.map(|_| true);
                 }
+                DEREGISTERED => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::Other,
+                        "I/O resource has been deregistered",
+                    ));
+                }
                 _ => return Ok(false),
             }
         }
@@ -317,6 +437,43 @@ impl Registration {
         self.poll_ready(None, Direction::Write)
     }
 
+    /// Poll for events on the I/O resource's error readiness stream.
+    ///
+    /// If the I/O resource receives a new error readiness event since the
+    /// last call to `poll_error_ready`, it is returned. If it has not, the
+    /// current task is notified once a new event is received.
+    ///
+    /// Error events are only reported on Unix platforms, such as when an ICMP
+    /// error is queued on a connected UDP socket. They are [edge-triggered]
+    /// and not part of the read readiness stream.
+    ///
+    /// Ensure that [`register`] has been called first.
+    ///
+    /// [`register`]: #method.register
+    /// [edge-triggered]: https://docs.rs/mio/0.6/mio/struct.Poll.html#edge-triggered-and-level-triggered
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if called from outside of a task context.
+    pub fn poll_error_ready(&self, cx: &mut Context<'_>) -> Poll<io::Result<mio::Ready>> {
+        match self.poll_ready(Some(cx), Direction::Error) {
+            Ok(Some(v)) => Poll::Ready(Ok(v)),
+            Ok(None) => Poll::Pending,
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    /// Consume any pending error readiness event.
+    ///
+    /// This function is identical to [`poll_error_ready`] **except** that it
+    /// will not notify the current task when a new event is received. As such,
+    /// it is safe to call this function from outside of a task context.
+    ///
+    /// [`poll_error_ready`]: #method.poll_error_ready
+    pub fn take_error_ready(&self) -> io::Result<Option<mio::Ready>> {
+        self.poll_ready(None, Direction::Error)
+    }
+
     fn poll_ready(
         &self,
         mut cx: Option<&mut Context<'_>>,
@@ -328,7 +485,7 @@ impl Registration {
         let mut node = None;
 
         loop {
-            match state {
+            match state & LIFECYCLE_MASK {
                 INIT => {
                     return Err(io::Error::new(
                         io::ErrorKind::Other,
@@ -340,6 +497,12 @@ impl Registration {
                     let inner = unsafe { (*self.inner.get()).as_ref().unwrap() };
                     return inner.poll_ready(cx, direction);
                 }
+                DEREGISTERED => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::Other,
+                        "I/O resource has been deregistered",
+                    ));
+                }
                 LOCKED => {
                     if cx.is_none() {
                         // Skip the notification tracking junk.
@@ -391,11 +554,11 @@ unsafe impl Sync for Registration {}
 // ===== impl Inner =====
 
 impl Inner {
-    fn new(io: &impl Evented, handle: HandlePriv) -> (Self, io::Result<()>) {
+    fn new(io: &impl Evented, handle: HandlePriv, interest: mio::Ready) -> (Self, io::Result<()>) {
         let mut res = Ok(());
 
         let token = match handle.inner() {
-            Some(inner) => match inner.add_source(io) {
+            Some(inner) => match inner.add_source(io, interest) {
                 Ok(token) => token,
                 Err(e) => {
                     res = Err(e);
@@ -430,6 +593,27 @@ impl Inner {
         inner.register(cx, self.token, direction);
     }
 
+    fn reregister<E: Evented>(
+        &self,
+        io: &E,
+        interest: mio::Ready,
+        opts: mio::PollOpt,
+    ) -> io::Result<()> {
+        if self.token == ERROR {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "failed to associate with reactor",
+            ));
+        }
+
+        let inner = match self.handle.inner() {
+            Some(inner) => inner,
+            None => return Err(io::Error::new(io::ErrorKind::Other, "reactor gone")),
+        };
+
+        inner.reregister_source(io, self.token, interest, opts)
+    }
+
     fn deregister<E: Evented>(&self, io: &E) -> io::Result<()> {
         if self.token == ERROR {
             return Err(io::Error::new(
@@ -486,6 +670,7 @@ impl Inner {
             match direction {
                 Direction::Read => sched.reader.register(&cx.waker()),
                 Direction::Write => sched.writer.register(&cx.waker()),
+                Direction::Error => sched.error.register(cx.waker()),
             }
 
             // Try again
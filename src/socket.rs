@@ -0,0 +1,542 @@
+//! Low-level socket helpers
+//!
+//! This module wraps the handful of libc calls that the socket types need
+//! beyond what `mio` exposes: raw socket options and `recvmsg`/`sendmsg` with
+//! ancillary data.
+
+use std::io::{self, IoSlice, IoSliceMut};
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::os::unix::io::RawFd;
+use std::{mem, ptr, slice};
+
+use libc::{c_int, c_void, socklen_t};
+
+/// Converts a libc return value into an `io::Result`.
+pub(crate) fn cvt<T: IsMinusOne>(ret: T) -> io::Result<T> {
+    if ret.is_minus_one() {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(ret)
+    }
+}
+
+pub(crate) trait IsMinusOne {
+    fn is_minus_one(&self) -> bool;
+}
+
+impl IsMinusOne for c_int {
+    fn is_minus_one(&self) -> bool {
+        *self == -1
+    }
+}
+
+impl IsMinusOne for isize {
+    fn is_minus_one(&self) -> bool {
+        *self == -1
+    }
+}
+
+/// Sets or clears the `FD_CLOEXEC` flag of a file descriptor.
+pub(crate) fn set_cloexec(fd: RawFd, on: bool) -> io::Result<()> {
+    unsafe {
+        let flags = cvt(libc::fcntl(fd, libc::F_GETFD))?;
+        let new = if on {
+            flags | libc::FD_CLOEXEC
+        } else {
+            flags & !libc::FD_CLOEXEC
+        };
+        if new != flags {
+            cvt(libc::fcntl(fd, libc::F_SETFD, new))?;
+        }
+    }
+    Ok(())
+}
+
+/// Sets a socket option to the given value.
+pub(crate) fn setsockopt<T>(fd: RawFd, level: c_int, name: c_int, val: T) -> io::Result<()> {
+    unsafe {
+        cvt(libc::setsockopt(
+            fd,
+            level,
+            name,
+            &val as *const T as *const c_void,
+            mem::size_of::<T>() as socklen_t,
+        ))?;
+    }
+    Ok(())
+}
+
+/// Gets the value of a socket option.
+pub(crate) fn getsockopt<T: Copy>(fd: RawFd, level: c_int, name: c_int) -> io::Result<T> {
+    unsafe {
+        let mut val: T = mem::zeroed();
+        let mut len = mem::size_of::<T>() as socklen_t;
+        cvt(libc::getsockopt(
+            fd,
+            level,
+            name,
+            &mut val as *mut T as *mut c_void,
+            &mut len,
+        ))?;
+        Ok(val)
+    }
+}
+
+/// Sets the `SO_REUSEPORT` option of a socket.
+#[cfg(not(any(target_os = "solaris", target_os = "illumos")))]
+pub(crate) fn set_reuseport(fd: RawFd, on: bool) -> io::Result<()> {
+    setsockopt(fd, libc::SOL_SOCKET, libc::SO_REUSEPORT, on as c_int)
+}
+
+/// Gets the `SO_REUSEPORT` option of a socket.
+#[cfg(not(any(target_os = "solaris", target_os = "illumos")))]
+pub(crate) fn reuseport(fd: RawFd) -> io::Result<bool> {
+    let on: c_int = getsockopt(fd, libc::SOL_SOCKET, libc::SO_REUSEPORT)?;
+    Ok(on != 0)
+}
+
+#[cfg(any(target_os = "solaris", target_os = "illumos"))]
+pub(crate) fn set_reuseport(_fd: RawFd, _on: bool) -> io::Result<()> {
+    Err(reuseport_unsupported())
+}
+
+#[cfg(any(target_os = "solaris", target_os = "illumos"))]
+pub(crate) fn reuseport(_fd: RawFd) -> io::Result<bool> {
+    Err(reuseport_unsupported())
+}
+
+#[cfg(any(target_os = "solaris", target_os = "illumos"))]
+fn reuseport_unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Other,
+        "SO_REUSEPORT is not supported on this platform",
+    )
+}
+
+/// Attaches a classic BPF program to a socket with the given socket option.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn attach_filter(fd: RawFd, name: c_int, prog: &[libc::sock_filter]) -> io::Result<()> {
+    if prog.len() > u16::MAX as usize {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "BPF program is too long",
+        ));
+    }
+    let fprog = libc::sock_fprog {
+        len: prog.len() as u16,
+        filter: prog.as_ptr() as *mut libc::sock_filter,
+    };
+    setsockopt(fd, libc::SOL_SOCKET, name, fprog)
+}
+
+/// Creates a non-blocking Unix socket of the given type and binds it to `name`
+/// in the Linux abstract namespace.
+#[cfg(target_os = "linux")]
+pub(crate) fn bind_abstract(ty: c_int, name: &[u8]) -> io::Result<RawFd> {
+    let mut addr: libc::sockaddr_un = unsafe { mem::zeroed() };
+    addr.sun_family = libc::AF_UNIX as libc::sa_family_t;
+
+    // The address starts with a null byte, which selects the abstract
+    // namespace, and the name is not null-terminated.
+    if name.len() >= addr.sun_path.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "abstract socket name is too long",
+        ));
+    }
+    for (dst, src) in addr.sun_path[1..].iter_mut().zip(name) {
+        *dst = *src as libc::c_char;
+    }
+    let len = mem::size_of::<libc::sa_family_t>() + 1 + name.len();
+
+    unsafe {
+        let fd = cvt(libc::socket(
+            libc::AF_UNIX,
+            ty | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC,
+            0,
+        ))?;
+        let addr = &addr as *const libc::sockaddr_un as *const libc::sockaddr;
+        if let Err(e) = cvt(libc::bind(fd, addr, len as socklen_t)) {
+            libc::close(fd);
+            return Err(e);
+        }
+        Ok(fd)
+    }
+}
+
+/// Lends the socket behind `fd` to `f` as a `socket2::Socket`, without
+/// transferring ownership of the descriptor.
+#[cfg(feature = "socket2")]
+pub(crate) fn with_socket2<F>(fd: RawFd, f: F) -> io::Result<()>
+where
+    F: FnOnce(&socket2::Socket) -> io::Result<()>,
+{
+    use std::mem::ManuallyDrop;
+    use std::os::unix::io::FromRawFd;
+
+    // The descriptor is still owned by the caller, so it must not be closed
+    // when the borrowed `Socket` goes out of scope.
+    let socket = ManuallyDrop::new(unsafe { socket2::Socket::from_raw_fd(fd) });
+    f(&socket)
+}
+
+/// Converts a `SocketAddr` into its C representation.
+pub(crate) fn sockaddr(addr: &SocketAddr) -> (libc::sockaddr_storage, socklen_t) {
+    unsafe {
+        let mut storage: libc::sockaddr_storage = mem::zeroed();
+        let len = match *addr {
+            SocketAddr::V4(ref a) => {
+                let sin = &mut *(&mut storage as *mut _ as *mut libc::sockaddr_in);
+                sin.sin_family = libc::AF_INET as libc::sa_family_t;
+                sin.sin_port = a.port().to_be();
+                sin.sin_addr = in_addr(a.ip());
+                mem::size_of::<libc::sockaddr_in>()
+            }
+            SocketAddr::V6(ref a) => {
+                let sin6 = &mut *(&mut storage as *mut _ as *mut libc::sockaddr_in6);
+                sin6.sin6_family = libc::AF_INET6 as libc::sa_family_t;
+                sin6.sin6_port = a.port().to_be();
+                sin6.sin6_flowinfo = a.flowinfo();
+                sin6.sin6_addr = in6_addr(a.ip());
+                sin6.sin6_scope_id = a.scope_id();
+                mem::size_of::<libc::sockaddr_in6>()
+            }
+        };
+        (storage, len as socklen_t)
+    }
+}
+
+/// Converts a C socket address into a `SocketAddr`.
+///
+/// Returns `None` for address families other than `AF_INET` and `AF_INET6`.
+pub(crate) fn socket_addr(storage: &libc::sockaddr_storage) -> Option<SocketAddr> {
+    unsafe {
+        match storage.ss_family as c_int {
+            libc::AF_INET => {
+                let sin = &*(storage as *const _ as *const libc::sockaddr_in);
+                let ip = ipv4_addr(&sin.sin_addr);
+                Some(SocketAddr::V4(SocketAddrV4::new(
+                    ip,
+                    u16::from_be(sin.sin_port),
+                )))
+            }
+            libc::AF_INET6 => {
+                let sin6 = &*(storage as *const _ as *const libc::sockaddr_in6);
+                Some(SocketAddr::V6(SocketAddrV6::new(
+                    ipv6_addr(&sin6.sin6_addr),
+                    u16::from_be(sin6.sin6_port),
+                    sin6.sin6_flowinfo,
+                    sin6.sin6_scope_id,
+                )))
+            }
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn in_addr(ip: &Ipv4Addr) -> libc::in_addr {
+    libc::in_addr {
+        s_addr: u32::from(*ip).to_be(),
+    }
+}
+
+pub(crate) fn ipv4_addr(addr: &libc::in_addr) -> Ipv4Addr {
+    Ipv4Addr::from(u32::from_be(addr.s_addr))
+}
+
+pub(crate) fn in6_addr(ip: &Ipv6Addr) -> libc::in6_addr {
+    unsafe {
+        let mut addr: libc::in6_addr = mem::zeroed();
+        addr.s6_addr = ip.octets();
+        addr
+    }
+}
+
+pub(crate) fn ipv6_addr(addr: &libc::in6_addr) -> Ipv6Addr {
+    Ipv6Addr::from(addr.s6_addr)
+}
+
+/// Buffer for ancillary data, aligned for `cmsghdr`.
+pub(crate) struct Control {
+    buf: Vec<usize>,
+    len: usize,
+}
+
+impl Control {
+    /// Creates a buffer that can hold `space` bytes of ancillary data.
+    ///
+    /// Use `Control::space` to compute the space needed by a message.
+    pub(crate) fn with_capacity(space: usize) -> Control {
+        let words = space.div_ceil(mem::size_of::<usize>());
+        Control {
+            buf: vec![0; words],
+            len: 0,
+        }
+    }
+
+    /// Returns the space taken by a message carrying `len` bytes of data.
+    pub(crate) fn space(len: usize) -> usize {
+        unsafe { libc::CMSG_SPACE(len as _) as usize }
+    }
+
+    fn capacity(&self) -> usize {
+        self.buf.len() * mem::size_of::<usize>()
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut c_void {
+        self.buf.as_mut_ptr() as *mut c_void
+    }
+
+    /// Appends a message carrying `data`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer is too small to hold the message.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub(crate) fn push<T: Copy>(&mut self, level: c_int, ty: c_int, data: &T) {
+        self.push_slice(level, ty, slice::from_ref(data))
+    }
+
+    /// Appends a message carrying the elements of `data`, back to back.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer is too small to hold the message.
+    pub(crate) fn push_slice<T: Copy>(&mut self, level: c_int, ty: c_int, data: &[T]) {
+        let size = mem::size_of_val(data);
+        let space = Control::space(size);
+        assert!(
+            self.len + space <= self.capacity(),
+            "control buffer too small"
+        );
+
+        unsafe {
+            let base = (self.buf.as_mut_ptr() as *mut u8).add(self.len);
+            ptr::write_bytes(base, 0, space);
+
+            let cmsg = base as *mut libc::cmsghdr;
+            (*cmsg).cmsg_level = level;
+            (*cmsg).cmsg_type = ty;
+            (*cmsg).cmsg_len = libc::CMSG_LEN(size as _) as _;
+            ptr::copy_nonoverlapping(data.as_ptr() as *const u8, libc::CMSG_DATA(cmsg), size);
+        }
+
+        self.len += space;
+    }
+
+    /// Iterates over the messages in this buffer.
+    pub(crate) fn iter(&self) -> Cmsgs<'_> {
+        let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+        msg.msg_control = self.buf.as_ptr() as *mut c_void;
+        msg.msg_controllen = self.len as _;
+
+        let cur = if self.len == 0 {
+            ptr::null()
+        } else {
+            unsafe { libc::CMSG_FIRSTHDR(&msg) }
+        };
+
+        Cmsgs {
+            msg,
+            cur,
+            _buf: self,
+        }
+    }
+}
+
+/// Iterator over the messages of a `Control` buffer.
+pub(crate) struct Cmsgs<'a> {
+    msg: libc::msghdr,
+    cur: *const libc::cmsghdr,
+    _buf: &'a Control,
+}
+
+/// A single ancillary data message.
+pub(crate) struct Cmsg<'a> {
+    pub(crate) level: c_int,
+    pub(crate) ty: c_int,
+    pub(crate) data: &'a [u8],
+}
+
+impl<'a> Cmsg<'a> {
+    /// Reads the message payload as a `T`.
+    ///
+    /// Returns `None` if the payload is too short.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub(crate) fn read<T: Copy>(&self) -> Option<T> {
+        if self.data.len() < mem::size_of::<T>() {
+            return None;
+        }
+        unsafe { Some(ptr::read_unaligned(self.data.as_ptr() as *const T)) }
+    }
+
+    /// Reads the message payload as a socket address.
+    ///
+    /// Returns `None` if the payload is not an IP socket address.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub(crate) fn read_addr(&self) -> Option<SocketAddr> {
+        unsafe {
+            let mut storage: libc::sockaddr_storage = mem::zeroed();
+            let len = self
+                .data
+                .len()
+                .min(mem::size_of::<libc::sockaddr_storage>());
+            ptr::copy_nonoverlapping(self.data.as_ptr(), &mut storage as *mut _ as *mut u8, len);
+            socket_addr(&storage)
+        }
+    }
+}
+
+impl<'a> Iterator for Cmsgs<'a> {
+    type Item = Cmsg<'a>;
+
+    fn next(&mut self) -> Option<Cmsg<'a>> {
+        if self.cur.is_null() {
+            return None;
+        }
+
+        unsafe {
+            let cmsg = &*self.cur;
+            let header = libc::CMSG_LEN(0) as usize;
+            let cmsg_len: usize = cmsg.cmsg_len as _;
+            let len = cmsg_len.saturating_sub(header);
+            let data = slice::from_raw_parts(libc::CMSG_DATA(cmsg), len);
+
+            self.cur = libc::CMSG_NXTHDR(&self.msg, cmsg);
+
+            Some(Cmsg {
+                level: cmsg.cmsg_level,
+                ty: cmsg.cmsg_type,
+                data,
+            })
+        }
+    }
+}
+
+/// The result of a `recv_msg` call.
+pub(crate) struct RecvMsg {
+    /// Number of bytes received.
+    pub(crate) len: usize,
+    /// Address of the sender, if it is an IP address.
+    pub(crate) addr: Option<SocketAddr>,
+}
+
+/// Receives a message, along with any ancillary data, via `recvmsg`.
+pub(crate) fn recv_msg(
+    fd: RawFd,
+    bufs: &mut [IoSliceMut<'_>],
+    mut control: Option<&mut Control>,
+    flags: c_int,
+) -> io::Result<RecvMsg> {
+    unsafe {
+        let mut storage: libc::sockaddr_storage = mem::zeroed();
+        let mut msg: libc::msghdr = mem::zeroed();
+        msg.msg_name = &mut storage as *mut _ as *mut c_void;
+        msg.msg_namelen = mem::size_of::<libc::sockaddr_storage>() as socklen_t;
+        msg.msg_iov = bufs.as_mut_ptr() as *mut libc::iovec;
+        msg.msg_iovlen = bufs.len() as _;
+
+        if let Some(ref mut control) = control {
+            msg.msg_control = control.as_mut_ptr();
+            msg.msg_controllen = control.capacity() as _;
+        }
+
+        let n = cvt(libc::recvmsg(fd, &mut msg, flags))?;
+
+        if let Some(control) = control {
+            control.len = msg.msg_controllen as usize;
+        }
+
+        let addr = if msg.msg_namelen == 0 {
+            None
+        } else {
+            socket_addr(&storage)
+        };
+
+        Ok(RecvMsg {
+            len: n as usize,
+            addr,
+        })
+    }
+}
+
+/// Sends a message, along with any ancillary data, via `sendmsg`.
+pub(crate) fn send_msg(
+    fd: RawFd,
+    bufs: &[IoSlice<'_>],
+    target: Option<&SocketAddr>,
+    control: Option<&Control>,
+    flags: c_int,
+) -> io::Result<usize> {
+    unsafe {
+        let mut msg: libc::msghdr = mem::zeroed();
+        let mut name = target.map(sockaddr);
+        if let Some((ref mut storage, len)) = name {
+            msg.msg_name = storage as *mut _ as *mut c_void;
+            msg.msg_namelen = len;
+        }
+        msg.msg_iov = bufs.as_ptr() as *mut libc::iovec;
+        msg.msg_iovlen = bufs.len() as _;
+
+        if let Some(control) = control {
+            if control.len > 0 {
+                msg.msg_control = control.buf.as_ptr() as *mut c_void;
+                msg.msg_controllen = control.len as _;
+            }
+        }
+
+        let n = cvt(libc::sendmsg(fd, &msg, flags))?;
+        Ok(n as usize)
+    }
+}
+
+/// The `SO_ZEROCOPY` socket option. Not yet exported by libc.
+#[cfg(target_os = "linux")]
+pub(crate) const SO_ZEROCOPY: c_int = if cfg!(target_arch = "sparc64") {
+    62
+} else {
+    60
+};
+
+// Not yet exported by libc.
+#[cfg(target_os = "linux")]
+const SO_EE_ORIGIN_ZEROCOPY: u8 = 5;
+#[cfg(target_os = "linux")]
+const SO_EE_CODE_ZEROCOPY_COPIED: u8 = 1;
+
+/// Reads a `MSG_ZEROCOPY` completion notification from the error queue of a
+/// socket. On success, returns the inclusive range of sends that completed,
+/// and whether the kernel fell back to copying their data.
+///
+/// Returns `WouldBlock` if the error queue is empty. Errors queued by other
+/// sources, such as ICMP, are returned as is.
+#[cfg(target_os = "linux")]
+pub(crate) fn recv_zerocopy(fd: RawFd) -> io::Result<(u32, u32, bool)> {
+    let space = mem::size_of::<libc::sock_extended_err>() + mem::size_of::<libc::sockaddr_in6>();
+    let mut control = Control::with_capacity(Control::space(space));
+    recv_msg(fd, &mut [], Some(&mut control), libc::MSG_ERRQUEUE)?;
+
+    for cmsg in control.iter() {
+        let recverr = (cmsg.level == libc::SOL_IP && cmsg.ty == libc::IP_RECVERR)
+            || (cmsg.level == libc::SOL_IPV6 && cmsg.ty == libc::IPV6_RECVERR);
+        if !recverr {
+            continue;
+        }
+        let err = match cmsg.read::<libc::sock_extended_err>() {
+            Some(err) => err,
+            None => continue,
+        };
+        if err.ee_origin == SO_EE_ORIGIN_ZEROCOPY {
+            let copied = err.ee_code & SO_EE_CODE_ZEROCOPY_COPIED != 0;
+            return Ok((err.ee_info, err.ee_data, copied));
+        }
+        if err.ee_errno != 0 {
+            return Err(io::Error::from_raw_os_error(err.ee_errno as c_int));
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "error queue message is not a zerocopy notification",
+    ))
+}
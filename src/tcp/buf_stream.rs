@@ -0,0 +1,202 @@
+use std::fmt;
+use std::io::{self, IoSlice, IoSliceMut};
+use std::pin::Pin;
+use std::task::Context;
+
+use futures::io::{AsyncBufRead, AsyncRead, AsyncWrite};
+use futures::{ready, Poll};
+
+use super::TcpStream;
+
+const DEFAULT_CAPACITY: usize = 8 * 1024;
+
+/// A `TcpStream` with a read buffer, implementing `AsyncBufRead`.
+///
+/// Reads are served from an internal buffer, which is refilled with a single
+/// read from the stream whenever it runs empty. This makes small and
+/// line-oriented reads, such as parsing the header of an HTTP/1.1 request,
+/// cheap. Reads at least as large as the buffer bypass it when it is empty.
+///
+/// Writes are passed through to the stream without buffering.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// #![feature(async_await)]
+/// use futures::prelude::*;
+/// use romio::tcp::{BufTcpStream, TcpStream};
+///
+/// # async fn run() -> Result<(), Box<dyn std::error::Error + 'static>> {
+/// let stream = TcpStream::connect(&"127.0.0.1:8080".parse()?).await?;
+/// let mut stream = BufTcpStream::new(stream);
+///
+/// let mut status = String::new();
+/// stream.read_line(&mut status).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct BufTcpStream {
+    stream: TcpStream,
+    buf: Box<[u8]>,
+    pos: usize,
+    cap: usize,
+}
+
+impl BufTcpStream {
+    /// Creates a new `BufTcpStream` with a buffer of 8 KiB.
+    pub fn new(stream: TcpStream) -> BufTcpStream {
+        BufTcpStream::with_capacity(stream, DEFAULT_CAPACITY)
+    }
+
+    /// Creates a new `BufTcpStream` with a buffer of `capacity` bytes.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `capacity` is zero.
+    pub fn with_capacity(stream: TcpStream, capacity: usize) -> BufTcpStream {
+        assert!(capacity > 0, "capacity must not be zero");
+        BufTcpStream {
+            stream,
+            buf: vec![0; capacity].into_boxed_slice(),
+            pos: 0,
+            cap: 0,
+        }
+    }
+
+    /// Returns a reference to the underlying stream.
+    pub fn get_ref(&self) -> &TcpStream {
+        &self.stream
+    }
+
+    /// Returns a mutable reference to the underlying stream.
+    ///
+    /// Care should be taken not to read from the stream directly, as that
+    /// would skip over the data that is buffered.
+    pub fn get_mut(&mut self) -> &mut TcpStream {
+        &mut self.stream
+    }
+
+    /// Returns the data that is currently buffered.
+    ///
+    /// Unlike `poll_fill_buf`, this never reads from the stream.
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf[self.pos..self.cap]
+    }
+
+    /// Consumes the `BufTcpStream`, returning the underlying stream.
+    ///
+    /// Any data that is still buffered is lost.
+    pub fn into_inner(self) -> TcpStream {
+        self.stream
+    }
+
+    fn discard_buffer(&mut self) {
+        self.pos = 0;
+        self.cap = 0;
+    }
+}
+
+impl From<TcpStream> for BufTcpStream {
+    fn from(stream: TcpStream) -> BufTcpStream {
+        BufTcpStream::new(stream)
+    }
+}
+
+impl AsyncRead for BufTcpStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        // Large reads skip the copy through the buffer.
+        if self.pos == self.cap && buf.len() >= self.buf.len() {
+            let res = ready!(Pin::new(&mut self.stream).poll_read(cx, buf));
+            self.discard_buffer();
+            return Poll::Ready(res);
+        }
+
+        let rem = ready!(self.as_mut().poll_fill_buf(cx))?;
+        let n = rem.len().min(buf.len());
+        buf[..n].copy_from_slice(&rem[..n]);
+        self.consume(n);
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_read_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let len = bufs.iter().map(|buf| buf.len()).sum::<usize>();
+        if self.pos == self.cap && len >= self.buf.len() {
+            let res = ready!(Pin::new(&mut self.stream).poll_read_vectored(cx, bufs));
+            self.discard_buffer();
+            return Poll::Ready(res);
+        }
+
+        let mut rem = ready!(self.as_mut().poll_fill_buf(cx))?;
+        let mut n = 0;
+        for buf in bufs {
+            let len = rem.len().min(buf.len());
+            buf[..len].copy_from_slice(&rem[..len]);
+            rem = &rem[len..];
+            n += len;
+        }
+        self.consume(n);
+        Poll::Ready(Ok(n))
+    }
+}
+
+impl AsyncBufRead for BufTcpStream {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        let this = self.get_mut();
+
+        if this.pos >= this.cap {
+            this.cap = ready!(Pin::new(&mut this.stream).poll_read(cx, &mut this.buf))?;
+            this.pos = 0;
+        }
+        Poll::Ready(Ok(&this.buf[this.pos..this.cap]))
+    }
+
+    fn consume(mut self: Pin<&mut Self>, amt: usize) {
+        self.pos = (self.pos + amt).min(self.cap);
+    }
+}
+
+impl AsyncWrite for BufTcpStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.stream).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.stream).poll_write_vectored(cx, bufs)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_close(cx)
+    }
+}
+
+impl fmt::Debug for BufTcpStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BufTcpStream")
+            .field("stream", &self.stream)
+            .field(
+                "buffer",
+                &format_args!("{}/{}", self.cap - self.pos, self.buf.len()),
+            )
+            .finish()
+    }
+}
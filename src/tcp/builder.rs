@@ -0,0 +1,362 @@
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+#[cfg(unix)]
+use std::os::unix::io::{AsRawFd, RawFd};
+
+use net2::TcpStreamExt;
+
+use super::{ConnectFuture, TcpListener, TcpStream};
+#[cfg(unix)]
+use crate::socket;
+
+/// A builder for a `TcpListener`, for configuring the socket before it is
+/// bound.
+///
+/// Some options only take effect if they are set before the socket is bound
+/// or starts listening, which rules out setting them on a `TcpListener`.
+///
+/// # Examples
+///
+/// Create a dual-stack listener, which accepts both IPv6 connections and IPv4
+/// connections through IPv4-mapped IPv6 addresses:
+///
+/// ```rust,no_run
+/// use romio::tcp::TcpListenerBuilder;
+///
+/// # fn main () -> Result<(), Box<dyn std::error::Error + 'static>> {
+/// let listener = TcpListenerBuilder::new()
+///     .only_v6(false)
+///     .bind(&"[::]:80".parse()?)?;
+/// # Ok(())}
+/// ```
+#[derive(Clone, Debug)]
+pub struct TcpListenerBuilder {
+    #[cfg(unix)]
+    reuseport: Option<bool>,
+    reuseaddr: Option<bool>,
+    only_v6: Option<bool>,
+    #[cfg(unix)]
+    recv_buffer_size: Option<usize>,
+    #[cfg(unix)]
+    send_buffer_size: Option<usize>,
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    fastopen: Option<u32>,
+    backlog: i32,
+}
+
+impl TcpListenerBuilder {
+    /// Creates a new builder, with every option left at the system default.
+    ///
+    /// Like [`TcpListener::bind`], the builder enables `SO_REUSEADDR` on Unix
+    /// platforms, which lets a restarted server reuse its address, and
+    /// creates the listener with a backlog of 1024 pending connections.
+    ///
+    /// [`TcpListener::bind`]: struct.TcpListener.html#method.bind
+    pub fn new() -> TcpListenerBuilder {
+        TcpListenerBuilder {
+            #[cfg(unix)]
+            reuseport: None,
+            reuseaddr: if cfg!(unix) { Some(true) } else { None },
+            only_v6: None,
+            #[cfg(unix)]
+            recv_buffer_size: None,
+            #[cfg(unix)]
+            send_buffer_size: None,
+            #[cfg(any(target_os = "linux", target_os = "macos"))]
+            fastopen: None,
+            backlog: 1024,
+        }
+    }
+
+    /// Sets the value of the `SO_REUSEPORT` option for the socket.
+    ///
+    /// Several listeners can be bound to the same address this way, as long
+    /// as all of them set the option and belong to the same user. On Linux
+    /// the kernel then distributes incoming connections across the
+    /// listeners. Binding fails on platforms that do not support
+    /// `SO_REUSEPORT`.
+    #[cfg(unix)]
+    pub fn reuseport(&mut self, on: bool) -> &mut TcpListenerBuilder {
+        self.reuseport = Some(on);
+        self
+    }
+
+    /// Sets the value of the `SO_REUSEADDR` option for the socket.
+    ///
+    /// On Unix platforms this allows binding to an address which still has
+    /// connections in the `TIME_WAIT` state, and is enabled by default. On
+    /// Windows it allows several sockets to bind to the same address, and is
+    /// left disabled by default.
+    pub fn reuseaddr(&mut self, on: bool) -> &mut TcpListenerBuilder {
+        self.reuseaddr = Some(on);
+        self
+    }
+
+    /// Sets the value of the `IPV6_V6ONLY` option for the socket.
+    ///
+    /// If this is set to `true` then the socket is restricted to sending and
+    /// receiving IPv6 packets only. If it is set to `false`, an IPv6 socket
+    /// bound to the unspecified address also accepts IPv4 connections, from
+    /// IPv4-mapped IPv6 addresses. The default depends on the platform, and
+    /// on Linux on the `net.ipv6.bindv6only` sysctl.
+    ///
+    /// This is ignored when binding to an IPv4 address.
+    pub fn only_v6(&mut self, only_v6: bool) -> &mut TcpListenerBuilder {
+        self.only_v6 = Some(only_v6);
+        self
+    }
+
+    /// Sets the value of the `SO_RCVBUF` option for the socket.
+    ///
+    /// Accepted connections inherit the size. Setting it before listening
+    /// matters for large buffers, as the TCP window scale is negotiated
+    /// during the handshake.
+    #[cfg(unix)]
+    pub fn recv_buffer_size(&mut self, size: usize) -> &mut TcpListenerBuilder {
+        self.recv_buffer_size = Some(size);
+        self
+    }
+
+    /// Sets the value of the `SO_SNDBUF` option for the socket.
+    ///
+    /// Accepted connections inherit the size.
+    #[cfg(unix)]
+    pub fn send_buffer_size(&mut self, size: usize) -> &mut TcpListenerBuilder {
+        self.send_buffer_size = Some(size);
+        self
+    }
+
+    /// Enables TCP Fast Open on the listener, with a queue of `queue` pending
+    /// Fast Open requests.
+    ///
+    /// For more information about this option, see
+    /// [`TcpListener::set_fastopen`].
+    ///
+    /// [`TcpListener::set_fastopen`]: struct.TcpListener.html#method.set_fastopen
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    pub fn fastopen(&mut self, queue: u32) -> &mut TcpListenerBuilder {
+        self.fastopen = Some(queue);
+        self
+    }
+
+    /// Sets the number of pending connections the listener has room for.
+    ///
+    /// For more information, see [`TcpListener::bind_with_backlog`].
+    ///
+    /// [`TcpListener::bind_with_backlog`]: struct.TcpListener.html#method.bind_with_backlog
+    pub fn backlog(&mut self, backlog: i32) -> &mut TcpListenerBuilder {
+        self.backlog = backlog;
+        self
+    }
+
+    /// Creates a new `TcpListener` with the configured options, bound to the
+    /// specified address.
+    pub fn bind(&self, addr: &SocketAddr) -> io::Result<TcpListener> {
+        let builder = match addr {
+            SocketAddr::V4(..) => net2::TcpBuilder::new_v4()?,
+            SocketAddr::V6(..) => {
+                let builder = net2::TcpBuilder::new_v6()?;
+                if let Some(only_v6) = self.only_v6 {
+                    builder.only_v6(only_v6)?;
+                }
+                builder
+            }
+        };
+        if let Some(on) = self.reuseaddr {
+            builder.reuse_address(on)?;
+        }
+
+        #[cfg(unix)]
+        {
+            let fd = builder.as_raw_fd();
+            if let Some(on) = self.reuseport {
+                socket::set_reuseport(fd, on)?;
+            }
+            if let Some(size) = self.recv_buffer_size {
+                set_buffer_size(fd, libc::SO_RCVBUF, size)?;
+            }
+            if let Some(size) = self.send_buffer_size {
+                set_buffer_size(fd, libc::SO_SNDBUF, size)?;
+            }
+        }
+
+        builder.bind(addr)?;
+        #[cfg(any(target_os = "linux", target_os = "macos"))]
+        {
+            if let Some(queue) = self.fastopen {
+                let queue = queue.min(libc::c_int::MAX as u32) as libc::c_int;
+                socket::setsockopt(
+                    builder.as_raw_fd(),
+                    libc::IPPROTO_TCP,
+                    libc::TCP_FASTOPEN,
+                    queue,
+                )?;
+            }
+        }
+        let listener = builder.listen(self.backlog)?;
+        TcpListener::from_std(listener)
+    }
+}
+
+impl Default for TcpListenerBuilder {
+    fn default() -> TcpListenerBuilder {
+        TcpListenerBuilder::new()
+    }
+}
+
+/// A builder for a `TcpStream`, for configuring the socket before it
+/// connects.
+///
+/// Some options only take effect if they are set before the connection is
+/// established, which rules out setting them on a `TcpStream`.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// #![feature(async_await)]
+/// use romio::tcp::TcpStreamBuilder;
+///
+/// # async fn run() -> Result<(), Box<dyn std::error::Error + 'static>> {
+/// let stream = TcpStreamBuilder::new()
+///     .nodelay(true)
+///     .recv_buffer_size(4 * 1024 * 1024)
+///     .connect(&"127.0.0.1:7878".parse()?)
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TcpStreamBuilder {
+    nodelay: Option<bool>,
+    send_buffer_size: Option<usize>,
+    recv_buffer_size: Option<usize>,
+    ttl: Option<u32>,
+    #[cfg(target_os = "linux")]
+    device: Option<Vec<u8>>,
+}
+
+impl TcpStreamBuilder {
+    /// Creates a new builder, with every option left at the system default.
+    pub fn new() -> TcpStreamBuilder {
+        TcpStreamBuilder::default()
+    }
+
+    /// Sets the value of the `TCP_NODELAY` option for the socket.
+    ///
+    /// For more information about this option, see
+    /// [`TcpStream::set_nodelay`].
+    ///
+    /// [`TcpStream::set_nodelay`]: struct.TcpStream.html#method.set_nodelay
+    pub fn nodelay(&mut self, nodelay: bool) -> &mut TcpStreamBuilder {
+        self.nodelay = Some(nodelay);
+        self
+    }
+
+    /// Sets the value of the `SO_SNDBUF` option for the socket.
+    ///
+    /// For more information about this option, see
+    /// [`TcpStream::set_send_buffer_size`].
+    ///
+    /// [`TcpStream::set_send_buffer_size`]: struct.TcpStream.html#method.set_send_buffer_size
+    pub fn send_buffer_size(&mut self, size: usize) -> &mut TcpStreamBuilder {
+        self.send_buffer_size = Some(size);
+        self
+    }
+
+    /// Sets the value of the `SO_RCVBUF` option for the socket.
+    ///
+    /// Setting it before connecting matters for large buffers, as the TCP
+    /// window scale is negotiated during the handshake.
+    pub fn recv_buffer_size(&mut self, size: usize) -> &mut TcpStreamBuilder {
+        self.recv_buffer_size = Some(size);
+        self
+    }
+
+    /// Sets the value of the `IP_TTL` option for the socket.
+    ///
+    /// This is the time-to-live of the packets sent from the socket, which
+    /// includes the SYN.
+    pub fn ttl(&mut self, ttl: u32) -> &mut TcpStreamBuilder {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Binds the socket to a particular network interface, using the
+    /// `SO_BINDTODEVICE` option.
+    ///
+    /// The connection is then established through `interface` regardless of
+    /// the routing table. Binding usually requires the `CAP_NET_RAW`
+    /// capability.
+    #[cfg(target_os = "linux")]
+    pub fn bind_device(&mut self, interface: &[u8]) -> &mut TcpStreamBuilder {
+        self.device = Some(interface.to_vec());
+        self
+    }
+
+    /// Creates a new TCP stream with the configured options, connected to the
+    /// specified address.
+    ///
+    /// The returned future behaves like the one returned by
+    /// [`TcpStream::connect`].
+    ///
+    /// [`TcpStream::connect`]: struct.TcpStream.html#method.connect
+    pub fn connect(&self, addr: &SocketAddr) -> ConnectFuture {
+        ConnectFuture::new(self.connect_priv(addr))
+    }
+
+    pub(super) fn connect_priv(&self, addr: &SocketAddr) -> io::Result<TcpStream> {
+        let builder = match addr {
+            SocketAddr::V4(..) => net2::TcpBuilder::new_v4()?,
+            SocketAddr::V6(..) => net2::TcpBuilder::new_v6()?,
+        };
+        if let Some(ttl) = self.ttl {
+            builder.ttl(ttl)?;
+        }
+        #[cfg(target_os = "linux")]
+        {
+            if let Some(interface) = &self.device {
+                bind_device(builder.as_raw_fd(), interface)?;
+            }
+        }
+        // mio can only connect bound sockets on Windows.
+        if cfg!(windows) {
+            let any = match addr {
+                SocketAddr::V4(..) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+                SocketAddr::V6(..) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+            };
+            builder.bind(SocketAddr::new(any, 0))?;
+        }
+
+        let stream = builder.to_tcp_stream()?;
+        if let Some(nodelay) = self.nodelay {
+            stream.set_nodelay(nodelay)?;
+        }
+        if let Some(size) = self.send_buffer_size {
+            stream.set_send_buffer_size(size)?;
+        }
+        if let Some(size) = self.recv_buffer_size {
+            stream.set_recv_buffer_size(size)?;
+        }
+        TcpStream::register(stream, Some(addr))
+    }
+}
+
+#[cfg(unix)]
+fn set_buffer_size(fd: RawFd, name: libc::c_int, size: usize) -> io::Result<()> {
+    let size = size.min(libc::c_int::MAX as usize) as libc::c_int;
+    socket::setsockopt(fd, libc::SOL_SOCKET, name, size)
+}
+
+#[cfg(target_os = "linux")]
+fn bind_device(fd: RawFd, interface: &[u8]) -> io::Result<()> {
+    unsafe {
+        socket::cvt(libc::setsockopt(
+            fd,
+            libc::SOL_SOCKET,
+            libc::SO_BINDTODEVICE,
+            interface.as_ptr() as *const libc::c_void,
+            interface.len() as libc::socklen_t,
+        ))?;
+    }
+    Ok(())
+}
@@ -1,15 +1,22 @@
-use super::TcpStream;
+use super::{TcpListenerBuilder, TcpStream};
 
-use std::convert::TryFrom;
+use std::convert::{Infallible, TryFrom};
 use std::fmt;
 use std::io;
 use std::net::{self, SocketAddr};
+#[cfg(unix)]
+use std::os::unix::io::AsRawFd;
+#[cfg(target_os = "linux")]
+use std::os::unix::io::RawFd;
+#[cfg(windows)]
+use std::os::windows::io::{AsRawSocket, RawSocket};
 use std::pin::Pin;
 use std::task::Context;
 
 use async_ready::AsyncReady;
-use futures::stream::Stream;
-use futures::{ready, Poll};
+use futures::stream::{FusedStream, Stream};
+use futures::task::{Spawn, SpawnExt};
+use futures::{ready, Future, Poll};
 use mio;
 
 use crate::raw::PollEvented;
@@ -45,13 +52,17 @@ use crate::raw::PollEvented;
 ///
 ///     // accept connections and process them serially
 ///     while let Some(stream) = incoming.next().await {
-///         recite_shakespeare(stream?).await;
+///         let (stream, _) = stream?;
+///         recite_shakespeare(stream).await;
 ///     }
 ///     Ok(())
 /// }
 /// ```
 pub struct TcpListener {
     io: PollEvented<mio::net::TcpListener>,
+    // mio does not expose the underlying socket on Windows.
+    #[cfg(windows)]
+    raw_socket: RawSocket,
 }
 
 impl TcpListener {
@@ -64,6 +75,11 @@ impl TcpListener {
     /// to this listener. The port allocated can be queried via the
     /// [`local_addr`] method.
     ///
+    /// The listener is created with a backlog of 1024 pending connections. To
+    /// use a different backlog, see [`bind_with_backlog`].
+    ///
+    /// [`bind_with_backlog`]: #method.bind_with_backlog
+    ///
     /// # Examples
     /// Create a TCP listener bound to 127.0.0.1:80:
     ///
@@ -78,13 +94,61 @@ impl TcpListener {
     ///
     /// [`local_addr`]: #method.local_addr
     pub fn bind(addr: &SocketAddr) -> io::Result<TcpListener> {
-        let l = mio::net::TcpListener::bind(addr)?;
-        Ok(TcpListener::new(l))
+        TcpListenerBuilder::default().bind(addr)
     }
 
-    fn new(listener: mio::net::TcpListener) -> TcpListener {
-        let io = PollEvented::new(listener);
-        TcpListener { io }
+    /// Creates a new `TcpListener` bound to the specified address, with room
+    /// for `backlog` pending connections.
+    ///
+    /// The backlog is the length of the queue of connections which have been
+    /// established by the kernel but not yet accepted. Once it is full, new
+    /// connection attempts are dropped or refused. The kernel caps the value:
+    /// on Linux it is silently limited to `net.core.somaxconn`.
+    ///
+    /// Otherwise, this behaves like [`bind`].
+    ///
+    /// [`bind`]: #method.bind
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use romio::tcp::TcpListener;
+    ///
+    /// # fn main () -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let socket_addr = "127.0.0.1:80".parse()?;
+    /// let listener = TcpListener::bind_with_backlog(&socket_addr, 4096)?;
+    /// # Ok(())}
+    /// ```
+    pub fn bind_with_backlog(addr: &SocketAddr, backlog: i32) -> io::Result<TcpListener> {
+        TcpListenerBuilder::new().backlog(backlog).bind(addr)
+    }
+
+    /// Creates a new `TcpListener` from a bound and listening
+    /// `std::net::TcpListener`.
+    ///
+    /// The listener is switched to non-blocking mode and registered with the
+    /// default reactor. This is the same as the `TryFrom` conversion.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use romio::tcp::TcpListener;
+    ///
+    /// # fn main () -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let std_listener = std::net::TcpListener::bind("127.0.0.1:80")?;
+    /// let listener = TcpListener::from_std(std_listener)?;
+    /// # Ok(())}
+    /// ```
+    pub fn from_std(listener: std::net::TcpListener) -> io::Result<TcpListener> {
+        listener.set_nonblocking(true)?;
+        #[cfg(windows)]
+        let raw_socket = listener.as_raw_socket();
+        let io = mio::net::TcpListener::from_std(listener)?;
+        Ok(TcpListener {
+            io: PollEvented::new(io),
+            #[cfg(windows)]
+            raw_socket,
+        })
     }
 
     /// Returns the local address that this listener is bound to.
@@ -110,6 +174,64 @@ impl TcpListener {
         self.io.get_ref().local_addr()
     }
 
+    /// Accepts a new incoming connection to this listener.
+    ///
+    /// The returned future resolves to the accepted stream along with the
+    /// address of the remote peer, once a connection arrives.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// use romio::tcp::TcpListener;
+    ///
+    /// # async fn work () -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let socket_addr = "127.0.0.1:80".parse()?;
+    /// let mut listener = TcpListener::bind(&socket_addr)?;
+    ///
+    /// let (stream, addr) = listener.accept().await?;
+    /// println!("new client from {}", addr);
+    /// # Ok(())}
+    /// ```
+    pub fn accept(&mut self) -> Accept<'_> {
+        Accept { listener: self }
+    }
+
+    /// Attempts to accept a new incoming connection to this listener.
+    ///
+    /// On success, returns the accepted stream along with the address of the
+    /// remote peer. If no connection is pending, the current task is notified
+    /// once one arrives.
+    ///
+    /// This is the primitive that [`accept`] and [`incoming`] are built on.
+    ///
+    /// [`accept`]: #method.accept
+    /// [`incoming`]: #method.incoming
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// use futures::future;
+    /// use romio::tcp::TcpListener;
+    /// use std::pin::Pin;
+    ///
+    /// # async fn work () -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let socket_addr = "127.0.0.1:80".parse()?;
+    /// let mut listener = TcpListener::bind(&socket_addr)?;
+    ///
+    /// let (stream, addr) = future::poll_fn(|cx| Pin::new(&mut listener).poll_accept(cx)).await?;
+    /// println!("new client from {}", addr);
+    /// # Ok(())}
+    /// ```
+    pub fn poll_accept(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<(TcpStream, SocketAddr)>> {
+        let (io, addr) = ready!(self.poll_accept_std(cx)?);
+        Poll::Ready(Ok((TcpStream::register(io, None)?, addr)))
+    }
+
     /// Consumes this listener, returning a stream of the sockets this listener
     /// accepts.
     ///
@@ -138,8 +260,8 @@ impl TcpListener {
     /// // accept connections and process them serially
     /// while let Some(stream) = incoming.next().await {
     ///     match stream {
-    ///         Ok(stream) => {
-    ///             println!("new client!");
+    ///         Ok((stream, addr)) => {
+    ///             println!("new client: {}", addr);
     ///         },
     ///         Err(e) => { /* connection failed */ }
     ///     }
@@ -150,6 +272,67 @@ impl TcpListener {
         Incoming { inner: self }
     }
 
+    /// Consumes this listener, accepting connections forever and spawning
+    /// `handler` for each of them onto `spawner`.
+    ///
+    /// The returned future only resolves if accepting a connection or spawning
+    /// its handler fails, in which case the error is returned and the listener
+    /// is closed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// use futures::executor::ThreadPool;
+    /// use futures::prelude::*;
+    /// use romio::tcp::{TcpListener, TcpStream};
+    /// use std::net::SocketAddr;
+    ///
+    /// async fn recite_shakespeare(mut stream: TcpStream, _peer: SocketAddr) {
+    ///     let _ = stream.write_all(b"Shall I hear more, or shall I speak at this?").await;
+    /// }
+    ///
+    /// # async fn work () -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let socket_addr = "127.0.0.1:80".parse()?;
+    /// let listener = TcpListener::bind(&socket_addr)?;
+    /// let pool = ThreadPool::new()?;
+    ///
+    /// listener.accept_loop(pool, recite_shakespeare).await?;
+    /// # Ok(())}
+    /// ```
+    pub fn accept_loop<S, F, Fut>(self, spawner: S, handler: F) -> AcceptLoop<S, F>
+    where
+        S: Spawn,
+        F: FnMut(TcpStream, SocketAddr) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        AcceptLoop {
+            listener: self,
+            spawner,
+            handler,
+        }
+    }
+
+    /// Sets the close-on-exec flag on the listener's file descriptor, so that
+    /// processes started with `exec` can't accept connections on it.
+    ///
+    /// This only needs to be called for listeners created with `from_std` or
+    /// `from_raw_fd`, see [`TcpStream::set_cloexec`].
+    ///
+    /// [`TcpStream::set_cloexec`]: struct.TcpStream.html#method.set_cloexec
+    #[cfg(unix)]
+    pub fn set_cloexec(&self) -> io::Result<()> {
+        crate::socket::set_cloexec(self.as_raw_fd(), true)
+    }
+
+    /// Clears the close-on-exec flag on the listener's file descriptor, so that
+    /// it can be handed to a child process, for example to restart a server
+    /// without closing the listening socket.
+    #[cfg(unix)]
+    pub fn clear_cloexec(&self) -> io::Result<()> {
+        crate::socket::set_cloexec(self.as_raw_fd(), false)
+    }
+
     /// Gets the value of the `IP_TTL` option for this socket.
     ///
     /// For more information about this option, see [`set_ttl`].
@@ -196,6 +379,92 @@ impl TcpListener {
         self.io.get_ref().set_ttl(ttl)
     }
 
+    /// Attaches a classic BPF program that selects which socket of a
+    /// `SO_REUSEPORT` group receives each new connection, using the
+    /// `SO_ATTACH_REUSEPORT_CBPF` option.
+    ///
+    /// The program returns the index of the socket in the group, in the order
+    /// the sockets were bound. If the index is out of range, the kernel falls
+    /// back to its default hash based selection.
+    ///
+    /// The program is attached to the whole group rather than to this socket,
+    /// and attaching another program through any member replaces it for all
+    /// of them. Every listener in the group should therefore agree on the same
+    /// program, otherwise steering depends on which one attached it last.
+    ///
+    /// The listener must have been created with `SO_REUSEPORT` set, for
+    /// example by converting a `std::net::TcpListener` configured with
+    /// `net2`.
+    #[cfg(target_os = "linux")]
+    pub fn attach_reuseport_bpf(&self, prog: &[libc::sock_filter]) -> io::Result<()> {
+        sys::attach_reuseport_bpf(self.io.get_ref(), prog)
+    }
+
+    /// Attaches an eBPF program that selects which socket of a `SO_REUSEPORT`
+    /// group receives each new connection, using the
+    /// `SO_ATTACH_REUSEPORT_EBPF` option.
+    ///
+    /// `prog_fd` must refer to a program of type `BPF_PROG_TYPE_SOCKET_FILTER`
+    /// or `BPF_PROG_TYPE_SK_REUSEPORT`, loaded with `bpf(2)`.
+    ///
+    /// For more information, see [`attach_reuseport_bpf`].
+    ///
+    /// [`attach_reuseport_bpf`]: #method.attach_reuseport_bpf
+    #[cfg(target_os = "linux")]
+    pub fn attach_reuseport_ebpf(&self, prog_fd: RawFd) -> io::Result<()> {
+        sys::attach_reuseport_ebpf(self.io.get_ref(), prog_fd)
+    }
+
+    /// Enables TCP Fast Open (RFC 7413) on this listener, with the
+    /// `TCP_FASTOPEN` option.
+    ///
+    /// Fast Open lets clients which connected before send data along with
+    /// the SYN, saving a round trip. On Linux, `queue` is the maximum number
+    /// of pending Fast Open requests which haven't completed the handshake
+    /// yet. On macOS it can't be tuned, and any non-zero value enables the
+    /// option. Support must also be enabled system-wide, through the
+    /// `net.ipv4.tcp_fastopen` sysctl on Linux and `net.inet.tcp.fastopen` on
+    /// macOS.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use romio::tcp::TcpListener;
+    ///
+    /// # fn main () -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let listener = TcpListener::bind(&"0.0.0.0:80".parse()?)?;
+    /// listener.set_fastopen(256)?;
+    /// # Ok(())}
+    /// ```
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    pub fn set_fastopen(&self, queue: u32) -> io::Result<()> {
+        sys::set_fastopen(self.io.get_ref(), queue)
+    }
+
+    /// Gets the value of the `IPV6_V6ONLY` option for this socket.
+    ///
+    /// For more information about this option, see [`set_only_v6`].
+    ///
+    /// [`set_only_v6`]: #method.set_only_v6
+    pub fn only_v6(&self) -> io::Result<bool> {
+        self.io.get_ref().only_v6()
+    }
+
+    /// Sets the value of the `IPV6_V6ONLY` option for this socket.
+    ///
+    /// If this is set to `true` then the socket is restricted to IPv6
+    /// connections only. If it is set to `false`, IPv4 connections are
+    /// accepted too, from IPv4-mapped IPv6 addresses.
+    ///
+    /// Most platforms only honor this option before the socket is bound, so
+    /// it should generally be set through [`TcpListenerBuilder::only_v6`]
+    /// instead.
+    ///
+    /// [`TcpListenerBuilder::only_v6`]: struct.TcpListenerBuilder.html#method.only_v6
+    pub fn set_only_v6(&self, only_v6: bool) -> io::Result<()> {
+        self.io.get_ref().set_only_v6(only_v6)
+    }
+
     fn poll_accept_std(
         mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
@@ -219,10 +488,7 @@ impl AsyncReady for TcpListener {
 
     /// Check if the stream can be read from.
     fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<Self::Ok, Self::Err>> {
-        let (io, addr) = ready!(self.poll_accept_std(cx)?);
-        let io = mio::net::TcpStream::from_stream(io)?;
-        let io = TcpStream::new(io);
-        Poll::Ready(Ok((io, addr)))
+        self.poll_accept(cx)
     }
 }
 
@@ -234,37 +500,403 @@ impl fmt::Debug for TcpListener {
 
 #[cfg(unix)]
 mod sys {
-    use super::TcpListener;
+    use super::{TcpListener, TcpListenerBuilder};
+    use std::io;
+    use std::net::SocketAddr;
     use std::os::unix::prelude::*;
 
+    use crate::raw::PollEvented;
+    use crate::socket;
+
     impl AsRawFd for TcpListener {
         fn as_raw_fd(&self) -> RawFd {
             self.io.get_ref().as_raw_fd()
         }
     }
+
+    impl FromRawFd for TcpListener {
+        /// Creates a new `TcpListener` from a raw file descriptor, and
+        /// registers it with the default reactor.
+        ///
+        /// # Safety
+        ///
+        /// `fd` must be a valid TCP socket, bound and listening, in
+        /// non-blocking mode. Ownership of `fd` is transferred to the listener,
+        /// which closes it when dropped.
+        unsafe fn from_raw_fd(fd: RawFd) -> TcpListener {
+            let io = mio::net::TcpListener::from_raw_fd(fd);
+            TcpListener {
+                io: PollEvented::new(io),
+            }
+        }
+    }
+
+    impl TcpListener {
+        /// Converts this listener into a `std::net::TcpListener`.
+        ///
+        /// The listener is deregistered from the reactor and switched back to
+        /// blocking mode, so it can be handed to synchronous code.
+        ///
+        /// # Examples
+        ///
+        /// ```rust,no_run
+        /// use romio::tcp::TcpListener;
+        ///
+        /// # fn main () -> Result<(), Box<dyn std::error::Error + 'static>> {
+        /// let listener = TcpListener::bind(&"127.0.0.1:80".parse()?)?;
+        /// let std_listener = listener.into_std()?;
+        /// let (stream, _) = std_listener.accept()?;
+        /// # Ok(())}
+        /// ```
+        pub fn into_std(self) -> io::Result<std::net::TcpListener> {
+            let fd = self.io.into_inner()?.into_raw_fd();
+            let listener = unsafe { std::net::TcpListener::from_raw_fd(fd) };
+            listener.set_nonblocking(false)?;
+            Ok(listener)
+        }
+
+        /// Creates a new `TcpListener` with `SO_REUSEPORT` set, bound to the
+        /// specified address.
+        ///
+        /// Several listeners can be bound to the same address this way, as
+        /// long as all of them set the option and belong to the same user. On
+        /// Linux the kernel then distributes incoming connections across the
+        /// listeners, which allows a server to be scaled over several
+        /// processes or threads. The option must be set before binding, which
+        /// is why this is a separate constructor.
+        ///
+        /// Returns an error on platforms that do not support `SO_REUSEPORT`.
+        ///
+        /// # Examples
+        ///
+        /// ```rust,no_run
+        /// use romio::tcp::TcpListener;
+        ///
+        /// # fn main () -> Result<(), Box<dyn std::error::Error + 'static>> {
+        /// let addr = "0.0.0.0:8080".parse()?;
+        /// let first = TcpListener::bind_reuseport(&addr)?;
+        /// let second = TcpListener::bind_reuseport(&addr)?;
+        /// # Ok(())}
+        /// ```
+        pub fn bind_reuseport(addr: &SocketAddr) -> io::Result<TcpListener> {
+            TcpListenerBuilder::new().reuseport(true).bind(addr)
+        }
+
+        /// Gets the value of the `SO_REUSEPORT` option for this socket.
+        ///
+        /// For more information about this option, see [`bind_reuseport`].
+        ///
+        /// [`bind_reuseport`]: #method.bind_reuseport
+        pub fn reuseport(&self) -> io::Result<bool> {
+            socket::reuseport(self.as_raw_fd())
+        }
+
+        /// Sets the value of the `SO_REUSEPORT` option for this socket.
+        ///
+        /// Setting the option on a listener which is already bound only
+        /// affects sockets bound to the same address later on. To share an
+        /// address between listeners, create them with [`bind_reuseport`].
+        ///
+        /// [`bind_reuseport`]: #method.bind_reuseport
+        pub fn set_reuseport(&self, on: bool) -> io::Result<()> {
+            socket::set_reuseport(self.as_raw_fd(), on)
+        }
+
+        /// Gets the value of the `SO_RCVBUF` option for this socket.
+        ///
+        /// For more information about this option, see
+        /// [`set_recv_buffer_size`].
+        ///
+        /// [`set_recv_buffer_size`]: #method.set_recv_buffer_size
+        pub fn recv_buffer_size(&self) -> io::Result<usize> {
+            let size: libc::c_int =
+                socket::getsockopt(self.as_raw_fd(), libc::SOL_SOCKET, libc::SO_RCVBUF)?;
+            Ok(size as usize)
+        }
+
+        /// Sets the value of the `SO_RCVBUF` option for this socket.
+        ///
+        /// Accepted streams inherit the option from the listener. Setting it
+        /// here, rather than on every accepted stream, also lets the kernel
+        /// take the buffer size into account for the TCP window scale, which
+        /// is negotiated during the handshake.
+        ///
+        /// The kernel may adjust the value. Linux doubles it to make room for
+        /// its own bookkeeping, so the getter returns twice the size that was
+        /// set.
+        ///
+        /// # Examples
+        ///
+        /// ```rust,no_run
+        /// use romio::tcp::TcpListener;
+        ///
+        /// # fn main () -> Result<(), Box<dyn std::error::Error + 'static>> {
+        /// let addr = "0.0.0.0:8080".parse()?;
+        /// let listener = TcpListener::bind(&addr)?;
+        /// listener.set_recv_buffer_size(4 * 1024 * 1024)?;
+        /// # Ok(())}
+        /// ```
+        pub fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
+            let size = size.min(libc::c_int::MAX as usize) as libc::c_int;
+            socket::setsockopt(self.as_raw_fd(), libc::SOL_SOCKET, libc::SO_RCVBUF, size)
+        }
+
+        /// Gets the value of the `SO_SNDBUF` option for this socket.
+        ///
+        /// For more information about this option, see
+        /// [`set_send_buffer_size`].
+        ///
+        /// [`set_send_buffer_size`]: #method.set_send_buffer_size
+        pub fn send_buffer_size(&self) -> io::Result<usize> {
+            let size: libc::c_int =
+                socket::getsockopt(self.as_raw_fd(), libc::SOL_SOCKET, libc::SO_SNDBUF)?;
+            Ok(size as usize)
+        }
+
+        /// Sets the value of the `SO_SNDBUF` option for this socket.
+        ///
+        /// Accepted streams inherit the option from the listener.
+        ///
+        /// The kernel may adjust the value. Linux doubles it to make room for
+        /// its own bookkeeping, so the getter returns twice the size that was
+        /// set.
+        pub fn set_send_buffer_size(&self, size: usize) -> io::Result<()> {
+            let size = size.min(libc::c_int::MAX as usize) as libc::c_int;
+            socket::setsockopt(self.as_raw_fd(), libc::SOL_SOCKET, libc::SO_SNDBUF, size)
+        }
+
+        /// Grants temporary access to the underlying socket as a
+        /// [`socket2::Socket`], for configuration this type does not expose.
+        ///
+        /// This is an escape hatch: changes made through it bypass romio's own
+        /// view of the socket. Options that affect how the socket is driven,
+        /// such as its blocking mode, must be left alone, and the socket must
+        /// not be closed.
+        ///
+        /// [`socket2::Socket`]: https://docs.rs/socket2/0.3/socket2/struct.Socket.html
+        ///
+        /// # Examples
+        ///
+        /// ```rust,no_run
+        /// use romio::tcp::TcpListener;
+        ///
+        /// # fn main () -> Result<(), Box<dyn std::error::Error + 'static>> {
+        /// let addr = "127.0.0.1:0".parse()?;
+        /// let listener = TcpListener::bind(&addr)?;
+        ///
+        /// listener.set_socket2_options(|socket| socket.set_reuse_address(true))?;
+        /// # Ok(())}
+        /// ```
+        #[cfg(feature = "socket2")]
+        pub fn set_socket2_options<F>(&self, f: F) -> io::Result<()>
+        where
+            F: FnOnce(&socket2::Socket) -> io::Result<()>,
+        {
+            crate::socket::with_socket2(self.as_raw_fd(), f)
+        }
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    pub(super) fn set_fastopen(listener: &mio::net::TcpListener, queue: u32) -> io::Result<()> {
+        let queue = queue.min(libc::c_int::MAX as u32) as libc::c_int;
+        socket::setsockopt(
+            listener.as_raw_fd(),
+            libc::IPPROTO_TCP,
+            libc::TCP_FASTOPEN,
+            queue,
+        )
+    }
+
+    #[cfg(target_os = "linux")]
+    pub(super) use self::linux::*;
+
+    #[cfg(target_os = "linux")]
+    mod linux {
+        use std::io;
+        use std::os::unix::prelude::*;
+
+        use crate::socket;
+
+        pub(crate) fn attach_reuseport_bpf(
+            listener: &mio::net::TcpListener,
+            prog: &[libc::sock_filter],
+        ) -> io::Result<()> {
+            socket::attach_filter(listener.as_raw_fd(), libc::SO_ATTACH_REUSEPORT_CBPF, prog)
+        }
+
+        pub(crate) fn attach_reuseport_ebpf(
+            listener: &mio::net::TcpListener,
+            prog_fd: RawFd,
+        ) -> io::Result<()> {
+            socket::setsockopt(
+                listener.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_ATTACH_REUSEPORT_EBPF,
+                prog_fd,
+            )
+        }
+    }
+}
+
+#[cfg(windows)]
+mod sys_windows {
+    use super::TcpListener;
+    use std::os::windows::prelude::*;
+
+    impl TcpListener {
+        /// Grants temporary access to the underlying socket as a
+        /// [`socket2::Socket`], for configuration this type does not expose.
+        ///
+        /// This is an escape hatch: changes made through it bypass romio's own
+        /// view of the socket. Options that affect how the socket is driven,
+        /// such as its blocking mode, must be left alone, and the socket must
+        /// not be closed.
+        ///
+        /// [`socket2::Socket`]: https://docs.rs/socket2/0.3/socket2/struct.Socket.html
+        #[cfg(feature = "socket2")]
+        pub fn set_socket2_options<F>(&self, f: F) -> std::io::Result<()>
+        where
+            F: FnOnce(&socket2::Socket) -> std::io::Result<()>,
+        {
+            // The socket is still owned by `self`, so it must not be closed
+            // when the borrowed `Socket` goes out of scope.
+            let socket = unsafe { socket2::Socket::from_raw_socket(self.as_raw_socket()) };
+            f(&std::mem::ManuallyDrop::new(socket))
+        }
+    }
+
+    impl AsRawSocket for TcpListener {
+        fn as_raw_socket(&self) -> RawSocket {
+            self.raw_socket
+        }
+    }
+
+    impl FromRawSocket for TcpListener {
+        /// Creates a new `TcpListener` from a raw socket, and registers it
+        /// with the default reactor.
+        ///
+        /// # Safety
+        ///
+        /// `socket` must be a valid TCP socket, bound and listening, in
+        /// non-blocking mode. Ownership of `socket` is transferred to the
+        /// listener, which closes it when dropped.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the local address of `socket` cannot be determined.
+        unsafe fn from_raw_socket(socket: RawSocket) -> TcpListener {
+            let listener = std::net::TcpListener::from_raw_socket(socket);
+            TcpListener::from_std(listener).expect("failed to adopt listening socket")
+        }
+    }
 }
 
 impl TryFrom<std::net::TcpListener> for TcpListener {
     type Error = io::Error;
 
     fn try_from(socket: std::net::TcpListener) -> Result<Self, Self::Error> {
-        mio::net::TcpListener::from_std(socket).map(TcpListener::new)
+        TcpListener::from_std(socket)
+    }
+}
+
+/// The future returned by `TcpListener::accept`.
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct Accept<'a> {
+    listener: &'a mut TcpListener,
+}
+
+impl<'a> Future for Accept<'a> {
+    type Output = io::Result<(TcpStream, SocketAddr)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut *self.listener).poll_accept(cx)
     }
 }
 
 /// Stream returned by the `TcpListener::incoming` function representing the
 /// stream of sockets received from a listener.
+///
+/// Each item is an accepted socket along with the address of its peer, like
+/// [`TcpListener::poll_accept`] returns. The stream never terminates, so it
+/// can be used with `select!` as is.
+///
+/// [`TcpListener::poll_accept`]: struct.TcpListener.html#method.poll_accept
 #[must_use = "streams do nothing unless polled"]
-#[derive(Debug)]
 pub struct Incoming<'a> {
     inner: &'a mut TcpListener,
 }
 
+impl<'a> Incoming<'a> {
+    /// Returns a reference to the listener this stream accepts sockets from.
+    pub fn listener(&self) -> &TcpListener {
+        self.inner
+    }
+}
+
+impl<'a> fmt::Debug for Incoming<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut dbg = f.debug_struct("Incoming");
+        match self.inner.local_addr() {
+            Ok(addr) => dbg.field("local_addr", &addr),
+            Err(_) => dbg.field("listener", &self.inner),
+        };
+        dbg.finish()
+    }
+}
+
 impl<'a> Stream for Incoming<'a> {
-    type Item = io::Result<TcpStream>;
+    type Item = io::Result<(TcpStream, SocketAddr)>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        let (socket, _) = ready!(Pin::new(&mut *self.inner).poll_ready(cx)?);
-        Poll::Ready(Some(Ok(socket)))
+        let accepted = ready!(Pin::new(&mut *self.inner).poll_accept(cx)?);
+        Poll::Ready(Some(Ok(accepted)))
+    }
+}
+
+impl<'a> FusedStream for Incoming<'a> {
+    /// A listener keeps accepting connections until it is dropped.
+    fn is_terminated(&self) -> bool {
+        false
+    }
+}
+
+/// Future returned by the `TcpListener::accept_loop` function, which spawns a
+/// handler for every connection received by a listener.
+#[must_use = "futures do nothing unless polled"]
+pub struct AcceptLoop<S, F> {
+    listener: TcpListener,
+    spawner: S,
+    handler: F,
+}
+
+// Neither the spawner nor the handler are ever pinned.
+impl<S, F> Unpin for AcceptLoop<S, F> {}
+
+impl<S, F, Fut> Future for AcceptLoop<S, F>
+where
+    S: Spawn,
+    F: FnMut(TcpStream, SocketAddr) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    type Output = io::Result<Infallible>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        loop {
+            let (socket, addr) = ready!(Pin::new(&mut this.listener).poll_accept(cx)?);
+            let fut = (this.handler)(socket, addr);
+            if let Err(e) = this.spawner.spawn(fut) {
+                return Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, e)));
+            }
+        }
+    }
+}
+
+impl<S, F> fmt::Debug for AcceptLoop<S, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AcceptLoop")
+            .field("listener", &self.listener)
+            .finish()
     }
 }
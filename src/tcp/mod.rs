@@ -34,14 +34,31 @@
 //!
 //!     // accept connections and process them serially
 //!     while let Some(stream) = incoming.next().await {
-//!         say_hello(stream?).await;
+//!         let (stream, _) = stream?;
+//!         say_hello(stream).await;
 //!     }
 //!     Ok(())
 //! }
 //! ```
 
+mod buf_stream;
+mod builder;
 mod listener;
+mod split;
 mod stream;
 
-pub use self::listener::{Incoming, TcpListener};
-pub use self::stream::{ConnectFuture, TcpStream};
+pub use self::buf_stream::BufTcpStream;
+pub use self::builder::{TcpListenerBuilder, TcpStreamBuilder};
+pub use self::listener::{Accept, AcceptLoop, Incoming, TcpListener};
+pub use self::split::{OwnedReadHalf, OwnedWriteHalf, ReadHalf, ReuniteError, WriteHalf};
+pub use self::stream::{
+    ConnectAny, ConnectFuture, CorkVectoredWrite, Peek, Readable, TcpStream, Writable,
+};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use self::stream::RecvOob;
+#[cfg(unix)]
+pub use self::stream::SendOob;
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "macos"))]
+pub use self::stream::Sendfile;
+#[cfg(target_os = "linux")]
+pub use self::stream::{SendZerocopy, ZerocopyNotification};
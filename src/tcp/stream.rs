@@ -1,17 +1,26 @@
 use std::fmt;
-use std::io;
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "macos"))]
+use std::fs::File;
+use std::io::{self, IoSlice, IoSliceMut, Read, Write};
 use std::mem;
 use std::net::{Shutdown, SocketAddr};
+#[cfg(unix)]
+use std::os::unix::io::AsRawFd;
+#[cfg(windows)]
+use std::os::windows::io::{AsRawSocket, RawSocket};
 use std::pin::Pin;
 use std::task::{Context, Poll};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use async_ready::{AsyncReadReady, AsyncWriteReady};
 use futures::io::{AsyncRead, AsyncWrite};
-use futures::Future;
+use futures::{ready, Future};
 use mio;
 
+use super::split::{self, OwnedReadHalf, OwnedWriteHalf, ReadHalf, WriteHalf};
+use super::TcpStreamBuilder;
 use crate::raw::PollEvented;
+use crate::timer::Delay;
 
 /// A TCP stream between a local and a remote socket.
 ///
@@ -29,6 +38,12 @@ use crate::raw::PollEvented;
 /// [listener]: struct.TcpListener.html
 pub struct TcpStream {
     io: PollEvented<mio::net::TcpStream>,
+    /// Whether `SO_ZEROCOPY` has been enabled by `send_zerocopy`.
+    #[cfg(target_os = "linux")]
+    zerocopy: bool,
+    // mio does not expose the underlying socket on Windows.
+    #[cfg(windows)]
+    raw_socket: RawSocket,
 }
 
 /// The future returned by `TcpStream::connect`, which will resolve to a `TcpStream`
@@ -47,6 +62,105 @@ enum ConnectFutureState {
     Empty,
 }
 
+/// The future returned by `TcpStream::connect_any`, which will resolve to a
+/// `TcpStream` connected to one of the addresses.
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct ConnectAny {
+    addrs: std::vec::IntoIter<SocketAddr>,
+    timeout: Duration,
+    attempt: Option<(ConnectFuture, Delay)>,
+    error: Option<io::Error>,
+}
+
+/// The future returned by `TcpStream::send_oob`.
+#[cfg(unix)]
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct SendOob<'a> {
+    stream: &'a mut TcpStream,
+    byte: u8,
+}
+
+/// The future returned by `TcpStream::recv_oob`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct RecvOob<'a, 'b> {
+    stream: &'a mut TcpStream,
+    buf: &'b mut [u8],
+}
+
+/// The future returned by `TcpStream::peek`.
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct Peek<'a> {
+    stream: &'a mut TcpStream,
+    buf: &'a mut [u8],
+}
+
+/// The future returned by `TcpStream::readable`.
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct Readable<'a> {
+    stream: &'a mut TcpStream,
+}
+
+/// The future returned by `TcpStream::writable`.
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct Writable<'a> {
+    stream: &'a mut TcpStream,
+}
+
+/// The future returned by `TcpStream::cork_vectored_write`.
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct CorkVectoredWrite<'a> {
+    stream: &'a mut TcpStream,
+    bufs: &'a [IoSlice<'a>],
+    written: usize,
+    corked: bool,
+}
+
+/// The future returned by `TcpStream::send_zerocopy`.
+#[cfg(target_os = "linux")]
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct SendZerocopy<'a> {
+    stream: &'a mut TcpStream,
+    buf: &'a [u8],
+}
+
+/// The future returned by `TcpStream::sendfile`.
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "macos"))]
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct Sendfile<'a> {
+    stream: &'a mut TcpStream,
+    file: &'a File,
+    offset: u64,
+    remaining: usize,
+    sent: usize,
+}
+
+/// A completion notification for sends made with `TcpStream::send_zerocopy`.
+///
+/// The kernel numbers successful zerocopy sends on a socket consecutively,
+/// starting at 0. A notification reports that the sends numbered `first`
+/// through `last` (inclusive) have completed, so their buffers may be reused.
+#[cfg(target_os = "linux")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ZerocopyNotification {
+    /// Number of the first completed send.
+    pub first: u32,
+    /// Number of the last completed send.
+    pub last: u32,
+    /// Whether the kernel fell back to copying the data of these sends, for
+    /// example because they were routed over the loopback interface.
+    pub copied: bool,
+}
+
 impl Unpin for TcpStream {}
 
 impl TcpStream {
@@ -70,19 +184,123 @@ impl TcpStream {
     /// # }
     /// ```
     pub fn connect(addr: &SocketAddr) -> ConnectFuture {
-        use self::ConnectFutureState::*;
+        TcpStreamBuilder::new().connect(addr)
+    }
 
-        let inner = match mio::net::TcpStream::connect(addr) {
-            Ok(tcp) => Waiting(TcpStream::new(tcp)),
-            Err(e) => Error(e),
-        };
+    /// Create a new TCP stream connected to the first of `addrs` that accepts
+    /// the connection.
+    ///
+    /// The addresses are tried one at a time, in order, such as the `A` and
+    /// `AAAA` records of a host name. An attempt that has not completed after
+    /// a timeout, 250 milliseconds by default, is abandoned in favor of the
+    /// next address. The timeout can be changed with [`ConnectAny::timeout`].
+    ///
+    /// If every attempt fails, the error of the last one is returned. An
+    /// error of kind `InvalidInput` is returned if `addrs` is empty.
+    ///
+    /// [`ConnectAny::timeout`]: struct.ConnectAny.html#method.timeout
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(async_await)]
+    /// # use std::io;
+    /// use romio::tcp::TcpStream;
+    /// use std::net::ToSocketAddrs;
+    ///
+    /// # async fn connect_localhost() -> io::Result<TcpStream> {
+    /// let addrs = "localhost:8080".to_socket_addrs()?;
+    /// TcpStream::connect_any(addrs).await
+    /// # }
+    /// ```
+    pub fn connect_any<I>(addrs: I) -> ConnectAny
+    where
+        I: IntoIterator<Item = SocketAddr>,
+    {
+        ConnectAny {
+            addrs: addrs.into_iter().collect::<Vec<_>>().into_iter(),
+            timeout: Duration::from_millis(250),
+            attempt: None,
+            error: None,
+        }
+    }
 
-        ConnectFuture { inner }
+    // Shared with the halves returned by `split_borrowed` and `into_split`,
+    // which only have shared access to the stream.
+    pub(super) fn poll_read_priv(
+        &self,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        ready!(self.io.poll_read_ready(cx)?);
+
+        let mut io = self.io.get_ref();
+        match io.read(buf) {
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                self.io.clear_read_ready(cx)?;
+                Poll::Pending
+            }
+            r => Poll::Ready(r),
+        }
+    }
+
+    pub(super) fn poll_write_priv(
+        &self,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        ready!(self.io.poll_write_ready(cx)?);
+
+        let mut io = self.io.get_ref();
+        match io.write(buf) {
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                self.io.clear_write_ready(cx)?;
+                Poll::Pending
+            }
+            r => Poll::Ready(r),
+        }
+    }
+
+    /// Hands `stream` over to mio, starting to connect it to `addr` if given,
+    /// and registers it with the default reactor.
+    pub(super) fn register(
+        stream: std::net::TcpStream,
+        addr: Option<&SocketAddr>,
+    ) -> io::Result<TcpStream> {
+        #[cfg(windows)]
+        let raw_socket = stream.as_raw_socket();
+        let io = match addr {
+            Some(addr) => mio::net::TcpStream::connect_stream(stream, addr)?,
+            None => mio::net::TcpStream::from_stream(stream)?,
+        };
+        Ok(TcpStream {
+            io: PollEvented::new(io),
+            #[cfg(target_os = "linux")]
+            zerocopy: false,
+            #[cfg(windows)]
+            raw_socket,
+        })
     }
 
-    pub(crate) fn new(connected: mio::net::TcpStream) -> TcpStream {
-        let io = PollEvented::new(connected);
-        TcpStream { io }
+    /// Creates a new `TcpStream` from a connected `std::net::TcpStream`.
+    ///
+    /// The stream is switched to non-blocking mode and registered with the
+    /// default reactor. This is the same as the `TryFrom` conversion.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use romio::tcp::TcpStream;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let std_stream = std::net::TcpStream::connect("127.0.0.1:8080")?;
+    /// let stream = TcpStream::from_std(std_stream)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_std(stream: std::net::TcpStream) -> io::Result<TcpStream> {
+        stream.set_nonblocking(true)?;
+        TcpStream::register(stream, None)
     }
 
     /// Returns the local address that this stream is bound to.
@@ -131,7 +349,16 @@ impl TcpStream {
     ///
     /// This function will cause all pending and future I/O on the specified
     /// portions to return immediately with an appropriate value (see the
-    /// documentation of `Shutdown`).
+    /// documentation of `Shutdown`):
+    ///
+    /// - `Shutdown::Write` sends a FIN to the peer, which will read the end
+    ///   of the stream once it has received all data written before. Further
+    ///   writes fail, but the stream can still be read from.
+    /// - `Shutdown::Read` makes further reads return the end of the stream.
+    ///   Nothing is sent to the peer, which can keep writing.
+    /// - `Shutdown::Both` does both of the above.
+    ///
+    /// Shutting down never blocks, so this is not an `async fn`.
     ///
     /// # Examples
     ///
@@ -151,6 +378,283 @@ impl TcpStream {
         self.io.get_ref().shutdown(how)
     }
 
+    /// Shuts down the read, write, or both halves of this connection from a
+    /// poll-based context.
+    ///
+    /// This always completes immediately. For more information, see
+    /// [`shutdown`].
+    ///
+    /// [`shutdown`]: #method.shutdown
+    pub fn poll_shutdown(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        how: Shutdown,
+    ) -> Poll<io::Result<()>> {
+        Poll::Ready(self.shutdown(how))
+    }
+
+    /// Splits the stream into a reading and a writing half, which borrow it.
+    ///
+    /// The halves can be driven concurrently, for example from the two
+    /// branches of a `join`, but not moved into separate tasks. For that, see
+    /// [`into_split`].
+    ///
+    /// [`into_split`]: #method.into_split
+    ///
+    /// This is not named `split`, so that it doesn't clash with
+    /// `AsyncReadExt::split`, which takes the stream by value.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// use futures::io::AsyncReadExt;
+    /// use romio::tcp::TcpStream;
+    ///
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let mut stream = TcpStream::connect(&"127.0.0.1:8080".parse()?).await?;
+    /// let (mut reader, mut writer) = stream.split_borrowed();
+    ///
+    /// // echo everything back to the peer
+    /// reader.copy_into(&mut writer).await?;
+    /// # Ok(())}
+    /// ```
+    pub fn split_borrowed(&mut self) -> (ReadHalf<'_>, WriteHalf<'_>) {
+        split::split(self)
+    }
+
+    /// Splits the stream into a reading and a writing half, which own it.
+    ///
+    /// Both halves share the stream, which is closed once both of them are
+    /// dropped, and each of them can be moved into a task of its own. They
+    /// can be put back together with [`OwnedReadHalf::reunite`].
+    ///
+    /// [`OwnedReadHalf::reunite`]: struct.OwnedReadHalf.html#method.reunite
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// use futures::executor::ThreadPool;
+    /// use futures::io::{AsyncReadExt, AsyncWriteExt};
+    /// use futures::task::SpawnExt;
+    /// use romio::tcp::TcpStream;
+    ///
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let mut pool = ThreadPool::new()?;
+    /// let stream = TcpStream::connect(&"127.0.0.1:8080".parse()?).await?;
+    /// let (mut reader, mut writer) = stream.into_split();
+    ///
+    /// pool.spawn(async move {
+    ///     let _ = writer.write_all(b"hello").await;
+    /// })?;
+    ///
+    /// let mut buf = vec![0; 1024];
+    /// let n = reader.read(&mut buf).await?;
+    /// # Ok(())}
+    /// ```
+    pub fn into_split(self) -> (OwnedReadHalf, OwnedWriteHalf) {
+        split::into_split(self)
+    }
+
+    /// Receives data on the socket without removing it from the queue. On
+    /// success, returns the number of bytes peeked.
+    ///
+    /// Successive calls return the same data, until it is consumed with a
+    /// read. Like a read, this returns 0 once the peer has shut down its
+    /// writing half and all data has been received.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// use futures::prelude::*;
+    /// use romio::tcp::TcpStream;
+    ///
+    /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let addr = "127.0.0.1:8080".parse()?;
+    /// let mut stream = TcpStream::connect(&addr).await?;
+    ///
+    /// // a TLS handshake starts with a record of type 22
+    /// let mut byte = [0; 1];
+    /// let n = stream.peek(&mut byte).await?;
+    /// let is_tls = n == 1 && byte[0] == 22;
+    ///
+    /// let mut buf = vec![0; 1024];
+    /// stream.read(&mut buf).await?;
+    /// # Ok(())}
+    /// ```
+    pub fn peek<'a>(&'a mut self, buf: &'a mut [u8]) -> Peek<'a> {
+        Peek { stream: self, buf }
+    }
+
+    /// Attempts to receive data on the socket without removing it from the
+    /// queue.
+    ///
+    /// For more information, see [`peek`].
+    ///
+    /// [`peek`]: #method.peek
+    pub fn poll_peek(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        ready!(self.io.poll_read_ready(cx)?);
+
+        match self.io.get_ref().peek(buf) {
+            Ok(n) => Poll::Ready(Ok(n)),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                self.io.clear_read_ready(cx)?;
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    /// Reads data from the stream, without waiting for it to become readable.
+    /// On success, returns the number of bytes read.
+    ///
+    /// This is meant to be called after [`readable`] resolves. If no data is
+    /// available, an error of kind `WouldBlock` is returned and the read
+    /// readiness of the stream is cleared, so that awaiting `readable` again
+    /// waits for new data. This does not register the current task for
+    /// wakeup.
+    ///
+    /// [`readable`]: #method.readable
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// use romio::tcp::TcpStream;
+    /// use std::io;
+    ///
+    /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let addr = "127.0.0.1:8080".parse()?;
+    /// let mut stream = TcpStream::connect(&addr).await?;
+    /// let mut buf = vec![0; 1024];
+    ///
+    /// loop {
+    ///     stream.readable().await?;
+    ///     match stream.try_read(&mut buf) {
+    ///         Ok(0) => break,
+    ///         Ok(n) => println!("read {} bytes", n),
+    ///         Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+    ///         Err(e) => return Err(e.into()),
+    ///     }
+    /// }
+    /// # Ok(())}
+    /// ```
+    pub fn try_read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.io.try_read_with(|mut io| io.read(buf))
+    }
+
+    /// Writes data to the stream, without waiting for it to become writable.
+    /// On success, returns the number of bytes written.
+    ///
+    /// This is meant to be called after [`writable`] resolves. If the send
+    /// buffer is full, an error of kind `WouldBlock` is returned and the write
+    /// readiness of the stream is cleared, so that awaiting `writable` again
+    /// waits for room in the send buffer. This does not register the current
+    /// task for wakeup.
+    ///
+    /// [`writable`]: #method.writable
+    pub fn try_write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.io.try_write_with(|mut io| io.write(buf))
+    }
+
+    /// Waits for the stream to become readable.
+    ///
+    /// The readiness is kept until [`try_read`] returns `WouldBlock`, so
+    /// calling this again before then completes immediately. Readiness may be
+    /// spurious, in which case `try_read` returns `WouldBlock` and the next
+    /// call waits for a new readiness event.
+    ///
+    /// [`try_read`]: #method.try_read
+    pub fn readable(&mut self) -> Readable<'_> {
+        Readable { stream: self }
+    }
+
+    /// Waits for the stream to become writable.
+    ///
+    /// The readiness is kept until [`try_write`] returns `WouldBlock`, so
+    /// calling this again before then completes immediately.
+    ///
+    /// [`try_write`]: #method.try_write
+    pub fn writable(&mut self) -> Writable<'_> {
+        Writable { stream: self }
+    }
+
+    /// Attempts to read from the stream into a slice of buffers, filling each
+    /// buffer in turn. On success, returns the number of bytes read.
+    ///
+    /// On Unix this is a single `readv`-style call, so data is never left
+    /// behind in the socket when it would fit in a later buffer. Other
+    /// platforms only read into the first non-empty buffer.
+    ///
+    /// This is also used by the `AsyncRead` implementation, so the future
+    /// returned by `AsyncReadExt::read_vectored` can be used to call it from
+    /// an `async` block.
+    pub fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<io::Result<usize>> {
+        ready!(self.io.poll_read_ready(cx)?);
+
+        #[cfg(unix)]
+        let res = sys::read_vectored(self.io.get_ref(), bufs);
+        #[cfg(not(unix))]
+        let res = {
+            let mut io = self.io.get_ref();
+            io.read_vectored(bufs)
+        };
+
+        match res {
+            Ok(n) => Poll::Ready(Ok(n)),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                self.io.clear_read_ready(cx)?;
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    /// Attempts to write a slice of buffers to the stream, gathering them in
+    /// order. On success, returns the number of bytes written.
+    ///
+    /// On Unix this is a single `writev`-style call, so the buffers are sent
+    /// without being copied into one first. Other platforms only write the
+    /// first non-empty buffer.
+    ///
+    /// This is also used by the `AsyncWrite` implementation, so the future
+    /// returned by `AsyncWriteExt::write_vectored` can be used to call it
+    /// from an `async` block.
+    pub fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        ready!(self.io.poll_write_ready(cx)?);
+
+        #[cfg(unix)]
+        let res = sys::write_vectored(self.io.get_ref(), bufs);
+        #[cfg(not(unix))]
+        let res = {
+            let mut io = self.io.get_ref();
+            io.write_vectored(bufs)
+        };
+
+        match res {
+            Ok(n) => Poll::Ready(Ok(n)),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                self.io.clear_write_ready(cx)?;
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
     /// Gets the value of the `TCP_NODELAY` option on this socket.
     ///
     /// For more information about this option, see [`set_nodelay`].
@@ -229,6 +733,9 @@ impl TcpStream {
     /// Changes the size of the operating system's receive buffer associated
     /// with the socket.
     ///
+    /// The kernel may adjust the value. Linux doubles it to make room for its
+    /// own bookkeeping, so the getter returns twice the size that was set.
+    ///
     /// # Examples
     ///
     /// ```rust
@@ -275,6 +782,9 @@ impl TcpStream {
     /// Changes the size of the operating system's send buffer associated with
     /// the socket.
     ///
+    /// The kernel may adjust the value. Linux doubles it to make room for its
+    /// own bookkeeping, so the getter returns twice the size that was set.
+    ///
     /// # Examples
     ///
     /// ```rust
@@ -349,6 +859,90 @@ impl TcpStream {
         self.io.get_ref().set_keepalive(keepalive)
     }
 
+    /// Enables keepalive messages on this socket and sets all of their
+    /// parameters.
+    ///
+    /// This sets `SO_KEEPALIVE`, the time to remain idle before the first
+    /// probe (`TCP_KEEPIDLE`, or `TCP_KEEPALIVE` on macOS and iOS), the time
+    /// between probes (`TCP_KEEPINTVL`) and the number of unanswered probes
+    /// after which the connection is dropped (`TCP_KEEPCNT`).
+    ///
+    /// The durations are specified in seconds, so sub-second specifications
+    /// are omitted.
+    ///
+    /// This function is not available on OpenBSD, which only supports
+    /// system-wide keepalive parameters.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #![feature(async_await)]
+    /// use romio::tcp::TcpStream;
+    /// use std::time::Duration;
+    ///
+    /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let addr = "127.0.0.1:8080".parse()?;
+    /// let stream = TcpStream::connect(&addr).await?;
+    ///
+    /// stream.set_keepalive_params(Duration::from_secs(60), Duration::from_secs(10), 5)?;
+    /// # Ok(())}
+    /// ```
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "netbsd",
+        target_os = "illumos",
+        target_os = "solaris"
+    ))]
+    pub fn set_keepalive_params(
+        &self,
+        idle: Duration,
+        interval: Duration,
+        retries: u32,
+    ) -> io::Result<()> {
+        sys::set_keepalive_params(self.io.get_ref(), idle, interval, retries)
+    }
+
+    /// Sets the close-on-exec flag on the stream's file descriptor, so that
+    /// it is closed in child processes after `exec`.
+    ///
+    /// Streams returned by `connect` or accepted from a `TcpListener` already
+    /// have the flag set. A stream created with `from_std` or `from_raw_fd`
+    /// keeps the flag of the descriptor it was given, which may have been
+    /// opened without it, and would then leak the connection into every
+    /// process spawned afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use romio::tcp::TcpStream;
+    ///
+    /// # fn run() -> std::io::Result<()> {
+    /// let std_stream = std::net::TcpStream::connect("127.0.0.1:8080")?;
+    /// let stream = TcpStream::from_std(std_stream)?;
+    /// stream.set_cloexec()?;
+    /// # Ok(())}
+    /// ```
+    #[cfg(unix)]
+    pub fn set_cloexec(&self) -> io::Result<()> {
+        crate::socket::set_cloexec(self.as_raw_fd(), true)
+    }
+
+    /// Clears the close-on-exec flag on the stream's file descriptor, so that
+    /// a child process can keep using the connection after `exec`.
+    ///
+    /// For more information about this flag, see [`set_cloexec`].
+    ///
+    /// [`set_cloexec`]: #method.set_cloexec
+    #[cfg(unix)]
+    pub fn clear_cloexec(&self) -> io::Result<()> {
+        crate::socket::set_cloexec(self.as_raw_fd(), false)
+    }
+
     /// Gets the value of the `IP_TTL` option for this socket.
     ///
     /// For more information about this option, see [`set_ttl`].
@@ -450,33 +1044,526 @@ impl TcpStream {
     pub fn set_linger(&self, dur: Option<Duration>) -> io::Result<()> {
         self.io.get_ref().set_linger(dur)
     }
-}
 
-impl AsyncRead for TcpStream {
-    fn poll_read(
-        mut self: Pin<&mut Self>,
-        cx: &mut Context<'_>,
-        buf: &mut [u8],
-    ) -> Poll<io::Result<usize>> {
-        Pin::new(&mut self.io).poll_read(cx, buf)
+    /// Gets the value of the `SO_ERROR` option on this socket.
+    ///
+    /// This will retrieve the stored error in the underlying socket, clearing
+    /// the field in the process. This is where the outcome of a non-blocking
+    /// connect is reported, and where errors such as a reset by the peer are
+    /// stored until the next read or write.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// use romio::tcp::TcpStream;
+    ///
+    /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let addr = "127.0.0.1:8080".parse()?;
+    /// let stream = TcpStream::connect(&addr).await?;
+    ///
+    /// if let Some(err) = stream.take_error()? {
+    ///     println!("socket error: {}", err);
+    /// }
+    /// # Ok(())}
+    /// ```
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        self.io.get_ref().take_error()
     }
-}
 
-impl AsyncWrite for TcpStream {
-    fn poll_write(
-        mut self: Pin<&mut Self>,
-        cx: &mut Context<'_>,
-        buf: &[u8],
+    /// Sends a single byte of urgent ("out-of-band") data with `MSG_OOB`.
+    /// On success, returns the number of bytes written.
+    ///
+    /// The byte is sent in order with the regular data, but the peer is
+    /// notified of it through the TCP urgent pointer and can read it
+    /// separately with [`recv_oob`] on Linux and Android. This is only needed
+    /// by legacy protocols such as Telnet.
+    ///
+    /// [`recv_oob`]: #method.recv_oob
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// use romio::tcp::TcpStream;
+    ///
+    /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let addr = "127.0.0.1:23".parse()?;
+    /// let mut stream = TcpStream::connect(&addr).await?;
+    ///
+    /// // Telnet "Data Mark"
+    /// stream.send_oob(242).await?;
+    /// # Ok(())}
+    /// ```
+    #[cfg(unix)]
+    pub fn send_oob(&mut self, byte: u8) -> SendOob<'_> {
+        SendOob { stream: self, byte }
+    }
+
+    /// Receives urgent ("out-of-band") data with `MSG_OOB`. On success,
+    /// returns the number of bytes read, which is at most 1.
+    ///
+    /// The returned future waits until the peer sends urgent data with
+    /// [`send_oob`], which the kernel signals as priority readiness. Waiting
+    /// does not consume the read readiness of the stream, so this can be
+    /// awaited alongside regular reads.
+    ///
+    /// If `SO_OOBINLINE` is set on the socket, urgent data is received in
+    /// line with the regular data instead, and this fails with `EINVAL`. The
+    /// same error is returned if the peer shuts down the connection without
+    /// sending urgent data.
+    ///
+    /// This function is only available on Linux and Android.
+    ///
+    /// [`send_oob`]: #method.send_oob
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// use romio::tcp::TcpStream;
+    ///
+    /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let addr = "127.0.0.1:23".parse()?;
+    /// let mut stream = TcpStream::connect(&addr).await?;
+    ///
+    /// let mut buf = [0; 1];
+    /// stream.recv_oob(&mut buf).await?;
+    /// # Ok(())}
+    /// ```
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn recv_oob<'a, 'b>(&'a mut self, buf: &'b mut [u8]) -> RecvOob<'a, 'b> {
+        RecvOob { stream: self, buf }
+    }
+
+    /// Attempts to send a single byte of urgent data with `MSG_OOB`.
+    ///
+    /// For more information, see [`send_oob`].
+    ///
+    /// [`send_oob`]: #method.send_oob
+    #[cfg(unix)]
+    pub fn poll_send_oob(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        byte: u8,
+    ) -> Poll<io::Result<usize>> {
+        ready!(self.io.poll_write_ready(cx)?);
+
+        match sys::send_oob(self.io.get_ref(), byte) {
+            Ok(n) => Poll::Ready(Ok(n)),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                Pin::new(&mut self.io).clear_write_ready(cx)?;
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    /// Attempts to receive urgent data with `MSG_OOB`.
+    ///
+    /// For more information, see [`recv_oob`].
+    ///
+    /// [`recv_oob`]: #method.recv_oob
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn poll_recv_oob(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        ready!(self.io.poll_priority_ready(cx)?);
+
+        match sys::recv_oob(self.io.get_ref(), buf) {
+            Ok(n) => {
+                // The urgent byte has been consumed, so the next call waits
+                // for new urgent data.
+                self.io.clear_priority_ready(cx)?;
+                Poll::Ready(Ok(n))
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                self.io.clear_priority_ready(cx)?;
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    /// Writes all of `bufs` to the stream while it is corked, so that the
+    /// kernel coalesces them into as few segments as possible. On success,
+    /// returns the number of bytes written.
+    ///
+    /// The stream is corked with `TCP_CORK` when the returned future is first
+    /// polled, and uncorked once it completes, fails or is dropped, at which
+    /// point any partial segment is sent. On platforms other than Linux the
+    /// buffers are written without corking.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// use romio::tcp::TcpStream;
+    /// use std::io::IoSlice;
+    ///
+    /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let addr = "127.0.0.1:8080".parse()?;
+    /// let mut stream = TcpStream::connect(&addr).await?;
+    ///
+    /// let header = [0, 12];
+    /// let bufs = [IoSlice::new(&header), IoSlice::new(b"hello, world")];
+    /// stream.cork_vectored_write(&bufs).await?;
+    /// # Ok(())}
+    /// ```
+    pub fn cork_vectored_write<'a>(&'a mut self, bufs: &'a [IoSlice<'a>]) -> CorkVectoredWrite<'a> {
+        CorkVectoredWrite {
+            stream: self,
+            bufs,
+            written: 0,
+            corked: false,
+        }
+    }
+
+    /// Gets the value of the `TCP_WINDOW_CLAMP` option for this socket.
+    ///
+    /// For more information about this option, see [`set_window_clamp`].
+    ///
+    /// [`set_window_clamp`]: #method.set_window_clamp
+    #[cfg(target_os = "linux")]
+    pub fn window_clamp(&self) -> io::Result<u32> {
+        sys::window_clamp(self.io.get_ref())
+    }
+
+    /// Sets the value of the `TCP_WINDOW_CLAMP` option on this socket.
+    ///
+    /// This caps the receive window advertised to the peer at `bytes`, which
+    /// throttles how fast the peer can send. Values below a small minimum
+    /// (half of the kernel's `SOCK_MIN_RCVBUF`, roughly 1 KiB) are raised to
+    /// that minimum.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// use romio::tcp::TcpStream;
+    ///
+    /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let addr = "127.0.0.1:8080".parse()?;
+    /// let stream = TcpStream::connect(&addr).await?;
+    ///
+    /// stream.set_window_clamp(64 * 1024)?;
+    /// assert_eq!(stream.window_clamp()?, 64 * 1024);
+    /// # Ok(())}
+    /// ```
+    #[cfg(target_os = "linux")]
+    pub fn set_window_clamp(&self, bytes: u32) -> io::Result<()> {
+        sys::set_window_clamp(self.io.get_ref(), bytes)
+    }
+
+    /// Removes the window clamp set with [`set_window_clamp`], by setting
+    /// `TCP_WINDOW_CLAMP` to 0.
+    ///
+    /// The kernel only accepts this while the socket is not connected, so on
+    /// an established stream this fails with `EINVAL`.
+    ///
+    /// [`set_window_clamp`]: #method.set_window_clamp
+    #[cfg(target_os = "linux")]
+    pub fn reset_window_clamp(&self) -> io::Result<()> {
+        sys::set_window_clamp(self.io.get_ref(), 0)
+    }
+
+    /// Gets the value of the `TCP_RTO_MAX_MS` option for this socket.
+    ///
+    /// For more information about this option, see [`set_rto_max`].
+    ///
+    /// [`set_rto_max`]: #method.set_rto_max
+    #[cfg(target_os = "linux")]
+    pub fn rto_max(&self) -> io::Result<Duration> {
+        sys::rto_max(self.io.get_ref())
+    }
+
+    /// Sets the value of the `TCP_RTO_MAX_MS` option on this socket.
+    ///
+    /// This caps the retransmission timeout, which otherwise backs off
+    /// exponentially up to 120 seconds on an unresponsive connection. The
+    /// value has millisecond resolution and must lie between 1 and 120
+    /// seconds.
+    ///
+    /// This option requires Linux 6.15 or later.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// use romio::tcp::TcpStream;
+    /// use std::time::Duration;
+    ///
+    /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let addr = "127.0.0.1:8080".parse()?;
+    /// let stream = TcpStream::connect(&addr).await?;
+    ///
+    /// stream.set_rto_max(Duration::from_secs(5))?;
+    /// assert_eq!(stream.rto_max()?, Duration::from_secs(5));
+    /// # Ok(())}
+    /// ```
+    #[cfg(target_os = "linux")]
+    pub fn set_rto_max(&self, dur: Duration) -> io::Result<()> {
+        sys::set_rto_max(self.io.get_ref(), dur)
+    }
+
+    /// Sets the value of the `TCP_RTO_MIN_US` option on this socket.
+    ///
+    /// This sets the lower bound of the retransmission timeout, which defaults
+    /// to 200 milliseconds. The value has microsecond resolution.
+    ///
+    /// This option requires Linux 6.15 or later.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// use romio::tcp::TcpStream;
+    /// use std::time::Duration;
+    ///
+    /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let addr = "127.0.0.1:8080".parse()?;
+    /// let stream = TcpStream::connect(&addr).await?;
+    ///
+    /// stream.set_rto_min(Duration::from_millis(5))?;
+    /// # Ok(())}
+    /// ```
+    #[cfg(target_os = "linux")]
+    pub fn set_rto_min(&self, dur: Duration) -> io::Result<()> {
+        sys::set_rto_min(self.io.get_ref(), dur)
+    }
+
+    /// Sends data on the stream with `MSG_ZEROCOPY`, so that the kernel
+    /// transmits it directly from `buf` instead of copying it first. On
+    /// success, returns the number of bytes written.
+    ///
+    /// `SO_ZEROCOPY` is enabled on the socket before the first send. The send
+    /// completes asynchronously: `buf` must remain valid and unmodified until
+    /// a notification covering it is read with [`recv_zerocopy_completion`].
+    /// Sends are numbered consecutively from 0 for the purpose of matching
+    /// them against notifications.
+    ///
+    /// Zerocopy only pays off for large writes; the kernel may fail the send
+    /// with `ENOBUFS` if too many notifications are outstanding.
+    ///
+    /// [`recv_zerocopy_completion`]: #method.recv_zerocopy_completion
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// use romio::tcp::TcpStream;
+    ///
+    /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let addr = "127.0.0.1:8080".parse()?;
+    /// let mut stream = TcpStream::connect(&addr).await?;
+    ///
+    /// let buf = vec![0; 1024 * 1024];
+    /// stream.send_zerocopy(&buf).await?;
+    /// # Ok(())}
+    /// ```
+    #[cfg(target_os = "linux")]
+    pub fn send_zerocopy<'a>(&'a mut self, buf: &'a [u8]) -> SendZerocopy<'a> {
+        SendZerocopy { stream: self, buf }
+    }
+
+    /// Attempts to send data on the stream with `MSG_ZEROCOPY`.
+    ///
+    /// For more information, see [`send_zerocopy`].
+    ///
+    /// [`send_zerocopy`]: #method.send_zerocopy
+    #[cfg(target_os = "linux")]
+    pub fn poll_send_zerocopy(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        ready!(self.io.poll_write_ready(cx)?);
+
+        if !self.zerocopy {
+            sys::set_zerocopy(self.io.get_ref())?;
+            self.zerocopy = true;
+        }
+        match sys::send_zerocopy(self.io.get_ref(), buf) {
+            Ok(n) => Poll::Ready(Ok(n)),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                Pin::new(&mut self.io).clear_write_ready(cx)?;
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    /// Reads a completion notification for sends made with [`send_zerocopy`]
+    /// from the error queue of the socket.
+    ///
+    /// Returns an error of kind `WouldBlock` if no notification is pending.
+    /// To wait for one, use [`poll_recv_zerocopy_completion`].
+    ///
+    /// [`send_zerocopy`]: #method.send_zerocopy
+    /// [`poll_recv_zerocopy_completion`]: #method.poll_recv_zerocopy_completion
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// use romio::tcp::TcpStream;
+    ///
+    /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let addr = "127.0.0.1:8080".parse()?;
+    /// let mut stream = TcpStream::connect(&addr).await?;
+    ///
+    /// let buf = vec![0; 1024 * 1024];
+    /// stream.send_zerocopy(&buf).await?;
+    ///
+    /// let notification = stream.recv_zerocopy_completion()?;
+    /// println!("sends {} to {} completed", notification.first, notification.last);
+    /// # Ok(())}
+    /// ```
+    #[cfg(target_os = "linux")]
+    pub fn recv_zerocopy_completion(&mut self) -> io::Result<ZerocopyNotification> {
+        sys::recv_zerocopy_completion(self.io.get_ref())
+    }
+
+    /// Attempts to read a completion notification for sends made with
+    /// [`send_zerocopy`], waiting for the error queue of the socket to become
+    /// readable if none is pending.
+    ///
+    /// For more information, see [`recv_zerocopy_completion`].
+    ///
+    /// [`send_zerocopy`]: #method.send_zerocopy
+    /// [`recv_zerocopy_completion`]: #method.recv_zerocopy_completion
+    #[cfg(target_os = "linux")]
+    pub fn poll_recv_zerocopy_completion(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<ZerocopyNotification>> {
+        ready!(self.io.poll_error_ready(cx)?);
+
+        match sys::recv_zerocopy_completion(self.io.get_ref()) {
+            Ok(notification) => Poll::Ready(Ok(notification)),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                self.io.clear_error_ready(cx)?;
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    /// Sends `count` bytes of `file`, starting at `offset`, on the stream with
+    /// `sendfile`. On success, returns the number of bytes sent.
+    ///
+    /// The data is copied from the page cache to the socket by the kernel,
+    /// without passing through user space, which makes this the fastest way
+    /// to serve static files. Fewer than `count` bytes are sent only if the
+    /// file ends first, or if an error occurs after some of the data was
+    /// sent; like for a partial write, the error is then returned by the next
+    /// call. The position of `file` is not changed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// use romio::tcp::TcpStream;
+    /// use std::fs::File;
+    ///
+    /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let addr = "127.0.0.1:8080".parse()?;
+    /// let mut stream = TcpStream::connect(&addr).await?;
+    ///
+    /// let file = File::open("index.html")?;
+    /// let len = file.metadata()?.len() as usize;
+    /// stream.sendfile(&file, 0, len).await?;
+    /// # Ok(())}
+    /// ```
+    #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "macos"))]
+    pub fn sendfile<'a>(&'a mut self, file: &'a File, offset: u64, count: usize) -> Sendfile<'a> {
+        Sendfile {
+            stream: self,
+            file,
+            offset,
+            remaining: count,
+            sent: 0,
+        }
+    }
+
+    /// Attempts to send up to `count` bytes of `file`, starting at `offset`,
+    /// on the stream with `sendfile`.
+    ///
+    /// Unlike [`sendfile`], this makes a single attempt, which may send fewer
+    /// than `count` bytes. A return value of 0 means that `offset` is at or
+    /// past the end of the file.
+    ///
+    /// [`sendfile`]: #method.sendfile
+    #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "macos"))]
+    pub fn poll_sendfile(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        file: &File,
+        offset: u64,
+        count: usize,
+    ) -> Poll<io::Result<usize>> {
+        if count == 0 {
+            return Poll::Ready(Ok(0));
+        }
+        ready!(self.io.poll_write_ready(cx)?);
+
+        match sys::sendfile(self.io.get_ref(), file, offset, count) {
+            Ok(n) => Poll::Ready(Ok(n)),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                Pin::new(&mut self.io).clear_write_ready(cx)?;
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+}
+
+impl AsyncRead for TcpStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.io).poll_read(cx, buf)
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<io::Result<usize>> {
+        TcpStream::poll_read_vectored(self, cx, bufs)
+    }
+}
+
+impl AsyncWrite for TcpStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
     ) -> Poll<io::Result<usize>> {
         Pin::new(&mut self.io).poll_write(cx, buf)
     }
 
-    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
-        Pin::new(&mut self.io).poll_flush(cx)
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        TcpStream::poll_write_vectored(self, cx, bufs)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        // Writes go straight to the kernel, which takes care of buffering.
+        Poll::Ready(Ok(()))
     }
 
-    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
-        Pin::new(&mut self.io).poll_close(cx)
+    /// Shuts down the writing portion of the stream, which signals the end of
+    /// the data to the peer. The stream can still be read from.
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_shutdown(cx, Shutdown::Write)
     }
 }
 
@@ -531,6 +1618,17 @@ impl fmt::Debug for TcpStream {
     }
 }
 
+impl ConnectFuture {
+    /// Waits for `stream`, whose connection is in progress, to connect.
+    pub(super) fn new(stream: io::Result<TcpStream>) -> ConnectFuture {
+        let inner = match stream {
+            Ok(stream) => ConnectFutureState::Waiting(stream),
+            Err(e) => ConnectFutureState::Error(e),
+        };
+        ConnectFuture { inner }
+    }
+}
+
 impl Future for ConnectFuture {
     type Output = io::Result<TcpStream>;
 
@@ -560,12 +1658,212 @@ impl Future for ConnectFuture {
     }
 }
 
+impl ConnectAny {
+    /// Sets how long to wait for each address before moving on to the next.
+    ///
+    /// This only affects attempts which haven't started yet.
+    pub fn timeout(mut self, timeout: Duration) -> ConnectAny {
+        self.timeout = timeout;
+        self
+    }
+}
+
+impl Future for ConnectAny {
+    type Output = io::Result<TcpStream>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<TcpStream>> {
+        let this = &mut *self;
+
+        loop {
+            if let Some((connect, delay)) = &mut this.attempt {
+                match Pin::new(connect).poll(cx) {
+                    Poll::Ready(Ok(stream)) => return Poll::Ready(Ok(stream)),
+                    Poll::Ready(Err(e)) => this.error = Some(e),
+                    Poll::Pending => match Pin::new(delay).poll(cx)? {
+                        Poll::Ready(()) => {
+                            this.error = Some(io::Error::new(
+                                io::ErrorKind::TimedOut,
+                                "connection attempt timed out",
+                            ))
+                        }
+                        Poll::Pending => return Poll::Pending,
+                    },
+                }
+                this.attempt = None;
+            }
+
+            let addr = match this.addrs.next() {
+                Some(addr) => addr,
+                None => {
+                    let e = this.error.take().unwrap_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidInput, "no addresses to connect to")
+                    });
+                    return Poll::Ready(Err(e));
+                }
+            };
+            let delay = Delay::new(Instant::now() + this.timeout)?;
+            this.attempt = Some((TcpStream::connect(&addr), delay));
+        }
+    }
+}
+
+#[cfg(unix)]
+impl<'a> Future for SendOob<'a> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<usize>> {
+        let byte = self.byte;
+        Pin::new(&mut *self.stream).poll_send_oob(cx, byte)
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl<'a, 'b> Future for RecvOob<'a, 'b> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<usize>> {
+        let RecvOob { stream, buf } = &mut *self;
+        Pin::new(&mut **stream).poll_recv_oob(cx, buf)
+    }
+}
+
+impl<'a> Future for Peek<'a> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<usize>> {
+        let Peek { stream, buf } = &mut *self;
+        Pin::new(&mut **stream).poll_peek(cx, buf)
+    }
+}
+
+impl<'a> Future for Readable<'a> {
+    type Output = io::Result<mio::Ready>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.stream.io.poll_read_ready(cx)
+    }
+}
+
+impl<'a> Future for Writable<'a> {
+    type Output = io::Result<mio::Ready>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.stream.io.poll_write_ready(cx)
+    }
+}
+
+impl<'a> CorkVectoredWrite<'a> {
+    fn set_cork(&mut self, on: bool) -> io::Result<()> {
+        #[cfg(target_os = "linux")]
+        sys::set_cork(self.stream.io.get_ref(), on)?;
+        self.corked = on;
+        Ok(())
+    }
+
+    fn poll_write_all(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<usize>> {
+        let total = self.bufs.iter().map(|buf| buf.len()).sum();
+        while self.written < total {
+            // skip what has been written already
+            let mut skip = self.written;
+            let bufs: Vec<IoSlice<'_>> = self
+                .bufs
+                .iter()
+                .filter_map(|buf| {
+                    if skip >= buf.len() {
+                        skip -= buf.len();
+                        None
+                    } else {
+                        let buf = IoSlice::new(&buf[skip..]);
+                        skip = 0;
+                        Some(buf)
+                    }
+                })
+                .collect();
+
+            match Pin::new(&mut *self.stream).poll_write_vectored(cx, &bufs) {
+                Poll::Ready(Ok(0)) => return Poll::Ready(Err(io::ErrorKind::WriteZero.into())),
+                Poll::Ready(Ok(n)) => self.written += n,
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        Poll::Ready(Ok(total))
+    }
+}
+
+impl<'a> Future for CorkVectoredWrite<'a> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<usize>> {
+        if !self.corked {
+            self.set_cork(true)?;
+        }
+
+        let res = match self.poll_write_all(cx) {
+            Poll::Ready(res) => res,
+            Poll::Pending => return Poll::Pending,
+        };
+
+        // uncork even if the write failed, but report the first error
+        let uncorked = self.set_cork(false);
+        Poll::Ready(res.and_then(|n| uncorked.map(|()| n)))
+    }
+}
+
+impl<'a> Drop for CorkVectoredWrite<'a> {
+    fn drop(&mut self) {
+        if self.corked {
+            let _ = self.set_cork(false);
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl<'a> Future for SendZerocopy<'a> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<usize>> {
+        let SendZerocopy { stream, buf } = &mut *self;
+        Pin::new(&mut **stream).poll_send_zerocopy(cx, buf)
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "macos"))]
+impl<'a> Future for Sendfile<'a> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<usize>> {
+        let this = &mut *self;
+        while this.remaining > 0 {
+            let res = ready!(Pin::new(&mut *this.stream).poll_sendfile(
+                cx,
+                this.file,
+                this.offset,
+                this.remaining
+            ));
+            let n = match res {
+                Ok(n) => n,
+                // the error is returned by the next call instead
+                Err(_) if this.sent > 0 => break,
+                Err(e) => return Poll::Ready(Err(e)),
+            };
+            if n == 0 {
+                // the file is shorter than expected
+                break;
+            }
+            this.offset += n as u64;
+            this.remaining -= n;
+            this.sent += n;
+        }
+        Poll::Ready(Ok(this.sent))
+    }
+}
+
 impl std::convert::TryFrom<std::net::TcpStream> for TcpStream {
     type Error = io::Error;
 
     fn try_from(stream: std::net::TcpStream) -> Result<Self, Self::Error> {
-        let tcp = mio::net::TcpStream::from_stream(stream)?;
-        Ok(TcpStream::new(tcp))
+        TcpStream::from_std(stream)
     }
 }
 
@@ -573,19 +1871,420 @@ impl std::convert::TryFrom<&std::net::SocketAddr> for TcpStream {
     type Error = io::Error;
 
     fn try_from(addr: &std::net::SocketAddr) -> Result<Self, Self::Error> {
-        let tcp = mio::net::TcpStream::connect(&addr)?;
-        Ok(TcpStream::new(tcp))
+        TcpStreamBuilder::new().connect_priv(addr)
     }
 }
 
 #[cfg(unix)]
 mod sys {
     use super::TcpStream;
+    #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "macos"))]
+    use std::fs::File;
+    use std::io::{self, IoSlice, IoSliceMut};
     use std::os::unix::prelude::*;
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "netbsd",
+        target_os = "illumos",
+        target_os = "solaris"
+    ))]
+    use std::time::Duration;
+
+    use crate::raw::PollEvented;
+    use crate::socket;
 
     impl AsRawFd for TcpStream {
         fn as_raw_fd(&self) -> RawFd {
             self.io.get_ref().as_raw_fd()
         }
     }
+
+    impl FromRawFd for TcpStream {
+        /// Creates a new `TcpStream` from a raw file descriptor, and registers
+        /// it with the default reactor.
+        ///
+        /// # Safety
+        ///
+        /// `fd` must be a valid TCP socket, connected or with a connection in
+        /// progress, in non-blocking mode. Ownership of `fd` is transferred to
+        /// the stream, which closes it when dropped.
+        unsafe fn from_raw_fd(fd: RawFd) -> TcpStream {
+            let io = mio::net::TcpStream::from_raw_fd(fd);
+            TcpStream {
+                io: PollEvented::new(io),
+                #[cfg(target_os = "linux")]
+                zerocopy: false,
+            }
+        }
+    }
+
+    impl TcpStream {
+        /// Converts this stream into a `std::net::TcpStream`.
+        ///
+        /// The stream is deregistered from the reactor and switched back to
+        /// blocking mode, so it can be handed to synchronous code.
+        ///
+        /// # Examples
+        ///
+        /// ```rust,no_run
+        /// #![feature(async_await)]
+        /// use romio::tcp::TcpStream;
+        /// use std::io::Write;
+        ///
+        /// # async fn run() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        /// let stream = TcpStream::connect(&"127.0.0.1:8080".parse()?).await?;
+        /// let mut std_stream = stream.into_std()?;
+        /// std_stream.write_all(b"hello")?;
+        /// # Ok(())
+        /// # }
+        /// ```
+        pub fn into_std(self) -> io::Result<std::net::TcpStream> {
+            let fd = self.io.into_inner()?.into_raw_fd();
+            let stream = unsafe { std::net::TcpStream::from_raw_fd(fd) };
+            stream.set_nonblocking(false)?;
+            Ok(stream)
+        }
+
+        /// Grants temporary access to the underlying socket as a
+        /// [`socket2::Socket`], for configuration this type does not expose.
+        ///
+        /// This is an escape hatch: changes made through it bypass romio's own
+        /// view of the socket. Options that affect how the socket is driven,
+        /// such as its blocking mode, must be left alone, and the socket must
+        /// not be closed.
+        ///
+        /// [`socket2::Socket`]: https://docs.rs/socket2/0.3/socket2/struct.Socket.html
+        ///
+        /// # Examples
+        ///
+        /// ```rust,no_run
+        /// #![feature(async_await)]
+        /// use romio::tcp::TcpStream;
+        ///
+        /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
+        /// let addr = "127.0.0.1:8080".parse()?;
+        /// let stream = TcpStream::connect(&addr).await?;
+        ///
+        /// stream.set_socket2_options(|socket| socket.set_linger(None))?;
+        /// # Ok(())}
+        /// ```
+        #[cfg(feature = "socket2")]
+        pub fn set_socket2_options<F>(&self, f: F) -> io::Result<()>
+        where
+            F: FnOnce(&socket2::Socket) -> io::Result<()>,
+        {
+            crate::socket::with_socket2(self.as_raw_fd(), f)
+        }
+    }
+
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    const TCP_KEEPIDLE: libc::c_int = libc::TCP_KEEPALIVE;
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "netbsd",
+        target_os = "illumos",
+        target_os = "solaris"
+    ))]
+    const TCP_KEEPIDLE: libc::c_int = libc::TCP_KEEPIDLE;
+
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "netbsd",
+        target_os = "illumos",
+        target_os = "solaris"
+    ))]
+    pub(super) fn set_keepalive_params(
+        stream: &mio::net::TcpStream,
+        idle: Duration,
+        interval: Duration,
+        retries: u32,
+    ) -> io::Result<()> {
+        let secs = |dur: Duration| dur.as_secs().min(libc::c_int::MAX as u64) as libc::c_int;
+        let retries = retries.min(libc::c_int::MAX as u32) as libc::c_int;
+
+        let fd = stream.as_raw_fd();
+        socket::setsockopt(fd, libc::SOL_SOCKET, libc::SO_KEEPALIVE, 1 as libc::c_int)?;
+        socket::setsockopt(fd, libc::IPPROTO_TCP, TCP_KEEPIDLE, secs(idle))?;
+        socket::setsockopt(fd, libc::IPPROTO_TCP, libc::TCP_KEEPINTVL, secs(interval))?;
+        socket::setsockopt(fd, libc::IPPROTO_TCP, libc::TCP_KEEPCNT, retries)
+    }
+
+    pub(super) fn read_vectored(
+        stream: &mio::net::TcpStream,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> io::Result<usize> {
+        let msg = socket::recv_msg(stream.as_raw_fd(), bufs, None, 0)?;
+        Ok(msg.len)
+    }
+
+    pub(super) fn write_vectored(
+        stream: &mio::net::TcpStream,
+        bufs: &[IoSlice<'_>],
+    ) -> io::Result<usize> {
+        // Like the regular writes, don't raise `SIGPIPE` if the peer is gone.
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        let flags = libc::MSG_NOSIGNAL;
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        let flags = 0;
+        socket::send_msg(stream.as_raw_fd(), bufs, None, None, flags)
+    }
+
+    pub(super) fn send_oob(stream: &mio::net::TcpStream, byte: u8) -> io::Result<usize> {
+        let n = unsafe {
+            crate::socket::cvt(libc::send(
+                stream.as_raw_fd(),
+                &byte as *const u8 as *const libc::c_void,
+                1,
+                libc::MSG_OOB,
+            ))?
+        };
+        Ok(n as usize)
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub(super) fn recv_oob(stream: &mio::net::TcpStream, buf: &mut [u8]) -> io::Result<usize> {
+        let n = unsafe {
+            crate::socket::cvt(libc::recv(
+                stream.as_raw_fd(),
+                buf.as_mut_ptr() as *mut libc::c_void,
+                buf.len(),
+                libc::MSG_OOB | libc::MSG_DONTWAIT,
+            ))?
+        };
+        Ok(n as usize)
+    }
+
+    /// Sends data from `file` with `sendfile`, returning how much was sent.
+    ///
+    /// The BSD variants report partial sends alongside `EAGAIN`, which are
+    /// turned into a successful return here.
+    #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "macos"))]
+    pub(super) fn sendfile(
+        stream: &mio::net::TcpStream,
+        file: &File,
+        offset: u64,
+        count: usize,
+    ) -> io::Result<usize> {
+        let offset = offset.min(libc::off_t::MAX as u64) as libc::off_t;
+
+        #[cfg(target_os = "linux")]
+        {
+            let mut offset = offset;
+            let n = unsafe {
+                crate::socket::cvt(libc::sendfile(
+                    stream.as_raw_fd(),
+                    file.as_raw_fd(),
+                    &mut offset,
+                    count,
+                ))?
+            };
+            Ok(n as usize)
+        }
+
+        #[cfg(target_os = "freebsd")]
+        {
+            let mut sent: libc::off_t = 0;
+            let res = unsafe {
+                crate::socket::cvt(libc::sendfile(
+                    file.as_raw_fd(),
+                    stream.as_raw_fd(),
+                    offset,
+                    count,
+                    std::ptr::null_mut(),
+                    &mut sent,
+                    0,
+                ))
+            };
+            match res {
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock && sent > 0 => {}
+                Err(e) => return Err(e),
+                Ok(_) => {}
+            }
+            Ok(sent as usize)
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            // On input, the number of bytes to send. Zero would mean "until
+            // the end of the file".
+            let mut len = count.min(libc::off_t::MAX as usize) as libc::off_t;
+            let res = unsafe {
+                crate::socket::cvt(libc::sendfile(
+                    file.as_raw_fd(),
+                    stream.as_raw_fd(),
+                    offset,
+                    &mut len,
+                    std::ptr::null_mut(),
+                    0,
+                ))
+            };
+            match res {
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock && len > 0 => {}
+                Err(e) => return Err(e),
+                Ok(_) => {}
+            }
+            Ok(len as usize)
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    pub(super) use self::linux::*;
+
+    #[cfg(target_os = "linux")]
+    mod linux {
+        use std::io;
+        use std::os::unix::prelude::*;
+        use std::time::Duration;
+
+        use libc::c_int;
+
+        use crate::socket;
+        use crate::tcp::ZerocopyNotification;
+
+        // Not yet exported by libc.
+        const TCP_RTO_MAX_MS: c_int = 44;
+        const TCP_RTO_MIN_US: c_int = 45;
+
+        pub(crate) fn set_cork(stream: &mio::net::TcpStream, on: bool) -> io::Result<()> {
+            socket::setsockopt(
+                stream.as_raw_fd(),
+                libc::IPPROTO_TCP,
+                libc::TCP_CORK,
+                on as c_int,
+            )
+        }
+
+        pub(crate) fn window_clamp(stream: &mio::net::TcpStream) -> io::Result<u32> {
+            let val: c_int = socket::getsockopt(
+                stream.as_raw_fd(),
+                libc::IPPROTO_TCP,
+                libc::TCP_WINDOW_CLAMP,
+            )?;
+            Ok(val as u32)
+        }
+
+        pub(crate) fn set_window_clamp(stream: &mio::net::TcpStream, bytes: u32) -> io::Result<()> {
+            let val = bytes.min(c_int::MAX as u32) as c_int;
+            socket::setsockopt(
+                stream.as_raw_fd(),
+                libc::IPPROTO_TCP,
+                libc::TCP_WINDOW_CLAMP,
+                val,
+            )
+        }
+
+        pub(crate) fn rto_max(stream: &mio::net::TcpStream) -> io::Result<Duration> {
+            let ms: c_int =
+                socket::getsockopt(stream.as_raw_fd(), libc::IPPROTO_TCP, TCP_RTO_MAX_MS)?;
+            Ok(Duration::from_millis(ms as u64))
+        }
+
+        pub(crate) fn set_rto_max(stream: &mio::net::TcpStream, dur: Duration) -> io::Result<()> {
+            let ms = dur.as_millis().min(c_int::MAX as u128) as c_int;
+            socket::setsockopt(stream.as_raw_fd(), libc::IPPROTO_TCP, TCP_RTO_MAX_MS, ms)
+        }
+
+        pub(crate) fn set_rto_min(stream: &mio::net::TcpStream, dur: Duration) -> io::Result<()> {
+            let us = dur.as_micros().min(c_int::MAX as u128) as c_int;
+            socket::setsockopt(stream.as_raw_fd(), libc::IPPROTO_TCP, TCP_RTO_MIN_US, us)
+        }
+
+        pub(crate) fn set_zerocopy(stream: &mio::net::TcpStream) -> io::Result<()> {
+            socket::setsockopt(
+                stream.as_raw_fd(),
+                libc::SOL_SOCKET,
+                socket::SO_ZEROCOPY,
+                1 as c_int,
+            )
+        }
+
+        pub(crate) fn send_zerocopy(stream: &mio::net::TcpStream, buf: &[u8]) -> io::Result<usize> {
+            let n = unsafe {
+                socket::cvt(libc::send(
+                    stream.as_raw_fd(),
+                    buf.as_ptr() as *const libc::c_void,
+                    buf.len(),
+                    libc::MSG_ZEROCOPY,
+                ))?
+            };
+            Ok(n as usize)
+        }
+
+        pub(crate) fn recv_zerocopy_completion(
+            stream: &mio::net::TcpStream,
+        ) -> io::Result<ZerocopyNotification> {
+            let (first, last, copied) = socket::recv_zerocopy(stream.as_raw_fd())?;
+            Ok(ZerocopyNotification {
+                first,
+                last,
+                copied,
+            })
+        }
+    }
+}
+
+#[cfg(windows)]
+mod sys_windows {
+    use super::TcpStream;
+    use std::os::windows::prelude::*;
+
+    impl TcpStream {
+        /// Grants temporary access to the underlying socket as a
+        /// [`socket2::Socket`], for configuration this type does not expose.
+        ///
+        /// This is an escape hatch: changes made through it bypass romio's own
+        /// view of the socket. Options that affect how the socket is driven,
+        /// such as its blocking mode, must be left alone, and the socket must
+        /// not be closed.
+        ///
+        /// [`socket2::Socket`]: https://docs.rs/socket2/0.3/socket2/struct.Socket.html
+        #[cfg(feature = "socket2")]
+        pub fn set_socket2_options<F>(&self, f: F) -> std::io::Result<()>
+        where
+            F: FnOnce(&socket2::Socket) -> std::io::Result<()>,
+        {
+            // The socket is still owned by `self`, so it must not be closed
+            // when the borrowed `Socket` goes out of scope.
+            let socket = unsafe { socket2::Socket::from_raw_socket(self.as_raw_socket()) };
+            f(&std::mem::ManuallyDrop::new(socket))
+        }
+    }
+
+    impl AsRawSocket for TcpStream {
+        fn as_raw_socket(&self) -> RawSocket {
+            self.raw_socket
+        }
+    }
+
+    impl FromRawSocket for TcpStream {
+        /// Creates a new `TcpStream` from a raw socket, and registers it with
+        /// the default reactor.
+        ///
+        /// # Safety
+        ///
+        /// `socket` must be a valid TCP socket, connected or with a connection
+        /// in progress, in non-blocking mode. Ownership of `socket` is
+        /// transferred to the stream, which closes it when dropped.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `socket` cannot be switched to non-blocking mode.
+        unsafe fn from_raw_socket(socket: RawSocket) -> TcpStream {
+            let stream = std::net::TcpStream::from_raw_socket(socket);
+            TcpStream::register(stream, None).expect("failed to adopt socket")
+        }
+    }
 }
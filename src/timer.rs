@@ -0,0 +1,631 @@
+//! Timers driven by the reactor.
+//!
+//! This module provides a [`Delay`] future, which completes once at a given
+//! instant, and an [`Interval`] stream, which yields repeatedly with a fixed
+//! period. Both can be combined with the I/O types of this crate to impose
+//! deadlines on operations.
+//!
+//! On Linux and Android timers are `timerfd`s registered with the reactor,
+//! so they are driven by the same event loop as the sockets they time out.
+//! On macOS and the BSDs they are `EVFILT_TIMER` events, each in a kqueue of
+//! its own which is registered with the reactor in the same way. Other
+//! platforms fall back to a single helper thread shared by all timers.
+//!
+//! [`Delay`]: struct.Delay.html
+//! [`Interval`]: struct.Interval.html
+
+use std::io;
+use std::pin::Pin;
+use std::task::Context;
+use std::time::{Duration, Instant};
+
+use futures::stream::Stream;
+use futures::{Future, Poll};
+
+/// A future which completes once a deadline has passed.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(async_await)]
+/// use romio::timer::Delay;
+/// use std::time::{Duration, Instant};
+///
+/// # async fn run() -> std::io::Result<()> {
+/// let when = Instant::now() + Duration::from_millis(100);
+/// Delay::new(when)?.await?;
+/// assert!(Instant::now() >= when);
+/// # Ok(())
+/// # }
+/// ```
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct Delay {
+    inner: sys::Delay,
+}
+
+impl Delay {
+    /// Creates a new `Delay` which completes at `when`.
+    ///
+    /// A `Delay` for an instant in the past completes right away. An error is
+    /// returned if the timer can't be created.
+    pub fn new(when: Instant) -> io::Result<Delay> {
+        let inner = sys::Delay::new(when)?;
+        Ok(Delay { inner })
+    }
+}
+
+impl Future for Delay {
+    type Output = io::Result<()>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.inner.poll_elapsed(cx)
+    }
+}
+
+/// A stream which yields once every period.
+///
+/// The stream never ends. Ticks which are missed because the stream isn't
+/// polled in time are merged into a single item, rather than yielded back to
+/// back.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(async_await)]
+/// use futures::prelude::*;
+/// use romio::timer::Interval;
+/// use std::time::Duration;
+///
+/// # async fn run() -> std::io::Result<()> {
+/// let mut interval = Interval::new(Duration::from_millis(10))?;
+///
+/// for _ in 0..3 {
+///     interval.next().await.unwrap()?;
+///     println!("tick");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[must_use = "streams do nothing unless polled"]
+#[derive(Debug)]
+pub struct Interval {
+    inner: sys::Interval,
+}
+
+impl Interval {
+    /// Creates a new `Interval` which yields every `period`, starting one
+    /// `period` from now.
+    ///
+    /// An error is returned if `period` is zero, or if the timer can't be
+    /// created.
+    pub fn new(period: Duration) -> io::Result<Interval> {
+        if period == Duration::from_secs(0) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "period must not be zero",
+            ));
+        }
+        let inner = sys::Interval::new(period)?;
+        Ok(Interval { inner })
+    }
+
+    /// Attempts to wait for the next tick of the interval.
+    ///
+    /// This is the same as polling the `Stream` implementation, but doesn't
+    /// require pinning the interval.
+    pub fn poll_tick(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.inner.poll_tick(cx)
+    }
+}
+
+impl Stream for Interval {
+    type Item = io::Result<()>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<io::Result<()>>> {
+        self.poll_tick(cx).map(Some)
+    }
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+mod sys {
+    use std::io;
+    use std::task::Context;
+    use std::time::{Duration, Instant};
+
+    use futures::{ready, Poll};
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    use self::kqueue::Kqueue as Timer;
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    use self::timerfd::TimerFd as Timer;
+    use crate::raw::PollEvented;
+
+    #[derive(Debug)]
+    pub(super) struct Delay {
+        io: PollEvented<Timer>,
+        elapsed: bool,
+    }
+
+    impl Delay {
+        pub(super) fn new(when: Instant) -> io::Result<Delay> {
+            let timer = Timer::new(when.saturating_duration_since(Instant::now()), false)?;
+            Ok(Delay {
+                io: PollEvented::new_with_interest(timer, mio::Ready::readable())?,
+                elapsed: false,
+            })
+        }
+
+        pub(super) fn poll_elapsed(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            if self.elapsed {
+                return Poll::Ready(Ok(()));
+            }
+
+            ready!(self.io.read_with(cx, |timer| timer.read()))?;
+            self.elapsed = true;
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[derive(Debug)]
+    pub(super) struct Interval {
+        io: PollEvented<Timer>,
+    }
+
+    impl Interval {
+        pub(super) fn new(period: Duration) -> io::Result<Interval> {
+            let timer = Timer::new(period, true)?;
+            Ok(Interval {
+                io: PollEvented::new_with_interest(timer, mio::Ready::readable())?,
+            })
+        }
+
+        pub(super) fn poll_tick(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            ready!(self.io.read_with(cx, |timer| timer.read()))?;
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    mod timerfd {
+        use std::io;
+        use std::os::unix::io::RawFd;
+        use std::time::Duration;
+        use std::{mem, ptr};
+
+        use mio::event::Evented;
+        use mio::unix::EventedFd;
+
+        use crate::socket::cvt;
+
+        /// A non-blocking `timerfd` on the monotonic clock.
+        #[derive(Debug)]
+        pub(super) struct TimerFd(RawFd);
+
+        impl TimerFd {
+            /// Creates a timer which expires after `dur`, and then again every
+            /// `dur` if it is `periodic`.
+            pub(super) fn new(dur: Duration, periodic: bool) -> io::Result<TimerFd> {
+                let fd = unsafe {
+                    cvt(libc::timerfd_create(
+                        libc::CLOCK_MONOTONIC,
+                        libc::TFD_NONBLOCK | libc::TFD_CLOEXEC,
+                    ))?
+                };
+                let timer = TimerFd(fd);
+
+                // An all-zero value would disarm the timer instead.
+                let period = if periodic {
+                    dur
+                } else {
+                    Duration::from_secs(0)
+                };
+                let dur = dur.max(Duration::from_nanos(1));
+                let mut spec: libc::itimerspec = unsafe { mem::zeroed() };
+                spec.it_value.tv_sec = dur.as_secs() as libc::time_t;
+                spec.it_value.tv_nsec = dur.subsec_nanos() as libc::c_long;
+                spec.it_interval.tv_sec = period.as_secs() as libc::time_t;
+                spec.it_interval.tv_nsec = period.subsec_nanos() as libc::c_long;
+                unsafe {
+                    cvt(libc::timerfd_settime(fd, 0, &spec, ptr::null_mut()))?;
+                }
+                Ok(timer)
+            }
+
+            /// Consumes the expirations of the timer, returning how many there
+            /// were since the last read.
+            pub(super) fn read(&self) -> io::Result<u64> {
+                let mut expirations = 0u64;
+                unsafe {
+                    cvt(libc::read(
+                        self.0,
+                        &mut expirations as *mut u64 as *mut libc::c_void,
+                        mem::size_of::<u64>(),
+                    ))?;
+                }
+                Ok(expirations)
+            }
+        }
+
+        impl Evented for TimerFd {
+            fn register(
+                &self,
+                poll: &mio::Poll,
+                token: mio::Token,
+                interest: mio::Ready,
+                opts: mio::PollOpt,
+            ) -> io::Result<()> {
+                EventedFd(&self.0).register(poll, token, interest, opts)
+            }
+
+            fn reregister(
+                &self,
+                poll: &mio::Poll,
+                token: mio::Token,
+                interest: mio::Ready,
+                opts: mio::PollOpt,
+            ) -> io::Result<()> {
+                EventedFd(&self.0).reregister(poll, token, interest, opts)
+            }
+
+            fn deregister(&self, poll: &mio::Poll) -> io::Result<()> {
+                EventedFd(&self.0).deregister(poll)
+            }
+        }
+
+        impl Drop for TimerFd {
+            fn drop(&mut self) {
+                unsafe {
+                    libc::close(self.0);
+                }
+            }
+        }
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    mod kqueue {
+        use std::io;
+        use std::os::unix::io::RawFd;
+        use std::time::Duration;
+        use std::{mem, ptr};
+
+        use mio::event::Evented;
+        use mio::unix::EventedFd;
+
+        use crate::socket::{self, cvt};
+
+        /// A kqueue holding a single `EVFILT_TIMER` event.
+        ///
+        /// The kqueue becomes readable whenever the timer expires, which lets
+        /// it be registered with the reactor like any other file descriptor.
+        #[derive(Debug)]
+        pub(super) struct Kqueue(RawFd);
+
+        impl Kqueue {
+            /// Creates a timer which expires after `dur`, and then again every
+            /// `dur` if it is `periodic`.
+            pub(super) fn new(dur: Duration, periodic: bool) -> io::Result<Kqueue> {
+                let fd = unsafe { cvt(libc::kqueue())? };
+                let timer = Kqueue(fd);
+                socket::set_cloexec(fd, true)?;
+
+                let (fflags, data) = timer_data(dur);
+                let mut change: libc::kevent = unsafe { mem::zeroed() };
+                change.filter = libc::EVFILT_TIMER;
+                change.flags = libc::EV_ADD;
+                if !periodic {
+                    change.flags |= libc::EV_ONESHOT;
+                }
+                change.fflags = fflags;
+                change.data = data as _;
+                unsafe {
+                    cvt(libc::kevent(
+                        fd,
+                        &change,
+                        1,
+                        ptr::null_mut(),
+                        0,
+                        ptr::null(),
+                    ))?;
+                }
+                Ok(timer)
+            }
+
+            /// Consumes the expirations of the timer, returning how many there
+            /// were since the last read.
+            pub(super) fn read(&self) -> io::Result<u64> {
+                let mut event: libc::kevent = unsafe { mem::zeroed() };
+                let timeout: libc::timespec = unsafe { mem::zeroed() };
+                let n = unsafe {
+                    cvt(libc::kevent(
+                        self.0,
+                        ptr::null(),
+                        0,
+                        &mut event,
+                        1,
+                        &timeout,
+                    ))?
+                };
+                if n == 0 {
+                    return Err(io::ErrorKind::WouldBlock.into());
+                }
+                Ok(event.data as u64)
+            }
+        }
+
+        /// Converts `dur` to the flags and data of an `EVFILT_TIMER`, rounding
+        /// up so that the timer never expires early.
+        fn timer_data(dur: Duration) -> (u32, i64) {
+            // Other platforms only support the default unit, milliseconds.
+            #[cfg(any(
+                target_os = "macos",
+                target_os = "ios",
+                target_os = "freebsd",
+                target_os = "netbsd"
+            ))]
+            let (fflags, unit) = (libc::NOTE_USECONDS, 1_000);
+            #[cfg(any(target_os = "dragonfly", target_os = "openbsd"))]
+            let (fflags, unit) = (0, 1_000_000);
+
+            let units = dur.as_nanos().div_ceil(unit);
+            (fflags, units.max(1).min(i64::MAX as u128) as i64)
+        }
+
+        impl Evented for Kqueue {
+            fn register(
+                &self,
+                poll: &mio::Poll,
+                token: mio::Token,
+                interest: mio::Ready,
+                opts: mio::PollOpt,
+            ) -> io::Result<()> {
+                EventedFd(&self.0).register(poll, token, interest, opts)
+            }
+
+            fn reregister(
+                &self,
+                poll: &mio::Poll,
+                token: mio::Token,
+                interest: mio::Ready,
+                opts: mio::PollOpt,
+            ) -> io::Result<()> {
+                EventedFd(&self.0).reregister(poll, token, interest, opts)
+            }
+
+            fn deregister(&self, poll: &mio::Poll) -> io::Result<()> {
+                EventedFd(&self.0).deregister(poll)
+            }
+        }
+
+        impl Drop for Kqueue {
+            fn drop(&mut self) {
+                unsafe {
+                    libc::close(self.0);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd"
+)))]
+mod sys {
+    use std::cmp::Reverse;
+    use std::collections::{BinaryHeap, HashMap};
+    use std::io;
+    use std::mem;
+    use std::sync::{Condvar, Mutex, MutexGuard};
+    use std::task::{Context, Waker};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use futures::{ready, Poll};
+    use lazy_static::lazy_static;
+
+    lazy_static! {
+        static ref TIMERS: Timers = Timers::default();
+    }
+
+    #[derive(Debug)]
+    pub(super) struct Delay {
+        timer: Timer,
+        elapsed: bool,
+    }
+
+    impl Delay {
+        pub(super) fn new(when: Instant) -> io::Result<Delay> {
+            Ok(Delay {
+                timer: Timer::new(when, None)?,
+                elapsed: false,
+            })
+        }
+
+        pub(super) fn poll_elapsed(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            if !self.elapsed {
+                ready!(self.timer.poll_fired(cx));
+                self.elapsed = true;
+            }
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[derive(Debug)]
+    pub(super) struct Interval {
+        timer: Timer,
+    }
+
+    impl Interval {
+        pub(super) fn new(period: Duration) -> io::Result<Interval> {
+            Ok(Interval {
+                timer: Timer::new(Instant::now() + period, Some(period))?,
+            })
+        }
+
+        pub(super) fn poll_tick(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            self.timer.poll_fired(cx).map(Ok)
+        }
+    }
+
+    /// The timers of the process, all driven by a single helper thread which
+    /// is started on first use.
+    #[derive(Default)]
+    struct Timers {
+        state: Mutex<State>,
+        changed: Condvar,
+    }
+
+    #[derive(Default)]
+    struct State {
+        // Deadlines by timer id, including those of timers which have since
+        // been dropped.
+        deadlines: BinaryHeap<Reverse<(Instant, u64)>>,
+        entries: HashMap<u64, Entry>,
+        next_id: u64,
+        running: bool,
+    }
+
+    struct Entry {
+        period: Option<Duration>,
+        fired: bool,
+        waker: Option<Waker>,
+    }
+
+    /// A timer scheduled on the helper thread, which is cancelled when this
+    /// is dropped.
+    #[derive(Debug)]
+    struct Timer {
+        id: u64,
+    }
+
+    impl Timer {
+        /// Schedules a timer which fires at `when`, and then every `period`.
+        fn new(when: Instant, period: Option<Duration>) -> io::Result<Timer> {
+            let mut state = lock();
+            if !state.running {
+                thread::Builder::new()
+                    .name("romio-timer".into())
+                    .spawn(run)?;
+                state.running = true;
+            }
+
+            let id = state.next_id;
+            state.next_id += 1;
+            let entry = Entry {
+                period,
+                fired: false,
+                waker: None,
+            };
+            state.entries.insert(id, entry);
+
+            // The thread only needs to wake up early for a new first deadline.
+            let first = state
+                .deadlines
+                .peek()
+                .is_none_or(|&Reverse((next, _))| when < next);
+            state.deadlines.push(Reverse((when, id)));
+            if first {
+                TIMERS.changed.notify_one();
+            }
+            Ok(Timer { id })
+        }
+
+        /// Returns whether the timer has fired since this was last called,
+        /// and otherwise arranges for the task to be woken when it does.
+        fn poll_fired(&self, cx: &mut Context<'_>) -> Poll<()> {
+            let mut state = lock();
+            let entry = state
+                .entries
+                .get_mut(&self.id)
+                .expect("timer not scheduled");
+            if mem::replace(&mut entry.fired, false) {
+                Poll::Ready(())
+            } else {
+                entry.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+
+    impl Drop for Timer {
+        fn drop(&mut self) {
+            let mut state = lock();
+            let State {
+                deadlines, entries, ..
+            } = &mut *state;
+            entries.remove(&self.id);
+
+            // Deadlines of dropped timers are normally skipped once they come
+            // up, but are pruned early so that they can't pile up.
+            if deadlines.len() > 2 * entries.len() + 64 {
+                deadlines.retain(|Reverse((_, id))| entries.contains_key(id));
+            }
+        }
+    }
+
+    fn lock() -> MutexGuard<'static, State> {
+        TIMERS.state.lock().unwrap()
+    }
+
+    /// Fires the timers as their deadlines pass.
+    fn run() {
+        let mut wakers = Vec::new();
+        let mut state = lock();
+        loop {
+            let now = Instant::now();
+            let State {
+                deadlines, entries, ..
+            } = &mut *state;
+            while let Some(&Reverse((when, id))) = deadlines.peek() {
+                if when > now {
+                    break;
+                }
+                deadlines.pop();
+
+                let entry = match entries.get_mut(&id) {
+                    Some(entry) => entry,
+                    None => continue,
+                };
+                entry.fired = true;
+                wakers.extend(entry.waker.take());
+                if let Some(period) = entry.period {
+                    // Ticks which were missed are merged into this one.
+                    let period = period.as_nanos();
+                    let missed = (now - when).as_nanos() / period;
+                    let next = when + Duration::from_nanos(((missed + 1) * period) as u64);
+                    deadlines.push(Reverse((next, id)));
+                }
+            }
+
+            if !wakers.is_empty() {
+                drop(state);
+                wakers.drain(..).for_each(Waker::wake);
+                state = lock();
+                continue;
+            }
+
+            state = match state.deadlines.peek() {
+                Some(&Reverse((when, _))) => {
+                    TIMERS.changed.wait_timeout(state, when - now).unwrap().0
+                }
+                None => TIMERS.changed.wait(state).unwrap(),
+            };
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use std::convert::TryFrom;
+use std::io;
+use std::net::SocketAddr;
+#[cfg(unix)]
+use std::os::unix::io::{AsRawFd, RawFd};
+
+use super::UdpSocket;
+#[cfg(unix)]
+use crate::socket;
+
+/// A builder for a `UdpSocket`, for configuring the socket before it is
+/// bound.
+///
+/// Some options only take effect if they are set before the socket is bound,
+/// which rules out setting them on a `UdpSocket`.
+///
+/// # Examples
+///
+/// Bind several sockets to the same address, with the kernel distributing
+/// incoming datagrams across them:
+///
+/// ```rust,no_run
+/// use romio::udp::UdpSocketBuilder;
+///
+/// # fn main () -> Result<(), Box<dyn std::error::Error + 'static>> {
+/// let addr = "0.0.0.0:5353".parse()?;
+/// let mut builder = UdpSocketBuilder::new();
+/// builder.reuseport(true).recv_buffer_size(4 * 1024 * 1024);
+///
+/// let first = builder.bind(&addr)?;
+/// let second = builder.bind(&addr)?;
+/// # Ok(())}
+/// ```
+#[derive(Clone, Debug)]
+pub struct UdpSocketBuilder {
+    #[cfg(unix)]
+    reuseport: Option<bool>,
+    reuseaddr: Option<bool>,
+    only_v6: Option<bool>,
+    #[cfg(unix)]
+    recv_buffer_size: Option<usize>,
+    #[cfg(unix)]
+    send_buffer_size: Option<usize>,
+    ttl: Option<u32>,
+}
+
+impl UdpSocketBuilder {
+    /// Creates a new builder, with every option left at the system default.
+    pub fn new() -> UdpSocketBuilder {
+        UdpSocketBuilder {
+            #[cfg(unix)]
+            reuseport: None,
+            reuseaddr: None,
+            only_v6: None,
+            #[cfg(unix)]
+            recv_buffer_size: None,
+            #[cfg(unix)]
+            send_buffer_size: None,
+            ttl: None,
+        }
+    }
+
+    /// Sets the value of the `SO_REUSEPORT` option for the socket.
+    ///
+    /// For more information about this option, see
+    /// [`UdpSocket::bind_reuseport`]. Binding fails on platforms that do not
+    /// support `SO_REUSEPORT`.
+    ///
+    /// [`UdpSocket::bind_reuseport`]: struct.UdpSocket.html#method.bind_reuseport
+    #[cfg(unix)]
+    pub fn reuseport(&mut self, on: bool) -> &mut UdpSocketBuilder {
+        self.reuseport = Some(on);
+        self
+    }
+
+    /// Sets the value of the `SO_REUSEADDR` option for the socket.
+    ///
+    /// This allows binding to an address which is still held by a socket in
+    /// the process of being closed, and on most platforms binding several
+    /// sockets to the same multicast address.
+    pub fn reuseaddr(&mut self, on: bool) -> &mut UdpSocketBuilder {
+        self.reuseaddr = Some(on);
+        self
+    }
+
+    /// Sets the value of the `IPV6_V6ONLY` option for the socket.
+    ///
+    /// If this is set to `true` then the socket is restricted to sending and
+    /// receiving IPv6 packets only. If it is set to `false`, an IPv6 socket
+    /// can also communicate with IPv4 peers, through IPv4-mapped IPv6
+    /// addresses. The default depends on the platform, and on Linux on the
+    /// `net.ipv6.bindv6only` sysctl.
+    ///
+    /// This is ignored when binding to an IPv4 address.
+    pub fn only_v6(&mut self, only_v6: bool) -> &mut UdpSocketBuilder {
+        self.only_v6 = Some(only_v6);
+        self
+    }
+
+    /// Sets the value of the `SO_RCVBUF` option for the socket.
+    ///
+    /// For more information about this option, see
+    /// [`UdpSocket::set_recv_buffer_size`].
+    ///
+    /// [`UdpSocket::set_recv_buffer_size`]: struct.UdpSocket.html#method.set_recv_buffer_size
+    #[cfg(unix)]
+    pub fn recv_buffer_size(&mut self, size: usize) -> &mut UdpSocketBuilder {
+        self.recv_buffer_size = Some(size);
+        self
+    }
+
+    /// Sets the value of the `SO_SNDBUF` option for the socket.
+    ///
+    /// For more information about this option, see
+    /// [`UdpSocket::set_send_buffer_size`].
+    ///
+    /// [`UdpSocket::set_send_buffer_size`]: struct.UdpSocket.html#method.set_send_buffer_size
+    #[cfg(unix)]
+    pub fn send_buffer_size(&mut self, size: usize) -> &mut UdpSocketBuilder {
+        self.send_buffer_size = Some(size);
+        self
+    }
+
+    /// Sets the value of the `IP_TTL` option for the socket.
+    ///
+    /// This is the time-to-live of the packets sent from the socket.
+    pub fn ttl(&mut self, ttl: u32) -> &mut UdpSocketBuilder {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Creates a new `UdpSocket` with the configured options, bound to the
+    /// specified address.
+    pub fn bind(&self, addr: &SocketAddr) -> io::Result<UdpSocket> {
+        let builder = match addr {
+            SocketAddr::V4(..) => net2::UdpBuilder::new_v4()?,
+            SocketAddr::V6(..) => {
+                let builder = net2::UdpBuilder::new_v6()?;
+                if let Some(only_v6) = self.only_v6 {
+                    builder.only_v6(only_v6)?;
+                }
+                builder
+            }
+        };
+        if let Some(on) = self.reuseaddr {
+            builder.reuse_address(on)?;
+        }
+        if let Some(ttl) = self.ttl {
+            builder.ttl(ttl)?;
+        }
+
+        #[cfg(unix)]
+        {
+            let fd = builder.as_raw_fd();
+            if let Some(on) = self.reuseport {
+                socket::set_reuseport(fd, on)?;
+            }
+            if let Some(size) = self.recv_buffer_size {
+                set_buffer_size(fd, libc::SO_RCVBUF, size)?;
+            }
+            if let Some(size) = self.send_buffer_size {
+                set_buffer_size(fd, libc::SO_SNDBUF, size)?;
+            }
+        }
+
+        UdpSocket::try_from(builder.bind(addr)?)
+    }
+}
+
+impl Default for UdpSocketBuilder {
+    fn default() -> UdpSocketBuilder {
+        UdpSocketBuilder::new()
+    }
+}
+
+#[cfg(unix)]
+fn set_buffer_size(fd: RawFd, name: libc::c_int, size: usize) -> io::Result<()> {
+    let size = size.min(libc::c_int::MAX as usize) as libc::c_int;
+    socket::setsockopt(fd, libc::SOL_SOCKET, name, size)
+}
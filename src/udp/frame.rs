@@ -0,0 +1,216 @@
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::task::Context;
+
+use bytes::{BufMut, BytesMut};
+use futures::sink::Sink;
+use futures::stream::Stream;
+use futures::{ready, Poll};
+
+use super::UdpSocket;
+use crate::codec::{Decoder, Encoder};
+
+/// Enough to hold the largest payload a UDP datagram can carry.
+const INITIAL_RD_CAPACITY: usize = 64 * 1024;
+const INITIAL_WR_CAPACITY: usize = 8 * 1024;
+
+/// A unified `Stream` and `Sink` interface to a `UdpSocket`, using a codec to
+/// decode and encode datagrams.
+///
+/// Every datagram received is handed to the codec's [`decode_eof`], and the
+/// frames it yields are paired with the address of the sender. Frames passed
+/// to the sink are encoded into a single datagram each and sent to the
+/// address they are paired with.
+///
+/// Datagrams the codec can't decode are discarded, and the error is returned
+/// from the stream, which can be polled again afterwards.
+///
+/// This type requires the `bytes` feature.
+///
+/// [`decode_eof`]: ../codec/trait.Decoder.html#method.decode_eof
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// #![feature(async_await)]
+/// # use std::error::Error;
+/// use bytes::BytesMut;
+/// use futures::prelude::*;
+/// use romio::codec::{Decoder, Encoder};
+/// use romio::udp::{UdpFramed, UdpSocket};
+/// use std::io;
+///
+/// struct Echo;
+///
+/// impl Decoder for Echo {
+///     type Item = BytesMut;
+///     type Error = io::Error;
+///
+///     fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<BytesMut>> {
+///         if src.is_empty() {
+///             return Ok(None);
+///         }
+///         let len = src.len();
+///         Ok(Some(src.split_to(len)))
+///     }
+/// }
+///
+/// impl Encoder for Echo {
+///     type Item = BytesMut;
+///     type Error = io::Error;
+///
+///     fn encode(&mut self, item: BytesMut, dst: &mut BytesMut) -> io::Result<()> {
+///         dst.extend_from_slice(&item);
+///         Ok(())
+///     }
+/// }
+///
+/// # async fn echo() -> Result<(), Box<dyn Error + 'static>> {
+/// let socket = UdpSocket::bind(&"127.0.0.1:7878".parse()?)?;
+/// let mut framed = UdpFramed::new(socket, Echo);
+///
+/// while let Some(frame) = framed.next().await {
+///     let (msg, addr) = frame?;
+///     framed.send((msg, addr)).await?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct UdpFramed<C> {
+    socket: UdpSocket,
+    codec: C,
+    rd: BytesMut,
+    /// The sender of the datagram in `rd`, while frames are being decoded
+    /// from it.
+    rd_addr: Option<SocketAddr>,
+    wr: BytesMut,
+    /// The target of the datagram in `wr`, until it has been sent.
+    wr_addr: Option<SocketAddr>,
+}
+
+impl<C> UdpFramed<C> {
+    /// Creates a new `UdpFramed` backed by the given socket and codec.
+    pub fn new(socket: UdpSocket, codec: C) -> UdpFramed<C> {
+        UdpFramed {
+            socket,
+            codec,
+            rd: BytesMut::with_capacity(INITIAL_RD_CAPACITY),
+            rd_addr: None,
+            wr: BytesMut::with_capacity(INITIAL_WR_CAPACITY),
+            wr_addr: None,
+        }
+    }
+
+    /// Returns a reference to the underlying socket.
+    pub fn get_ref(&self) -> &UdpSocket {
+        &self.socket
+    }
+
+    /// Returns a mutable reference to the underlying socket.
+    ///
+    /// Care should be taken not to receive from or send on the socket
+    /// directly, as that would interleave with the datagrams of this
+    /// `UdpFramed`.
+    pub fn get_mut(&mut self) -> &mut UdpSocket {
+        &mut self.socket
+    }
+
+    /// Returns a reference to the codec.
+    pub fn codec(&self) -> &C {
+        &self.codec
+    }
+
+    /// Returns a mutable reference to the codec.
+    pub fn codec_mut(&mut self) -> &mut C {
+        &mut self.codec
+    }
+
+    /// Consumes the `UdpFramed`, returning the underlying socket.
+    ///
+    /// Frames that were decoded from a datagram but not yet returned, and
+    /// a datagram that was encoded but not yet flushed, are lost.
+    pub fn into_inner(self) -> UdpSocket {
+        self.socket
+    }
+}
+
+impl<C: Decoder<Error = io::Error> + Unpin> Stream for UdpFramed<C> {
+    type Item = io::Result<(C::Item, SocketAddr)>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            if let Some(addr) = this.rd_addr {
+                match this.codec.decode_eof(&mut this.rd) {
+                    Ok(Some(frame)) => return Poll::Ready(Some(Ok((frame, addr)))),
+                    Ok(None) => {}
+                    Err(e) => {
+                        this.rd_addr = None;
+                        this.rd.clear();
+                        return Poll::Ready(Some(Err(e)));
+                    }
+                }
+                this.rd_addr = None;
+                this.rd.clear();
+            }
+
+            this.rd.reserve(INITIAL_RD_CAPACITY);
+            // Safety: `recv_from` only writes to the buffer, and `advance_mut`
+            // is called with the number of bytes it initialized.
+            unsafe {
+                let (n, addr) = ready!(this.socket.poll_recv_from_priv(cx, this.rd.bytes_mut()))?;
+                this.rd.advance_mut(n);
+                this.rd_addr = Some(addr);
+            }
+        }
+    }
+}
+
+impl<C: Encoder<Error = io::Error> + Unpin> Sink<(C::Item, SocketAddr)> for UdpFramed<C> {
+    type Error = io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        // Only one datagram is buffered at a time.
+        self.poll_flush(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: (C::Item, SocketAddr)) -> io::Result<()> {
+        let this = self.get_mut();
+        let (frame, addr) = item;
+
+        this.codec.encode(frame, &mut this.wr)?;
+        this.wr_addr = Some(addr);
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+
+        let addr = match this.wr_addr {
+            Some(addr) => addr,
+            None => return Poll::Ready(Ok(())),
+        };
+
+        let n = ready!(this.socket.poll_send_to_priv(cx, &this.wr, &addr))?;
+        let len = this.wr.len();
+        this.wr.clear();
+        this.wr_addr = None;
+
+        if n == len {
+            Poll::Ready(Ok(()))
+        } else {
+            Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::Other,
+                "failed to write entire datagram to socket",
+            )))
+        }
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_flush(cx)
+    }
+}
@@ -0,0 +1,4028 @@
+//! Async UDP bindings.
+//!
+//! This module contains the UDP networking types, similar to those found in
+//! `std::net`, but suitable for async programming via futures and
+//! `async`/`await`.
+//!
+//! After creating a `UdpSocket` by [`bind`]ing it to a socket address, data can be
+//! [sent to] and [received from] any other socket address.
+//!
+//! [`bind`]: #method.bind
+//! [received from]: #method.poll_recv_from
+//! [sent to]: #method.poll_send_to
+
+mod builder;
+#[cfg(feature = "bytes")]
+mod frame;
+#[cfg(target_os = "linux")]
+mod timestamping;
+
+pub use self::builder::UdpSocketBuilder;
+#[cfg(feature = "bytes")]
+pub use self::frame::UdpFramed;
+#[cfg(target_os = "linux")]
+pub use self::timestamping::{TimestampingFlags, Timestamps};
+
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, IoSlice, IoSliceMut};
+#[cfg(target_os = "linux")]
+use std::mem;
+#[cfg(any(target_os = "linux", target_os = "android", feature = "bytes"))]
+use std::net::IpAddr;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::net::SocketAddrV6;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+#[cfg(unix)]
+use std::os::unix::io::AsRawFd;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::os::unix::io::RawFd;
+#[cfg(windows)]
+use std::os::windows::io::{AsRawSocket, RawSocket};
+use std::pin::Pin;
+#[cfg(target_os = "linux")]
+use std::sync::atomic::AtomicBool;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::Context;
+use std::time::{Duration, Instant};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::time::SystemTime;
+
+use async_datagram::AsyncDatagram;
+use async_ready::{AsyncReadReady, AsyncWriteReady};
+#[cfg(feature = "bytes")]
+use bytes::{Buf, BufMut};
+use futures::Future;
+use futures::{ready, Poll};
+use mio;
+
+use crate::raw::PollEvented;
+use crate::timer::Delay;
+
+/// A UDP socket.
+pub struct UdpSocket {
+    io: PollEvented<mio::net::UdpSocket>,
+    /// The IPv6 flow label set with `set_flow_label_v6`, or 0.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    flow_label: AtomicU32,
+    /// The `(multiaddr, interface)` pairs joined with `join_multicast_v4`.
+    multicast_v4: Mutex<Vec<(Ipv4Addr, Ipv4Addr)>>,
+    /// The numbering of the sends made with `send_to_zerocopy`.
+    #[cfg(target_os = "linux")]
+    zerocopy: Zerocopy,
+    /// The handle of the underlying socket, which mio doesn't expose on
+    /// Windows.
+    #[cfg(windows)]
+    raw_socket: RawSocket,
+}
+
+impl UdpSocket {
+    /// Creates a UDP socket from the given address.
+    ///
+    /// Binding with a port number of 0 will request that the OS assigns a port
+    /// to this socket. The port allocated can be queried via the
+    /// [`local_addr`] method.
+    ///
+    /// To set options which must be set before binding, use a
+    /// [`UdpSocketBuilder`].
+    ///
+    /// [`local_addr`]: #method.local_addr
+    /// [`UdpSocketBuilder`]: struct.UdpSocketBuilder.html
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use romio::udp::UdpSocket;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let socket_addr = "127.0.0.1:0".parse()?;
+    /// let socket = UdpSocket::bind(&socket_addr)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn bind(addr: &SocketAddr) -> io::Result<UdpSocket> {
+        UdpSocketBuilder::default().bind(addr)
+    }
+
+    fn new(socket: mio::net::UdpSocket, #[cfg(windows)] raw_socket: RawSocket) -> UdpSocket {
+        let io = PollEvented::new(socket);
+        UdpSocket {
+            io: io,
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            flow_label: AtomicU32::new(0),
+            multicast_v4: Mutex::new(Vec::new()),
+            #[cfg(target_os = "linux")]
+            zerocopy: Zerocopy::default(),
+            #[cfg(windows)]
+            raw_socket,
+        }
+    }
+
+    /// Returns the address datagrams to `target` are sent to, carrying the
+    /// flow label of this socket if one is set.
+    fn send_target(&self, target: &SocketAddr) -> SocketAddr {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            let label = self.flow_label.load(Ordering::Relaxed);
+            if let SocketAddr::V6(addr) = target {
+                if label != 0 && addr.flowinfo() == 0 {
+                    // `sin6_flowinfo` is copied as is, so it must be in network order
+                    let flowinfo = label.to_be();
+                    return SocketAddrV6::new(*addr.ip(), addr.port(), flowinfo, addr.scope_id())
+                        .into();
+                }
+            }
+        }
+        *target
+    }
+
+    // Shared by `AsyncDatagram` and the halves returned by `split`, which only
+    // have shared access to the socket.
+    fn poll_send_to_priv(
+        &self,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+        target: &SocketAddr,
+    ) -> Poll<io::Result<usize>> {
+        let target = self.send_target(target);
+        self.io.write_with(cx, |io| io.send_to(buf, &target))
+    }
+
+    fn poll_recv_from_priv(
+        &self,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<(usize, SocketAddr)>> {
+        self.io.read_with(cx, |io| io.recv_from(buf))
+    }
+
+    /// Returns the local address that this listener is bound to.
+    ///
+    /// This can be useful, for example, when binding to port 0 to figure out
+    /// which port was actually bound.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    ///	use romio::udp::UdpSocket;
+    ///
+    ///	# fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let socket_addr = "127.0.0.1:0".parse()?;
+    /// # let socket = UdpSocket::bind(&socket_addr)?;
+    /// println!("Socket addr: {:?}", socket.local_addr());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.io.get_ref().local_addr()
+    }
+
+    /// Returns the address of the peer this socket is connected to.
+    ///
+    /// Returns an error of kind `NotConnected` if the socket has not been
+    /// [`connect`]ed.
+    ///
+    /// [`connect`]: #method.connect
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use romio::udp::UdpSocket;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let socket = UdpSocket::bind(&"127.0.0.1:0".parse()?)?;
+    /// let peer = "127.0.0.1:7878".parse()?;
+    ///
+    /// socket.connect(&peer)?;
+    /// assert_eq!(socket.peer_addr()?, peer);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(unix)]
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        sys::peer_addr(self.io.get_ref())
+    }
+
+    /// Connects the socket to a remote address, so that [`send`] and [`recv`]
+    /// can be used instead of [`send_to`] and [`recv_from`].
+    ///
+    /// Once connected, the socket only receives datagrams from `addr`. Calling
+    /// `connect` again changes the peer.
+    ///
+    /// Sending to another address with [`send_to`] is passed through to the
+    /// operating system, which may either send the datagram or fail with
+    /// `EISCONN`, depending on the platform.
+    ///
+    /// [`send`]: #method.send
+    /// [`recv`]: #method.recv
+    /// [`send_to`]: #method.send_to
+    /// [`recv_from`]: #method.recv_from
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use romio::udp::UdpSocket;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let socket = UdpSocket::bind(&"127.0.0.1:0".parse()?)?;
+    /// socket.connect(&"127.0.0.1:7878".parse()?)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn connect(&self, addr: &SocketAddr) -> io::Result<()> {
+        self.io.get_ref().connect(self.send_target(addr))
+    }
+
+    /// Sends data on the socket to the given address. On success, returns the
+    /// number of bytes written.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// # use std::error::Error;
+    /// use romio::udp::UdpSocket;
+    ///
+    /// const THE_MERCHANT_OF_VENICE: &[u8] = b"
+    ///     If you prick us, do we not bleed?
+    ///     If you tickle us, do we not laugh?
+    ///     If you poison us, do we not die?
+    ///     And if you wrong us, shall we not revenge?
+    /// ";
+    ///
+    /// # async fn send_data() -> Result<(), Box<dyn Error + 'static>> {
+    /// let addr = "127.0.0.1:0".parse()?;
+    /// let target = "127.0.0.1:7878".parse()?;
+    /// let mut socket = UdpSocket::bind(&addr)?;
+    ///
+    /// socket.send_to(THE_MERCHANT_OF_VENICE, &target).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn send_to<'a, 'b>(&'a mut self, buf: &'b [u8], target: &'b SocketAddr) -> SendTo<'a, 'b> {
+        SendTo {
+            buf,
+            target,
+            socket: self,
+        }
+    }
+
+    /// Sends data on the socket to the given address, giving up once `timeout`
+    /// has elapsed. On success, returns the number of bytes written.
+    ///
+    /// If the datagram can't be sent in time, an error of kind
+    /// `io::ErrorKind::TimedOut` is returned and nothing is sent. The timer
+    /// starts when this method is called.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// # use std::error::Error;
+    /// use romio::udp::UdpSocket;
+    /// use std::time::Duration;
+    ///
+    /// # async fn send_data() -> Result<(), Box<dyn Error + 'static>> {
+    /// let addr = "127.0.0.1:0".parse()?;
+    /// let target = "127.0.0.1:7878".parse()?;
+    /// let mut socket = UdpSocket::bind(&addr)?;
+    ///
+    /// let timeout = Duration::from_secs(1);
+    /// socket.send_to_timeout(b"Tubal", &target, timeout).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn send_to_timeout<'a, 'b>(
+        &'a mut self,
+        buf: &'b [u8],
+        target: &'b SocketAddr,
+        timeout: Duration,
+    ) -> SendToTimeout<'a, 'b> {
+        SendToTimeout {
+            socket: self,
+            buf,
+            target,
+            deadline: Instant::now() + timeout,
+            delay: None,
+        }
+    }
+
+    /// Receives data from the socket. On success, returns the number of bytes
+    /// read and the address from whence the data came.
+    ///
+    /// # Exampes
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// # use std::error::Error;
+    /// use romio::udp::UdpSocket;
+    ///
+    /// # async fn recv_data() -> Result<Vec<u8>, Box<dyn Error + 'static>> {
+    /// let addr = "127.0.0.1:0".parse()?;
+    /// let mut socket = UdpSocket::bind(&addr)?;
+    /// let mut buf = vec![0; 1024];
+    ///
+    /// socket.recv_from(&mut buf).await?;
+    /// # Ok(buf)
+    /// # }
+    /// ```
+    pub fn recv_from<'a, 'b>(&'a mut self, buf: &'b mut [u8]) -> RecvFrom<'a, 'b> {
+        RecvFrom { buf, socket: self }
+    }
+
+    /// Receives data from the socket, giving up once `timeout` has elapsed. On
+    /// success, returns the number of bytes read and the address from whence
+    /// the data came.
+    ///
+    /// If no datagram arrives in time, an error of kind
+    /// `io::ErrorKind::TimedOut` is returned. The timer starts when this
+    /// method is called.
+    ///
+    /// Dropping the returned future, or the timeout expiring, never loses a
+    /// datagram: one is only taken off the socket when the future completes
+    /// with it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// # use std::error::Error;
+    /// use romio::udp::UdpSocket;
+    /// use std::io;
+    /// use std::time::Duration;
+    ///
+    /// # async fn recv_data() -> Result<(), Box<dyn Error + 'static>> {
+    /// let addr = "127.0.0.1:0".parse()?;
+    /// let mut socket = UdpSocket::bind(&addr)?;
+    /// let mut buf = vec![0; 1024];
+    ///
+    /// match socket.recv_from_timeout(&mut buf, Duration::from_secs(1)).await {
+    ///     Ok((len, peer)) => println!("{} bytes from {}", len, peer),
+    ///     Err(ref e) if e.kind() == io::ErrorKind::TimedOut => println!("nothing yet"),
+    ///     Err(e) => return Err(e.into()),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn recv_from_timeout<'a, 'b>(
+        &'a mut self,
+        buf: &'b mut [u8],
+        timeout: Duration,
+    ) -> RecvFromTimeout<'a, 'b> {
+        RecvFromTimeout {
+            socket: self,
+            buf,
+            deadline: Instant::now() + timeout,
+            delay: None,
+        }
+    }
+
+    /// Sends the contents of `buf` on the socket to the given address. On
+    /// success, returns the number of bytes written.
+    ///
+    /// The datagram is built from the first contiguous chunk of `buf`, as
+    /// returned by [`Buf::bytes`], which covers the whole buffer for types
+    /// such as `Bytes`, `BytesMut` and `io::Cursor<&[u8]>`. The buffer itself
+    /// is not advanced.
+    ///
+    /// This method requires the `bytes` feature.
+    ///
+    /// [`Buf::bytes`]: https://docs.rs/bytes/0.4/bytes/trait.Buf.html#tymethod.bytes
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// # use std::error::Error;
+    /// use bytes::Bytes;
+    /// use romio::udp::UdpSocket;
+    /// use std::io::Cursor;
+    ///
+    /// # async fn send_data() -> Result<usize, Box<dyn Error + 'static>> {
+    /// let mut socket = UdpSocket::bind(&"127.0.0.1:0".parse()?)?;
+    /// let buf = Cursor::new(Bytes::from_static(b"hello world"));
+    ///
+    /// let n = socket.send_buf(&buf, &"127.0.0.1:8080".parse()?).await?;
+    /// # Ok(n)
+    /// # }
+    /// ```
+    #[cfg(feature = "bytes")]
+    pub fn send_buf<'a, B: Buf>(
+        &'a mut self,
+        buf: &'a B,
+        target: &'a SocketAddr,
+    ) -> SendBuf<'a, B> {
+        SendBuf {
+            buf,
+            target,
+            socket: self,
+        }
+    }
+
+    /// Receives a datagram into the remaining capacity of `buf`, advancing it
+    /// by the number of bytes read. On success, returns the number of bytes
+    /// read and the address from whence the data came.
+    ///
+    /// If `buf` has no remaining capacity, this resolves immediately with
+    /// `Ok((0, addr))` without consuming a datagram, where `addr` is the
+    /// unspecified address of the socket's family.
+    ///
+    /// This method requires the `bytes` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// # use std::error::Error;
+    /// use bytes::BytesMut;
+    /// use romio::udp::UdpSocket;
+    ///
+    /// # async fn recv_data() -> Result<BytesMut, Box<dyn Error + 'static>> {
+    /// let mut socket = UdpSocket::bind(&"127.0.0.1:0".parse()?)?;
+    /// let mut buf = BytesMut::with_capacity(1024);
+    ///
+    /// let (n, addr) = socket.recv_buf(&mut buf).await?;
+    /// println!("Received {} bytes from {}", n, addr);
+    /// # Ok(buf)
+    /// # }
+    /// ```
+    #[cfg(feature = "bytes")]
+    pub fn recv_buf<'a, B: BufMut>(&'a mut self, buf: &'a mut B) -> RecvBuf<'a, B> {
+        RecvBuf { buf, socket: self }
+    }
+
+    /// Sends a single datagram gathered from a slice of buffers to the given
+    /// address. On success, returns the number of bytes written.
+    ///
+    /// On Unix the buffers are passed to `sendmsg` as they are, without being
+    /// copied together first.
+    ///
+    /// # Note
+    ///
+    /// On platforms without `sendmsg`, only the first buffer is sent.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// # use std::error::Error;
+    /// use romio::udp::UdpSocket;
+    /// use std::io::IoSlice;
+    ///
+    /// # async fn send_data() -> Result<usize, Box<dyn Error + 'static>> {
+    /// let mut socket = UdpSocket::bind(&"127.0.0.1:0".parse()?)?;
+    /// let header = [1, 0, 0, 12];
+    /// let bufs = [IoSlice::new(&header), IoSlice::new(b"hello, world")];
+    ///
+    /// let n = socket.send_to_vectored(&bufs, &"127.0.0.1:8080".parse()?).await?;
+    /// # Ok(n)
+    /// # }
+    /// ```
+    pub fn send_to_vectored<'a>(
+        &'a mut self,
+        bufs: &'a [IoSlice<'a>],
+        target: &'a SocketAddr,
+    ) -> SendToVectored<'a> {
+        SendToVectored {
+            bufs,
+            target,
+            socket: self,
+        }
+    }
+
+    /// Attempts to send a single datagram gathered from a slice of buffers to
+    /// the given address.
+    ///
+    /// For more information, see [`send_to_vectored`].
+    ///
+    /// [`send_to_vectored`]: #method.send_to_vectored
+    pub fn poll_send_to_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+        target: &SocketAddr,
+    ) -> Poll<io::Result<usize>> {
+        ready!(self.io.poll_write_ready(cx)?);
+
+        let target = self.send_target(target);
+        #[cfg(unix)]
+        let res = sys::send_to_vectored(self.io.get_ref(), bufs, &target);
+        #[cfg(not(unix))]
+        let res = match bufs.first() {
+            Some(buf) => self.io.get_ref().send_to(buf, &target),
+            None => self.io.get_ref().send_to(&[], &target),
+        };
+
+        match res {
+            Ok(n) => Poll::Ready(Ok(n)),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                self.io.clear_write_ready(cx)?;
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    /// Receives a single datagram, scattering it across a slice of buffers.
+    /// On success, returns the number of bytes read and the address from
+    /// whence the data came.
+    ///
+    /// The buffers are filled in order, and whatever doesn't fit into them is
+    /// discarded.
+    ///
+    /// # Note
+    ///
+    /// On platforms without `recvmsg`, only the first buffer is filled.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// # use std::error::Error;
+    /// use romio::udp::UdpSocket;
+    /// use std::io::IoSliceMut;
+    ///
+    /// # async fn recv_data() -> Result<(), Box<dyn Error + 'static>> {
+    /// let mut socket = UdpSocket::bind(&"127.0.0.1:0".parse()?)?;
+    /// let mut header = [0; 4];
+    /// let mut body = vec![0; 1024];
+    /// let mut bufs = [IoSliceMut::new(&mut header), IoSliceMut::new(&mut body)];
+    ///
+    /// let (n, addr) = socket.recv_from_vectored(&mut bufs).await?;
+    /// println!("Received {} bytes from {}", n, addr);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn recv_from_vectored<'a, 'b>(
+        &'a mut self,
+        bufs: &'a mut [IoSliceMut<'b>],
+    ) -> RecvFromVectored<'a, 'b> {
+        RecvFromVectored { bufs, socket: self }
+    }
+
+    /// Attempts to receive a single datagram, scattering it across a slice of
+    /// buffers.
+    ///
+    /// For more information, see [`recv_from_vectored`].
+    ///
+    /// [`recv_from_vectored`]: #method.recv_from_vectored
+    pub fn poll_recv_from_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<io::Result<(usize, SocketAddr)>> {
+        ready!(self.io.poll_read_ready(cx)?);
+
+        #[cfg(unix)]
+        let res = sys::recv_from_vectored(self.io.get_ref(), bufs);
+        #[cfg(not(unix))]
+        let res = match bufs.first_mut() {
+            Some(buf) => self.io.get_ref().recv_from(buf),
+            None => self.io.get_ref().recv_from(&mut []),
+        };
+
+        match res {
+            Ok(r) => Poll::Ready(Ok(r)),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                self.io.clear_read_ready(cx)?;
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    /// Sends a batch of datagrams on the socket. On success, returns the
+    /// number of datagrams sent, which may be less than `msgs.len()` if the
+    /// socket's send buffer fills up.
+    ///
+    /// On Linux the whole batch is handed to the kernel with a single
+    /// `sendmmsg` call. Other platforms fall back to sending the datagrams one
+    /// at a time.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// # use std::error::Error;
+    /// use romio::udp::{SendMsg, UdpSocket};
+    ///
+    /// # async fn send_data() -> Result<(), Box<dyn Error + 'static>> {
+    /// let mut socket = UdpSocket::bind(&"127.0.0.1:0".parse()?)?;
+    /// let target = "127.0.0.1:8080".parse()?;
+    /// let msgs = [
+    ///     SendMsg { buf: b"first", target },
+    ///     SendMsg { buf: b"second", target },
+    /// ];
+    ///
+    /// let mut sent = 0;
+    /// while sent < msgs.len() {
+    ///     sent += socket.send_mmsg(&msgs[sent..]).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn send_mmsg<'a>(&'a mut self, msgs: &'a [SendMsg<'a>]) -> SendMmsg<'a> {
+        SendMmsg { msgs, socket: self }
+    }
+
+    /// Attempts to send a batch of datagrams on the socket.
+    ///
+    /// For more information, see [`send_mmsg`].
+    ///
+    /// [`send_mmsg`]: #method.send_mmsg
+    pub fn poll_send_mmsg(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        msgs: &[SendMsg<'_>],
+    ) -> Poll<io::Result<usize>> {
+        if msgs.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        ready!(self.io.poll_write_ready(cx)?);
+
+        #[cfg(target_os = "linux")]
+        let res = sys::send_mmsg(self.io.get_ref(), msgs, |target| self.send_target(target));
+        #[cfg(not(target_os = "linux"))]
+        let res = {
+            let mut sent = 0;
+            let mut res = Ok(0);
+            for msg in msgs {
+                let target = self.send_target(&msg.target);
+                match self.io.get_ref().send_to(msg.buf, &target) {
+                    Ok(_) => sent += 1,
+                    Err(e) => {
+                        res = Err(e);
+                        break;
+                    }
+                }
+            }
+            // Report the datagrams that went out, and any error on the next
+            // call.
+            if sent > 0 {
+                Ok(sent)
+            } else {
+                res
+            }
+        };
+
+        match res {
+            Ok(n) => Poll::Ready(Ok(n)),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                self.io.clear_write_ready(cx)?;
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    /// Receives a batch of datagrams from the socket, one into each of
+    /// `msgs`. On success, returns the number of datagrams received, which is
+    /// at least one.
+    ///
+    /// Only the messages up to the returned count are filled in; for each of
+    /// them [`RecvMsg::len`] and [`RecvMsg::addr`] are set. Datagrams that
+    /// don't fit into their buffer are truncated.
+    ///
+    /// On Linux the batch is received with a single `recvmmsg` call. Other
+    /// platforms fall back to receiving the datagrams one at a time.
+    ///
+    /// [`RecvMsg::len`]: struct.RecvMsg.html#structfield.len
+    /// [`RecvMsg::addr`]: struct.RecvMsg.html#structfield.addr
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// # use std::error::Error;
+    /// use romio::udp::{RecvMsg, UdpSocket};
+    ///
+    /// # async fn recv_data() -> Result<(), Box<dyn Error + 'static>> {
+    /// let mut socket = UdpSocket::bind(&"127.0.0.1:0".parse()?)?;
+    /// let mut bufs = vec![[0u8; 1500]; 32];
+    /// let mut msgs: Vec<_> = bufs.iter_mut().map(|buf| RecvMsg::new(buf)).collect();
+    ///
+    /// let n = socket.recv_mmsg(&mut msgs).await?;
+    /// for msg in &msgs[..n] {
+    ///     println!("{} bytes from {:?}", msg.len, msg.addr);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn recv_mmsg<'a, 'b>(&'a mut self, msgs: &'a mut [RecvMsg<'b>]) -> RecvMmsg<'a, 'b> {
+        RecvMmsg { msgs, socket: self }
+    }
+
+    /// Attempts to receive a batch of datagrams from the socket.
+    ///
+    /// For more information, see [`recv_mmsg`].
+    ///
+    /// [`recv_mmsg`]: #method.recv_mmsg
+    pub fn poll_recv_mmsg(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        msgs: &mut [RecvMsg<'_>],
+    ) -> Poll<io::Result<usize>> {
+        if msgs.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        ready!(self.io.poll_read_ready(cx)?);
+
+        #[cfg(target_os = "linux")]
+        let res = sys::recv_mmsg(self.io.get_ref(), msgs);
+        #[cfg(not(target_os = "linux"))]
+        let res = {
+            let mut received = 0;
+            let mut res = Ok(0);
+            for msg in msgs.iter_mut() {
+                match self.io.get_ref().recv_from(msg.buf) {
+                    Ok((n, addr)) => {
+                        msg.len = n;
+                        msg.addr = Some(addr);
+                        received += 1;
+                    }
+                    Err(e) => {
+                        res = Err(e);
+                        break;
+                    }
+                }
+            }
+            if received > 0 {
+                Ok(received)
+            } else {
+                res
+            }
+        };
+
+        match res {
+            Ok(n) => Poll::Ready(Ok(n)),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                self.io.clear_read_ready(cx)?;
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    /// Sends data on the socket to the given address, without waiting for the
+    /// socket to become writable. On success, returns the number of bytes
+    /// written.
+    ///
+    /// If the socket is not ready for writing, an error of kind `WouldBlock`
+    /// is returned and the write readiness of the socket is cleared, so that
+    /// [`writable`] waits for the socket to become writable again. This does
+    /// not register the current task for wakeup.
+    ///
+    /// [`writable`]: #method.writable
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use romio::udp::UdpSocket;
+    /// use std::io;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut socket = UdpSocket::bind(&"127.0.0.1:0".parse()?)?;
+    /// let target = "127.0.0.1:7878".parse()?;
+    ///
+    /// match socket.try_send_to(b"ping", &target) {
+    ///     Ok(n) => println!("sent {} bytes", n),
+    ///     Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+    ///     Err(e) => return Err(e.into()),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn try_send_to(&mut self, buf: &[u8], target: &SocketAddr) -> io::Result<usize> {
+        let target = self.send_target(target);
+        self.io.try_write_with(|io| io.send_to(buf, &target))
+    }
+
+    /// Receives data from the socket, without waiting for the socket to
+    /// become readable. On success, returns the number of bytes read and the
+    /// address from whence the data came.
+    ///
+    /// If no datagram is queued, an error of kind `WouldBlock` is returned and
+    /// the read readiness of the socket is cleared, so that [`readable`] waits
+    /// for a new datagram. This does not register the current task for
+    /// wakeup.
+    ///
+    /// [`readable`]: #method.readable
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use romio::udp::UdpSocket;
+    /// use std::io;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut socket = UdpSocket::bind(&"127.0.0.1:7878".parse()?)?;
+    /// let mut buf = vec![0; 1024];
+    ///
+    /// loop {
+    ///     match socket.try_recv_from(&mut buf) {
+    ///         Ok((n, peer)) => println!("{} bytes from {}", n, peer),
+    ///         Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => break,
+    ///         Err(e) => return Err(e.into()),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn try_recv_from(&mut self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        self.io.try_read_with(|io| io.recv_from(buf))
+    }
+
+    /// Waits for the socket to become readable.
+    ///
+    /// The readiness is kept until [`try_recv_from`] returns `WouldBlock`, so
+    /// calling this again before then completes immediately. Readiness may be
+    /// spurious, in which case `try_recv_from` returns `WouldBlock` and the
+    /// next call waits for a new readiness event.
+    ///
+    /// [`try_recv_from`]: #method.try_recv_from
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// # use std::error::Error;
+    /// use romio::udp::UdpSocket;
+    /// use std::io;
+    ///
+    /// # async fn drain() -> Result<(), Box<dyn Error + 'static>> {
+    /// let mut socket = UdpSocket::bind(&"127.0.0.1:7878".parse()?)?;
+    /// let mut buf = vec![0; 1024];
+    ///
+    /// loop {
+    ///     socket.readable().await?;
+    ///     loop {
+    ///         match socket.try_recv_from(&mut buf) {
+    ///             Ok((n, peer)) => println!("{} bytes from {}", n, peer),
+    ///             Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => break,
+    ///             Err(e) => return Err(e.into()),
+    ///         }
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn readable(&mut self) -> Readable<'_> {
+        Readable { socket: self }
+    }
+
+    /// Waits for the socket to become writable.
+    ///
+    /// The readiness is kept until [`try_send_to`] returns `WouldBlock`, so
+    /// calling this again before then completes immediately.
+    ///
+    /// [`try_send_to`]: #method.try_send_to
+    pub fn writable(&mut self) -> Writable<'_> {
+        Writable { socket: self }
+    }
+
+    /// Receives data from the socket without removing it from the queue. On
+    /// success, returns the number of bytes read and the address from whence
+    /// the data came.
+    ///
+    /// Successive calls return the same datagram, until it is consumed with
+    /// [`recv_from`]. If `buf` is too small to hold the datagram, it is
+    /// truncated, so this can be used to inspect a header before choosing a
+    /// buffer for the whole datagram.
+    ///
+    /// [`recv_from`]: #method.recv_from
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// # use std::error::Error;
+    /// use romio::udp::UdpSocket;
+    ///
+    /// # async fn recv_data() -> Result<Vec<u8>, Box<dyn Error + 'static>> {
+    /// let addr = "127.0.0.1:0".parse()?;
+    /// let mut socket = UdpSocket::bind(&addr)?;
+    ///
+    /// // peek at the length prefix before receiving the datagram
+    /// let mut len = [0; 2];
+    /// socket.peek_from(&mut len).await?;
+    /// let mut buf = vec![0; 2 + u16::from_be_bytes(len) as usize];
+    /// socket.recv_from(&mut buf).await?;
+    /// # Ok(buf)
+    /// # }
+    /// ```
+    #[cfg(unix)]
+    pub fn peek_from<'a, 'b>(&'a mut self, buf: &'b mut [u8]) -> PeekFrom<'a, 'b> {
+        PeekFrom { buf, socket: self }
+    }
+
+    /// Attempts to receive data from the socket without removing it from the
+    /// queue.
+    ///
+    /// For more information, see [`peek_from`].
+    ///
+    /// [`peek_from`]: #method.peek_from
+    #[cfg(unix)]
+    pub fn poll_peek_from(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<(usize, SocketAddr)>> {
+        ready!(Pin::new(&mut self.io).poll_read_ready(cx)?);
+
+        match sys::peek_from(self.io.get_ref(), buf) {
+            Ok(n) => Poll::Ready(Ok(n)),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                Pin::new(&mut self.io).clear_read_ready(cx)?;
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    /// Sends data on the socket to the peer it is connected to. On success,
+    /// returns the number of bytes written.
+    ///
+    /// The socket must first be connected with [`connect`].
+    ///
+    /// [`connect`]: #method.connect
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// # use std::error::Error;
+    /// use romio::udp::UdpSocket;
+    ///
+    /// # async fn send_data() -> Result<(), Box<dyn Error + 'static>> {
+    /// let addr = "127.0.0.1:0".parse()?;
+    /// let peer = "127.0.0.1:7878".parse()?;
+    /// let mut socket = UdpSocket::bind(&addr)?;
+    /// socket.connect(&peer)?;
+    ///
+    /// socket.send(b"ping").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn send<'a>(&'a mut self, buf: &'a [u8]) -> Send<'a> {
+        Send { buf, socket: self }
+    }
+
+    /// Receives data from the peer the socket is connected to. On success,
+    /// returns the number of bytes read.
+    ///
+    /// The socket must first be connected with [`connect`].
+    ///
+    /// [`connect`]: #method.connect
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// # use std::error::Error;
+    /// use romio::udp::UdpSocket;
+    ///
+    /// # async fn recv_data() -> Result<Vec<u8>, Box<dyn Error + 'static>> {
+    /// let addr = "127.0.0.1:0".parse()?;
+    /// let peer = "127.0.0.1:7878".parse()?;
+    /// let mut socket = UdpSocket::bind(&addr)?;
+    /// socket.connect(&peer)?;
+    ///
+    /// let mut buf = vec![0; 1024];
+    /// let n = socket.recv(&mut buf).await?;
+    /// buf.truncate(n);
+    /// # Ok(buf)
+    /// # }
+    /// ```
+    pub fn recv<'a>(&'a mut self, buf: &'a mut [u8]) -> Recv<'a> {
+        Recv { buf, socket: self }
+    }
+
+    /// Attempts to send data on the socket to the peer it is connected to.
+    ///
+    /// For more information, see [`send`].
+    ///
+    /// [`send`]: #method.send
+    pub fn poll_send(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        ready!(self.io.poll_write_ready(cx)?);
+
+        match self.io.get_ref().send(buf) {
+            Ok(n) => Poll::Ready(Ok(n)),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                Pin::new(&mut self.io).clear_write_ready(cx)?;
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    /// Attempts to receive data from the peer the socket is connected to.
+    ///
+    /// For more information, see [`recv`].
+    ///
+    /// [`recv`]: #method.recv
+    pub fn poll_recv(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        ready!(Pin::new(&mut self.io).poll_read_ready(cx)?);
+
+        match self.io.get_ref().recv(buf) {
+            Ok(n) => Poll::Ready(Ok(n)),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                Pin::new(&mut self.io).clear_read_ready(cx)?;
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    /// Receives data from the socket, along with the local address the
+    /// datagram was sent to. On success, returns the number of bytes read,
+    /// the address from whence the data came and the destination address.
+    ///
+    /// This is useful on multi-homed hosts where a socket bound to the
+    /// unspecified address needs to reply from the same address the client
+    /// sent its request to, see [`send_from_source`].
+    ///
+    /// The destination address is delivered as `IP_PKTINFO` (or
+    /// `IPV6_PKTINFO`) ancillary data, which must first be enabled with
+    /// [`set_recv_pktinfo`]. If it is missing, an error is returned.
+    ///
+    /// [`send_from_source`]: #method.send_from_source
+    /// [`set_recv_pktinfo`]: #method.set_recv_pktinfo
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// # use std::error::Error;
+    /// use romio::udp::UdpSocket;
+    ///
+    /// # async fn echo() -> Result<(), Box<dyn Error + 'static>> {
+    /// let addr = "0.0.0.0:7878".parse()?;
+    /// let mut socket = UdpSocket::bind(&addr)?;
+    /// socket.set_recv_pktinfo(true)?;
+    ///
+    /// let mut buf = vec![0; 1024];
+    /// let (n, peer, local) = socket.recv_from_with_source(&mut buf).await?;
+    /// socket.send_from_source(&buf[..n], &peer, &local).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn recv_from_with_source<'a, 'b>(
+        &'a mut self,
+        buf: &'b mut [u8],
+    ) -> RecvFromWithSource<'a, 'b> {
+        RecvFromWithSource { buf, socket: self }
+    }
+
+    /// Sends data on the socket to the given address, using `source` as the
+    /// source address of the datagram. On success, returns the number of bytes
+    /// written.
+    ///
+    /// The source address is passed to the kernel as `IP_PKTINFO` (or
+    /// `IPV6_PKTINFO`) ancillary data. It must be an address assigned to the
+    /// host and of the same family as `target`.
+    ///
+    /// For more information, see [`recv_from_with_source`].
+    ///
+    /// [`recv_from_with_source`]: #method.recv_from_with_source
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn send_from_source<'a, 'b>(
+        &'a mut self,
+        buf: &'b [u8],
+        target: &'b SocketAddr,
+        source: &'b IpAddr,
+    ) -> SendFromSource<'a, 'b> {
+        SendFromSource {
+            buf,
+            target,
+            source,
+            socket: self,
+        }
+    }
+
+    /// Attempts to receive data on the socket, along with the local address
+    /// the datagram was sent to.
+    ///
+    /// For more information, see [`recv_from_with_source`].
+    ///
+    /// [`recv_from_with_source`]: #method.recv_from_with_source
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn poll_recv_from_with_source(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<(usize, SocketAddr, IpAddr)>> {
+        ready!(Pin::new(&mut self.io).poll_read_ready(cx)?);
+
+        match sys::recv_from_with_source(self.io.get_ref(), buf) {
+            Ok(r) => Poll::Ready(Ok(r)),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                Pin::new(&mut self.io).clear_read_ready(cx)?;
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    /// Attempts to send data on the socket to the given address, using
+    /// `source` as the source address of the datagram.
+    ///
+    /// For more information, see [`send_from_source`].
+    ///
+    /// [`send_from_source`]: #method.send_from_source
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn poll_send_from_source(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+        target: &SocketAddr,
+        source: &IpAddr,
+    ) -> Poll<io::Result<usize>> {
+        ready!(self.io.poll_write_ready(cx)?);
+
+        let target = self.send_target(target);
+        match sys::send_from_source(self.io.get_ref(), buf, &target, source) {
+            Ok(n) => Poll::Ready(Ok(n)),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                Pin::new(&mut self.io).clear_write_ready(cx)?;
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    /// Sets the value of the `IP_PKTINFO` option for this socket, or
+    /// `IPV6_RECVPKTINFO` if the socket is bound to an IPv6 address.
+    ///
+    /// When enabled, the destination address of every received datagram is
+    /// delivered as ancillary data and reported by [`recv_from_with_source`].
+    ///
+    /// [`recv_from_with_source`]: #method.recv_from_with_source
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn set_recv_pktinfo(&self, on: bool) -> io::Result<()> {
+        sys::set_recv_pktinfo(self.io.get_ref(), on)
+    }
+
+    /// Receives data from the socket, along with the original destination
+    /// address of the datagram. On success, returns the number of bytes read,
+    /// the address from whence the data came and the original destination.
+    ///
+    /// The original destination differs from the local address of the socket
+    /// when the datagram was redirected to it, for example by a `TPROXY`
+    /// firewall rule for a transparent proxy. It is delivered as
+    /// `IP_ORIGDSTADDR` (or `IPV6_ORIGDSTADDR`) ancillary data, which must
+    /// first be enabled with [`set_recv_orig_dst_addr`]. If it is missing, an
+    /// error is returned.
+    ///
+    /// [`set_recv_orig_dst_addr`]: #method.set_recv_orig_dst_addr
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// # use std::error::Error;
+    /// use romio::udp::UdpSocket;
+    ///
+    /// # async fn recv_data() -> Result<(), Box<dyn Error + 'static>> {
+    /// let addr = "127.0.0.1:7878".parse()?;
+    /// let mut socket = UdpSocket::bind(&addr)?;
+    /// socket.set_recv_orig_dst_addr(true)?;
+    ///
+    /// let mut buf = vec![0; 1024];
+    /// let (n, peer, orig_dst) = socket.recv_from_with_orig_dst(&mut buf).await?;
+    /// println!("{} bytes from {} to {}", n, peer, orig_dst);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn recv_from_with_orig_dst<'a, 'b>(
+        &'a mut self,
+        buf: &'b mut [u8],
+    ) -> RecvFromWithOrigDst<'a, 'b> {
+        RecvFromWithOrigDst { buf, socket: self }
+    }
+
+    /// Attempts to receive data on the socket, along with the original
+    /// destination address of the datagram.
+    ///
+    /// For more information, see [`recv_from_with_orig_dst`].
+    ///
+    /// [`recv_from_with_orig_dst`]: #method.recv_from_with_orig_dst
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn poll_recv_from_with_orig_dst(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<(usize, SocketAddr, SocketAddr)>> {
+        ready!(Pin::new(&mut self.io).poll_read_ready(cx)?);
+
+        match sys::recv_from_with_orig_dst(self.io.get_ref(), buf) {
+            Ok(r) => Poll::Ready(Ok(r)),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                Pin::new(&mut self.io).clear_read_ready(cx)?;
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    /// Sets the value of the `IP_RECVORIGDSTADDR` option for this socket, or
+    /// `IPV6_RECVORIGDSTADDR` if the socket is bound to an IPv6 address.
+    ///
+    /// When enabled, the original destination address of every received
+    /// datagram is delivered as ancillary data and reported by
+    /// [`recv_from_with_orig_dst`].
+    ///
+    /// [`recv_from_with_orig_dst`]: #method.recv_from_with_orig_dst
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn set_recv_orig_dst_addr(&self, on: bool) -> io::Result<()> {
+        sys::set_recv_orig_dst_addr(self.io.get_ref(), on)
+    }
+
+    /// Receives data from the socket, along with the IP time-to-live (or IPv6
+    /// hop limit) of the datagram. On success, returns the number of bytes
+    /// read, the address from whence the data came and the TTL.
+    ///
+    /// Some protocols use the TTL to check that a datagram originates from a
+    /// directly connected peer: a datagram sent with a TTL of 255 still
+    /// carries 255 on arrival only if it has not been routed.
+    ///
+    /// The TTL is delivered as `IP_TTL` (or `IPV6_HOPLIMIT`) ancillary data,
+    /// which must first be enabled with [`set_recv_ttl`]. If it is missing,
+    /// an error is returned.
+    ///
+    /// [`set_recv_ttl`]: #method.set_recv_ttl
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// # use std::error::Error;
+    /// use romio::udp::UdpSocket;
+    ///
+    /// # async fn recv_data() -> Result<(), Box<dyn Error + 'static>> {
+    /// let addr = "0.0.0.0:123".parse()?;
+    /// let mut socket = UdpSocket::bind(&addr)?;
+    /// socket.set_recv_ttl(true)?;
+    ///
+    /// let mut buf = vec![0; 1024];
+    /// let (n, peer, ttl) = socket.recv_from_with_ttl(&mut buf).await?;
+    /// if ttl != 255 {
+    ///     println!("ignoring routed datagram from {}", peer);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn recv_from_with_ttl<'a, 'b>(&'a mut self, buf: &'b mut [u8]) -> RecvFromWithTtl<'a, 'b> {
+        RecvFromWithTtl { buf, socket: self }
+    }
+
+    /// Attempts to receive data on the socket, along with the IP time-to-live
+    /// of the datagram.
+    ///
+    /// For more information, see [`recv_from_with_ttl`].
+    ///
+    /// [`recv_from_with_ttl`]: #method.recv_from_with_ttl
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn poll_recv_from_with_ttl(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<(usize, SocketAddr, u8)>> {
+        ready!(Pin::new(&mut self.io).poll_read_ready(cx)?);
+
+        match sys::recv_from_with_ttl(self.io.get_ref(), buf) {
+            Ok(r) => Poll::Ready(Ok(r)),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                Pin::new(&mut self.io).clear_read_ready(cx)?;
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    /// Sets the value of the `IP_RECVTTL` option for this socket, or
+    /// `IPV6_RECVHOPLIMIT` if the socket is bound to an IPv6 address.
+    ///
+    /// When enabled, the TTL of every received datagram is delivered as
+    /// ancillary data and reported by [`recv_from_with_ttl`].
+    ///
+    /// [`recv_from_with_ttl`]: #method.recv_from_with_ttl
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn set_recv_ttl(&self, on: bool) -> io::Result<()> {
+        sys::set_recv_ttl(self.io.get_ref(), on)
+    }
+
+    /// Receives data from the socket, along with the time the kernel received
+    /// the datagram. On success, returns the number of bytes read, the address
+    /// from whence the data came and the receive timestamp.
+    ///
+    /// The timestamp is delivered as `SCM_TIMESTAMPNS` ancillary data with
+    /// nanosecond resolution, and is only present if it has been enabled with
+    /// [`set_timestampns`].
+    ///
+    /// [`set_timestampns`]: #method.set_timestampns
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// # use std::error::Error;
+    /// use romio::udp::UdpSocket;
+    ///
+    /// # async fn recv_data() -> Result<(), Box<dyn Error + 'static>> {
+    /// let addr = "127.0.0.1:0".parse()?;
+    /// let mut socket = UdpSocket::bind(&addr)?;
+    /// socket.set_timestampns(true)?;
+    ///
+    /// let mut buf = vec![0; 1024];
+    /// let (n, peer, time) = socket.recv_from_timestampns(&mut buf).await?;
+    /// println!("{} bytes from {} at {:?}", n, peer, time);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn recv_from_timestampns<'a, 'b>(
+        &'a mut self,
+        buf: &'b mut [u8],
+    ) -> RecvFromTimestampNs<'a, 'b> {
+        RecvFromTimestampNs { buf, socket: self }
+    }
+
+    /// Attempts to receive data on the socket, along with the time the kernel
+    /// received the datagram.
+    ///
+    /// For more information, see [`recv_from_timestampns`].
+    ///
+    /// [`recv_from_timestampns`]: #method.recv_from_timestampns
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn poll_recv_from_timestampns(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<(usize, SocketAddr, Option<SystemTime>)>> {
+        ready!(Pin::new(&mut self.io).poll_read_ready(cx)?);
+
+        match sys::recv_from_timestampns(self.io.get_ref(), buf) {
+            Ok(r) => Poll::Ready(Ok(r)),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                Pin::new(&mut self.io).clear_read_ready(cx)?;
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    /// Sets the value of the `SO_TIMESTAMPNS` option for this socket.
+    ///
+    /// When enabled, the kernel records the time every datagram is received
+    /// with nanosecond resolution, which is reported by
+    /// [`recv_from_timestampns`].
+    ///
+    /// [`recv_from_timestampns`]: #method.recv_from_timestampns
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn set_timestampns(&self, on: bool) -> io::Result<()> {
+        sys::set_timestampns(self.io.get_ref(), on)
+    }
+
+    /// Receives data from the socket, along with the timestamps reported
+    /// through `SCM_TIMESTAMPING`. On success, returns the number of bytes
+    /// read, the address from whence the data came and the timestamps.
+    ///
+    /// The timestamps are only present if they have been enabled with
+    /// [`set_timestamping`].
+    ///
+    /// [`set_timestamping`]: #method.set_timestamping
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// # use std::error::Error;
+    /// use romio::udp::{TimestampingFlags, UdpSocket};
+    ///
+    /// # async fn recv_data() -> Result<(), Box<dyn Error + 'static>> {
+    /// let addr = "0.0.0.0:319".parse()?;
+    /// let mut socket = UdpSocket::bind(&addr)?;
+    /// socket.set_timestamping(
+    ///     TimestampingFlags::RX_HARDWARE
+    ///         | TimestampingFlags::RAW_HARDWARE
+    ///         | TimestampingFlags::RX_SOFTWARE
+    ///         | TimestampingFlags::SOFTWARE,
+    /// )?;
+    ///
+    /// let mut buf = vec![0; 1024];
+    /// let (n, peer, timestamps) = socket.recv_from_with_timestamp(&mut buf).await?;
+    /// if let Some(timestamps) = timestamps {
+    ///     println!("{} bytes from {} at {:?}", n, peer, timestamps.hardware);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(target_os = "linux")]
+    pub fn recv_from_with_timestamp<'a, 'b>(
+        &'a mut self,
+        buf: &'b mut [u8],
+    ) -> RecvFromWithTimestamp<'a, 'b> {
+        RecvFromWithTimestamp { buf, socket: self }
+    }
+
+    /// Attempts to receive data on the socket, along with the timestamps
+    /// reported through `SCM_TIMESTAMPING`.
+    ///
+    /// For more information, see [`recv_from_with_timestamp`].
+    ///
+    /// [`recv_from_with_timestamp`]: #method.recv_from_with_timestamp
+    #[cfg(target_os = "linux")]
+    pub fn poll_recv_from_with_timestamp(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<(usize, SocketAddr, Option<Timestamps>)>> {
+        ready!(Pin::new(&mut self.io).poll_read_ready(cx)?);
+
+        match sys::recv_from_with_timestamp(self.io.get_ref(), buf) {
+            Ok(r) => Poll::Ready(Ok(r)),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                Pin::new(&mut self.io).clear_read_ready(cx)?;
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    /// Sets the value of the `SO_TIMESTAMPING` option for this socket.
+    ///
+    /// The flags select which timestamps the kernel generates and reports for
+    /// this socket. Receive timestamps are reported by
+    /// [`recv_from_with_timestamp`]. An empty set of flags disables
+    /// timestamping.
+    ///
+    /// Hardware timestamps additionally require the network adapter to be
+    /// configured for them, with the `SIOCSHWTSTAMP` ioctl.
+    ///
+    /// [`recv_from_with_timestamp`]: #method.recv_from_with_timestamp
+    #[cfg(target_os = "linux")]
+    pub fn set_timestamping(&self, flags: TimestampingFlags) -> io::Result<()> {
+        sys::set_timestamping(self.io.get_ref(), flags)
+    }
+
+    /// Attaches a classic BPF program to this socket with the
+    /// `SO_ATTACH_FILTER` option, replacing any filter already attached.
+    ///
+    /// The filter runs in the kernel for every datagram received on the
+    /// socket, and only datagrams it accepts are queued for userspace. This
+    /// avoids waking up the task for traffic it would discard anyway.
+    ///
+    /// The program is checked by the kernel before it is attached: it must
+    /// terminate, may only access packet data through bounds checked loads,
+    /// and may not contain more than 4096 instructions. A program that fails
+    /// these checks is rejected with an error rather than being run, so a
+    /// bogus filter cannot harm the kernel, it can only drop (or pass) the
+    /// wrong datagrams.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use romio::udp::UdpSocket;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let socket = UdpSocket::bind(&"127.0.0.1:0".parse()?)?;
+    ///
+    /// // accept every datagram in full
+    /// let prog = [libc::sock_filter { code: 0x06, jt: 0, jf: 0, k: 0xffff_ffff }];
+    /// socket.attach_bpf(&prog)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn attach_bpf(&self, prog: &[libc::sock_filter]) -> io::Result<()> {
+        sys::attach_bpf(self.io.get_ref(), prog)
+    }
+
+    /// Detaches the classic BPF program attached to this socket with
+    /// [`attach_bpf`], using the `SO_DETACH_FILTER` option.
+    ///
+    /// [`attach_bpf`]: #method.attach_bpf
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn detach_bpf(&self) -> io::Result<()> {
+        sys::detach_bpf(self.io.get_ref())
+    }
+
+    /// Attaches an eBPF program to this socket with the `SO_ATTACH_BPF`
+    /// option, replacing any filter already attached.
+    ///
+    /// This is the extended counterpart of [`attach_bpf`]: `prog_fd` must
+    /// refer to a program of type `BPF_PROG_TYPE_SOCKET_FILTER` loaded with
+    /// `bpf(2)`, which the kernel verifies when it is loaded.
+    ///
+    /// [`attach_bpf`]: #method.attach_bpf
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn attach_ebpf(&self, prog_fd: RawFd) -> io::Result<()> {
+        sys::attach_ebpf(self.io.get_ref(), prog_fd)
+    }
+
+    /// Detaches the eBPF program attached to this socket with
+    /// [`attach_ebpf`], using the `SO_DETACH_BPF` option.
+    ///
+    /// [`attach_ebpf`]: #method.attach_ebpf
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn detach_ebpf(&self) -> io::Result<()> {
+        sys::detach_ebpf(self.io.get_ref())
+    }
+
+    /// Gets the IPv6 flow label of this socket.
+    ///
+    /// Returns 0 if no flow label has been set. For more information, see
+    /// [`set_flow_label_v6`].
+    ///
+    /// [`set_flow_label_v6`]: #method.set_flow_label_v6
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn flow_label_v6(&self) -> u32 {
+        self.flow_label.load(Ordering::Relaxed)
+    }
+
+    /// Sets the IPv6 flow label of the datagrams sent by this socket.
+    ///
+    /// Flow labels are 20 bit values, so `label` must be at most `0xfffff`.
+    /// The label is leased from the kernel with the `IPV6_FLOWLABEL_MGR`
+    /// option, and `IPV6_FLOWINFO_SEND` is enabled so that it is applied to
+    /// every datagram sent with [`send_to`] or [`send_from_source`] to an IPv6
+    /// address which does not carry flow information of its own. Setting a
+    /// label of 0 releases the lease and sends datagrams without a label again.
+    ///
+    /// On error, the previous label stays in effect.
+    ///
+    /// [`send_to`]: #method.send_to
+    /// [`send_from_source`]: #method.send_from_source
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use romio::udp::UdpSocket;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let socket = UdpSocket::bind(&"[::1]:0".parse()?)?;
+    /// socket.set_flow_label_v6(0x12345)?;
+    /// assert_eq!(socket.flow_label_v6(), 0x12345);
+    /// # Ok(()) }
+    /// ```
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn set_flow_label_v6(&self, label: u32) -> io::Result<()> {
+        if label > 0xf_ffff {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "flow labels are limited to 20 bits",
+            ));
+        }
+
+        let old = self.flow_label.load(Ordering::Relaxed);
+        if label == old {
+            return Ok(());
+        }
+        if label != 0 {
+            sys::get_flow_label(self.io.get_ref(), label)?;
+        }
+        if old != 0 {
+            if let Err(e) = sys::put_flow_label(self.io.get_ref(), old) {
+                if label != 0 {
+                    let _ = sys::put_flow_label(self.io.get_ref(), label);
+                }
+                return Err(e);
+            }
+        }
+        self.flow_label.store(label, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Sets the value of the `SO_ZEROCOPY` option for this socket.
+    ///
+    /// This must be enabled before sending with [`send_to_zerocopy`].
+    ///
+    /// [`send_to_zerocopy`]: #method.send_to_zerocopy
+    #[cfg(target_os = "linux")]
+    pub fn set_zerocopy(&self, on: bool) -> io::Result<()> {
+        sys::set_zerocopy(self.io.get_ref(), on)?;
+        self.zerocopy.enabled.store(on, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Gets the value of the `SO_ZEROCOPY` option for this socket.
+    ///
+    /// For more information about this option, see [`set_zerocopy`].
+    ///
+    /// [`set_zerocopy`]: #method.set_zerocopy
+    #[cfg(target_os = "linux")]
+    pub fn zerocopy(&self) -> io::Result<bool> {
+        sys::zerocopy(self.io.get_ref())
+    }
+
+    /// Sends data on the socket to the given address with `MSG_ZEROCOPY`, so
+    /// that the kernel transmits it directly from `buf` instead of copying it
+    /// first. On success, returns the number of bytes written.
+    ///
+    /// `SO_ZEROCOPY` must first be enabled with [`set_zerocopy`], otherwise
+    /// the future fails with `InvalidInput`. The kernel keeps reading from
+    /// `buf` after the datagram has been queued, so the future borrows it
+    /// until the completion notification of the send arrives on the error
+    /// queue of the socket. Sends are numbered consecutively from 0. The
+    /// future consumes the completion of its own send, while completions of
+    /// earlier sends made with [`poll_send_to_zerocopy`] that it reads while
+    /// waiting are still returned by [`recv_zerocopy_completions`].
+    ///
+    /// Zerocopy is only beneficial for large datagrams, above roughly 10KB.
+    /// For smaller ones, the cost of pinning the pages and handling the
+    /// completion exceeds the cost of the copy.
+    ///
+    /// [`set_zerocopy`]: #method.set_zerocopy
+    /// [`poll_send_to_zerocopy`]: #method.poll_send_to_zerocopy
+    /// [`recv_zerocopy_completions`]: #method.recv_zerocopy_completions
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// # use std::error::Error;
+    /// use romio::udp::UdpSocket;
+    ///
+    /// # async fn send_data() -> Result<(), Box<dyn Error + 'static>> {
+    /// let addr = "127.0.0.1:0".parse()?;
+    /// let target = "127.0.0.1:7878".parse()?;
+    /// let mut socket = UdpSocket::bind(&addr)?;
+    /// socket.set_zerocopy(true)?;
+    ///
+    /// let buf = vec![0; 60 * 1024];
+    /// socket.send_to_zerocopy(&buf, &target).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(target_os = "linux")]
+    pub fn send_to_zerocopy<'a>(
+        &'a mut self,
+        buf: &'a [u8],
+        target: &'a SocketAddr,
+    ) -> SendToZerocopy<'a> {
+        SendToZerocopy {
+            buf,
+            target,
+            socket: self,
+            sent: None,
+        }
+    }
+
+    /// Attempts to send data on the socket to the given address with
+    /// `MSG_ZEROCOPY`.
+    ///
+    /// Unlike [`send_to_zerocopy`], this returns as soon as the datagram has
+    /// been queued, so the caller has to keep `buf` unmodified until
+    /// [`recv_zerocopy_completions`] reports the send as completed.
+    ///
+    /// For more information, see [`send_to_zerocopy`].
+    ///
+    /// [`recv_zerocopy_completions`]: #method.recv_zerocopy_completions
+    ///
+    /// [`send_to_zerocopy`]: #method.send_to_zerocopy
+    #[cfg(target_os = "linux")]
+    pub fn poll_send_to_zerocopy(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+        target: &SocketAddr,
+    ) -> Poll<io::Result<usize>> {
+        ready!(self.io.poll_write_ready(cx)?);
+
+        let target = self.send_target(target);
+        match sys::send_to_zerocopy(self.io.get_ref(), buf, &target) {
+            Ok(n) => {
+                self.zerocopy.next = self.zerocopy.next.wrapping_add(1);
+                Poll::Ready(Ok(n))
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                Pin::new(&mut self.io).clear_write_ready(cx)?;
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    /// Drains the completions of sends made with [`poll_send_to_zerocopy`]
+    /// from the error queue of the socket.
+    ///
+    /// Returns an empty vector if no completion is pending. Completions
+    /// consumed by a [`send_to_zerocopy`] future are not returned.
+    ///
+    /// [`poll_send_to_zerocopy`]: #method.poll_send_to_zerocopy
+    /// [`send_to_zerocopy`]: #method.send_to_zerocopy
+    #[cfg(target_os = "linux")]
+    pub fn recv_zerocopy_completions(&mut self) -> io::Result<Vec<ZerocopyRange>> {
+        loop {
+            match self.recv_zerocopy_completion() {
+                Ok(range) => self.zerocopy.completions.push(range),
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    return Ok(mem::take(&mut self.zerocopy.completions));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Reads a completion from the error queue and moves `done` past it.
+    #[cfg(target_os = "linux")]
+    fn recv_zerocopy_completion(&mut self) -> io::Result<ZerocopyRange> {
+        let range = sys::recv_zerocopy_completion(self.io.get_ref())?;
+        let zerocopy = &mut self.zerocopy;
+        if range.last.wrapping_sub(zerocopy.done) as i32 >= 0 {
+            zerocopy.done = range.last.wrapping_add(1);
+        }
+        Ok(range)
+    }
+
+    /// Gets the value of the `SO_RCVBUF` option for this socket.
+    ///
+    /// For more information about this option, see [`set_recv_buffer_size`].
+    ///
+    /// [`set_recv_buffer_size`]: #method.set_recv_buffer_size
+    #[cfg(unix)]
+    pub fn recv_buffer_size(&self) -> io::Result<usize> {
+        sys::buffer_size(self.io.get_ref(), libc::SO_RCVBUF)
+    }
+
+    /// Sets the value of the `SO_RCVBUF` option for this socket.
+    ///
+    /// Changes the size of the operating system's receive buffer associated
+    /// with the socket. Datagrams arriving while the buffer is full are
+    /// dropped, so high-throughput applications often need a larger buffer
+    /// than the default.
+    ///
+    /// # Note
+    ///
+    /// Linux doubles the requested size to make room for its own bookkeeping,
+    /// and [`recv_buffer_size`] returns the doubled value. The size is also
+    /// capped by the `net.core.rmem_max` sysctl.
+    ///
+    /// [`recv_buffer_size`]: #method.recv_buffer_size
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use romio::udp::UdpSocket;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let socket = UdpSocket::bind(&"127.0.0.1:0".parse()?)?;
+    /// socket.set_recv_buffer_size(4 * 1024 * 1024)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(unix)]
+    pub fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
+        sys::set_buffer_size(self.io.get_ref(), libc::SO_RCVBUF, size)
+    }
+
+    /// Gets the value of the `SO_SNDBUF` option for this socket.
+    ///
+    /// For more information about this option, see [`set_send_buffer_size`].
+    ///
+    /// [`set_send_buffer_size`]: #method.set_send_buffer_size
+    #[cfg(unix)]
+    pub fn send_buffer_size(&self) -> io::Result<usize> {
+        sys::buffer_size(self.io.get_ref(), libc::SO_SNDBUF)
+    }
+
+    /// Sets the value of the `SO_SNDBUF` option for this socket.
+    ///
+    /// Changes the size of the operating system's send buffer associated with
+    /// the socket.
+    ///
+    /// # Note
+    ///
+    /// Linux doubles the requested size to make room for its own bookkeeping,
+    /// and [`send_buffer_size`] returns the doubled value. The size is also
+    /// capped by the `net.core.wmem_max` sysctl.
+    ///
+    /// [`send_buffer_size`]: #method.send_buffer_size
+    #[cfg(unix)]
+    pub fn set_send_buffer_size(&self, size: usize) -> io::Result<()> {
+        sys::set_buffer_size(self.io.get_ref(), libc::SO_SNDBUF, size)
+    }
+
+    /// Sets the close-on-exec flag on the socket's file descriptor, so that
+    /// it is closed in child processes after `exec`.
+    ///
+    /// This only needs to be called for sockets converted from a
+    /// `std::net::UdpSocket`, see [`TcpStream::set_cloexec`].
+    ///
+    /// [`TcpStream::set_cloexec`]: ../tcp/struct.TcpStream.html#method.set_cloexec
+    #[cfg(unix)]
+    pub fn set_cloexec(&self) -> io::Result<()> {
+        crate::socket::set_cloexec(self.as_raw_fd(), true)
+    }
+
+    /// Clears the close-on-exec flag on the socket's file descriptor, so that
+    /// a child process can keep sending and receiving on it after `exec`.
+    #[cfg(unix)]
+    pub fn clear_cloexec(&self) -> io::Result<()> {
+        crate::socket::set_cloexec(self.as_raw_fd(), false)
+    }
+
+    /// Gets the value of the `IP_TOS` option for this socket.
+    ///
+    /// For more information about this option, see [`set_tos`].
+    ///
+    /// [`set_tos`]: #method.set_tos
+    #[cfg(unix)]
+    pub fn tos(&self) -> io::Result<u8> {
+        sys::traffic_class(self.io.get_ref(), libc::IPPROTO_IP, libc::IP_TOS)
+    }
+
+    /// Sets the value of the `IP_TOS` option for this socket.
+    ///
+    /// This value sets the type-of-service byte carried in the header of every
+    /// IPv4 packet sent from this socket. The upper six bits hold the DSCP
+    /// code point, and the lower two bits hold the ECN field, which is passed
+    /// through unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use romio::udp::UdpSocket;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let socket = UdpSocket::bind(&"127.0.0.1:0".parse()?)?;
+    ///
+    /// // DSCP Expedited Forwarding (46), not ECN-capable.
+    /// socket.set_tos(46 << 2)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(unix)]
+    pub fn set_tos(&self, tos: u8) -> io::Result<()> {
+        sys::set_traffic_class(self.io.get_ref(), libc::IPPROTO_IP, libc::IP_TOS, tos)
+    }
+
+    /// Gets the value of the `IPV6_TCLASS` option for this socket.
+    ///
+    /// For more information about this option, see [`set_tclass_v6`].
+    ///
+    /// [`set_tclass_v6`]: #method.set_tclass_v6
+    #[cfg(unix)]
+    pub fn tclass_v6(&self) -> io::Result<u8> {
+        sys::traffic_class(self.io.get_ref(), libc::IPPROTO_IPV6, libc::IPV6_TCLASS)
+    }
+
+    /// Sets the value of the `IPV6_TCLASS` option for this socket.
+    ///
+    /// This is the IPv6 counterpart of [`set_tos`]: the traffic class byte
+    /// carries the DSCP code point in its upper six bits and the ECN field in
+    /// its lower two bits.
+    ///
+    /// [`set_tos`]: #method.set_tos
+    #[cfg(unix)]
+    pub fn set_tclass_v6(&self, tclass: u8) -> io::Result<()> {
+        sys::set_traffic_class(
+            self.io.get_ref(),
+            libc::IPPROTO_IPV6,
+            libc::IPV6_TCLASS,
+            tclass,
+        )
+    }
+
+    /// Gets the value of the `SO_BROADCAST` option for this socket.
+    ///
+    /// For more information about this option, see [`set_broadcast`].
+    ///
+    /// [`set_broadcast`]: #method.set_broadcast
+    pub fn broadcast(&self) -> io::Result<bool> {
+        self.io.get_ref().broadcast()
+    }
+
+    /// Sets the value of the `SO_BROADCAST` option for this socket.
+    ///
+    /// When enabled, this socket is allowed to send packets to a broadcast
+    /// address.
+    pub fn set_broadcast(&self, on: bool) -> io::Result<()> {
+        self.io.get_ref().set_broadcast(on)
+    }
+
+    /// Binds this socket to a particular network interface, using the
+    /// `SO_BINDTODEVICE` option.
+    ///
+    /// Once bound, only packets received on `interface` are delivered to the
+    /// socket, and packets sent from it leave through `interface` regardless
+    /// of the routing table. When `interface` is a VRF device, the socket is
+    /// scoped to that VRF. Passing `None` removes the binding.
+    ///
+    /// Binding usually requires the `CAP_NET_RAW` capability.
+    ///
+    /// # Errors
+    ///
+    /// This option is only available on Linux and Android; other platforms
+    /// return an error of kind `Unsupported`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use romio::udp::UdpSocket;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let socket = UdpSocket::bind(&"0.0.0.0:5353".parse()?)?;
+    /// socket.bind_device(Some(b"eth0"))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn bind_device(&self, interface: Option<&[u8]>) -> io::Result<()> {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        return sys::bind_device(self.io.get_ref(), interface);
+
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        {
+            let _ = interface;
+            Err(bind_device_unsupported())
+        }
+    }
+
+    /// Gets the value of the `SO_BINDTODEVICE` option for this socket.
+    ///
+    /// Returns the name of the interface this socket is bound to, or `None`
+    /// if it isn't bound to one. For more information about this option, see
+    /// [`bind_device`].
+    ///
+    /// [`bind_device`]: #method.bind_device
+    pub fn device(&self) -> io::Result<Option<Vec<u8>>> {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        return sys::device(self.io.get_ref());
+
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        Err(bind_device_unsupported())
+    }
+
+    /// Gets the value of the `IP_MULTICAST_LOOP` option for this socket.
+    ///
+    /// For more information about this option, see [`set_multicast_loop_v4`].
+    ///
+    /// [`set_multicast_loop_v4`]: #method.set_multicast_loop_v4
+    pub fn multicast_loop_v4(&self) -> io::Result<bool> {
+        self.io.get_ref().multicast_loop_v4()
+    }
+
+    /// Sets the value of the `IP_MULTICAST_LOOP` option for this socket.
+    ///
+    /// If enabled, multicast packets will be looped back to the local socket.
+    ///
+    /// # Note
+    ///
+    /// This may not have any affect on IPv6 sockets.
+    pub fn set_multicast_loop_v4(&self, on: bool) -> io::Result<()> {
+        self.io.get_ref().set_multicast_loop_v4(on)
+    }
+
+    /// Gets the value of the `IP_MULTICAST_TTL` option for this socket.
+    ///
+    /// For more information about this option, see [`set_multicast_ttl_v4`].
+    ///
+    /// [`set_multicast_ttl_v4`]: #method.set_multicast_ttl_v4
+    pub fn multicast_ttl_v4(&self) -> io::Result<u32> {
+        self.io.get_ref().multicast_ttl_v4()
+    }
+
+    /// Sets the value of the `IP_MULTICAST_TTL` option for this socket.
+    ///
+    /// Indicates the time-to-live value of outgoing multicast packets for
+    /// this socket. The default value is 1 which means that multicast packets
+    /// don't leave the local network unless explicitly requested.
+    ///
+    /// # Note
+    ///
+    /// This may not have any affect on IPv6 sockets.
+    pub fn set_multicast_ttl_v4(&self, ttl: u32) -> io::Result<()> {
+        self.io.get_ref().set_multicast_ttl_v4(ttl)
+    }
+
+    /// Gets the value of the `IP_MULTICAST_IF` option for this socket.
+    ///
+    /// For more information about this option, see [`set_multicast_if_v4`].
+    ///
+    /// [`set_multicast_if_v4`]: #method.set_multicast_if_v4
+    #[cfg(unix)]
+    pub fn multicast_if_v4(&self) -> io::Result<Ipv4Addr> {
+        sys::multicast_if_v4(self.io.get_ref())
+    }
+
+    /// Sets the value of the `IP_MULTICAST_IF` option for this socket.
+    ///
+    /// Specifies the address of the local interface that outgoing multicast
+    /// packets are sent from. If it's equal to `INADDR_ANY`, the interface is
+    /// chosen by the routing table.
+    ///
+    /// # Note
+    ///
+    /// This may not have any affect on IPv6 sockets.
+    #[cfg(unix)]
+    pub fn set_multicast_if_v4(&self, interface: &Ipv4Addr) -> io::Result<()> {
+        sys::set_multicast_if_v4(self.io.get_ref(), interface)
+    }
+
+    /// Gets the value of the `IPV6_MULTICAST_IF` option for this socket.
+    ///
+    /// For more information about this option, see [`set_multicast_if_v6`].
+    ///
+    /// [`set_multicast_if_v6`]: #method.set_multicast_if_v6
+    #[cfg(unix)]
+    pub fn multicast_if_v6(&self) -> io::Result<u32> {
+        sys::multicast_if_v6(self.io.get_ref())
+    }
+
+    /// Sets the value of the `IPV6_MULTICAST_IF` option for this socket.
+    ///
+    /// Specifies the index of the local interface that outgoing multicast
+    /// packets are sent from. If it's 0, the interface is chosen by the
+    /// routing table.
+    ///
+    /// # Note
+    ///
+    /// This may not have any affect on IPv4 sockets.
+    #[cfg(unix)]
+    pub fn set_multicast_if_v6(&self, interface: u32) -> io::Result<()> {
+        sys::set_multicast_if_v6(self.io.get_ref(), interface)
+    }
+
+    /// Gets the value of the `IPV6_MULTICAST_LOOP` option for this socket.
+    ///
+    /// For more information about this option, see [`set_multicast_loop_v6`].
+    ///
+    /// [`set_multicast_loop_v6`]: #method.set_multicast_loop_v6
+    pub fn multicast_loop_v6(&self) -> io::Result<bool> {
+        self.io.get_ref().multicast_loop_v6()
+    }
+
+    /// Sets the value of the `IPV6_MULTICAST_LOOP` option for this socket.
+    ///
+    /// Controls whether this socket sees the multicast packets it sends itself.
+    ///
+    /// # Note
+    ///
+    /// This may not have any affect on IPv4 sockets.
+    pub fn set_multicast_loop_v6(&self, on: bool) -> io::Result<()> {
+        self.io.get_ref().set_multicast_loop_v6(on)
+    }
+
+    /// Gets the value of the `IPV6_MULTICAST_HOPS` option for this socket.
+    ///
+    /// For more information about this option, see [`set_multicast_hops_v6`].
+    ///
+    /// [`set_multicast_hops_v6`]: #method.set_multicast_hops_v6
+    #[cfg(unix)]
+    pub fn multicast_hops_v6(&self) -> io::Result<u32> {
+        sys::multicast_hops_v6(self.io.get_ref())
+    }
+
+    /// Sets the value of the `IPV6_MULTICAST_HOPS` option for this socket.
+    ///
+    /// Indicates the hop limit of outgoing multicast packets for this socket.
+    /// The default value is 1 which means that multicast packets don't leave
+    /// the local network unless explicitly requested.
+    ///
+    /// # Note
+    ///
+    /// This may not have any affect on IPv4 sockets.
+    #[cfg(unix)]
+    pub fn set_multicast_hops_v6(&self, hops: u32) -> io::Result<()> {
+        sys::set_multicast_hops_v6(self.io.get_ref(), hops)
+    }
+
+    /// Gets the value of the `IP_TTL` option for this socket.
+    ///
+    /// For more information about this option, see [`set_ttl`].
+    ///
+    /// [`set_ttl`]: #method.set_ttl
+    pub fn ttl(&self) -> io::Result<u32> {
+        self.io.get_ref().ttl()
+    }
+
+    /// Sets the value for the `IP_TTL` option on this socket.
+    ///
+    /// This value sets the time-to-live field that is used in every packet sent
+    /// from this socket.
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        self.io.get_ref().set_ttl(ttl)
+    }
+
+    /// Gets the value of the `SO_ERROR` option on this socket.
+    ///
+    /// This will retrieve the stored error in the underlying socket, clearing
+    /// the field in the process. On connected sockets this is where
+    /// asynchronous ICMP errors, such as a destination being unreachable, are
+    /// reported.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use romio::udp::UdpSocket;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let socket = UdpSocket::bind(&"127.0.0.1:0".parse()?)?;
+    /// if let Some(err) = socket.take_error()? {
+    ///     println!("socket error: {}", err);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        self.io.get_ref().take_error()
+    }
+
+    /// Executes an operation of the `IP_ADD_MEMBERSHIP` type.
+    ///
+    /// This function specifies a new multicast group for this socket to join.
+    /// The address must be a valid multicast address, and `interface` is the
+    /// address of the local interface with which the system should join the
+    /// multicast group. If it's equal to `INADDR_ANY` then an appropriate
+    /// interface is chosen by the system.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use romio::udp::UdpSocket;
+    /// use std::net::Ipv4Addr;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let socket_addr = "127.0.0.1:0".parse()?;
+    /// let interface = Ipv4Addr::new(0, 0, 0, 0);
+    /// let mdns_addr = Ipv4Addr::new(224, 0, 0, 123);
+    ///
+    /// let socket = UdpSocket::bind(&socket_addr)?;
+    /// socket.join_multicast_v4(&mdns_addr, &interface)?;
+    /// # Ok(()) }
+    /// ```
+    pub fn join_multicast_v4(&self, multiaddr: &Ipv4Addr, interface: &Ipv4Addr) -> io::Result<()> {
+        self.io.get_ref().join_multicast_v4(multiaddr, interface)?;
+        let mut groups = self.multicast_v4.lock().unwrap();
+        if !groups.contains(&(*multiaddr, *interface)) {
+            groups.push((*multiaddr, *interface));
+        }
+        Ok(())
+    }
+
+    /// Executes an operation of the `IPV6_ADD_MEMBERSHIP` type.
+    ///
+    /// This function specifies a new multicast group for this socket to join.
+    /// The address must be a valid multicast address, and `interface` is the
+    /// index of the interface to join/leave (or 0 to indicate any interface).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use romio::udp::UdpSocket;
+    /// use std::net::{Ipv6Addr, SocketAddr};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let socket_addr = SocketAddr::new(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 0).into(), 0);
+    /// let mdns_addr = Ipv6Addr::new(0xFF02, 0, 0, 0, 0, 0, 0, 0x0123) ;
+    /// let socket = UdpSocket::bind(&socket_addr)?;
+    ///
+    /// socket.join_multicast_v6(&mdns_addr, 0)?;
+    /// # Ok(()) }
+    /// ```
+    pub fn join_multicast_v6(&self, multiaddr: &Ipv6Addr, interface: u32) -> io::Result<()> {
+        self.io.get_ref().join_multicast_v6(multiaddr, interface)
+    }
+
+    /// Executes an operation of the `IP_DROP_MEMBERSHIP` type.
+    ///
+    /// For more information about this option, see [`join_multicast_v4`].
+    ///
+    /// [`join_multicast_v4`]: #method.join_multicast_v4
+    pub fn leave_multicast_v4(&self, multiaddr: &Ipv4Addr, interface: &Ipv4Addr) -> io::Result<()> {
+        self.io.get_ref().leave_multicast_v4(multiaddr, interface)?;
+        self.multicast_v4
+            .lock()
+            .unwrap()
+            .retain(|group| *group != (*multiaddr, *interface));
+        Ok(())
+    }
+
+    /// Leaves every IPv4 multicast group this socket has joined.
+    ///
+    /// This first issues `IP_DROP_MEMBERSHIP` with `INADDR_ANY` as the group
+    /// address, which some kernels interpret as "leave all groups". Where that
+    /// is not supported, it falls back to leaving each group joined through
+    /// [`join_multicast_v4`] in turn. Groups that cannot be left are kept, and
+    /// the first error encountered is returned.
+    ///
+    /// The fallback only knows about groups joined with [`join_multicast_v4`]
+    /// on this `UdpSocket`. Groups joined on a socket before it was converted
+    /// with `UdpSocket::try_from`, or joined through `set_socket2_options`,
+    /// are not tracked and must be left explicitly on kernels without the
+    /// shortcut, such as Linux.
+    ///
+    /// [`join_multicast_v4`]: #method.join_multicast_v4
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use romio::udp::UdpSocket;
+    /// use std::net::Ipv4Addr;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let socket_addr = "0.0.0.0:0".parse()?;
+    /// let interface = Ipv4Addr::new(0, 0, 0, 0);
+    ///
+    /// let socket = UdpSocket::bind(&socket_addr)?;
+    /// socket.join_multicast_v4(&Ipv4Addr::new(224, 0, 0, 123), &interface)?;
+    /// socket.join_multicast_v4(&Ipv4Addr::new(224, 0, 0, 124), &interface)?;
+    ///
+    /// socket.leave_all_multicast_v4()?;
+    /// # Ok(()) }
+    /// ```
+    pub fn leave_all_multicast_v4(&self) -> io::Result<()> {
+        let mut groups = self.multicast_v4.lock().unwrap();
+        let any = Ipv4Addr::UNSPECIFIED;
+        if self.io.get_ref().leave_multicast_v4(&any, &any).is_ok() {
+            groups.clear();
+            return Ok(());
+        }
+
+        let mut res = Ok(());
+        let io = self.io.get_ref();
+        groups.retain(|(multiaddr, interface)| {
+            if let Err(e) = io.leave_multicast_v4(multiaddr, interface) {
+                if res.is_ok() {
+                    res = Err(e);
+                }
+                return true;
+            }
+            false
+        });
+        res
+    }
+
+    /// Executes an operation of the `IP_ADD_SOURCE_MEMBERSHIP` type.
+    ///
+    /// This function joins the multicast group `multiaddr` on the local
+    /// interface with address `interface`, like [`join_multicast_v4`], but only
+    /// accepts datagrams sent to the group by `source` (source-specific
+    /// multicast). It can be called repeatedly to accept several sources.
+    ///
+    /// [`join_multicast_v4`]: #method.join_multicast_v4
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use romio::udp::UdpSocket;
+    /// use std::net::Ipv4Addr;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let socket_addr = "0.0.0.0:5000".parse()?;
+    /// let interface = Ipv4Addr::new(0, 0, 0, 0);
+    /// let group = Ipv4Addr::new(232, 1, 1, 1);
+    /// let source = Ipv4Addr::new(192, 0, 2, 1);
+    ///
+    /// let socket = UdpSocket::bind(&socket_addr)?;
+    /// socket.join_multicast_v4_source(&group, &interface, &source)?;
+    /// # Ok(()) }
+    /// ```
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd",
+        target_os = "solaris",
+        target_os = "illumos"
+    ))]
+    pub fn join_multicast_v4_source(
+        &self,
+        multiaddr: &Ipv4Addr,
+        interface: &Ipv4Addr,
+        source: &Ipv4Addr,
+    ) -> io::Result<()> {
+        sys::set_source_membership(self.io.get_ref(), true, multiaddr, interface, source)
+    }
+
+    /// Executes an operation of the `IP_DROP_SOURCE_MEMBERSHIP` type.
+    ///
+    /// For more information about this option, see
+    /// [`join_multicast_v4_source`].
+    ///
+    /// [`join_multicast_v4_source`]: #method.join_multicast_v4_source
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd",
+        target_os = "solaris",
+        target_os = "illumos"
+    ))]
+    pub fn leave_multicast_v4_source(
+        &self,
+        multiaddr: &Ipv4Addr,
+        interface: &Ipv4Addr,
+        source: &Ipv4Addr,
+    ) -> io::Result<()> {
+        sys::set_source_membership(self.io.get_ref(), false, multiaddr, interface, source)
+    }
+
+    /// Executes an operation of the `IPV6_DROP_MEMBERSHIP` type.
+    ///
+    /// For more information about this option, see [`join_multicast_v6`].
+    ///
+    /// [`join_multicast_v6`]: #method.join_multicast_v6
+    pub fn leave_multicast_v6(&self, multiaddr: &Ipv6Addr, interface: u32) -> io::Result<()> {
+        self.io.get_ref().leave_multicast_v6(multiaddr, interface)
+    }
+
+    /// Splits the socket into a half for sending and a half for receiving,
+    /// which can be used from separate tasks.
+    ///
+    /// Both halves share the socket, which is closed once both of them are
+    /// dropped. They can be put back together with
+    /// [`UdpSocketSendHalf::reunite`].
+    ///
+    /// [`UdpSocketSendHalf::reunite`]: struct.UdpSocketSendHalf.html#method.reunite
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// # use std::error::Error;
+    /// use romio::udp::UdpSocket;
+    ///
+    /// # async fn echo() -> Result<(), Box<dyn Error + 'static>> {
+    /// let addr = "127.0.0.1:7878".parse()?;
+    /// let socket = UdpSocket::bind(&addr)?;
+    /// let (mut send, mut recv) = socket.split();
+    ///
+    /// let mut buf = vec![0; 1024];
+    /// let (n, peer) = recv.recv_from(&mut buf).await?;
+    /// send.send_to(&buf[..n], &peer).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn split(self) -> (UdpSocketSendHalf, UdpSocketRecvHalf) {
+        let socket = Arc::new(self);
+        (UdpSocketSendHalf(socket.clone()), UdpSocketRecvHalf(socket))
+    }
+}
+
+impl AsyncDatagram for UdpSocket {
+    type Sender = SocketAddr;
+    type Receiver = SocketAddr;
+    type Err = io::Error;
+
+    fn poll_send_to(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+        receiver: &Self::Receiver,
+    ) -> Poll<io::Result<usize>> {
+        self.poll_send_to_priv(cx, buf, receiver)
+    }
+
+    fn poll_recv_from(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<(usize, Self::Sender)>> {
+        self.poll_recv_from_priv(cx, buf)
+    }
+}
+
+impl AsyncReadReady for UdpSocket
+where
+    Self: Unpin,
+{
+    type Ok = mio::Ready;
+    type Err = io::Error;
+
+    /// Check the UDP socket's read readiness state.
+    ///
+    /// If the socket is not ready for receiving then `Poll::Pending` is
+    /// returned and the current task is notified once a new event is received.
+    ///
+    /// The socket will remain in a read-ready state until calls to `poll_recv`
+    /// return `Pending`.
+    fn poll_read_ready(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Self::Ok, Self::Err>> {
+        Pin::new(&mut self.io).poll_read_ready(cx)
+    }
+}
+
+impl AsyncWriteReady for UdpSocket {
+    type Ok = mio::Ready;
+    type Err = io::Error;
+
+    /// Check the UDP socket's write readiness state.
+    ///
+    /// If the socket is not ready for sending then `Poll::Pending` is
+    /// returned and the current task is notified once a new event is received.
+    ///
+    /// The I/O resource will remain in a write-ready state until calls to
+    /// `poll_send` return `Pending`.
+    fn poll_write_ready(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Self::Ok, Self::Err>> {
+        self.io.poll_write_ready(cx)
+    }
+}
+
+impl fmt::Debug for UdpSocket {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.io.get_ref().fmt(f)
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn bind_device_unsupported() -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, "SO_BINDTODEVICE not supported")
+}
+
+#[cfg(all(unix))]
+mod sys {
+    use super::{UdpSocket, UdpSocketBuilder};
+    use std::io::{self, IoSlice, IoSliceMut};
+    use std::mem;
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::os::unix::prelude::*;
+
+    use crate::socket;
+
+    impl AsRawFd for UdpSocket {
+        fn as_raw_fd(&self) -> RawFd {
+            self.io.get_ref().as_raw_fd()
+        }
+    }
+
+    impl UdpSocket {
+        /// Converts this socket into a `std::net::UdpSocket`.
+        ///
+        /// The socket is deregistered from the reactor and switched back to
+        /// blocking mode, so it can be handed to synchronous code. Socket
+        /// options, multicast memberships and a connected peer are kept, as
+        /// they belong to the underlying socket.
+        ///
+        /// # Examples
+        ///
+        /// ```rust,no_run
+        /// use romio::udp::UdpSocket;
+        ///
+        /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+        /// let socket = UdpSocket::bind(&"127.0.0.1:0".parse()?)?;
+        /// let std_socket = socket.into_std()?;
+        /// std_socket.send_to(b"hello", "127.0.0.1:8080")?;
+        /// # Ok(())
+        /// # }
+        /// ```
+        pub fn into_std(self) -> io::Result<std::net::UdpSocket> {
+            let fd = self.io.into_inner()?.into_raw_fd();
+            let socket = unsafe { std::net::UdpSocket::from_raw_fd(fd) };
+            socket.set_nonblocking(false)?;
+            Ok(socket)
+        }
+
+        /// Creates a UDP socket with `SO_REUSEPORT` set, bound to the given
+        /// address.
+        ///
+        /// Several sockets can be bound to the same address this way, as long
+        /// as all of them set the option and belong to the same user. On Linux
+        /// the kernel then distributes incoming datagrams across the sockets,
+        /// which allows a server to be scaled over several processes or
+        /// threads. The option must be set before binding, which is why this
+        /// is a separate constructor.
+        ///
+        /// Returns an error on platforms that do not support `SO_REUSEPORT`.
+        ///
+        /// # Examples
+        ///
+        /// ```rust,no_run
+        /// use romio::udp::UdpSocket;
+        ///
+        /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+        /// let addr = "0.0.0.0:5353".parse()?;
+        /// let first = UdpSocket::bind_reuseport(&addr)?;
+        /// let second = UdpSocket::bind_reuseport(&addr)?;
+        /// # Ok(())
+        /// # }
+        /// ```
+        pub fn bind_reuseport(addr: &SocketAddr) -> io::Result<UdpSocket> {
+            UdpSocketBuilder::new().reuseport(true).bind(addr)
+        }
+
+        /// Gets the value of the `SO_REUSEPORT` option for this socket.
+        ///
+        /// For more information about this option, see [`bind_reuseport`].
+        ///
+        /// [`bind_reuseport`]: #method.bind_reuseport
+        pub fn reuseport(&self) -> io::Result<bool> {
+            socket::reuseport(self.as_raw_fd())
+        }
+
+        /// Sets the value of the `SO_REUSEPORT` option for this socket.
+        ///
+        /// Setting the option on a socket which is already bound only affects
+        /// sockets bound to the same address later on. To share an address
+        /// between sockets, create them with [`bind_reuseport`].
+        ///
+        /// [`bind_reuseport`]: #method.bind_reuseport
+        pub fn set_reuseport(&self, on: bool) -> io::Result<()> {
+            socket::set_reuseport(self.as_raw_fd(), on)
+        }
+
+        /// Grants temporary access to the underlying socket as a
+        /// [`socket2::Socket`], for configuration this type does not expose.
+        ///
+        /// This is an escape hatch: changes made through it bypass romio's own
+        /// view of the socket. Options that affect how the socket is driven,
+        /// such as its blocking mode, must be left alone, and the socket must
+        /// not be closed.
+        ///
+        /// [`socket2::Socket`]: https://docs.rs/socket2/0.3/socket2/struct.Socket.html
+        ///
+        /// # Examples
+        ///
+        /// ```rust,no_run
+        /// use romio::udp::UdpSocket;
+        ///
+        /// # fn main () -> Result<(), Box<dyn std::error::Error + 'static>> {
+        /// let addr = "127.0.0.1:0".parse()?;
+        /// let socket = UdpSocket::bind(&addr)?;
+        ///
+        /// socket.set_socket2_options(|socket| socket.set_broadcast(true))?;
+        /// # Ok(())}
+        /// ```
+        #[cfg(feature = "socket2")]
+        pub fn set_socket2_options<F>(&self, f: F) -> io::Result<()>
+        where
+            F: FnOnce(&socket2::Socket) -> io::Result<()>,
+        {
+            crate::socket::with_socket2(self.as_raw_fd(), f)
+        }
+    }
+
+    pub(super) fn peer_addr(socket: &mio::net::UdpSocket) -> io::Result<SocketAddr> {
+        unsafe {
+            let mut storage: libc::sockaddr_storage = mem::zeroed();
+            let mut len = mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+            socket::cvt(libc::getpeername(
+                socket.as_raw_fd(),
+                &mut storage as *mut _ as *mut libc::sockaddr,
+                &mut len,
+            ))?;
+            socket::socket_addr(&storage).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "unexpected peer address family")
+            })
+        }
+    }
+
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd",
+        target_os = "solaris",
+        target_os = "illumos"
+    ))]
+    pub(super) fn set_source_membership(
+        socket: &mio::net::UdpSocket,
+        join: bool,
+        multiaddr: &Ipv4Addr,
+        interface: &Ipv4Addr,
+        source: &Ipv4Addr,
+    ) -> io::Result<()> {
+        let name = if join {
+            libc::IP_ADD_SOURCE_MEMBERSHIP
+        } else {
+            libc::IP_DROP_SOURCE_MEMBERSHIP
+        };
+        let mreq = libc::ip_mreq_source {
+            imr_multiaddr: socket::in_addr(multiaddr),
+            imr_interface: socket::in_addr(interface),
+            imr_sourceaddr: socket::in_addr(source),
+        };
+        socket::setsockopt(socket.as_raw_fd(), libc::IPPROTO_IP, name, mreq)
+    }
+
+    pub(super) fn multicast_if_v4(socket: &mio::net::UdpSocket) -> io::Result<Ipv4Addr> {
+        let addr: libc::in_addr =
+            socket::getsockopt(socket.as_raw_fd(), libc::IPPROTO_IP, libc::IP_MULTICAST_IF)?;
+        Ok(socket::ipv4_addr(&addr))
+    }
+
+    pub(super) fn set_multicast_if_v4(
+        socket: &mio::net::UdpSocket,
+        interface: &Ipv4Addr,
+    ) -> io::Result<()> {
+        socket::setsockopt(
+            socket.as_raw_fd(),
+            libc::IPPROTO_IP,
+            libc::IP_MULTICAST_IF,
+            socket::in_addr(interface),
+        )
+    }
+
+    pub(super) fn multicast_if_v6(socket: &mio::net::UdpSocket) -> io::Result<u32> {
+        let index: libc::c_uint = socket::getsockopt(
+            socket.as_raw_fd(),
+            libc::IPPROTO_IPV6,
+            libc::IPV6_MULTICAST_IF,
+        )?;
+        Ok(index as u32)
+    }
+
+    pub(super) fn set_multicast_if_v6(
+        socket: &mio::net::UdpSocket,
+        interface: u32,
+    ) -> io::Result<()> {
+        socket::setsockopt(
+            socket.as_raw_fd(),
+            libc::IPPROTO_IPV6,
+            libc::IPV6_MULTICAST_IF,
+            interface as libc::c_uint,
+        )
+    }
+
+    pub(super) fn send_to_vectored(
+        socket: &mio::net::UdpSocket,
+        bufs: &[IoSlice<'_>],
+        target: &SocketAddr,
+    ) -> io::Result<usize> {
+        socket::send_msg(socket.as_raw_fd(), bufs, Some(target), None, 0)
+    }
+
+    pub(super) fn recv_from_vectored(
+        socket: &mio::net::UdpSocket,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> io::Result<(usize, SocketAddr)> {
+        let msg = socket::recv_msg(socket.as_raw_fd(), bufs, None, 0)?;
+        let addr = msg.addr.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "unexpected sender address family",
+            )
+        })?;
+        Ok((msg.len, addr))
+    }
+
+    pub(super) fn buffer_size(
+        socket: &mio::net::UdpSocket,
+        name: libc::c_int,
+    ) -> io::Result<usize> {
+        let size: libc::c_int = socket::getsockopt(socket.as_raw_fd(), libc::SOL_SOCKET, name)?;
+        Ok(size as usize)
+    }
+
+    pub(super) fn set_buffer_size(
+        socket: &mio::net::UdpSocket,
+        name: libc::c_int,
+        size: usize,
+    ) -> io::Result<()> {
+        let size = size.min(libc::c_int::MAX as usize) as libc::c_int;
+        socket::setsockopt(socket.as_raw_fd(), libc::SOL_SOCKET, name, size)
+    }
+
+    pub(super) fn traffic_class(
+        socket: &mio::net::UdpSocket,
+        level: libc::c_int,
+        name: libc::c_int,
+    ) -> io::Result<u8> {
+        let class: libc::c_int = socket::getsockopt(socket.as_raw_fd(), level, name)?;
+        Ok(class as u8)
+    }
+
+    pub(super) fn set_traffic_class(
+        socket: &mio::net::UdpSocket,
+        level: libc::c_int,
+        name: libc::c_int,
+        class: u8,
+    ) -> io::Result<()> {
+        // Both options take an `int`, which some platforms insist on even
+        // though only the low byte is used.
+        socket::setsockopt(socket.as_raw_fd(), level, name, libc::c_int::from(class))
+    }
+
+    pub(super) fn multicast_hops_v6(socket: &mio::net::UdpSocket) -> io::Result<u32> {
+        let hops: libc::c_int = socket::getsockopt(
+            socket.as_raw_fd(),
+            libc::IPPROTO_IPV6,
+            libc::IPV6_MULTICAST_HOPS,
+        )?;
+        Ok(hops as u32)
+    }
+
+    pub(super) fn set_multicast_hops_v6(socket: &mio::net::UdpSocket, hops: u32) -> io::Result<()> {
+        socket::setsockopt(
+            socket.as_raw_fd(),
+            libc::IPPROTO_IPV6,
+            libc::IPV6_MULTICAST_HOPS,
+            hops as libc::c_int,
+        )
+    }
+
+    pub(super) fn peek_from(
+        socket: &mio::net::UdpSocket,
+        buf: &mut [u8],
+    ) -> io::Result<(usize, SocketAddr)> {
+        let msg = socket::recv_msg(
+            socket.as_raw_fd(),
+            &mut [IoSliceMut::new(buf)],
+            None,
+            libc::MSG_PEEK,
+        )?;
+        let addr = msg.addr.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "unexpected sender address family")
+        })?;
+        Ok((msg.len, addr))
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub(super) use self::linux::*;
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    mod linux {
+        use std::io::{self, IoSlice, IoSliceMut};
+        use std::mem;
+        use std::net::{IpAddr, Ipv6Addr, SocketAddr};
+        use std::os::unix::prelude::*;
+        use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+        use crate::socket::{self, Cmsg, Control};
+        #[cfg(target_os = "linux")]
+        use crate::udp::{RecvMsg, SendMsg, TimestampingFlags, Timestamps, ZerocopyRange};
+
+        /// Argument of the `IPV6_FLOWLABEL_MGR` option, not exported by libc.
+        #[repr(C)]
+        #[derive(Copy, Clone)]
+        #[allow(non_camel_case_types)]
+        struct in6_flowlabel_req {
+            flr_dst: libc::in6_addr,
+            flr_label: u32,
+            flr_action: u8,
+            flr_share: u8,
+            flr_flags: u16,
+            flr_expires: u16,
+            flr_linger: u16,
+            __flr_pad: u32,
+        }
+
+        const IPV6_FL_A_GET: u8 = 0;
+        const IPV6_FL_A_PUT: u8 = 1;
+        const IPV6_FL_F_CREATE: u16 = 1;
+        const IPV6_FL_S_ANY: u8 = 255;
+
+        fn flow_label_mgr(
+            socket: &mio::net::UdpSocket,
+            label: u32,
+            action: u8,
+            flags: u16,
+        ) -> io::Result<()> {
+            let mut req: in6_flowlabel_req = unsafe { mem::zeroed() };
+            // The kernel records a destination with every lease, which is only
+            // used when sending without an explicit address. Unconnected
+            // sockets always pass one, so any unicast address will do.
+            req.flr_dst = socket::in6_addr(&Ipv6Addr::LOCALHOST);
+            req.flr_label = label.to_be();
+            req.flr_action = action;
+            req.flr_share = IPV6_FL_S_ANY;
+            req.flr_flags = flags;
+            socket::setsockopt(
+                socket.as_raw_fd(),
+                libc::IPPROTO_IPV6,
+                libc::IPV6_FLOWLABEL_MGR,
+                req,
+            )
+        }
+
+        pub(crate) fn get_flow_label(socket: &mio::net::UdpSocket, label: u32) -> io::Result<()> {
+            flow_label_mgr(socket, label, IPV6_FL_A_GET, IPV6_FL_F_CREATE)?;
+            let res = socket::setsockopt(
+                socket.as_raw_fd(),
+                libc::IPPROTO_IPV6,
+                libc::IPV6_FLOWINFO_SEND,
+                1 as libc::c_int,
+            );
+            if res.is_err() {
+                let _ = put_flow_label(socket, label);
+            }
+            res
+        }
+
+        pub(crate) fn put_flow_label(socket: &mio::net::UdpSocket, label: u32) -> io::Result<()> {
+            flow_label_mgr(socket, label, IPV6_FL_A_PUT, 0)
+        }
+
+        /// Sets the `v4` or `v6` variant of an `IPPROTO_IP`/`IPPROTO_IPV6`
+        /// option, depending on the address family of the socket.
+        fn set_ip_opt(
+            socket: &mio::net::UdpSocket,
+            v4: libc::c_int,
+            v6: libc::c_int,
+            on: bool,
+        ) -> io::Result<()> {
+            let (level, name) = match socket.local_addr()? {
+                SocketAddr::V4(..) => (libc::IPPROTO_IP, v4),
+                SocketAddr::V6(..) => (libc::IPPROTO_IPV6, v6),
+            };
+            socket::setsockopt(socket.as_raw_fd(), level, name, on as libc::c_int)
+        }
+
+        /// Receives a datagram along with ancillary data of up to `space`
+        /// bytes, and returns the first value `parse` extracts from it.
+        fn recv_from_with_cmsg<T>(
+            socket: &mio::net::UdpSocket,
+            buf: &mut [u8],
+            space: usize,
+            parse: impl FnMut(Cmsg<'_>) -> Option<T>,
+        ) -> io::Result<(usize, SocketAddr, Option<T>)> {
+            let mut control = Control::with_capacity(space);
+            let msg = socket::recv_msg(
+                socket.as_raw_fd(),
+                &mut [IoSliceMut::new(buf)],
+                Some(&mut control),
+                0,
+            )?;
+            let addr = msg.addr.ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "unexpected sender address family")
+            })?;
+
+            Ok((msg.len, addr, control.iter().find_map(parse)))
+        }
+
+        pub(crate) fn set_recv_pktinfo(socket: &mio::net::UdpSocket, on: bool) -> io::Result<()> {
+            set_ip_opt(socket, libc::IP_PKTINFO, libc::IPV6_RECVPKTINFO, on)
+        }
+
+        pub(crate) fn set_recv_orig_dst_addr(
+            socket: &mio::net::UdpSocket,
+            on: bool,
+        ) -> io::Result<()> {
+            set_ip_opt(
+                socket,
+                libc::IP_RECVORIGDSTADDR,
+                libc::IPV6_RECVORIGDSTADDR,
+                on,
+            )
+        }
+
+        pub(crate) fn bind_device(
+            socket: &mio::net::UdpSocket,
+            interface: Option<&[u8]>,
+        ) -> io::Result<()> {
+            // An empty name removes the binding.
+            let interface = interface.unwrap_or(&[]);
+            unsafe {
+                socket::cvt(libc::setsockopt(
+                    socket.as_raw_fd(),
+                    libc::SOL_SOCKET,
+                    libc::SO_BINDTODEVICE,
+                    interface.as_ptr() as *const libc::c_void,
+                    interface.len() as libc::socklen_t,
+                ))?;
+            }
+            Ok(())
+        }
+
+        pub(crate) fn device(socket: &mio::net::UdpSocket) -> io::Result<Option<Vec<u8>>> {
+            let mut buf = [0u8; libc::IFNAMSIZ];
+            let mut len = buf.len() as libc::socklen_t;
+            unsafe {
+                socket::cvt(libc::getsockopt(
+                    socket.as_raw_fd(),
+                    libc::SOL_SOCKET,
+                    libc::SO_BINDTODEVICE,
+                    buf.as_mut_ptr() as *mut libc::c_void,
+                    &mut len,
+                ))?;
+            }
+            // The returned name includes its NUL terminator.
+            let name = &buf[..len as usize];
+            let name = match name.iter().position(|&b| b == 0) {
+                Some(end) => &name[..end],
+                None => name,
+            };
+            if name.is_empty() {
+                Ok(None)
+            } else {
+                Ok(Some(name.to_vec()))
+            }
+        }
+
+        pub(crate) fn set_recv_ttl(socket: &mio::net::UdpSocket, on: bool) -> io::Result<()> {
+            set_ip_opt(socket, libc::IP_RECVTTL, libc::IPV6_RECVHOPLIMIT, on)
+        }
+
+        pub(crate) fn attach_bpf(
+            socket: &mio::net::UdpSocket,
+            prog: &[libc::sock_filter],
+        ) -> io::Result<()> {
+            socket::attach_filter(socket.as_raw_fd(), libc::SO_ATTACH_FILTER, prog)
+        }
+
+        pub(crate) fn detach_bpf(socket: &mio::net::UdpSocket) -> io::Result<()> {
+            socket::setsockopt(
+                socket.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_DETACH_FILTER,
+                0 as libc::c_int,
+            )
+        }
+
+        pub(crate) fn attach_ebpf(socket: &mio::net::UdpSocket, prog_fd: RawFd) -> io::Result<()> {
+            socket::setsockopt(
+                socket.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_ATTACH_BPF,
+                prog_fd,
+            )
+        }
+
+        pub(crate) fn detach_ebpf(socket: &mio::net::UdpSocket) -> io::Result<()> {
+            socket::setsockopt(
+                socket.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_DETACH_BPF,
+                0 as libc::c_int,
+            )
+        }
+
+        pub(crate) fn set_timestampns(socket: &mio::net::UdpSocket, on: bool) -> io::Result<()> {
+            socket::setsockopt(
+                socket.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_TIMESTAMPNS,
+                on as libc::c_int,
+            )
+        }
+
+        pub(crate) fn recv_from_timestampns(
+            socket: &mio::net::UdpSocket,
+            buf: &mut [u8],
+        ) -> io::Result<(usize, SocketAddr, Option<SystemTime>)> {
+            let space = Control::space(mem::size_of::<libc::timespec>());
+            recv_from_with_cmsg(socket, buf, space, |cmsg| match (cmsg.level, cmsg.ty) {
+                (libc::SOL_SOCKET, libc::SCM_TIMESTAMPNS) => {
+                    cmsg.read::<libc::timespec>().map(|ts| {
+                        UNIX_EPOCH + Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32)
+                    })
+                }
+                _ => None,
+            })
+        }
+
+        #[cfg(target_os = "linux")]
+        pub(crate) fn send_mmsg(
+            socket: &mio::net::UdpSocket,
+            msgs: &[SendMsg<'_>],
+            send_target: impl Fn(&SocketAddr) -> SocketAddr,
+        ) -> io::Result<usize> {
+            let mut names: Vec<_> = msgs
+                .iter()
+                .map(|msg| socket::sockaddr(&send_target(&msg.target)))
+                .collect();
+            let mut iovecs: Vec<_> = msgs.iter().map(|msg| IoSlice::new(msg.buf)).collect();
+            let mut hdrs: Vec<libc::mmsghdr> = names
+                .iter_mut()
+                .zip(iovecs.iter_mut())
+                .map(|((storage, len), iov)| {
+                    let mut hdr: libc::mmsghdr = unsafe { mem::zeroed() };
+                    hdr.msg_hdr.msg_name = storage as *mut _ as *mut libc::c_void;
+                    hdr.msg_hdr.msg_namelen = *len;
+                    hdr.msg_hdr.msg_iov = iov as *mut _ as *mut libc::iovec;
+                    hdr.msg_hdr.msg_iovlen = 1;
+                    hdr
+                })
+                .collect();
+
+            let n = unsafe {
+                socket::cvt(libc::sendmmsg(
+                    socket.as_raw_fd(),
+                    hdrs.as_mut_ptr(),
+                    hdrs.len().min(libc::c_uint::MAX as usize) as libc::c_uint,
+                    0,
+                ))?
+            };
+            Ok(n as usize)
+        }
+
+        #[cfg(target_os = "linux")]
+        pub(crate) fn recv_mmsg(
+            socket: &mio::net::UdpSocket,
+            msgs: &mut [RecvMsg<'_>],
+        ) -> io::Result<usize> {
+            let mut names: Vec<libc::sockaddr_storage> = vec![unsafe { mem::zeroed() }; msgs.len()];
+            let mut iovecs: Vec<_> = msgs
+                .iter_mut()
+                .map(|msg| IoSliceMut::new(&mut *msg.buf))
+                .collect();
+            let mut hdrs: Vec<libc::mmsghdr> = names
+                .iter_mut()
+                .zip(iovecs.iter_mut())
+                .map(|(storage, iov)| {
+                    let mut hdr: libc::mmsghdr = unsafe { mem::zeroed() };
+                    hdr.msg_hdr.msg_name = storage as *mut _ as *mut libc::c_void;
+                    hdr.msg_hdr.msg_namelen =
+                        mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+                    hdr.msg_hdr.msg_iov = iov as *mut _ as *mut libc::iovec;
+                    hdr.msg_hdr.msg_iovlen = 1;
+                    hdr
+                })
+                .collect();
+
+            let n = unsafe {
+                socket::cvt(libc::recvmmsg(
+                    socket.as_raw_fd(),
+                    hdrs.as_mut_ptr(),
+                    hdrs.len().min(libc::c_uint::MAX as usize) as libc::c_uint,
+                    0,
+                    std::ptr::null_mut(),
+                ))? as usize
+            };
+            drop(iovecs);
+
+            for ((msg, hdr), storage) in msgs.iter_mut().zip(&hdrs).zip(&names).take(n) {
+                msg.len = hdr.msg_len as usize;
+                msg.addr = socket::socket_addr(storage);
+            }
+            Ok(n)
+        }
+
+        #[cfg(target_os = "linux")]
+        pub(crate) fn set_timestamping(
+            socket: &mio::net::UdpSocket,
+            flags: TimestampingFlags,
+        ) -> io::Result<()> {
+            socket::setsockopt(
+                socket.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_TIMESTAMPING,
+                flags.bits() as libc::c_int,
+            )
+        }
+
+        #[cfg(target_os = "linux")]
+        pub(crate) fn recv_from_with_timestamp(
+            socket: &mio::net::UdpSocket,
+            buf: &mut [u8],
+        ) -> io::Result<(usize, SocketAddr, Option<Timestamps>)> {
+            fn nonzero(ts: &libc::timespec) -> Option<Duration> {
+                if ts.tv_sec == 0 && ts.tv_nsec == 0 {
+                    None
+                } else {
+                    Some(Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32))
+                }
+            }
+
+            // `struct scm_timestamping` holds the software timestamp, a
+            // deprecated one, and the raw hardware timestamp.
+            let space = Control::space(mem::size_of::<[libc::timespec; 3]>());
+            recv_from_with_cmsg(socket, buf, space, |cmsg| match (cmsg.level, cmsg.ty) {
+                (libc::SOL_SOCKET, libc::SCM_TIMESTAMPING) => {
+                    cmsg.read::<[libc::timespec; 3]>().map(|ts| Timestamps {
+                        software: nonzero(&ts[0]).map(|d| UNIX_EPOCH + d),
+                        hardware: nonzero(&ts[2]),
+                    })
+                }
+                _ => None,
+            })
+        }
+
+        pub(crate) fn recv_from_with_source(
+            socket: &mio::net::UdpSocket,
+            buf: &mut [u8],
+        ) -> io::Result<(usize, SocketAddr, IpAddr)> {
+            let space = Control::space(mem::size_of::<libc::in_pktinfo>())
+                + Control::space(mem::size_of::<libc::in6_pktinfo>());
+            let res = recv_from_with_cmsg(socket, buf, space, |cmsg| match (cmsg.level, cmsg.ty) {
+                (libc::IPPROTO_IP, libc::IP_PKTINFO) => cmsg
+                    .read::<libc::in_pktinfo>()
+                    .map(|info| IpAddr::V4(socket::ipv4_addr(&info.ipi_addr))),
+                (libc::IPPROTO_IPV6, libc::IPV6_PKTINFO) => cmsg
+                    .read::<libc::in6_pktinfo>()
+                    .map(|info| IpAddr::V6(socket::ipv6_addr(&info.ipi6_addr))),
+                _ => None,
+            })?;
+
+            match res {
+                (n, addr, Some(local)) => Ok((n, addr, local)),
+                _ => Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "destination address not received, is IP_PKTINFO enabled?",
+                )),
+            }
+        }
+
+        pub(crate) fn recv_from_with_orig_dst(
+            socket: &mio::net::UdpSocket,
+            buf: &mut [u8],
+        ) -> io::Result<(usize, SocketAddr, SocketAddr)> {
+            let space = Control::space(mem::size_of::<libc::sockaddr_in>())
+                + Control::space(mem::size_of::<libc::sockaddr_in6>());
+            let res = recv_from_with_cmsg(socket, buf, space, |cmsg| match (cmsg.level, cmsg.ty) {
+                (libc::IPPROTO_IP, libc::IP_ORIGDSTADDR)
+                | (libc::IPPROTO_IPV6, libc::IPV6_ORIGDSTADDR) => cmsg.read_addr(),
+                _ => None,
+            })?;
+
+            match res {
+                (n, addr, Some(orig_dst)) => Ok((n, addr, orig_dst)),
+                _ => Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "original destination address not received, is IP_RECVORIGDSTADDR enabled?",
+                )),
+            }
+        }
+
+        pub(crate) fn recv_from_with_ttl(
+            socket: &mio::net::UdpSocket,
+            buf: &mut [u8],
+        ) -> io::Result<(usize, SocketAddr, u8)> {
+            let space = Control::space(mem::size_of::<libc::c_int>());
+            let res = recv_from_with_cmsg(socket, buf, space, |cmsg| match (cmsg.level, cmsg.ty) {
+                (libc::IPPROTO_IP, libc::IP_TTL) | (libc::IPPROTO_IPV6, libc::IPV6_HOPLIMIT) => {
+                    cmsg.read::<libc::c_int>().map(|ttl| ttl as u8)
+                }
+                _ => None,
+            })?;
+
+            match res {
+                (n, addr, Some(ttl)) => Ok((n, addr, ttl)),
+                _ => Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "TTL not received, is IP_RECVTTL enabled?",
+                )),
+            }
+        }
+
+        #[cfg(target_os = "linux")]
+        pub(crate) fn set_zerocopy(socket: &mio::net::UdpSocket, on: bool) -> io::Result<()> {
+            socket::setsockopt(
+                socket.as_raw_fd(),
+                libc::SOL_SOCKET,
+                socket::SO_ZEROCOPY,
+                on as libc::c_int,
+            )
+        }
+
+        #[cfg(target_os = "linux")]
+        pub(crate) fn zerocopy(socket: &mio::net::UdpSocket) -> io::Result<bool> {
+            let on: libc::c_int =
+                socket::getsockopt(socket.as_raw_fd(), libc::SOL_SOCKET, socket::SO_ZEROCOPY)?;
+            Ok(on != 0)
+        }
+
+        #[cfg(target_os = "linux")]
+        pub(crate) fn send_to_zerocopy(
+            socket: &mio::net::UdpSocket,
+            buf: &[u8],
+            target: &SocketAddr,
+        ) -> io::Result<usize> {
+            socket::send_msg(
+                socket.as_raw_fd(),
+                &[IoSlice::new(buf)],
+                Some(target),
+                None,
+                libc::MSG_ZEROCOPY,
+            )
+        }
+
+        #[cfg(target_os = "linux")]
+        pub(crate) fn recv_zerocopy_completion(
+            socket: &mio::net::UdpSocket,
+        ) -> io::Result<ZerocopyRange> {
+            let (first, last, copied) = socket::recv_zerocopy(socket.as_raw_fd())?;
+            Ok(ZerocopyRange {
+                first,
+                last,
+                copied,
+            })
+        }
+
+        pub(crate) fn send_from_source(
+            socket: &mio::net::UdpSocket,
+            buf: &[u8],
+            target: &SocketAddr,
+            source: &IpAddr,
+        ) -> io::Result<usize> {
+            let mut control = Control::with_capacity(Control::space(
+                mem::size_of::<libc::in6_pktinfo>().max(mem::size_of::<libc::in_pktinfo>()),
+            ));
+
+            match (target, source) {
+                (SocketAddr::V4(..), IpAddr::V4(source)) => {
+                    let mut info: libc::in_pktinfo = unsafe { mem::zeroed() };
+                    info.ipi_spec_dst = socket::in_addr(source);
+                    control.push(libc::IPPROTO_IP, libc::IP_PKTINFO, &info);
+                }
+                (SocketAddr::V6(..), IpAddr::V6(source)) => {
+                    let mut info: libc::in6_pktinfo = unsafe { mem::zeroed() };
+                    info.ipi6_addr = socket::in6_addr(source);
+                    control.push(libc::IPPROTO_IPV6, libc::IPV6_PKTINFO, &info);
+                }
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "source and target address families differ",
+                    ))
+                }
+            }
+
+            socket::send_msg(
+                socket.as_raw_fd(),
+                &[IoSlice::new(buf)],
+                Some(target),
+                Some(&control),
+                0,
+            )
+        }
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use super::UdpSocket;
+    use std::os::windows::prelude::*;
+
+    impl UdpSocket {
+        /// Grants temporary access to the underlying socket as a
+        /// [`socket2::Socket`], for configuration this type does not expose.
+        ///
+        /// This is an escape hatch: changes made through it bypass romio's own
+        /// view of the socket. Options that affect how the socket is driven,
+        /// such as its blocking mode, must be left alone, and the socket must
+        /// not be closed.
+        ///
+        /// [`socket2::Socket`]: https://docs.rs/socket2/0.3/socket2/struct.Socket.html
+        #[cfg(feature = "socket2")]
+        pub fn set_socket2_options<F>(&self, f: F) -> std::io::Result<()>
+        where
+            F: FnOnce(&socket2::Socket) -> std::io::Result<()>,
+        {
+            // The socket is still owned by `self`, so it must not be closed
+            // when the borrowed `Socket` goes out of scope.
+            let socket = unsafe { socket2::Socket::from_raw_socket(self.as_raw_socket()) };
+            f(&std::mem::ManuallyDrop::new(socket))
+        }
+    }
+
+    impl AsRawSocket for UdpSocket {
+        fn as_raw_socket(&self) -> RawSocket {
+            self.raw_socket
+        }
+    }
+}
+
+impl TryFrom<std::net::UdpSocket> for UdpSocket {
+    type Error = io::Error;
+
+    fn try_from(socket: std::net::UdpSocket) -> Result<Self, Self::Error> {
+        // mio only switches the socket to non-blocking mode on Unix.
+        socket.set_nonblocking(true)?;
+
+        #[cfg(not(windows))]
+        return mio::net::UdpSocket::from_socket(socket).map(UdpSocket::new);
+
+        #[cfg(windows)]
+        {
+            let raw_socket = socket.as_raw_socket();
+            mio::net::UdpSocket::from_socket(socket).map(|io| UdpSocket::new(io, raw_socket))
+        }
+    }
+}
+
+/// The future returned by `UdpSocket::send_to`
+#[derive(Debug)]
+pub struct SendTo<'a, 'b> {
+    socket: &'a UdpSocket,
+    buf: &'b [u8],
+    target: &'b SocketAddr,
+}
+
+impl<'a, 'b> Future for SendTo<'a, 'b> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let SendTo {
+            socket,
+            buf,
+            target,
+        } = &mut *self;
+        socket.poll_send_to_priv(cx, buf, target)
+    }
+}
+
+/// The future returned by `UdpSocket::send_to_timeout`
+#[derive(Debug)]
+pub struct SendToTimeout<'a, 'b> {
+    socket: &'a UdpSocket,
+    buf: &'b [u8],
+    target: &'b SocketAddr,
+    deadline: Instant,
+    delay: Option<Delay>,
+}
+
+impl<'a, 'b> Future for SendToTimeout<'a, 'b> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let SendToTimeout {
+            socket,
+            buf,
+            target,
+            deadline,
+            delay,
+        } = &mut *self;
+        if let Poll::Ready(res) = socket.poll_send_to_priv(cx, buf, target) {
+            return Poll::Ready(res);
+        }
+        poll_timeout(cx, *deadline, delay)
+    }
+}
+
+/// Resolves to a `TimedOut` error once `deadline` has passed, creating the
+/// timer on first use.
+fn poll_timeout<T>(
+    cx: &mut Context<'_>,
+    deadline: Instant,
+    delay: &mut Option<Delay>,
+) -> Poll<io::Result<T>> {
+    let delay = match delay {
+        Some(delay) => delay,
+        None => delay.get_or_insert(Delay::new(deadline)?),
+    };
+    ready!(Pin::new(delay).poll(cx)?);
+    Poll::Ready(Err(io::Error::new(io::ErrorKind::TimedOut, "timed out")))
+}
+
+/// The future returned by `UdpSocket::readable`
+#[derive(Debug)]
+pub struct Readable<'a> {
+    socket: &'a mut UdpSocket,
+}
+
+impl<'a> Future for Readable<'a> {
+    type Output = io::Result<mio::Ready>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.socket.io.poll_read_ready(cx)
+    }
+}
+
+/// The future returned by `UdpSocket::writable`
+#[derive(Debug)]
+pub struct Writable<'a> {
+    socket: &'a mut UdpSocket,
+}
+
+impl<'a> Future for Writable<'a> {
+    type Output = io::Result<mio::Ready>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.socket.io.poll_write_ready(cx)
+    }
+}
+
+/// The future returned by `UdpSocket::send_to_vectored`
+#[derive(Debug)]
+pub struct SendToVectored<'a> {
+    socket: &'a mut UdpSocket,
+    bufs: &'a [IoSlice<'a>],
+    target: &'a SocketAddr,
+}
+
+impl<'a> Future for SendToVectored<'a> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let SendToVectored {
+            socket,
+            bufs,
+            target,
+        } = &mut *self;
+        Pin::new(&mut **socket).poll_send_to_vectored(cx, bufs, target)
+    }
+}
+
+/// The future returned by `UdpSocket::recv_from_vectored`
+#[derive(Debug)]
+pub struct RecvFromVectored<'a, 'b> {
+    socket: &'a mut UdpSocket,
+    bufs: &'a mut [IoSliceMut<'b>],
+}
+
+impl<'a, 'b> Future for RecvFromVectored<'a, 'b> {
+    type Output = io::Result<(usize, SocketAddr)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let RecvFromVectored { socket, bufs } = &mut *self;
+        Pin::new(&mut **socket).poll_recv_from_vectored(cx, bufs)
+    }
+}
+
+/// The future returned by `UdpSocket::send_mmsg`
+#[derive(Debug)]
+pub struct SendMmsg<'a> {
+    socket: &'a mut UdpSocket,
+    msgs: &'a [SendMsg<'a>],
+}
+
+impl<'a> Future for SendMmsg<'a> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let SendMmsg { socket, msgs } = &mut *self;
+        Pin::new(&mut **socket).poll_send_mmsg(cx, msgs)
+    }
+}
+
+/// The future returned by `UdpSocket::recv_mmsg`
+#[derive(Debug)]
+pub struct RecvMmsg<'a, 'b> {
+    socket: &'a mut UdpSocket,
+    msgs: &'a mut [RecvMsg<'b>],
+}
+
+impl<'a, 'b> Future for RecvMmsg<'a, 'b> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let RecvMmsg { socket, msgs } = &mut *self;
+        Pin::new(&mut **socket).poll_recv_mmsg(cx, msgs)
+    }
+}
+
+/// The future returned by `UdpSocket::peek_from`
+#[cfg(unix)]
+#[derive(Debug)]
+pub struct PeekFrom<'a, 'b> {
+    socket: &'a mut UdpSocket,
+    buf: &'b mut [u8],
+}
+
+#[cfg(unix)]
+impl<'a, 'b> Future for PeekFrom<'a, 'b> {
+    type Output = io::Result<(usize, SocketAddr)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let PeekFrom { socket, buf } = &mut *self;
+        Pin::new(&mut **socket).poll_peek_from(cx, buf)
+    }
+}
+
+/// The future returned by `UdpSocket::send`
+#[derive(Debug)]
+pub struct Send<'a> {
+    socket: &'a mut UdpSocket,
+    buf: &'a [u8],
+}
+
+impl<'a> Future for Send<'a> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let Send { socket, buf } = &mut *self;
+        Pin::new(&mut **socket).poll_send(cx, buf)
+    }
+}
+
+/// The future returned by `UdpSocket::recv`
+#[derive(Debug)]
+pub struct Recv<'a> {
+    socket: &'a mut UdpSocket,
+    buf: &'a mut [u8],
+}
+
+impl<'a> Future for Recv<'a> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let Recv { socket, buf } = &mut *self;
+        Pin::new(&mut **socket).poll_recv(cx, buf)
+    }
+}
+
+/// The future returned by `UdpSocket::recv_from`
+#[derive(Debug)]
+pub struct RecvFrom<'a, 'b> {
+    socket: &'a UdpSocket,
+    buf: &'b mut [u8],
+}
+
+impl<'a, 'b> Future for RecvFrom<'a, 'b> {
+    type Output = io::Result<(usize, SocketAddr)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let RecvFrom { socket, buf } = &mut *self;
+        socket.poll_recv_from_priv(cx, buf)
+    }
+}
+
+/// The future returned by `UdpSocket::recv_from_timeout`
+#[derive(Debug)]
+pub struct RecvFromTimeout<'a, 'b> {
+    socket: &'a UdpSocket,
+    buf: &'b mut [u8],
+    deadline: Instant,
+    delay: Option<Delay>,
+}
+
+impl<'a, 'b> Future for RecvFromTimeout<'a, 'b> {
+    type Output = io::Result<(usize, SocketAddr)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let RecvFromTimeout {
+            socket,
+            buf,
+            deadline,
+            delay,
+        } = &mut *self;
+        if let Poll::Ready(res) = socket.poll_recv_from_priv(cx, buf) {
+            return Poll::Ready(res);
+        }
+        poll_timeout(cx, *deadline, delay)
+    }
+}
+
+/// The future returned by `UdpSocket::send_buf`
+#[cfg(feature = "bytes")]
+#[derive(Debug)]
+pub struct SendBuf<'a, B> {
+    socket: &'a UdpSocket,
+    buf: &'a B,
+    target: &'a SocketAddr,
+}
+
+#[cfg(feature = "bytes")]
+impl<'a, B: Buf> Future for SendBuf<'a, B> {
+    type Output = io::Result<usize>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.socket
+            .poll_send_to_priv(cx, self.buf.bytes(), self.target)
+    }
+}
+
+/// The future returned by `UdpSocket::recv_buf`
+#[cfg(feature = "bytes")]
+#[derive(Debug)]
+pub struct RecvBuf<'a, B> {
+    socket: &'a UdpSocket,
+    buf: &'a mut B,
+}
+
+#[cfg(feature = "bytes")]
+impl<'a, B: BufMut> Future for RecvBuf<'a, B> {
+    type Output = io::Result<(usize, SocketAddr)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let RecvBuf { socket, buf } = &mut *self;
+
+        if !buf.has_remaining_mut() {
+            let unspecified = match socket.local_addr()? {
+                SocketAddr::V4(..) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+                SocketAddr::V6(..) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+            };
+            return Poll::Ready(Ok((0, SocketAddr::new(unspecified, 0))));
+        }
+
+        // Safety: `recv_from` only writes to the buffer, and `advance_mut` is
+        // called with the number of bytes it initialized.
+        let (n, addr) = ready!(socket.poll_recv_from_priv(cx, unsafe { buf.bytes_mut() }))?;
+        unsafe { buf.advance_mut(n) };
+        Poll::Ready(Ok((n, addr)))
+    }
+}
+
+/// The future returned by `UdpSocket::recv_from_with_source`
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Debug)]
+pub struct RecvFromWithSource<'a, 'b> {
+    socket: &'a mut UdpSocket,
+    buf: &'b mut [u8],
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl<'a, 'b> Future for RecvFromWithSource<'a, 'b> {
+    type Output = io::Result<(usize, SocketAddr, IpAddr)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let RecvFromWithSource { socket, buf } = &mut *self;
+        Pin::new(&mut **socket).poll_recv_from_with_source(cx, buf)
+    }
+}
+
+/// The future returned by `UdpSocket::send_from_source`
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Debug)]
+pub struct SendFromSource<'a, 'b> {
+    socket: &'a mut UdpSocket,
+    buf: &'b [u8],
+    target: &'b SocketAddr,
+    source: &'b IpAddr,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl<'a, 'b> Future for SendFromSource<'a, 'b> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let SendFromSource {
+            socket,
+            buf,
+            target,
+            source,
+        } = &mut *self;
+        Pin::new(&mut **socket).poll_send_from_source(cx, buf, target, source)
+    }
+}
+
+/// The future returned by `UdpSocket::recv_from_with_timestamp`
+#[cfg(target_os = "linux")]
+#[derive(Debug)]
+pub struct RecvFromWithTimestamp<'a, 'b> {
+    socket: &'a mut UdpSocket,
+    buf: &'b mut [u8],
+}
+
+#[cfg(target_os = "linux")]
+impl<'a, 'b> Future for RecvFromWithTimestamp<'a, 'b> {
+    type Output = io::Result<(usize, SocketAddr, Option<Timestamps>)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let RecvFromWithTimestamp { socket, buf } = &mut *self;
+        Pin::new(&mut **socket).poll_recv_from_with_timestamp(cx, buf)
+    }
+}
+
+/// The future returned by `UdpSocket::recv_from_timestampns`
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Debug)]
+pub struct RecvFromTimestampNs<'a, 'b> {
+    socket: &'a mut UdpSocket,
+    buf: &'b mut [u8],
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl<'a, 'b> Future for RecvFromTimestampNs<'a, 'b> {
+    type Output = io::Result<(usize, SocketAddr, Option<SystemTime>)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let RecvFromTimestampNs { socket, buf } = &mut *self;
+        Pin::new(&mut **socket).poll_recv_from_timestampns(cx, buf)
+    }
+}
+
+/// The future returned by `UdpSocket::recv_from_with_orig_dst`
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Debug)]
+pub struct RecvFromWithOrigDst<'a, 'b> {
+    socket: &'a mut UdpSocket,
+    buf: &'b mut [u8],
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl<'a, 'b> Future for RecvFromWithOrigDst<'a, 'b> {
+    type Output = io::Result<(usize, SocketAddr, SocketAddr)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let RecvFromWithOrigDst { socket, buf } = &mut *self;
+        Pin::new(&mut **socket).poll_recv_from_with_orig_dst(cx, buf)
+    }
+}
+
+/// The future returned by `UdpSocket::recv_from_with_ttl`
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Debug)]
+pub struct RecvFromWithTtl<'a, 'b> {
+    socket: &'a mut UdpSocket,
+    buf: &'b mut [u8],
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl<'a, 'b> Future for RecvFromWithTtl<'a, 'b> {
+    type Output = io::Result<(usize, SocketAddr, u8)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let RecvFromWithTtl { socket, buf } = &mut *self;
+        Pin::new(&mut **socket).poll_recv_from_with_ttl(cx, buf)
+    }
+}
+
+/// The future returned by `UdpSocket::send_to_zerocopy`
+#[cfg(target_os = "linux")]
+#[derive(Debug)]
+pub struct SendToZerocopy<'a> {
+    socket: &'a mut UdpSocket,
+    buf: &'a [u8],
+    target: &'a SocketAddr,
+    /// The number of the send and the bytes written, once it was queued.
+    sent: Option<(u32, usize)>,
+}
+
+#[cfg(target_os = "linux")]
+impl<'a> Future for SendToZerocopy<'a> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let SendToZerocopy {
+            socket,
+            buf,
+            target,
+            sent,
+        } = &mut *self;
+
+        let (seq, n) = match *sent {
+            Some(sent) => sent,
+            None => {
+                if !socket.zerocopy.enabled.load(Ordering::Relaxed) {
+                    // Without it the kernel copies the data and never posts
+                    // a notification.
+                    return Poll::Ready(Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "SO_ZEROCOPY is not enabled",
+                    )));
+                }
+                let seq = socket.zerocopy.next;
+                let n = ready!(Pin::new(&mut **socket).poll_send_to_zerocopy(cx, buf, target))?;
+                *sent = Some((seq, n));
+                (seq, n)
+            }
+        };
+
+        // The send has completed once `done` has moved past it.
+        while (socket.zerocopy.done.wrapping_sub(seq) as i32) <= 0 {
+            ready!(socket.io.poll_error_ready(cx)?);
+            match socket.recv_zerocopy_completion() {
+                Ok(mut range) => {
+                    // Only the sends before this one can have been made with
+                    // `poll_send_to_zerocopy`, and are recorded for
+                    // `recv_zerocopy_completions`.
+                    if (seq.wrapping_sub(range.first) as i32) > 0 {
+                        if (range.last.wrapping_sub(seq) as i32) >= 0 {
+                            range.last = seq.wrapping_sub(1);
+                        }
+                        socket.zerocopy.completions.push(range);
+                    }
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    socket.io.clear_error_ready(cx)?;
+                    return Poll::Pending;
+                }
+                Err(e) => return Poll::Ready(Err(e)),
+            }
+        }
+        Poll::Ready(Ok(n))
+    }
+}
+
+/// A datagram to be sent with `UdpSocket::send_mmsg`.
+#[derive(Clone, Copy, Debug)]
+pub struct SendMsg<'a> {
+    /// The payload of the datagram.
+    pub buf: &'a [u8],
+    /// The address to send the datagram to.
+    pub target: SocketAddr,
+}
+
+/// A buffer to receive a datagram into with `UdpSocket::recv_mmsg`.
+#[derive(Debug)]
+pub struct RecvMsg<'a> {
+    /// The buffer the datagram is written to.
+    pub buf: &'a mut [u8],
+    /// The number of bytes received into `buf`.
+    pub len: usize,
+    /// The address the datagram came from, or `None` if no datagram has been
+    /// received into this message.
+    pub addr: Option<SocketAddr>,
+}
+
+impl<'a> RecvMsg<'a> {
+    /// Creates a message that receives into `buf`.
+    pub fn new(buf: &'a mut [u8]) -> RecvMsg<'a> {
+        RecvMsg {
+            buf,
+            len: 0,
+            addr: None,
+        }
+    }
+
+    /// Returns the received part of the buffer.
+    pub fn data(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+/// The sends made with `MSG_ZEROCOPY` on a `UdpSocket`, numbered like the
+/// kernel does.
+#[cfg(target_os = "linux")]
+#[derive(Debug, Default)]
+struct Zerocopy {
+    /// Whether `SO_ZEROCOPY` was enabled with `set_zerocopy`.
+    enabled: AtomicBool,
+    /// The number of the next send.
+    next: u32,
+    /// One past the number of the last completed send.
+    done: u32,
+    /// The completions of sends made with `poll_send_to_zerocopy` that were
+    /// read by a `SendToZerocopy` future, until they are returned by
+    /// `recv_zerocopy_completions`.
+    completions: Vec<ZerocopyRange>,
+}
+
+/// A range of sends made with `UdpSocket::send_to_zerocopy` that have
+/// completed.
+///
+/// The kernel numbers successful zerocopy sends on a socket consecutively,
+/// starting at 0. A range covers the sends numbered `first` through `last`
+/// (inclusive), whose buffers may now be reused.
+#[cfg(target_os = "linux")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ZerocopyRange {
+    /// Number of the first completed send.
+    pub first: u32,
+    /// Number of the last completed send.
+    pub last: u32,
+    /// Whether the kernel fell back to copying the data of these sends, for
+    /// example because they were routed over the loopback interface.
+    pub copied: bool,
+}
+
+/// The sending half of a `UdpSocket`, created by `UdpSocket::split`.
+#[derive(Debug)]
+pub struct UdpSocketSendHalf(Arc<UdpSocket>);
+
+/// The receiving half of a `UdpSocket`, created by `UdpSocket::split`.
+#[derive(Debug)]
+pub struct UdpSocketRecvHalf(Arc<UdpSocket>);
+
+/// Error returned by `UdpSocketSendHalf::reunite` when the halves do not
+/// belong to the same socket.
+///
+/// The halves are handed back unchanged.
+#[derive(Debug)]
+pub struct ReuniteError(pub UdpSocketSendHalf, pub UdpSocketRecvHalf);
+
+impl UdpSocketSendHalf {
+    /// Puts the socket back together from its two halves.
+    ///
+    /// Fails if `recv` was not split from the same socket as this half.
+    pub fn reunite(self, recv: UdpSocketRecvHalf) -> Result<UdpSocket, ReuniteError> {
+        if !Arc::ptr_eq(&self.0, &recv.0) {
+            return Err(ReuniteError(self, recv));
+        }
+        drop(recv);
+        Ok(Arc::try_unwrap(self.0).expect("UdpSocket: only the two halves own the socket"))
+    }
+
+    /// Returns the local address that the socket is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.0.local_addr()
+    }
+
+    /// Sends data on the socket to the given address. On success, returns the
+    /// number of bytes written.
+    ///
+    /// For more information, see [`UdpSocket::send_to`].
+    ///
+    /// [`UdpSocket::send_to`]: struct.UdpSocket.html#method.send_to
+    pub fn send_to<'a, 'b>(&'a mut self, buf: &'b [u8], target: &'b SocketAddr) -> SendTo<'a, 'b> {
+        SendTo {
+            buf,
+            target,
+            socket: &self.0,
+        }
+    }
+
+    /// Attempts to send data on the socket to the given address.
+    ///
+    /// For more information, see [`send_to`].
+    ///
+    /// [`send_to`]: #method.send_to
+    pub fn poll_send_to(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+        target: &SocketAddr,
+    ) -> Poll<io::Result<usize>> {
+        self.0.poll_send_to_priv(cx, buf, target)
+    }
+}
+
+impl UdpSocketRecvHalf {
+    /// Returns the local address that the socket is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.0.local_addr()
+    }
+
+    /// Receives data from the socket. On success, returns the number of bytes
+    /// read and the address from whence the data came.
+    ///
+    /// For more information, see [`UdpSocket::recv_from`].
+    ///
+    /// [`UdpSocket::recv_from`]: struct.UdpSocket.html#method.recv_from
+    pub fn recv_from<'a, 'b>(&'a mut self, buf: &'b mut [u8]) -> RecvFrom<'a, 'b> {
+        RecvFrom {
+            buf,
+            socket: &self.0,
+        }
+    }
+
+    /// Attempts to receive data from the socket.
+    ///
+    /// For more information, see [`recv_from`].
+    ///
+    /// [`recv_from`]: #method.recv_from
+    pub fn poll_recv_from(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<(usize, SocketAddr)>> {
+        self.0.poll_recv_from_priv(cx, buf)
+    }
+}
+
+/// Sends datagrams through the socket.
+///
+/// This half only sends, so `poll_recv_from` always fails. Receiving from
+/// both halves would have their tasks compete for the socket's single read
+/// readiness.
+impl AsyncDatagram for UdpSocketSendHalf {
+    type Sender = SocketAddr;
+    type Receiver = SocketAddr;
+    type Err = io::Error;
+
+    fn poll_send_to(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+        receiver: &Self::Receiver,
+    ) -> Poll<io::Result<usize>> {
+        self.0.poll_send_to_priv(cx, buf, receiver)
+    }
+
+    fn poll_recv_from(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        _buf: &mut [u8],
+    ) -> Poll<io::Result<(usize, Self::Sender)>> {
+        Poll::Ready(Err(io::Error::new(
+            io::ErrorKind::Other,
+            "cannot receive on the sending half of a UdpSocket",
+        )))
+    }
+}
+
+/// Receives datagrams from the socket.
+///
+/// This half only receives, so `poll_send_to` always fails. Sending from both
+/// halves would have their tasks compete for the socket's single write
+/// readiness.
+impl AsyncDatagram for UdpSocketRecvHalf {
+    type Sender = SocketAddr;
+    type Receiver = SocketAddr;
+    type Err = io::Error;
+
+    fn poll_send_to(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        _buf: &[u8],
+        _receiver: &Self::Receiver,
+    ) -> Poll<io::Result<usize>> {
+        Poll::Ready(Err(io::Error::new(
+            io::ErrorKind::Other,
+            "cannot send on the receiving half of a UdpSocket",
+        )))
+    }
+
+    fn poll_recv_from(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<(usize, Self::Sender)>> {
+        self.0.poll_recv_from_priv(cx, buf)
+    }
+}
+
+impl fmt::Display for ReuniteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "tried to reunite halves that are not from the same socket"
+        )
+    }
+}
+
+impl Error for ReuniteError {}
@@ -0,0 +1,69 @@
+use std::time::{Duration, SystemTime};
+
+use bitflags::bitflags;
+
+bitflags! {
+    /// Flags for the `SO_TIMESTAMPING` option, set with
+    /// [`UdpSocket::set_timestamping`].
+    ///
+    /// The flags fall into three groups: the `TX_*` and `RX_*` flags select
+    /// the points at which timestamps are generated, the `SOFTWARE` and
+    /// `*_HARDWARE` flags select which timestamps are reported, and the
+    /// `OPT_*` flags tweak how they are reported. For example, receive
+    /// timestamps taken by the kernel need both `RX_SOFTWARE` and `SOFTWARE`.
+    ///
+    /// See the kernel's `Documentation/networking/timestamping.rst` for the
+    /// full semantics of every flag.
+    ///
+    /// [`UdpSocket::set_timestamping`]: struct.UdpSocket.html#method.set_timestamping
+    pub struct TimestampingFlags: u32 {
+        /// Generate transmit timestamps in the network adapter.
+        const TX_HARDWARE = libc::SOF_TIMESTAMPING_TX_HARDWARE;
+        /// Generate transmit timestamps when a packet leaves the kernel.
+        const TX_SOFTWARE = libc::SOF_TIMESTAMPING_TX_SOFTWARE;
+        /// Generate receive timestamps in the network adapter.
+        const RX_HARDWARE = libc::SOF_TIMESTAMPING_RX_HARDWARE;
+        /// Generate receive timestamps when a packet enters the kernel.
+        const RX_SOFTWARE = libc::SOF_TIMESTAMPING_RX_SOFTWARE;
+        /// Report software timestamps.
+        const SOFTWARE = libc::SOF_TIMESTAMPING_SOFTWARE;
+        /// Report hardware timestamps converted to system time. Deprecated
+        /// by the kernel and ignored.
+        const SYS_HARDWARE = libc::SOF_TIMESTAMPING_SYS_HARDWARE;
+        /// Report hardware timestamps in the adapter's own clock.
+        const RAW_HARDWARE = libc::SOF_TIMESTAMPING_RAW_HARDWARE;
+        /// Tag transmit timestamps with a per-socket counter.
+        const OPT_ID = libc::SOF_TIMESTAMPING_OPT_ID;
+        /// Generate transmit timestamps when a packet enters the packet
+        /// scheduler.
+        const TX_SCHED = libc::SOF_TIMESTAMPING_TX_SCHED;
+        /// Generate transmit timestamps when all data has been acknowledged.
+        /// Only meaningful for TCP.
+        const TX_ACK = libc::SOF_TIMESTAMPING_TX_ACK;
+        /// Deliver `IP_PKTINFO` along with every transmit timestamp.
+        const OPT_CMSG = libc::SOF_TIMESTAMPING_OPT_CMSG;
+        /// Don't loop the transmitted payload back with transmit timestamps.
+        const OPT_TSONLY = libc::SOF_TIMESTAMPING_OPT_TSONLY;
+    }
+}
+
+/// The timestamps of a received datagram, reported through `SCM_TIMESTAMPING`
+/// ancillary data.
+///
+/// Which timestamps are present depends on the [`TimestampingFlags`] the
+/// socket was configured with, and on whether the network adapter supports
+/// hardware timestamping.
+///
+/// [`TimestampingFlags`]: struct.TimestampingFlags.html
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Timestamps {
+    /// The time the kernel received the datagram, if software timestamps are
+    /// enabled.
+    pub software: Option<SystemTime>,
+    /// The time the network adapter received the datagram, if hardware
+    /// timestamps are enabled.
+    ///
+    /// This is read from the adapter's own clock, such as a PTP hardware
+    /// clock, which generally isn't synchronized with the system clock.
+    pub hardware: Option<Duration>,
+}
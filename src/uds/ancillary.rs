@@ -0,0 +1,71 @@
+use super::UCred;
+
+use crate::socket::{Cmsg, Control};
+
+use std::mem;
+use std::os::unix::io::RawFd;
+
+/// An ancillary data message received on a Unix socket.
+///
+/// Messages are returned by [`UnixStream::recv_with_ancillary`].
+///
+/// [`UnixStream::recv_with_ancillary`]: struct.UnixStream.html#method.recv_with_ancillary
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AncillaryMessage {
+    /// File descriptors passed with `SCM_RIGHTS`.
+    ///
+    /// The descriptors are owned by the receiver, which is responsible for
+    /// closing them.
+    Rights(Vec<RawFd>),
+    /// Credentials of the sending process, passed with `SCM_CREDENTIALS`.
+    Credentials(UCred),
+    /// A message of a type this crate does not parse.
+    Unknown {
+        /// The originating protocol level of the message.
+        level: i32,
+        /// The protocol specific type of the message.
+        type_: i32,
+        /// The raw payload of the message.
+        data: Vec<u8>,
+    },
+}
+
+impl AncillaryMessage {
+    pub(crate) fn parse(control: &Control) -> Vec<AncillaryMessage> {
+        control.iter().map(AncillaryMessage::from_cmsg).collect()
+    }
+
+    fn from_cmsg(cmsg: Cmsg<'_>) -> AncillaryMessage {
+        match (cmsg.level, cmsg.ty) {
+            (libc::SOL_SOCKET, libc::SCM_RIGHTS) => {
+                let fds = cmsg
+                    .data
+                    .chunks_exact(mem::size_of::<RawFd>())
+                    .map(|chunk| {
+                        let mut fd = [0; mem::size_of::<RawFd>()];
+                        fd.copy_from_slice(chunk);
+                        RawFd::from_ne_bytes(fd)
+                    })
+                    .collect();
+                return AncillaryMessage::Rights(fds);
+            }
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            (libc::SOL_SOCKET, libc::SCM_CREDENTIALS) => {
+                if let Some(cred) = cmsg.read::<libc::ucred>() {
+                    return AncillaryMessage::Credentials(UCred {
+                        uid: cred.uid,
+                        gid: cred.gid,
+                        pid: Some(cred.pid),
+                    });
+                }
+            }
+            _ => {}
+        }
+
+        AncillaryMessage::Unknown {
+            level: cmsg.level,
+            type_: cmsg.ty,
+            data: cmsg.data.to_vec(),
+        }
+    }
+}
@@ -2,7 +2,7 @@ use crate::raw::PollEvented;
 
 use async_datagram::AsyncDatagram;
 use async_ready::{AsyncReadReady, AsyncWriteReady, TakeError};
-use futures::{ready, Poll};
+use futures::Poll;
 use mio_uds;
 
 use std::fmt;
@@ -57,6 +57,38 @@ impl<'a, 'b> Future for RecvFrom<'a, 'b> {
     }
 }
 
+/// The future returned by `UnixDatagram::send`.
+#[derive(Debug)]
+pub struct Send<'a> {
+    socket: &'a mut UnixDatagram,
+    buf: &'a [u8],
+}
+
+impl<'a> Future for Send<'a> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let Send { socket, buf } = &mut *self;
+        Pin::new(&mut **socket).poll_send(cx, buf)
+    }
+}
+
+/// The future returned by `UnixDatagram::recv`.
+#[derive(Debug)]
+pub struct Recv<'a> {
+    socket: &'a mut UnixDatagram,
+    buf: &'a mut [u8],
+}
+
+impl<'a> Future for Recv<'a> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let Recv { socket, buf } = &mut *self;
+        Pin::new(&mut **socket).poll_recv(cx, buf)
+    }
+}
+
 impl UnixDatagram {
     /// Creates a new `UnixDatagram` bound to the specified path.
     ///
@@ -74,11 +106,41 @@ impl UnixDatagram {
         Ok(UnixDatagram::new(socket))
     }
 
+    /// Creates a new `UnixDatagram` bound to `name` in the abstract namespace.
+    ///
+    /// Abstract sockets have no presence in the file system, so there is no
+    /// socket file to clean up: the name is released once the socket and all
+    /// its duplicates are closed. The name may contain any bytes, including
+    /// null bytes, and must be shorter than 108 bytes.
+    ///
+    /// This is only available on Linux.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use romio::uds::UnixDatagram;
+    ///
+    /// # fn run() -> std::io::Result<()> {
+    /// let sock = UnixDatagram::bind_abstract(b"romio")?;
+    /// # Ok(()) }
+    /// ```
+    #[cfg(target_os = "linux")]
+    pub fn bind_abstract(name: &[u8]) -> io::Result<UnixDatagram> {
+        use std::os::unix::io::FromRawFd;
+
+        let fd = crate::socket::bind_abstract(libc::SOCK_DGRAM, name)?;
+        let socket = unsafe { std::os::unix::net::UnixDatagram::from_raw_fd(fd) };
+        let socket = mio_uds::UnixDatagram::from_datagram(socket)?;
+        Ok(UnixDatagram::new(socket))
+    }
+
     /// Creates an unnamed pair of connected sockets.
     ///
     /// This function will create a pair of interconnected Unix sockets for
-    /// communicating back and forth between one another. Each socket will be
-    /// associated with the event loop whose handle is also provided.
+    /// communicating back and forth between one another, with `socketpair`.
+    /// Neither socket needs to be bound, each of them can send to and receive
+    /// from the other, and message boundaries are preserved. Both sockets are
+    /// registered with the default reactor.
     ///
     /// # Examples
     ///
@@ -118,6 +180,27 @@ impl UnixDatagram {
         Ok(UnixDatagram::new(socket))
     }
 
+    /// Connects the socket to the specified address.
+    ///
+    /// The socket then only receives datagrams from that address, and
+    /// [`send_to`] can still be used to send to other addresses.
+    ///
+    /// [`send_to`]: #method.send_to
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use romio::uds::UnixDatagram;
+    ///
+    /// # fn run() -> std::io::Result<()> {
+    /// let sock = UnixDatagram::unbound()?;
+    /// sock.connect("/dev/log")?;
+    /// # Ok(()) }
+    /// ```
+    pub fn connect(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.io.get_ref().connect(path)
+    }
+
     /// Returns the local address that this socket is bound to.
     /// # Examples
     ///
@@ -172,6 +255,25 @@ impl UnixDatagram {
         self.io.get_ref().shutdown(how)
     }
 
+    /// Sets the close-on-exec flag on the socket's file descriptor, so that
+    /// it is closed in child processes after `exec`.
+    ///
+    /// Sockets created by this crate already have the flag set, so this only
+    /// undoes [`clear_cloexec`]. See [`TcpStream::set_cloexec`] for why the
+    /// flag matters.
+    ///
+    /// [`clear_cloexec`]: #method.clear_cloexec
+    /// [`TcpStream::set_cloexec`]: ../tcp/struct.TcpStream.html#method.set_cloexec
+    pub fn set_cloexec(&self) -> io::Result<()> {
+        crate::socket::set_cloexec(self.as_raw_fd(), true)
+    }
+
+    /// Clears the close-on-exec flag on the socket's file descriptor, so that
+    /// a child process can keep sending and receiving on it after `exec`.
+    pub fn clear_cloexec(&self) -> io::Result<()> {
+        crate::socket::set_cloexec(self.as_raw_fd(), false)
+    }
+
     /// Sends data on the socket to the given address. On success, returns the
     /// number of bytes written.
     ///
@@ -180,7 +282,8 @@ impl UnixDatagram {
     /// ```no_run
     /// #![feature(async_await)]
     /// # use std::error::Error;
-    /// use romio::udp::UdpSocket;
+    /// use romio::uds::UnixDatagram;
+    /// use std::path::PathBuf;
     ///
     /// const THE_MERCHANT_OF_VENICE: &[u8] = b"
     ///     If you prick us, do we not bleed?
@@ -190,9 +293,8 @@ impl UnixDatagram {
     /// ";
     ///
     /// # async fn send_data() -> Result<(), Box<dyn Error + 'static>> {
-    /// let addr = "/tmp/out.socket".parse()?;
-    /// let target = "/tmp/in.socket".parse()?;
-    /// let mut socket = UdpSocket::bind(&addr)?;
+    /// let target = PathBuf::from("/tmp/in.socket");
+    /// let mut socket = UnixDatagram::bind("/tmp/out.socket")?;
     ///
     /// socket.send_to(THE_MERCHANT_OF_VENICE, &target).await?;
     /// # Ok(())
@@ -209,16 +311,15 @@ impl UnixDatagram {
     /// Receives data from the socket. On success, returns the number of bytes
     /// read and the address from whence the data came.
     ///
-    /// # Exampes
+    /// # Examples
     ///
     /// ```no_run
     /// #![feature(async_await)]
     /// # use std::error::Error;
-    /// use romio::udp::UdpSocket;
+    /// use romio::uds::UnixDatagram;
     ///
     /// # async fn recv_data() -> Result<Vec<u8>, Box<dyn Error + 'static>> {
-    /// let addr = "/tmp/in.socket".parse()?;
-    /// let mut socket = UdpSocket::bind(&addr)?;
+    /// let mut socket = UnixDatagram::bind("/tmp/in.socket")?;
     /// let mut buf = vec![0; 1024];
     ///
     /// socket.recv_from(&mut buf).await?;
@@ -228,6 +329,132 @@ impl UnixDatagram {
     pub fn recv_from<'a, 'b>(&'a mut self, buf: &'b mut [u8]) -> RecvFrom<'a, 'b> {
         RecvFrom { buf, socket: self }
     }
+
+    /// Sends data on the socket to the peer it is connected to. On success,
+    /// returns the number of bytes written.
+    ///
+    /// The socket must first be connected with [`connect`], or be one half of
+    /// a [`pair`]. Otherwise an error is returned: `NotConnected` on Linux,
+    /// and `EDESTADDRREQ` on most other platforms.
+    ///
+    /// [`connect`]: #method.connect
+    /// [`pair`]: #method.pair
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// # use std::error::Error;
+    /// use romio::uds::UnixDatagram;
+    ///
+    /// # async fn send_data() -> Result<(), Box<dyn Error + 'static>> {
+    /// let mut socket = UnixDatagram::unbound()?;
+    /// socket.connect("/tmp/in.socket")?;
+    ///
+    /// socket.send(b"ping").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn send<'a>(&'a mut self, buf: &'a [u8]) -> Send<'a> {
+        Send { buf, socket: self }
+    }
+
+    /// Receives data from the peer the socket is connected to. On success,
+    /// returns the number of bytes read.
+    ///
+    /// Once connected with [`connect`], the socket only receives datagrams
+    /// sent by its peer.
+    ///
+    /// [`connect`]: #method.connect
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// # use std::error::Error;
+    /// use romio::uds::UnixDatagram;
+    ///
+    /// # async fn recv_data() -> Result<Vec<u8>, Box<dyn Error + 'static>> {
+    /// let mut socket = UnixDatagram::bind("/tmp/out.socket")?;
+    /// socket.connect("/tmp/in.socket")?;
+    ///
+    /// let mut buf = vec![0; 1024];
+    /// let n = socket.recv(&mut buf).await?;
+    /// buf.truncate(n);
+    /// # Ok(buf)
+    /// # }
+    /// ```
+    pub fn recv<'a>(&'a mut self, buf: &'a mut [u8]) -> Recv<'a> {
+        Recv { buf, socket: self }
+    }
+
+    /// Attempts to send data on the socket to the peer it is connected to.
+    ///
+    /// For more information, see [`send`].
+    ///
+    /// [`send`]: #method.send
+    pub fn poll_send(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.io.write_with(cx, |io| io.send(buf))
+    }
+
+    /// Attempts to receive data from the peer the socket is connected to.
+    ///
+    /// For more information, see [`recv`].
+    ///
+    /// [`recv`]: #method.recv
+    pub fn poll_recv(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        self.io.read_with(cx, |io| io.recv(buf))
+    }
+
+    /// Sends data on the socket to the given address, without waiting for
+    /// the socket to become writable. On success, returns the number of
+    /// bytes written.
+    ///
+    /// If the socket is not ready for writing, an error of kind `WouldBlock`
+    /// is returned. This does not register the current task for wakeup, so
+    /// it is meant for use after readiness has been observed some other way.
+    pub fn try_send_to(&mut self, buf: &[u8], target: impl AsRef<Path>) -> io::Result<usize> {
+        self.io.get_ref().send_to(buf, target)
+    }
+
+    /// Receives data from the socket, without waiting for the socket to
+    /// become readable. On success, returns the number of bytes read and the
+    /// address from whence the data came.
+    ///
+    /// If no datagram is queued, an error of kind `WouldBlock` is returned.
+    /// This does not register the current task for wakeup, so it is meant for
+    /// draining a socket after readiness has been observed some other way.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use romio::uds::UnixDatagram;
+    /// use std::io;
+    ///
+    /// # fn run() -> std::io::Result<()> {
+    /// let mut socket = UnixDatagram::bind("/tmp/sock")?;
+    /// let mut buf = vec![0; 1024];
+    ///
+    /// loop {
+    ///     match socket.try_recv_from(&mut buf) {
+    ///         Ok((n, _)) => println!("{} bytes", n),
+    ///         Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => break,
+    ///         Err(e) => return Err(e),
+    ///     }
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub fn try_recv_from(&mut self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        self.io.get_ref().recv_from(buf)
+    }
 }
 
 impl AsyncDatagram for UnixDatagram {
@@ -236,38 +463,20 @@ impl AsyncDatagram for UnixDatagram {
     type Err = io::Error;
 
     fn poll_send_to(
-        mut self: Pin<&mut Self>,
+        self: Pin<&mut Self>,
         cx: &mut Context<'_>,
         buf: &[u8],
         receiver: &Self::Receiver,
     ) -> Poll<io::Result<usize>> {
-        ready!(self.io.poll_write_ready(cx)?);
-
-        match self.io.get_ref().send_to(buf, receiver) {
-            Ok(n) => Poll::Ready(Ok(n)),
-            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
-                Pin::new(&mut self.io).clear_write_ready(cx)?;
-                Poll::Pending
-            }
-            Err(e) => Poll::Ready(Err(e)),
-        }
+        self.io.write_with(cx, |io| io.send_to(buf, receiver))
     }
 
     fn poll_recv_from(
-        mut self: Pin<&mut Self>,
+        self: Pin<&mut Self>,
         cx: &mut Context<'_>,
         buf: &mut [u8],
     ) -> Poll<io::Result<(usize, Self::Sender)>> {
-        ready!(Pin::new(&mut self.io).poll_read_ready(cx)?);
-
-        let r = self.io.get_ref().recv_from(buf);
-
-        if is_wouldblock(&r) {
-            Pin::new(&mut self.io).clear_read_ready(cx)?;
-            Poll::Pending
-        } else {
-            Poll::Ready(r)
-        }
+        self.io.read_with(cx, |io| io.recv_from(buf))
     }
 }
 
@@ -332,10 +541,3 @@ impl AsRawFd for UnixDatagram {
         self.io.get_ref().as_raw_fd()
     }
 }
-
-fn is_wouldblock<T>(r: &io::Result<T>) -> bool {
-    match *r {
-        Ok(_) => false,
-        Err(ref e) => e.kind() == io::ErrorKind::WouldBlock,
-    }
-}
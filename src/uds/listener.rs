@@ -3,12 +3,12 @@ use super::UnixStream;
 use crate::raw::PollEvented;
 
 use async_ready::{AsyncReady, TakeError};
-use futures::{ready, Poll, Stream};
+use futures::{ready, Future, Poll, Stream};
 use mio_uds;
 
 use std::fmt;
 use std::io;
-use std::os::unix::io::{AsRawFd, RawFd};
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
 use std::os::unix::net::{self, SocketAddr};
 use std::path::Path;
 use std::pin::Pin;
@@ -28,7 +28,7 @@ use std::task::Context;
 /// }
 ///
 /// async fn listen() -> Result<(), Box<dyn std::error::Error + 'static>> {
-///     let listener = UnixListener::bind("/tmp/sock")?;
+///     let mut listener = UnixListener::bind("/tmp/sock")?;
 ///     let mut incoming = listener.incoming();
 ///
 ///     // accept connections and process them serially
@@ -62,6 +62,35 @@ impl UnixListener {
         Ok(UnixListener { io })
     }
 
+    /// Creates a new `UnixListener` bound to `name` in the abstract namespace.
+    ///
+    /// Abstract sockets have no presence in the file system, so there is no
+    /// socket file to clean up: the name is released once the listener and
+    /// all its duplicates are closed. The name may contain any bytes,
+    /// including null bytes, and must be shorter than 108 bytes.
+    ///
+    /// This is only available on Linux.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use romio::uds::UnixListener;
+    ///
+    /// # fn main () -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let listener = UnixListener::bind_abstract(b"romio")?;
+    /// # Ok(())}
+    /// ```
+    #[cfg(target_os = "linux")]
+    pub fn bind_abstract(name: &[u8]) -> io::Result<UnixListener> {
+        let fd = crate::socket::bind_abstract(libc::SOCK_STREAM, name)?;
+        let listener = unsafe { net::UnixListener::from_raw_fd(fd) };
+        // the same backlog as `std::os::unix::net::UnixListener::bind`
+        unsafe {
+            crate::socket::cvt(libc::listen(fd, 128))?;
+        }
+        UnixListener::from_std(listener)
+    }
+
     /// Returns the local socket address of this listener.
     ///
     /// # Examples
@@ -78,8 +107,104 @@ impl UnixListener {
         self.io.get_ref().local_addr()
     }
 
-    /// Consumes this listener, returning a stream of the sockets this listener
-    /// accepts.
+    /// Sets the close-on-exec flag on the listener's file descriptor, so that
+    /// processes started with `exec` can't accept connections on it.
+    ///
+    /// This only needs to be called for listeners created with `from_std`,
+    /// see [`TcpStream::set_cloexec`].
+    ///
+    /// [`TcpStream::set_cloexec`]: ../tcp/struct.TcpStream.html#method.set_cloexec
+    pub fn set_cloexec(&self) -> io::Result<()> {
+        crate::socket::set_cloexec(self.as_raw_fd(), true)
+    }
+
+    /// Clears the close-on-exec flag on the listener's file descriptor, so that
+    /// it can be handed to a child process, for example to restart a server
+    /// without unlinking and binding the socket path again.
+    pub fn clear_cloexec(&self) -> io::Result<()> {
+        crate::socket::set_cloexec(self.as_raw_fd(), false)
+    }
+
+    /// Creates a new `UnixListener` from a bound and listening
+    /// `std::os::unix::net::UnixListener`.
+    ///
+    /// The listener is switched to non-blocking mode and registered with the
+    /// default reactor.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use romio::uds::UnixListener;
+    ///
+    /// # fn main () -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let std_listener = std::os::unix::net::UnixListener::bind("/tmp/sock")?;
+    /// let listener = UnixListener::from_std(std_listener)?;
+    /// # Ok(())}
+    /// ```
+    pub fn from_std(listener: net::UnixListener) -> io::Result<UnixListener> {
+        let listener = mio_uds::UnixListener::from_listener(listener)?;
+        let io = PollEvented::new(listener);
+        Ok(UnixListener { io })
+    }
+
+    /// Converts this listener into a `std::os::unix::net::UnixListener`.
+    ///
+    /// The listener is deregistered from the reactor and switched back to
+    /// blocking mode, so it can be handed to synchronous code.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use romio::uds::UnixListener;
+    ///
+    /// # fn main () -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let listener = UnixListener::bind("/tmp/sock")?;
+    /// let std_listener = listener.into_std()?;
+    /// let (stream, _) = std_listener.accept()?;
+    /// # Ok(())}
+    /// ```
+    pub fn into_std(self) -> io::Result<net::UnixListener> {
+        let fd = self.io.into_inner()?.into_raw_fd();
+        let listener = unsafe { net::UnixListener::from_raw_fd(fd) };
+        listener.set_nonblocking(false)?;
+        Ok(listener)
+    }
+
+    /// Accepts a new incoming connection to this listener.
+    ///
+    /// The returned future resolves to the connected stream and the address
+    /// of the peer, which is usually unnamed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// use romio::uds::UnixListener;
+    ///
+    /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let mut listener = UnixListener::bind("/tmp/sock")?;
+    /// let (stream, addr) = listener.accept().await?;
+    /// # Ok(())}
+    /// ```
+    pub fn accept(&mut self) -> Accept<'_> {
+        Accept { listener: self }
+    }
+
+    /// Attempts to accept a new incoming connection to this listener.
+    ///
+    /// For more information, see [`accept`].
+    ///
+    /// [`accept`]: #method.accept
+    pub fn poll_accept(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<(UnixStream, SocketAddr)>> {
+        let (io, addr) = ready!(self.poll_accept_std(cx)?);
+        let io = mio_uds::UnixStream::from_stream(io)?;
+        Poll::Ready(Ok((UnixStream::new(io), addr)))
+    }
+
+    /// Returns a stream of the sockets this listener accepts.
     ///
     /// This method returns an implementation of the `Stream` trait which
     /// resolves to the sockets the are accepted on this listener.
@@ -93,7 +218,7 @@ impl UnixListener {
     /// use futures::prelude::*;
     ///
     /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
-    /// let listener = UnixListener::bind("/tmp/sock")?;
+    /// let mut listener = UnixListener::bind("/tmp/sock")?;
     /// let mut incoming = listener.incoming();
     ///
     /// // accept connections and process them serially
@@ -107,8 +232,8 @@ impl UnixListener {
     /// }
     /// # Ok(())}
     /// ```
-    pub fn incoming(self) -> Incoming {
-        Incoming::new(self)
+    pub fn incoming(&mut self) -> Incoming<'_> {
+        Incoming { inner: self }
     }
 
     fn poll_accept_std(
@@ -138,9 +263,7 @@ impl AsyncReady for UnixListener {
 
     /// Check if the stream can be read from.
     fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<Self::Ok, Self::Err>> {
-        let (io, addr) = ready!(self.poll_accept_std(cx)?);
-        let io = mio_uds::UnixStream::from_stream(io)?;
-        Poll::Ready(Ok((UnixStream::new(io), addr)))
+        self.poll_accept(cx)
     }
 }
 
@@ -180,23 +303,34 @@ impl AsRawFd for UnixListener {
     }
 }
 
-/// Stream of listeners
+/// The future returned by `UnixListener::accept`.
+#[must_use = "futures do nothing unless polled"]
 #[derive(Debug)]
-pub struct Incoming {
-    inner: UnixListener,
+pub struct Accept<'a> {
+    listener: &'a mut UnixListener,
 }
 
-impl Incoming {
-    pub(crate) fn new(listener: UnixListener) -> Incoming {
-        Incoming { inner: listener }
+impl<'a> Future for Accept<'a> {
+    type Output = io::Result<(UnixStream, SocketAddr)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut *self.listener).poll_accept(cx)
     }
 }
 
-impl Stream for Incoming {
+/// Stream returned by the `UnixListener::incoming` function representing the
+/// stream of sockets received from a listener.
+#[must_use = "streams do nothing unless polled"]
+#[derive(Debug)]
+pub struct Incoming<'a> {
+    inner: &'a mut UnixListener,
+}
+
+impl<'a> Stream for Incoming<'a> {
     type Item = io::Result<UnixStream>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        let (socket, _) = ready!(Pin::new(&mut self.inner).poll_ready(cx)?);
+        let (socket, _) = ready!(Pin::new(&mut *self.inner).poll_accept(cx)?);
         Poll::Ready(Some(Ok(socket)))
     }
 }
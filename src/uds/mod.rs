@@ -12,7 +12,7 @@
 //! }
 //!
 //! async fn listen() -> Result<(), Box<dyn std::error::Error + 'static>> {
-//!     let listener = UnixListener::bind("/tmp/sock")?;
+//!     let mut listener = UnixListener::bind("/tmp/sock")?;
 //!     let mut incoming = listener.incoming();
 //!
 //!     // accept connections and process them serially
@@ -23,12 +23,28 @@
 //! }
 //! ```
 
+mod ancillary;
 mod datagram;
 mod listener;
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+mod seqpacket;
+mod split;
 mod stream;
 mod ucred;
 
-pub use self::datagram::UnixDatagram;
-pub use self::listener::{Incoming, UnixListener};
-pub use self::stream::{ConnectFuture, UnixStream};
+pub use self::ancillary::AncillaryMessage;
+pub use self::datagram::{Recv, Send, UnixDatagram};
+pub use self::listener::{Accept, Incoming, UnixListener};
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+pub use self::seqpacket::{
+    SeqPacketAccept, SeqPacketConnect, SeqPacketIncoming, UnixSeqPacket, UnixSeqPacketListener,
+};
+pub use self::split::{
+    OwnedUnixReadHalf, OwnedUnixWriteHalf, ReuniteError, UnixReadHalf, UnixWriteHalf,
+};
+pub use self::stream::{
+    ConnectFuture, Peek, Readable, RecvFds, RecvWithAncillary, SendFds, UnixStream, Writable,
+};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use self::stream::RecvWithCred;
 pub use self::ucred::UCred;
@@ -0,0 +1,567 @@
+use crate::raw::PollEvented;
+use crate::socket::cvt;
+
+use async_ready::TakeError;
+use futures::io::{AsyncRead, AsyncWrite};
+use futures::{ready, Future, Poll, Stream};
+use mio::event::Evented;
+use mio::unix::EventedFd;
+
+use std::fmt;
+use std::io;
+use std::mem::{self, ManuallyDrop};
+use std::net::Shutdown;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::os::unix::net::{self, SocketAddr};
+use std::path::Path;
+use std::pin::Pin;
+use std::ptr;
+use std::task::Context;
+
+/// A connected `SOCK_SEQPACKET` Unix socket.
+///
+/// Like a [`UnixStream`], the socket is connection-oriented and delivers data
+/// reliably and in order, but like a [`UnixDatagram`] it preserves message
+/// boundaries. Every write sends one message, and every read receives
+/// exactly one message. A message which doesn't fit into the buffer it is
+/// read into is truncated, and the rest of it is discarded. A read returns 0
+/// once the peer has shut down its writing half.
+///
+/// A socket can be connected with [`connect`], accepted from a
+/// [`UnixSeqPacketListener`], or created as one of a pair with [`pair`].
+///
+/// [`UnixStream`]: struct.UnixStream.html
+/// [`UnixDatagram`]: struct.UnixDatagram.html
+/// [`UnixSeqPacketListener`]: struct.UnixSeqPacketListener.html
+/// [`connect`]: #method.connect
+/// [`pair`]: #method.pair
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// #![feature(async_await)]
+/// use futures::prelude::*;
+/// use romio::uds::UnixSeqPacket;
+///
+/// # async fn run() -> std::io::Result<()> {
+/// let mut socket = UnixSeqPacket::connect("/tmp/sock").await?;
+/// socket.write_all(b"hello").await?;
+///
+/// let mut buf = vec![0; 1024];
+/// let n = socket.read(&mut buf).await?;
+/// println!("received a message of {} bytes", n);
+/// # Ok(()) }
+/// ```
+pub struct UnixSeqPacket {
+    io: PollEvented<Socket>,
+}
+
+/// A `SOCK_SEQPACKET` Unix socket which can accept connections from other
+/// Unix sockets.
+///
+/// # Examples
+///
+/// ```no_run
+/// #![feature(async_await)]
+/// use romio::uds::{UnixSeqPacket, UnixSeqPacketListener};
+/// use futures::prelude::*;
+///
+/// async fn say_hello(mut socket: UnixSeqPacket) {
+///     socket.write_all(b"Shall I hear more, or shall I speak at this?!").await;
+/// }
+///
+/// async fn listen() -> Result<(), Box<dyn std::error::Error + 'static>> {
+///     let mut listener = UnixSeqPacketListener::bind("/tmp/sock")?;
+///     let mut incoming = listener.incoming();
+///
+///     // accept connections and process them serially
+///     while let Some(socket) = incoming.next().await {
+///         say_hello(socket?).await;
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct UnixSeqPacketListener {
+    io: PollEvented<Socket>,
+}
+
+/// Future returned by `UnixSeqPacket::connect` which will resolve to a
+/// `UnixSeqPacket` when the socket is connected.
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct SeqPacketConnect {
+    inner: State,
+}
+
+#[derive(Debug)]
+enum State {
+    Waiting(UnixSeqPacket),
+    Error(io::Error),
+    Empty,
+}
+
+impl Unpin for UnixSeqPacket {}
+
+impl UnixSeqPacket {
+    /// Connects to the socket named by `path`.
+    ///
+    /// The socket at `path` must be a `SOCK_SEQPACKET` socket, such as a
+    /// [`UnixSeqPacketListener`].
+    ///
+    /// [`UnixSeqPacketListener`]: struct.UnixSeqPacketListener.html
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// use romio::uds::UnixSeqPacket;
+    ///
+    /// # async fn run() -> std::io::Result<()> {
+    /// let socket = UnixSeqPacket::connect("/tmp/sock").await?;
+    /// # Ok(()) }
+    /// ```
+    pub fn connect(path: impl AsRef<Path>) -> SeqPacketConnect {
+        let res = Socket::new().and_then(|socket| {
+            socket.connect(path.as_ref())?;
+            Ok(UnixSeqPacket::new(socket))
+        });
+
+        let inner = match res {
+            Ok(socket) => State::Waiting(socket),
+            Err(e) => State::Error(e),
+        };
+
+        SeqPacketConnect { inner }
+    }
+
+    /// Creates an unnamed pair of connected sockets.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use romio::uds::UnixSeqPacket;
+    ///
+    /// # fn main() -> std::io::Result<()> {
+    /// let (sock1, sock2) = UnixSeqPacket::pair()?;
+    /// # Ok(()) }
+    /// ```
+    pub fn pair() -> io::Result<(UnixSeqPacket, UnixSeqPacket)> {
+        let (a, b) = Socket::pair()?;
+        Ok((UnixSeqPacket::new(a), UnixSeqPacket::new(b)))
+    }
+
+    fn new(socket: Socket) -> UnixSeqPacket {
+        let io = PollEvented::new(socket);
+        UnixSeqPacket { io }
+    }
+
+    /// Returns the socket address of the local half of this connection.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.io.get_ref().local_addr()
+    }
+
+    /// Returns the socket address of the remote half of this connection.
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.io.get_ref().peer_addr()
+    }
+
+    /// Shuts down the read, write, or both halves of this connection.
+    ///
+    /// This function will cause all pending and future I/O calls on the
+    /// specified portions to immediately return with an appropriate value
+    /// (see the documentation of `Shutdown`).
+    pub fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        self.io.get_ref().shutdown(how)
+    }
+}
+
+impl AsyncRead for UnixSeqPacket {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        self.io.read_with(cx, |io| io.recv(buf))
+    }
+}
+
+impl AsyncWrite for UnixSeqPacket {
+    /// Sends `buf` as a single message.
+    ///
+    /// Messages are sent whole or not at all: a message larger than the send
+    /// buffer of the socket fails with an error.
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.io.write_with(cx, |io| io.send(buf))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        // Writes go straight to the kernel, which takes care of buffering.
+        Poll::Ready(Ok(()))
+    }
+
+    /// Shuts down the writing portion of the socket, after which the peer
+    /// reads 0. The socket can still be read from.
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(self.shutdown(Shutdown::Write))
+    }
+}
+
+impl TakeError for UnixSeqPacket {
+    type Ok = io::Error;
+    type Err = io::Error;
+
+    /// Returns the value of the `SO_ERROR` option.
+    fn take_error(&self) -> Result<Option<Self::Ok>, Self::Err> {
+        self.io.get_ref().take_error()
+    }
+}
+
+impl fmt::Debug for UnixSeqPacket {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("UnixSeqPacket")
+            .field("fd", &self.as_raw_fd())
+            .finish()
+    }
+}
+
+impl AsRawFd for UnixSeqPacket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.io.get_ref().0
+    }
+}
+
+impl Future for SeqPacketConnect {
+    type Output = io::Result<UnixSeqPacket>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<UnixSeqPacket>> {
+        match self.inner {
+            State::Waiting(ref mut socket) => {
+                ready!(socket.io.poll_write_ready(cx)?);
+
+                if let Some(e) = socket.io.get_ref().take_error()? {
+                    return Poll::Ready(Err(e));
+                }
+            }
+            State::Error(_) => {
+                let e = match mem::replace(&mut self.inner, State::Empty) {
+                    State::Error(e) => e,
+                    _ => unreachable!(),
+                };
+
+                return Poll::Ready(Err(e));
+            }
+            State::Empty => panic!("can't poll socket twice"),
+        }
+
+        match mem::replace(&mut self.inner, State::Empty) {
+            State::Waiting(socket) => Poll::Ready(Ok(socket)),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl UnixSeqPacketListener {
+    /// Creates a new `UnixSeqPacketListener` bound to the specified path.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use romio::uds::UnixSeqPacketListener;
+    ///
+    /// # fn main () -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let listener = UnixSeqPacketListener::bind("/tmp/sock")?;
+    /// # Ok(())}
+    /// ```
+    pub fn bind(path: impl AsRef<Path>) -> io::Result<UnixSeqPacketListener> {
+        let socket = Socket::new()?;
+        socket.bind(path.as_ref())?;
+        UnixSeqPacketListener::listen(socket)
+    }
+
+    /// Creates a new `UnixSeqPacketListener` bound to `name` in the abstract
+    /// namespace.
+    ///
+    /// For more information about abstract sockets, see
+    /// [`UnixListener::bind_abstract`].
+    ///
+    /// [`UnixListener::bind_abstract`]: struct.UnixListener.html#method.bind_abstract
+    #[cfg(target_os = "linux")]
+    pub fn bind_abstract(name: &[u8]) -> io::Result<UnixSeqPacketListener> {
+        let fd = crate::socket::bind_abstract(libc::SOCK_SEQPACKET, name)?;
+        UnixSeqPacketListener::listen(Socket(fd))
+    }
+
+    fn listen(socket: Socket) -> io::Result<UnixSeqPacketListener> {
+        // the same backlog as `std::os::unix::net::UnixListener::bind`
+        unsafe {
+            cvt(libc::listen(socket.0, 128))?;
+        }
+        let io = PollEvented::new(socket);
+        Ok(UnixSeqPacketListener { io })
+    }
+
+    /// Returns the local socket address of this listener.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.io.get_ref().local_addr()
+    }
+
+    /// Accepts a new incoming connection to this listener.
+    ///
+    /// The returned future resolves to the connected socket and the address
+    /// of the peer, which is usually unnamed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// use romio::uds::UnixSeqPacketListener;
+    ///
+    /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let mut listener = UnixSeqPacketListener::bind("/tmp/sock")?;
+    /// let (socket, addr) = listener.accept().await?;
+    /// # Ok(())}
+    /// ```
+    pub fn accept(&mut self) -> SeqPacketAccept<'_> {
+        SeqPacketAccept { listener: self }
+    }
+
+    /// Attempts to accept a new incoming connection to this listener.
+    ///
+    /// For more information, see [`accept`].
+    ///
+    /// [`accept`]: #method.accept
+    pub fn poll_accept(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<(UnixSeqPacket, SocketAddr)>> {
+        let socket = ready!(self.io.read_with(cx, |io| io.accept()))?;
+        let addr = socket.peer_addr()?;
+        Poll::Ready(Ok((UnixSeqPacket::new(socket), addr)))
+    }
+
+    /// Returns a stream of the sockets this listener accepts.
+    pub fn incoming(&mut self) -> SeqPacketIncoming<'_> {
+        SeqPacketIncoming { inner: self }
+    }
+}
+
+impl fmt::Debug for UnixSeqPacketListener {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("UnixSeqPacketListener")
+            .field("fd", &self.as_raw_fd())
+            .finish()
+    }
+}
+
+impl AsRawFd for UnixSeqPacketListener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.io.get_ref().0
+    }
+}
+
+/// The future returned by `UnixSeqPacketListener::accept`.
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct SeqPacketAccept<'a> {
+    listener: &'a mut UnixSeqPacketListener,
+}
+
+impl<'a> Future for SeqPacketAccept<'a> {
+    type Output = io::Result<(UnixSeqPacket, SocketAddr)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut *self.listener).poll_accept(cx)
+    }
+}
+
+/// Stream returned by the `UnixSeqPacketListener::incoming` function
+/// representing the stream of sockets received from a listener.
+#[must_use = "streams do nothing unless polled"]
+#[derive(Debug)]
+pub struct SeqPacketIncoming<'a> {
+    inner: &'a mut UnixSeqPacketListener,
+}
+
+impl<'a> Stream for SeqPacketIncoming<'a> {
+    type Item = io::Result<UnixSeqPacket>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let (socket, _) = ready!(Pin::new(&mut *self.inner).poll_accept(cx)?);
+        Poll::Ready(Some(Ok(socket)))
+    }
+}
+
+/// A non-blocking `SOCK_SEQPACKET` Unix socket, which mio has no type for.
+#[derive(Debug)]
+struct Socket(RawFd);
+
+const FLAGS: libc::c_int = libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC;
+
+impl Socket {
+    fn new() -> io::Result<Socket> {
+        let fd = unsafe { cvt(libc::socket(libc::AF_UNIX, libc::SOCK_SEQPACKET | FLAGS, 0))? };
+        Ok(Socket(fd))
+    }
+
+    fn pair() -> io::Result<(Socket, Socket)> {
+        let mut fds = [0; 2];
+        unsafe {
+            cvt(libc::socketpair(
+                libc::AF_UNIX,
+                libc::SOCK_SEQPACKET | FLAGS,
+                0,
+                fds.as_mut_ptr(),
+            ))?;
+        }
+        Ok((Socket(fds[0]), Socket(fds[1])))
+    }
+
+    fn bind(&self, path: &Path) -> io::Result<()> {
+        let (addr, len) = sockaddr_un(path)?;
+        unsafe {
+            cvt(libc::bind(
+                self.0,
+                &addr as *const _ as *const libc::sockaddr,
+                len,
+            ))?;
+        }
+        Ok(())
+    }
+
+    fn connect(&self, path: &Path) -> io::Result<()> {
+        let (addr, len) = sockaddr_un(path)?;
+        match unsafe {
+            cvt(libc::connect(
+                self.0,
+                &addr as *const _ as *const libc::sockaddr,
+                len,
+            ))
+        } {
+            Ok(_) => Ok(()),
+            Err(ref e) if e.raw_os_error() == Some(libc::EINPROGRESS) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn accept(&self) -> io::Result<Socket> {
+        let fd = unsafe {
+            cvt(libc::accept4(
+                self.0,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                FLAGS,
+            ))?
+        };
+        Ok(Socket(fd))
+    }
+
+    fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = unsafe {
+            cvt(libc::recv(
+                self.0,
+                buf.as_mut_ptr() as *mut libc::c_void,
+                buf.len(),
+                0,
+            ))?
+        };
+        Ok(n as usize)
+    }
+
+    fn send(&self, buf: &[u8]) -> io::Result<usize> {
+        let n = unsafe {
+            cvt(libc::send(
+                self.0,
+                buf.as_ptr() as *const libc::c_void,
+                buf.len(),
+                libc::MSG_NOSIGNAL,
+            ))?
+        };
+        Ok(n as usize)
+    }
+
+    fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        self.std().shutdown(how)
+    }
+
+    fn take_error(&self) -> io::Result<Option<io::Error>> {
+        self.std().take_error()
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.std().local_addr()
+    }
+
+    fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.std().peer_addr()
+    }
+
+    /// Borrows the socket as a `std` Unix socket, for the functionality which
+    /// doesn't depend on the socket type.
+    fn std(&self) -> ManuallyDrop<net::UnixDatagram> {
+        ManuallyDrop::new(unsafe { net::UnixDatagram::from_raw_fd(self.0) })
+    }
+}
+
+impl Evented for Socket {
+    fn register(
+        &self,
+        poll: &mio::Poll,
+        token: mio::Token,
+        interest: mio::Ready,
+        opts: mio::PollOpt,
+    ) -> io::Result<()> {
+        EventedFd(&self.0).register(poll, token, interest, opts)
+    }
+
+    fn reregister(
+        &self,
+        poll: &mio::Poll,
+        token: mio::Token,
+        interest: mio::Ready,
+        opts: mio::PollOpt,
+    ) -> io::Result<()> {
+        EventedFd(&self.0).reregister(poll, token, interest, opts)
+    }
+
+    fn deregister(&self, poll: &mio::Poll) -> io::Result<()> {
+        EventedFd(&self.0).deregister(poll)
+    }
+}
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.0);
+        }
+    }
+}
+
+fn sockaddr_un(path: &Path) -> io::Result<(libc::sockaddr_un, libc::socklen_t)> {
+    let mut addr: libc::sockaddr_un = unsafe { mem::zeroed() };
+    addr.sun_family = libc::AF_UNIX as libc::sa_family_t;
+
+    // The path must be null-terminated.
+    let path = path.as_os_str().as_bytes();
+    if path.contains(&0) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "paths may not contain interior null bytes",
+        ));
+    }
+    if path.len() >= addr.sun_path.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "path must be shorter than SUN_LEN",
+        ));
+    }
+    for (dst, src) in addr.sun_path.iter_mut().zip(path) {
+        *dst = *src as libc::c_char;
+    }
+    let len = mem::size_of::<libc::sa_family_t>() + path.len() + 1;
+    Ok((addr, len as libc::socklen_t))
+}
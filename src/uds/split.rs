@@ -0,0 +1,164 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::net::Shutdown;
+use std::os::unix::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures::io::{AsyncRead, AsyncWrite};
+
+use super::UnixStream;
+
+/// The reading half of a `UnixStream`, borrowed by `UnixStream::split_borrowed`.
+#[derive(Debug)]
+pub struct UnixReadHalf<'a>(&'a UnixStream);
+
+/// The writing half of a `UnixStream`, borrowed by `UnixStream::split_borrowed`.
+///
+/// Closing this half shuts down the writing portion of the stream.
+#[derive(Debug)]
+pub struct UnixWriteHalf<'a>(&'a UnixStream);
+
+/// The reading half of a `UnixStream`, created by `UnixStream::into_split`.
+#[derive(Debug)]
+pub struct OwnedUnixReadHalf(Arc<UnixStream>);
+
+/// The writing half of a `UnixStream`, created by `UnixStream::into_split`.
+///
+/// Closing this half shuts down the writing portion of the stream.
+#[derive(Debug)]
+pub struct OwnedUnixWriteHalf(Arc<UnixStream>);
+
+/// Error returned by `reunite` when the halves do not belong to the same
+/// stream.
+///
+/// The halves are handed back unchanged.
+#[derive(Debug)]
+pub struct ReuniteError(pub OwnedUnixReadHalf, pub OwnedUnixWriteHalf);
+
+pub(super) fn split(stream: &mut UnixStream) -> (UnixReadHalf<'_>, UnixWriteHalf<'_>) {
+    (UnixReadHalf(&*stream), UnixWriteHalf(&*stream))
+}
+
+pub(super) fn into_split(stream: UnixStream) -> (OwnedUnixReadHalf, OwnedUnixWriteHalf) {
+    let stream = Arc::new(stream);
+    (
+        OwnedUnixReadHalf(stream.clone()),
+        OwnedUnixWriteHalf(stream),
+    )
+}
+
+fn reunite(read: OwnedUnixReadHalf, write: OwnedUnixWriteHalf) -> Result<UnixStream, ReuniteError> {
+    if !Arc::ptr_eq(&read.0, &write.0) {
+        return Err(ReuniteError(read, write));
+    }
+    drop(write);
+    Ok(Arc::try_unwrap(read.0).expect("UnixStream: only the two halves own the stream"))
+}
+
+impl<'a> AsyncRead for UnixReadHalf<'a> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        self.0.poll_read_priv(cx, buf)
+    }
+}
+
+impl<'a> AsyncWrite for UnixWriteHalf<'a> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.0.poll_write_priv(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(self.0.shutdown(Shutdown::Write))
+    }
+}
+
+impl OwnedUnixReadHalf {
+    /// Puts the stream back together from its two halves.
+    ///
+    /// Fails if `write` was not split from the same stream as this half.
+    pub fn reunite(self, write: OwnedUnixWriteHalf) -> Result<UnixStream, ReuniteError> {
+        reunite(self, write)
+    }
+
+    /// Returns the socket address of the local half of this connection.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.0.local_addr()
+    }
+
+    /// Returns the socket address of the remote half of this connection.
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.0.peer_addr()
+    }
+}
+
+impl AsyncRead for OwnedUnixReadHalf {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        self.0.poll_read_priv(cx, buf)
+    }
+}
+
+impl OwnedUnixWriteHalf {
+    /// Puts the stream back together from its two halves.
+    ///
+    /// Fails if `read` was not split from the same stream as this half.
+    pub fn reunite(self, read: OwnedUnixReadHalf) -> Result<UnixStream, ReuniteError> {
+        reunite(read, self)
+    }
+
+    /// Returns the socket address of the local half of this connection.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.0.local_addr()
+    }
+
+    /// Returns the socket address of the remote half of this connection.
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.0.peer_addr()
+    }
+}
+
+impl AsyncWrite for OwnedUnixWriteHalf {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.0.poll_write_priv(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(self.0.shutdown(Shutdown::Write))
+    }
+}
+
+impl fmt::Display for ReuniteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "tried to reunite halves that are not from the same stream"
+        )
+    }
+}
+
+impl Error for ReuniteError {}
@@ -1,15 +1,19 @@
+use super::split::{self, OwnedUnixReadHalf, OwnedUnixWriteHalf, UnixReadHalf, UnixWriteHalf};
 use super::ucred::{self, UCred};
+use super::AncillaryMessage;
 
 use crate::raw::PollEvented;
+use crate::socket::{self, Control};
 
 use async_ready::{AsyncReadReady, AsyncWriteReady, TakeError};
 use futures::io::{AsyncRead, AsyncWrite};
 use futures::{ready, Future, Poll};
 
 use std::fmt;
-use std::io;
+use std::io::{self, IoSlice, IoSliceMut, Read, Write};
+use std::mem;
 use std::net::Shutdown;
-use std::os::unix::io::{AsRawFd, RawFd};
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
 use std::os::unix::net::SocketAddr;
 use std::path::Path;
 use std::pin::Pin;
@@ -26,6 +30,7 @@ pub struct UnixStream {
 
 /// Future returned by `UnixStream::connect` which will resolve to a
 /// `UnixStream` when the stream is connected.
+#[must_use = "futures do nothing unless polled"]
 #[derive(Debug)]
 pub struct ConnectFuture {
     inner: State,
@@ -47,6 +52,11 @@ impl UnixStream {
     /// specified, associating the returned stream with the default event loop's
     /// handle.
     ///
+    /// Errors that the non-blocking `connect` reports right away, such as
+    /// `NotFound` when nothing exists at `path`, are returned the first time
+    /// the future is polled. Otherwise the future waits for the connection
+    /// to complete and returns any error reported by the socket.
+    ///
     /// # Examples
     ///
     /// ```rust,no_run
@@ -92,6 +102,24 @@ impl UnixStream {
         Ok((a, b))
     }
 
+    // Shared with the halves returned by `split_borrowed` and `into_split`,
+    // which only have shared access to the stream.
+    pub(super) fn poll_read_priv(
+        &self,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        self.io.read_with(cx, |mut io| io.read(buf))
+    }
+
+    pub(super) fn poll_write_priv(
+        &self,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.io.write_with(cx, |mut io| io.write(buf))
+    }
+
     pub(crate) fn new(stream: mio_uds::UnixStream) -> UnixStream {
         let io = PollEvented::new(stream);
         UnixStream { io }
@@ -116,6 +144,9 @@ impl UnixStream {
 
     /// Returns the socket address of the remote half of this connection.
     ///
+    /// Clients rarely `bind` before connecting, so on an accepted stream the
+    /// address is usually unnamed, which `SocketAddr::is_unnamed` reports.
+    ///
     /// # Examples
     ///
     /// ```rust,no_run
@@ -148,6 +179,300 @@ impl UnixStream {
         ucred::get_peer_cred(self)
     }
 
+    /// Sets the value of the `SO_PASSCRED` option for this socket.
+    ///
+    /// When enabled, the credentials of the sending process are delivered as
+    /// `SCM_CREDENTIALS` ancillary data with the data read from the socket,
+    /// see [`recv_with_cred`]. Streams accepted from a listener inherit the
+    /// option, so it is best enabled on the listening socket before the peer
+    /// starts sending.
+    ///
+    /// [`recv_with_cred`]: #method.recv_with_cred
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn set_passcred(&self, on: bool) -> io::Result<()> {
+        socket::setsockopt(
+            self.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PASSCRED,
+            on as libc::c_int,
+        )
+    }
+
+    /// Gets the value of the `SO_PASSCRED` option for this socket.
+    ///
+    /// For more information about this option, see [`set_passcred`].
+    ///
+    /// [`set_passcred`]: #method.set_passcred
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn passcred(&self) -> io::Result<bool> {
+        let on: libc::c_int =
+            socket::getsockopt(self.as_raw_fd(), libc::SOL_SOCKET, libc::SO_PASSCRED)?;
+        Ok(on != 0)
+    }
+
+    /// Receives data on the socket, along with any ancillary data sent with
+    /// it. On success, returns the number of bytes read and the parsed
+    /// ancillary messages.
+    ///
+    /// `max_ancillary` is the size in bytes of the buffer for ancillary data.
+    /// Messages that do not fit are discarded by the kernel. File descriptors
+    /// received in [`AncillaryMessage::Rights`] are owned by the caller; on
+    /// Linux they are received with the close-on-exec flag set.
+    ///
+    /// [`AncillaryMessage::Rights`]: enum.AncillaryMessage.html#variant.Rights
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// use romio::uds::{AncillaryMessage, UnixStream};
+    ///
+    /// # async fn run() -> std::io::Result<()> {
+    /// let mut stream = UnixStream::connect("/tmp/sock").await?;
+    /// let mut buf = vec![0; 1024];
+    /// let (n, messages) = stream.recv_with_ancillary(&mut buf, 128).await?;
+    ///
+    /// for message in messages {
+    ///     if let AncillaryMessage::Rights(fds) = message {
+    ///         println!("received file descriptors {:?}", fds);
+    ///     }
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub fn recv_with_ancillary<'a, 'b>(
+        &'a mut self,
+        buf: &'b mut [u8],
+        max_ancillary: usize,
+    ) -> RecvWithAncillary<'a, 'b> {
+        RecvWithAncillary {
+            stream: self,
+            buf,
+            max_ancillary,
+        }
+    }
+
+    /// Attempts to receive data on the socket, along with any ancillary data
+    /// sent with it.
+    ///
+    /// For more information, see [`recv_with_ancillary`].
+    ///
+    /// [`recv_with_ancillary`]: #method.recv_with_ancillary
+    pub fn poll_recv_with_ancillary(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+        max_ancillary: usize,
+    ) -> Poll<io::Result<(usize, Vec<AncillaryMessage>)>> {
+        ready!(Pin::new(&mut self.io).poll_read_ready(cx)?);
+
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        let flags = libc::MSG_CMSG_CLOEXEC;
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        let flags = 0;
+
+        let mut control = Control::with_capacity(max_ancillary);
+        let fd = self.io.get_ref().as_raw_fd();
+        match socket::recv_msg(fd, &mut [IoSliceMut::new(buf)], Some(&mut control), flags) {
+            Ok(msg) => Poll::Ready(Ok((msg.len, AncillaryMessage::parse(&control)))),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                Pin::new(&mut self.io).clear_read_ready(cx)?;
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    /// Receives data on the socket, along with the credentials of the process
+    /// that sent it. On success, returns the number of bytes read and the
+    /// credentials.
+    ///
+    /// The credentials are passed as `SCM_CREDENTIALS` ancillary data, which
+    /// must first be enabled with [`set_passcred`]. If they are missing, an
+    /// error is returned. Unlike [`peer_cred`], which reports who connected
+    /// the socket, this reports who sent the data.
+    ///
+    /// [`set_passcred`]: #method.set_passcred
+    /// [`peer_cred`]: #method.peer_cred
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// use romio::uds::UnixStream;
+    ///
+    /// # async fn run() -> std::io::Result<()> {
+    /// let mut stream = UnixStream::connect("/tmp/sock").await?;
+    /// stream.set_passcred(true)?;
+    ///
+    /// let mut buf = vec![0; 1024];
+    /// let (n, cred) = stream.recv_with_cred(&mut buf).await?;
+    /// println!("{} bytes from uid {}", n, cred.uid);
+    /// # Ok(()) }
+    /// ```
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn recv_with_cred<'a>(&'a mut self, buf: &'a mut [u8]) -> RecvWithCred<'a> {
+        RecvWithCred { stream: self, buf }
+    }
+
+    /// Attempts to receive data on the socket, along with the credentials of
+    /// the process that sent it.
+    ///
+    /// For more information, see [`recv_with_cred`].
+    ///
+    /// [`recv_with_cred`]: #method.recv_with_cred
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn poll_recv_with_cred(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<(usize, UCred)>> {
+        let space = Control::space(mem::size_of::<libc::ucred>());
+        let (n, messages) = ready!(self.poll_recv_with_ancillary(cx, buf, space))?;
+
+        let cred = messages.into_iter().find_map(|message| match message {
+            AncillaryMessage::Credentials(cred) => Some(cred),
+            _ => None,
+        });
+        match cred {
+            Some(cred) => Poll::Ready(Ok((n, cred))),
+            None => Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "credentials not received, is SO_PASSCRED enabled?",
+            ))),
+        }
+    }
+
+    /// Sends data on the socket, passing the file descriptors `fds` along
+    /// with it as `SCM_RIGHTS` ancillary data.
+    ///
+    /// The receiver gets duplicates of the descriptors, so they can be closed
+    /// on this side once the future completes. On success, returns the number
+    /// of bytes written. The descriptors are only sent if some of the data is
+    /// written, so `data` should not be empty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// use romio::uds::UnixStream;
+    /// use std::fs::File;
+    /// use std::os::unix::io::AsRawFd;
+    ///
+    /// # async fn run() -> std::io::Result<()> {
+    /// let mut stream = UnixStream::connect("/tmp/sock").await?;
+    /// let file = File::open("/etc/hosts")?;
+    /// stream.send_fds(b"hosts", &[file.as_raw_fd()]).await?;
+    /// # Ok(()) }
+    /// ```
+    pub fn send_fds<'a, 'b>(&'a mut self, data: &'b [u8], fds: &'b [RawFd]) -> SendFds<'a, 'b> {
+        SendFds {
+            stream: self,
+            data,
+            fds,
+        }
+    }
+
+    /// Attempts to send data on the socket, passing file descriptors along
+    /// with it.
+    ///
+    /// For more information, see [`send_fds`].
+    ///
+    /// [`send_fds`]: #method.send_fds
+    pub fn poll_send_fds(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        data: &[u8],
+        fds: &[RawFd],
+    ) -> Poll<io::Result<usize>> {
+        ready!(self.io.poll_write_ready(cx)?);
+
+        let mut control = Control::with_capacity(Control::space(mem::size_of_val(fds)));
+        if !fds.is_empty() {
+            control.push_slice(libc::SOL_SOCKET, libc::SCM_RIGHTS, fds);
+        }
+
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        let flags = libc::MSG_NOSIGNAL;
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        let flags = 0;
+
+        let fd = self.io.get_ref().as_raw_fd();
+        match socket::send_msg(fd, &[IoSlice::new(data)], None, Some(&control), flags) {
+            Ok(n) => Poll::Ready(Ok(n)),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                self.io.clear_write_ready(cx)?;
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    /// Receives data on the socket, along with up to `max_fds` file
+    /// descriptors passed as `SCM_RIGHTS` ancillary data.
+    ///
+    /// On success, returns the number of bytes read and the descriptors
+    /// received, which are owned by the caller and have the close-on-exec
+    /// flag set where the platform supports it. Descriptors beyond `max_fds`
+    /// are closed, as are descriptors the kernel had to drop because they did
+    /// not fit.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// use romio::uds::UnixStream;
+    /// use std::fs::File;
+    /// use std::os::unix::io::FromRawFd;
+    ///
+    /// # async fn run() -> std::io::Result<()> {
+    /// let mut stream = UnixStream::connect("/tmp/sock").await?;
+    /// let mut buf = vec![0; 1024];
+    /// let (n, fds) = stream.recv_fds(&mut buf, 1).await?;
+    ///
+    /// if let Some(&fd) = fds.first() {
+    ///     let file = unsafe { File::from_raw_fd(fd) };
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub fn recv_fds<'a, 'b>(&'a mut self, data: &'b mut [u8], max_fds: usize) -> RecvFds<'a, 'b> {
+        RecvFds {
+            stream: self,
+            data,
+            max_fds,
+        }
+    }
+
+    /// Attempts to receive data on the socket, along with file descriptors
+    /// passed with it.
+    ///
+    /// For more information, see [`recv_fds`].
+    ///
+    /// [`recv_fds`]: #method.recv_fds
+    pub fn poll_recv_fds(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        data: &mut [u8],
+        max_fds: usize,
+    ) -> Poll<io::Result<(usize, Vec<RawFd>)>> {
+        let space = Control::space(max_fds * mem::size_of::<RawFd>());
+        let (n, messages) = ready!(self.poll_recv_with_ancillary(cx, data, space))?;
+
+        let mut fds = Vec::new();
+        for message in messages {
+            if let AncillaryMessage::Rights(received) = message {
+                fds.extend(received);
+            }
+        }
+        // The control buffer is rounded up for alignment, so there may be
+        // room for more descriptors than were asked for.
+        for fd in fds.drain(max_fds.min(fds.len())..) {
+            unsafe {
+                libc::close(fd);
+            }
+        }
+        Poll::Ready(Ok((n, fds)))
+    }
+
     /// Shuts down the read, write, or both halves of this connection.
     ///
     /// This function will cause all pending and future I/O calls on the
@@ -167,6 +492,327 @@ impl UnixStream {
     pub fn shutdown(&self, how: Shutdown) -> io::Result<()> {
         self.io.get_ref().shutdown(how)
     }
+
+    /// Shuts down the read, write, or both halves of this connection from a
+    /// poll-based context.
+    ///
+    /// This always completes immediately. For more information, see
+    /// [`shutdown`].
+    ///
+    /// [`shutdown`]: #method.shutdown
+    pub fn poll_shutdown(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        how: Shutdown,
+    ) -> Poll<io::Result<()>> {
+        Poll::Ready(self.shutdown(how))
+    }
+
+    /// Receives data on the socket without removing it from the queue. On
+    /// success, returns the number of bytes peeked.
+    ///
+    /// Successive calls return the same data, until it is consumed with a
+    /// read. Like a read, this returns 0 once the peer has shut down its
+    /// writing half and all data has been received.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// use futures::prelude::*;
+    /// use romio::uds::UnixStream;
+    ///
+    /// # async fn run() -> std::io::Result<()> {
+    /// let mut stream = UnixStream::connect("/tmp/sock").await?;
+    ///
+    /// // JSON-RPC requests start with an object
+    /// let mut byte = [0; 1];
+    /// let n = stream.peek(&mut byte).await?;
+    /// let is_json = n == 1 && byte[0] == b'{';
+    ///
+    /// let mut buf = vec![0; 1024];
+    /// stream.read(&mut buf).await?;
+    /// # Ok(()) }
+    /// ```
+    pub fn peek<'a>(&'a mut self, buf: &'a mut [u8]) -> Peek<'a> {
+        Peek { stream: self, buf }
+    }
+
+    /// Attempts to receive data on the socket without removing it from the
+    /// queue.
+    ///
+    /// For more information, see [`peek`].
+    ///
+    /// [`peek`]: #method.peek
+    pub fn poll_peek(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        ready!(self.io.poll_read_ready(cx)?);
+
+        let res = unsafe {
+            socket::cvt(libc::recv(
+                self.io.get_ref().as_raw_fd(),
+                buf.as_mut_ptr() as *mut libc::c_void,
+                buf.len(),
+                libc::MSG_PEEK,
+            ))
+        };
+        match res {
+            Ok(n) => Poll::Ready(Ok(n as usize)),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                self.io.clear_read_ready(cx)?;
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    /// Reads data from the stream, without waiting for it to become readable.
+    /// On success, returns the number of bytes read.
+    ///
+    /// This is meant to be called after [`readable`] resolves. If no data is
+    /// available, an error of kind `WouldBlock` is returned and the read
+    /// readiness of the stream is cleared, so that awaiting `readable` again
+    /// waits for new data. This does not register the current task for
+    /// wakeup.
+    ///
+    /// [`readable`]: #method.readable
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// use romio::uds::UnixStream;
+    /// use std::io;
+    ///
+    /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let mut stream = UnixStream::connect("/tmp/sock").await?;
+    /// let mut buf = vec![0; 1024];
+    ///
+    /// loop {
+    ///     stream.readable().await?;
+    ///     match stream.try_read(&mut buf) {
+    ///         Ok(0) => break,
+    ///         Ok(n) => println!("read {} bytes", n),
+    ///         Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+    ///         Err(e) => return Err(e.into()),
+    ///     }
+    /// }
+    /// # Ok(())}
+    /// ```
+    pub fn try_read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.io.try_read_with(|mut io| io.read(buf))
+    }
+
+    /// Writes data to the stream, without waiting for it to become writable.
+    /// On success, returns the number of bytes written.
+    ///
+    /// This is meant to be called after [`writable`] resolves. If the send
+    /// buffer is full, an error of kind `WouldBlock` is returned and the write
+    /// readiness of the stream is cleared, so that awaiting `writable` again
+    /// waits for room in the send buffer. This does not register the current
+    /// task for wakeup.
+    ///
+    /// [`writable`]: #method.writable
+    pub fn try_write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.io.try_write_with(|mut io| io.write(buf))
+    }
+
+    /// Waits for the stream to become readable.
+    ///
+    /// The readiness is kept until [`try_read`] returns `WouldBlock`, so
+    /// calling this again before then completes immediately. Readiness may be
+    /// spurious, in which case `try_read` returns `WouldBlock` and the next
+    /// call waits for a new readiness event.
+    ///
+    /// [`try_read`]: #method.try_read
+    pub fn readable(&mut self) -> Readable<'_> {
+        Readable { stream: self }
+    }
+
+    /// Waits for the stream to become writable.
+    ///
+    /// The readiness is kept until [`try_write`] returns `WouldBlock`, so
+    /// calling this again before then completes immediately.
+    ///
+    /// [`try_write`]: #method.try_write
+    pub fn writable(&mut self) -> Writable<'_> {
+        Writable { stream: self }
+    }
+
+    /// Attempts to read from the stream into a slice of buffers, filling each
+    /// buffer in turn. On success, returns the number of bytes read.
+    ///
+    /// This is a single `readv`-style call, so data is never left behind in
+    /// the socket when it would fit in a later buffer.
+    ///
+    /// This is also used by the `AsyncRead` implementation, so the future
+    /// returned by `AsyncReadExt::read_vectored` can be used to call it from
+    /// an `async` block.
+    pub fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<io::Result<usize>> {
+        ready!(self.io.poll_read_ready(cx)?);
+
+        let fd = self.io.get_ref().as_raw_fd();
+        match socket::recv_msg(fd, bufs, None, 0) {
+            Ok(msg) => Poll::Ready(Ok(msg.len)),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                self.io.clear_read_ready(cx)?;
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    /// Attempts to write a slice of buffers to the stream, gathering them in
+    /// order. On success, returns the number of bytes written.
+    ///
+    /// This is a single `writev`-style call, so the buffers are sent without
+    /// being copied into one first.
+    ///
+    /// This is also used by the `AsyncWrite` implementation, so the future
+    /// returned by `AsyncWriteExt::write_vectored` can be used to call it
+    /// from an `async` block.
+    pub fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        ready!(self.io.poll_write_ready(cx)?);
+
+        // Like the regular writes, don't raise `SIGPIPE` if the peer is gone.
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        let flags = libc::MSG_NOSIGNAL;
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        let flags = 0;
+
+        let fd = self.io.get_ref().as_raw_fd();
+        match socket::send_msg(fd, bufs, None, None, flags) {
+            Ok(n) => Poll::Ready(Ok(n)),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                self.io.clear_write_ready(cx)?;
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    /// Sets the close-on-exec flag on the stream's file descriptor, so that
+    /// it is closed in child processes after `exec`.
+    ///
+    /// This is needed for streams created with `from_raw_fd`, such as from a
+    /// descriptor received with [`recv_fds`] on platforms other than Linux,
+    /// which don't set the flag on received descriptors. See
+    /// [`TcpStream::set_cloexec`] for why this matters.
+    ///
+    /// [`recv_fds`]: #method.recv_fds
+    /// [`TcpStream::set_cloexec`]: ../tcp/struct.TcpStream.html#method.set_cloexec
+    pub fn set_cloexec(&self) -> io::Result<()> {
+        crate::socket::set_cloexec(self.as_raw_fd(), true)
+    }
+
+    /// Clears the close-on-exec flag on the stream's file descriptor, so that
+    /// a child process can keep using the connection after `exec`.
+    pub fn clear_cloexec(&self) -> io::Result<()> {
+        crate::socket::set_cloexec(self.as_raw_fd(), false)
+    }
+
+    /// Splits the stream into a reading and a writing half, which borrow it.
+    ///
+    /// The halves can be driven concurrently, for example from the two
+    /// branches of a `join`, but not moved into separate tasks. For that, see
+    /// [`into_split`].
+    ///
+    /// [`into_split`]: #method.into_split
+    ///
+    /// This is not named `split`, so that it doesn't clash with
+    /// `AsyncReadExt::split`, which takes the stream by value.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// use futures::io::AsyncReadExt;
+    /// use romio::uds::UnixStream;
+    ///
+    /// # async fn run() -> std::io::Result<()> {
+    /// let mut stream = UnixStream::connect("/tmp/sock").await?;
+    /// let (mut reader, mut writer) = stream.split_borrowed();
+    ///
+    /// // echo everything back to the peer
+    /// reader.copy_into(&mut writer).await?;
+    /// # Ok(()) }
+    /// ```
+    pub fn split_borrowed(&mut self) -> (UnixReadHalf<'_>, UnixWriteHalf<'_>) {
+        split::split(self)
+    }
+
+    /// Splits the stream into a reading and a writing half, which own it.
+    ///
+    /// Both halves share the stream, which is closed once both of them are
+    /// dropped, and each of them can be moved into a task of its own. They
+    /// can be put back together with [`OwnedUnixReadHalf::reunite`] or
+    /// [`OwnedUnixWriteHalf::reunite`].
+    ///
+    /// [`OwnedUnixReadHalf::reunite`]: struct.OwnedUnixReadHalf.html#method.reunite
+    /// [`OwnedUnixWriteHalf::reunite`]: struct.OwnedUnixWriteHalf.html#method.reunite
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// use futures::executor::ThreadPool;
+    /// use futures::io::{AsyncReadExt, AsyncWriteExt};
+    /// use futures::task::SpawnExt;
+    /// use romio::uds::UnixStream;
+    ///
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let mut pool = ThreadPool::new()?;
+    /// let stream = UnixStream::connect("/tmp/sock").await?;
+    /// let (mut reader, mut writer) = stream.into_split();
+    ///
+    /// pool.spawn(async move {
+    ///     let _ = writer.write_all(b"hello").await;
+    /// })?;
+    ///
+    /// let mut buf = vec![0; 1024];
+    /// let n = reader.read(&mut buf).await?;
+    /// # Ok(()) }
+    /// ```
+    pub fn into_split(self) -> (OwnedUnixReadHalf, OwnedUnixWriteHalf) {
+        split::into_split(self)
+    }
+
+    /// Converts this stream into a `std::os::unix::net::UnixStream`.
+    ///
+    /// The stream is deregistered from the reactor and switched back to
+    /// blocking mode, so it can be handed to synchronous code.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await)]
+    /// use romio::uds::UnixStream;
+    /// use std::io::Write;
+    ///
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let stream = UnixStream::connect("/tmp/sock").await?;
+    /// let mut std_stream = stream.into_std()?;
+    /// std_stream.write_all(b"hello")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn into_std(self) -> io::Result<std::os::unix::net::UnixStream> {
+        let fd = self.io.into_inner()?.into_raw_fd();
+        let stream = unsafe { std::os::unix::net::UnixStream::from_raw_fd(fd) };
+        stream.set_nonblocking(false)?;
+        Ok(stream)
+    }
 }
 
 impl AsyncRead for UnixStream {
@@ -177,6 +823,14 @@ impl AsyncRead for UnixStream {
     ) -> Poll<io::Result<usize>> {
         Pin::new(&mut self.io).poll_read(cx, buf)
     }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<io::Result<usize>> {
+        UnixStream::poll_read_vectored(self, cx, bufs)
+    }
 }
 
 impl AsyncWrite for UnixStream {
@@ -188,12 +842,23 @@ impl AsyncWrite for UnixStream {
         Pin::new(&mut self.io).poll_write(cx, buf)
     }
 
-    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
-        Pin::new(&mut self.io).poll_flush(cx)
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        UnixStream::poll_write_vectored(self, cx, bufs)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        // Writes go straight to the kernel, which takes care of buffering.
+        Poll::Ready(Ok(()))
     }
 
-    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
-        Pin::new(&mut self.io).poll_close(cx)
+    /// Shuts down the writing portion of the stream, which signals the end of
+    /// the data to the peer. The stream can still be read from.
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_shutdown(cx, Shutdown::Write)
     }
 }
 
@@ -260,12 +925,24 @@ impl AsRawFd for UnixStream {
     }
 }
 
+impl FromRawFd for UnixStream {
+    /// Creates a new `UnixStream` from a raw file descriptor, and registers it
+    /// with the default reactor.
+    ///
+    /// # Safety
+    ///
+    /// `fd` must be a valid Unix stream socket, connected or with a connection
+    /// in progress, in non-blocking mode. Ownership of `fd` is transferred to
+    /// the stream, which closes it when dropped.
+    unsafe fn from_raw_fd(fd: RawFd) -> UnixStream {
+        UnixStream::new(mio_uds::UnixStream::from_raw_fd(fd))
+    }
+}
+
 impl Future for ConnectFuture {
     type Output = io::Result<UnixStream>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<UnixStream>> {
-        use std::mem;
-
         match self.inner {
             State::Waiting(ref mut stream) => {
                 ready!(stream.io.poll_write_ready(cx)?);
@@ -291,3 +968,130 @@ impl Future for ConnectFuture {
         }
     }
 }
+
+/// Future returned by `UnixStream::recv_with_ancillary`.
+#[derive(Debug)]
+pub struct RecvWithAncillary<'a, 'b> {
+    stream: &'a mut UnixStream,
+    buf: &'b mut [u8],
+    max_ancillary: usize,
+}
+
+impl<'a, 'b> Future for RecvWithAncillary<'a, 'b> {
+    type Output = io::Result<(usize, Vec<AncillaryMessage>)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let RecvWithAncillary {
+            stream,
+            buf,
+            max_ancillary,
+        } = &mut *self;
+        Pin::new(&mut **stream).poll_recv_with_ancillary(cx, buf, *max_ancillary)
+    }
+}
+
+/// Future returned by `UnixStream::peek`.
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct Peek<'a> {
+    stream: &'a mut UnixStream,
+    buf: &'a mut [u8],
+}
+
+impl<'a> Future for Peek<'a> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<usize>> {
+        let Peek { stream, buf } = &mut *self;
+        Pin::new(&mut **stream).poll_peek(cx, buf)
+    }
+}
+
+/// Future returned by `UnixStream::readable`.
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct Readable<'a> {
+    stream: &'a mut UnixStream,
+}
+
+impl<'a> Future for Readable<'a> {
+    type Output = io::Result<mio::Ready>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.stream.io.poll_read_ready(cx)
+    }
+}
+
+/// Future returned by `UnixStream::writable`.
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct Writable<'a> {
+    stream: &'a mut UnixStream,
+}
+
+impl<'a> Future for Writable<'a> {
+    type Output = io::Result<mio::Ready>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.stream.io.poll_write_ready(cx)
+    }
+}
+
+/// Future returned by `UnixStream::recv_with_cred`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct RecvWithCred<'a> {
+    stream: &'a mut UnixStream,
+    buf: &'a mut [u8],
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl<'a> Future for RecvWithCred<'a> {
+    type Output = io::Result<(usize, UCred)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let RecvWithCred { stream, buf } = &mut *self;
+        Pin::new(&mut **stream).poll_recv_with_cred(cx, buf)
+    }
+}
+
+/// Future returned by `UnixStream::send_fds`.
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct SendFds<'a, 'b> {
+    stream: &'a mut UnixStream,
+    data: &'b [u8],
+    fds: &'b [RawFd],
+}
+
+impl<'a, 'b> Future for SendFds<'a, 'b> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let SendFds { stream, data, fds } = &mut *self;
+        Pin::new(&mut **stream).poll_send_fds(cx, data, fds)
+    }
+}
+
+/// Future returned by `UnixStream::recv_fds`.
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct RecvFds<'a, 'b> {
+    stream: &'a mut UnixStream,
+    data: &'b mut [u8],
+    max_fds: usize,
+}
+
+impl<'a, 'b> Future for RecvFds<'a, 'b> {
+    type Output = io::Result<(usize, Vec<RawFd>)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let RecvFds {
+            stream,
+            data,
+            max_fds,
+        } = &mut *self;
+        Pin::new(&mut **stream).poll_recv_fds(cx, data, *max_fds)
+    }
+}
@@ -1,17 +1,92 @@
-use libc::{gid_t, uid_t};
+use libc::{gid_t, pid_t, uid_t};
+
+use std::fmt;
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+use std::io;
 
 /// Credentials of a process
+///
+/// The `Display` implementation formats the credentials as
+/// `uid=1000 gid=1000`, followed by ` pid=4242` when the PID is known, like
+/// the `key=value` fields of system logs. This format is stable.
+///
+/// With the `serde` feature, `UCred` implements `Serialize` and `Deserialize`
+/// as a struct with `uid`, `gid` and `pid` fields.
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UCred {
     /// UID (user ID) of the process
     pub uid: uid_t,
     /// GID (group ID) of the process
     pub gid: gid_t,
+    /// PID (process ID) of the process
+    ///
+    /// This is only reported on Linux, Android, Solaris and illumos,
+    /// `getpeereid` on macOS and the BSDs doesn't provide it.
+    pub pid: Option<pid_t>,
+}
+
+impl UCred {
+    /// Returns the effective credentials of the current process.
+    pub fn current() -> UCred {
+        unsafe {
+            UCred {
+                uid: libc::geteuid(),
+                gid: libc::getegid(),
+                pid: Some(libc::getpid()),
+            }
+        }
+    }
+
+    /// Looks up the effective credentials of the process with the given PID.
+    ///
+    /// On Linux and Android the credentials are read from
+    /// `/proc/<pid>/status`, on macOS they are queried with `proc_pidinfo`,
+    /// and on FreeBSD and OpenBSD with the `kern.proc.pid` sysctl. If there is
+    /// no such process, an error of kind `NotFound` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use romio::uds::UCred;
+    ///
+    /// # fn main() -> std::io::Result<()> {
+    /// let cred = UCred::from_process(std::process::id())?;
+    /// assert_eq!(cred, UCred::current());
+    /// # Ok(()) }
+    /// ```
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "freebsd",
+        target_os = "openbsd"
+    ))]
+    pub fn from_process(pid: u32) -> io::Result<UCred> {
+        get_process_cred(pid)
+    }
+}
+
+impl fmt::Display for UCred {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "uid={} gid={}", self.uid, self.gid)?;
+        if let Some(pid) = self.pid {
+            write!(f, " pid={}", pid)?;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(any(target_os = "linux", target_os = "android"))]
 pub(crate) use self::impl_linux::get_peer_cred;
 
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use self::impl_linux::get_process_cred;
+
+#[cfg(any(target_os = "macos", target_os = "freebsd", target_os = "openbsd"))]
+use self::impl_macos::get_process_cred;
+
 #[cfg(any(
     target_os = "dragonfly",
     target_os = "macos",
@@ -22,12 +97,15 @@ pub(crate) use self::impl_linux::get_peer_cred;
 ))]
 pub(crate) use self::impl_macos::get_peer_cred;
 
+#[cfg(any(target_os = "solaris", target_os = "illumos"))]
+pub(crate) use self::impl_solaris::get_peer_cred;
+
 #[cfg(any(target_os = "linux", target_os = "android"))]
 pub(crate) mod impl_linux {
     use crate::uds::UnixStream;
     use libc::{c_void, getsockopt, socklen_t, SOL_SOCKET, SO_PEERCRED};
     use std::os::unix::io::AsRawFd;
-    use std::{io, mem};
+    use std::{fs, io, mem};
 
     use libc::ucred;
 
@@ -60,12 +138,35 @@ pub(crate) mod impl_linux {
                 Ok(super::UCred {
                     uid: ucred.uid,
                     gid: ucred.gid,
+                    pid: Some(ucred.pid),
                 })
             } else {
                 Err(io::Error::last_os_error())
             }
         }
     }
+
+    pub(crate) fn get_process_cred(pid: u32) -> io::Result<super::UCred> {
+        let status = fs::read_to_string(format!("/proc/{}/status", pid))?;
+
+        // The lines list the real, effective, saved and file system IDs.
+        let effective = |key: &str| {
+            status
+                .lines()
+                .find(|line| line.starts_with(key))
+                .and_then(|line| line[key.len()..].split_whitespace().nth(1))
+                .and_then(|id| id.parse().ok())
+                .ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, "malformed /proc/<pid>/status")
+                })
+        };
+
+        Ok(super::UCred {
+            uid: effective("Uid:")?,
+            gid: effective("Gid:")?,
+            pid: Some(pid as libc::pid_t),
+        })
+    }
 }
 
 #[cfg(any(
@@ -86,14 +187,134 @@ pub(crate) mod impl_macos {
         unsafe {
             let raw_fd = sock.as_raw_fd();
 
-            let mut cred = mem::MaybeUninit::<super::UCred>::uninit();
-            let ret = {
-                let cred_mut = cred.as_mut_ptr();
-                getpeereid(raw_fd, &mut (*cred_mut).uid, &mut (*cred_mut).gid)
-            };
+            let mut uid = mem::MaybeUninit::uninit();
+            let mut gid = mem::MaybeUninit::uninit();
+            let ret = getpeereid(raw_fd, uid.as_mut_ptr(), gid.as_mut_ptr());
 
             if ret == 0 {
-                Ok(cred.assume_init())
+                Ok(super::UCred {
+                    uid: uid.assume_init(),
+                    gid: gid.assume_init(),
+                    pid: None,
+                })
+            } else {
+                Err(io::Error::last_os_error())
+            }
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    pub(crate) fn get_process_cred(pid: u32) -> io::Result<super::UCred> {
+        unsafe {
+            let mut info: libc::proc_bsdinfo = mem::zeroed();
+            let size = mem::size_of::<libc::proc_bsdinfo>() as libc::c_int;
+            let ret = libc::proc_pidinfo(
+                pid as libc::c_int,
+                libc::PROC_PIDTBSDINFO,
+                0,
+                &mut info as *mut libc::proc_bsdinfo as *mut libc::c_void,
+                size,
+            );
+
+            if ret == size {
+                Ok(super::UCred {
+                    uid: info.pbi_uid,
+                    gid: info.pbi_gid,
+                    pid: Some(pid as libc::pid_t),
+                })
+            } else {
+                let err = io::Error::last_os_error();
+                if err.raw_os_error() == Some(libc::ESRCH) {
+                    Err(io::Error::new(io::ErrorKind::NotFound, "no such process"))
+                } else {
+                    Err(err)
+                }
+            }
+        }
+    }
+
+    #[cfg(any(target_os = "freebsd", target_os = "openbsd"))]
+    pub(crate) fn get_process_cred(pid: u32) -> io::Result<super::UCred> {
+        unsafe {
+            let mut info: libc::kinfo_proc = mem::zeroed();
+            let mut size = mem::size_of::<libc::kinfo_proc>();
+            #[cfg(target_os = "freebsd")]
+            let mib = [
+                libc::CTL_KERN,
+                libc::KERN_PROC,
+                libc::KERN_PROC_PID,
+                pid as libc::c_int,
+            ];
+            // OpenBSD also takes the size and number of entries to return.
+            #[cfg(target_os = "openbsd")]
+            let mib = [
+                libc::CTL_KERN,
+                libc::KERN_PROC,
+                libc::KERN_PROC_PID,
+                pid as libc::c_int,
+                size as libc::c_int,
+                1,
+            ];
+            let ret = libc::sysctl(
+                mib.as_ptr(),
+                mib.len() as libc::c_uint,
+                &mut info as *mut libc::kinfo_proc as *mut libc::c_void,
+                &mut size,
+                std::ptr::null_mut(),
+                0,
+            );
+
+            // FreeBSD fails with ESRCH for a missing process, while OpenBSD
+            // returns no entry.
+            if ret != 0 {
+                let err = io::Error::last_os_error();
+                if err.raw_os_error() != Some(libc::ESRCH) {
+                    return Err(err);
+                }
+            }
+            if ret != 0 || size == 0 {
+                return Err(io::Error::new(io::ErrorKind::NotFound, "no such process"));
+            }
+
+            // The first group is the effective one.
+            #[cfg(target_os = "freebsd")]
+            let (uid, gid) = (info.ki_uid, info.ki_groups[0]);
+            #[cfg(target_os = "openbsd")]
+            let (uid, gid) = (info.p_uid, info.p_gid);
+            Ok(super::UCred {
+                uid,
+                gid,
+                pid: Some(pid as libc::pid_t),
+            })
+        }
+    }
+}
+
+#[cfg(any(target_os = "solaris", target_os = "illumos"))]
+pub(crate) mod impl_solaris {
+    use crate::uds::UnixStream;
+    use std::os::unix::io::AsRawFd;
+    use std::{io, ptr};
+
+    pub(crate) fn get_peer_cred(sock: &UnixStream) -> io::Result<super::UCred> {
+        unsafe {
+            let raw_fd = sock.as_raw_fd();
+
+            let mut cred = ptr::null_mut();
+            let ret = libc::getpeerucred(raw_fd, &mut cred);
+
+            if ret == 0 {
+                let uid = libc::ucred_geteuid(cred);
+                let gid = libc::ucred_getegid(cred);
+                let pid = libc::ucred_getpid(cred);
+
+                libc::ucred_free(cred);
+
+                Ok(super::UCred {
+                    uid,
+                    gid,
+                    pid: Some(pid),
+                })
             } else {
                 Err(io::Error::last_os_error())
             }
@@ -105,7 +326,7 @@ pub(crate) mod impl_macos {
 #[cfg(not(target_os = "dragonfly"))]
 #[cfg(test)]
 mod test {
-    use crate::uds::UnixStream;
+    use crate::uds::{UCred, UnixStream};
     use libc::getegid;
     use libc::geteuid;
 
@@ -129,5 +350,73 @@ mod test {
 
         assert_eq!(cred_a.uid, uid);
         assert_eq!(cred_a.gid, gid);
+
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "solaris",
+            target_os = "illumos"
+        ))]
+        assert_eq!(cred_a.pid, Some(std::process::id() as libc::pid_t));
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "solaris",
+            target_os = "illumos"
+        )))]
+        assert_eq!(cred_a.pid, None);
+    }
+
+    #[test]
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "freebsd",
+        target_os = "openbsd"
+    ))]
+    fn test_from_process() {
+        let cred = UCred::from_process(std::process::id()).unwrap();
+        assert_eq!(cred, UCred::current());
+
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            let (a, _b) = UnixStream::pair().unwrap();
+            assert_eq!(a.peer_cred().unwrap(), cred);
+        }
+
+        // PIDs are below 2^22 on Linux and 99999 on macOS and the BSDs
+        let err = UCred::from_process(i32::MAX as u32).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_display() {
+        let mut cred = UCred {
+            uid: 1000,
+            gid: 100,
+            pid: None,
+        };
+        assert_eq!(cred.to_string(), "uid=1000 gid=100");
+        cred.pid = Some(4242);
+        assert_eq!(cred.to_string(), "uid=1000 gid=100 pid=4242");
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serde() {
+        let cred = UCred {
+            uid: 1000,
+            gid: 100,
+            pid: Some(4242),
+        };
+        let json = serde_json::to_string(&cred).unwrap();
+        assert_eq!(json, r#"{"uid":1000,"gid":100,"pid":4242}"#);
+        assert_eq!(serde_json::from_str::<UCred>(&json).unwrap(), cred);
+
+        let cred = UCred { pid: None, ..cred };
+        let json = serde_json::to_string(&cred).unwrap();
+        assert_eq!(json, r#"{"uid":1000,"gid":100,"pid":null}"#);
+        assert_eq!(serde_json::from_str::<UCred>(&json).unwrap(), cred);
     }
 }
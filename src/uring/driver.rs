@@ -0,0 +1,283 @@
+use std::any::Any;
+use std::fmt;
+use std::io;
+use std::mem;
+use std::os::unix::io::RawFd;
+use std::sync::Arc;
+use std::task::{Context, Waker};
+use std::thread;
+
+use futures::Poll;
+use io_uring::{opcode, squeue, IoUring};
+use lazy_static::lazy_static;
+use log::debug;
+use parking_lot::Mutex;
+use slab::Slab;
+
+use crate::socket::cvt;
+
+const ENTRIES: u32 = 256;
+
+/// The user data of cancellations, whose completions are skipped.
+const CANCEL: u64 = u64::MAX;
+
+lazy_static! {
+    static ref DRIVER: io::Result<Driver> = Driver::new();
+}
+
+/// The ring shared by all `io_uring` backed sockets.
+///
+/// Operations are submitted by the tasks that start them. A background thread
+/// waits on an eventfd which the kernel signals whenever it posts a
+/// completion, and hands the results to the operations, waking their tasks.
+///
+/// Like the default reactor, the ring is global rather than scoped to a task
+/// or executor: romio's futures run on whatever executor polls them, so there
+/// is no runtime that could own a ring and drive it while idle.
+pub(super) struct Driver {
+    ring: Arc<Mutex<Ring>>,
+}
+
+struct Ring {
+    uring: IoUring,
+    ops: Slab<Lifecycle>,
+}
+
+enum Lifecycle {
+    Submitted,
+    Waiting(Waker),
+    Completed(io::Result<u32>),
+    /// The `Op` was dropped before completion, leaving behind the resources
+    /// the kernel may still access.
+    Ignored(#[allow(dead_code)] Box<dyn Any + Send>),
+}
+
+/// An operation submitted to the ring, which owns the resources the kernel
+/// accesses until it completes.
+pub(super) struct Op<T: Send + 'static> {
+    driver: &'static Driver,
+    key: usize,
+    data: Option<T>,
+}
+
+impl Driver {
+    fn new() -> io::Result<Driver> {
+        let uring = IoUring::new(ENTRIES)?;
+        let eventfd = EventFd::new()?;
+        uring.submitter().register_eventfd(eventfd.0)?;
+
+        let ring = Arc::new(Mutex::new(Ring {
+            uring,
+            ops: Slab::new(),
+        }));
+        let ring2 = ring.clone();
+        thread::Builder::new()
+            .name("romio-uring".into())
+            .spawn(move || run(eventfd, &ring2))?;
+
+        Ok(Driver { ring })
+    }
+
+    /// Returns the global driver, starting it on first use.
+    fn get() -> io::Result<&'static Driver> {
+        match &*DRIVER {
+            Ok(driver) => Ok(driver),
+            Err(e) => Err(io::Error::new(e.kind(), e.to_string())),
+        }
+    }
+}
+
+fn run(eventfd: EventFd, ring: &Mutex<Ring>) {
+    debug!("starting io_uring driver");
+    loop {
+        match eventfd.read() {
+            Ok(_) => {}
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => {
+                debug!("io_uring driver failed: {}", e);
+                return;
+            }
+        }
+
+        let wakers = {
+            let mut ring = ring.lock();
+            let wakers = ring.complete();
+            // Entries whose submission failed, for example because the
+            // completion queue was full, are still queued.
+            ring.submit();
+            wakers
+        };
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
+
+impl Ring {
+    /// Pushes `entry` onto the submission queue and submits it.
+    ///
+    /// An error is only returned if `entry` couldn't be queued. Once it is
+    /// queued, failing to submit it is retried with the next submission.
+    ///
+    /// # Safety
+    ///
+    /// Everything `entry` points to must stay valid until it completes.
+    unsafe fn push(&mut self, entry: &squeue::Entry) -> io::Result<()> {
+        if self.uring.submission().push(entry).is_err() {
+            // Without `SQPOLL`, submitting makes room for the entry.
+            self.uring.submit()?;
+            self.uring.submission().push(entry).map_err(|_| {
+                io::Error::new(io::ErrorKind::Other, "io_uring submission queue is full")
+            })?;
+        }
+        self.submit();
+        Ok(())
+    }
+
+    /// Submits the queued entries, if any.
+    fn submit(&mut self) {
+        if self.uring.submission().is_empty() {
+            return;
+        }
+        if let Err(e) = self.uring.submit() {
+            debug!("failed to submit to io_uring: {}", e);
+        }
+    }
+
+    /// Drains the completion queue, returning the wakers of the operations
+    /// which completed.
+    fn complete(&mut self) -> Vec<Waker> {
+        let Ring { uring, ops } = self;
+        let mut wakers = Vec::new();
+        for cqe in uring.completion() {
+            if cqe.user_data() == CANCEL {
+                continue;
+            }
+            let key = cqe.user_data() as usize;
+            let res = match cqe.result() {
+                res if res < 0 => Err(io::Error::from_raw_os_error(-res)),
+                res => Ok(res as u32),
+            };
+            match mem::replace(&mut ops[key], Lifecycle::Completed(res)) {
+                Lifecycle::Submitted => {}
+                Lifecycle::Waiting(waker) => wakers.push(waker),
+                Lifecycle::Ignored(_) => {
+                    ops.remove(key);
+                }
+                Lifecycle::Completed(_) => unreachable!("operation completed twice"),
+            }
+        }
+        wakers
+    }
+}
+
+impl<T: Send + 'static> Op<T> {
+    /// Submits `entry`, which owns `data` until it completes.
+    ///
+    /// # Safety
+    ///
+    /// `entry` must only point into heap allocations owned by `data`, which
+    /// stay in place when `data` is moved.
+    pub(super) unsafe fn submit(entry: squeue::Entry, data: T) -> io::Result<Op<T>> {
+        let driver = Driver::get()?;
+        let mut ring = driver.ring.lock();
+        let key = ring.ops.insert(Lifecycle::Submitted);
+
+        if let Err(e) = ring.push(&entry.user_data(key as u64)) {
+            // The entry wasn't queued, so the kernel never sees `data`.
+            ring.ops.remove(key);
+            return Err(e);
+        }
+        Ok(Op {
+            driver,
+            key,
+            data: Some(data),
+        })
+    }
+
+    /// Polls the operation for completion, returning its result and the
+    /// resources it owned.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if it is called again after the operation has
+    /// completed.
+    pub(super) fn poll(&mut self, cx: &mut Context<'_>) -> Poll<(io::Result<u32>, T)> {
+        let mut ring = self.driver.ring.lock();
+        let lifecycle = &mut ring.ops[self.key];
+        match mem::replace(lifecycle, Lifecycle::Submitted) {
+            Lifecycle::Submitted | Lifecycle::Waiting(_) => {
+                *lifecycle = Lifecycle::Waiting(cx.waker().clone());
+                Poll::Pending
+            }
+            Lifecycle::Completed(res) => {
+                ring.ops.remove(self.key);
+                let data = self.data.take().expect("operation polled after completion");
+                Poll::Ready((res, data))
+            }
+            Lifecycle::Ignored(_) => unreachable!("polled a dropped operation"),
+        }
+    }
+}
+
+impl<T: Send + 'static> Drop for Op<T> {
+    fn drop(&mut self) {
+        let data = match self.data.take() {
+            Some(data) => data,
+            None => return,
+        };
+
+        let mut ring = self.driver.ring.lock();
+        match ring.ops[self.key] {
+            Lifecycle::Completed(_) => {
+                ring.ops.remove(self.key);
+            }
+            _ => {
+                ring.ops[self.key] = Lifecycle::Ignored(Box::new(data));
+                // Operations like accepts may never complete on their own.
+                let cancel = opcode::AsyncCancel::new(self.key as u64)
+                    .build()
+                    .user_data(CANCEL);
+                unsafe {
+                    let _ = ring.push(&cancel);
+                }
+            }
+        }
+    }
+}
+
+impl<T: Send + 'static> fmt::Debug for Op<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Op").field("key", &self.key).finish()
+    }
+}
+
+/// A blocking eventfd, which the ring signals on completions.
+struct EventFd(RawFd);
+
+impl EventFd {
+    fn new() -> io::Result<EventFd> {
+        let fd = unsafe { cvt(libc::eventfd(0, libc::EFD_CLOEXEC))? };
+        Ok(EventFd(fd))
+    }
+
+    fn read(&self) -> io::Result<u64> {
+        let mut count = 0u64;
+        unsafe {
+            cvt(libc::read(
+                self.0,
+                &mut count as *mut u64 as *mut libc::c_void,
+                mem::size_of::<u64>(),
+            ))?;
+        }
+        Ok(count)
+    }
+}
+
+impl Drop for EventFd {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.0);
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use std::fmt;
+use std::io;
+use std::mem;
+use std::net::{self, SocketAddr};
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::pin::Pin;
+use std::task::Context;
+
+use futures::stream::{FusedStream, Stream};
+use futures::{ready, Future, Poll};
+use io_uring::{opcode, types};
+
+use super::driver::Op;
+use super::TcpStream;
+use crate::socket::socket_addr;
+
+/// A TCP socket server backed by `io_uring`, listening for connections.
+///
+/// This is the `io_uring` counterpart of [`tcp::TcpListener`].
+///
+/// [`tcp::TcpListener`]: ../tcp/struct.TcpListener.html
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// #![feature(async_await)]
+/// use futures::prelude::*;
+/// use romio::uring::TcpListener;
+///
+/// # async fn run() -> Result<(), Box<dyn std::error::Error + 'static>> {
+/// let mut listener = TcpListener::bind(&"127.0.0.1:80".parse()?)?;
+/// let mut incoming = listener.incoming();
+///
+/// while let Some(stream) = incoming.next().await {
+///     let (mut stream, _) = stream?;
+///     stream.write_all(b"Shall I hear more, or shall I speak at this?").await?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct TcpListener {
+    io: net::TcpListener,
+    accept: Option<Op<Box<Peer>>>,
+}
+
+/// The address of an accepted connection, written by the kernel.
+struct Peer {
+    addr: libc::sockaddr_storage,
+    len: libc::socklen_t,
+}
+
+impl TcpListener {
+    /// Creates a new `TcpListener` which will be bound to the specified
+    /// address.
+    ///
+    /// Like [`tcp::TcpListener::bind`], the listener is created with a backlog
+    /// of 1024 pending connections.
+    ///
+    /// [`tcp::TcpListener::bind`]: ../tcp/struct.TcpListener.html#method.bind
+    pub fn bind(addr: &SocketAddr) -> io::Result<TcpListener> {
+        let builder = match addr {
+            SocketAddr::V4(..) => net2::TcpBuilder::new_v4()?,
+            SocketAddr::V6(..) => net2::TcpBuilder::new_v6()?,
+        };
+        builder.reuse_address(true)?;
+        let listener = builder.bind(addr)?.listen(1024)?;
+        TcpListener::from_std(listener)
+    }
+
+    /// Creates a new `TcpListener` from a bound and listening
+    /// `std::net::TcpListener`.
+    pub fn from_std(listener: net::TcpListener) -> io::Result<TcpListener> {
+        Ok(TcpListener {
+            io: listener,
+            accept: None,
+        })
+    }
+
+    /// Returns the local address that this listener is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.io.local_addr()
+    }
+
+    /// Accepts a new incoming connection to this listener.
+    ///
+    /// The returned future resolves to the stream and the address of the
+    /// peer.
+    pub fn accept(&mut self) -> Accept<'_> {
+        Accept { listener: self }
+    }
+
+    /// Attempts to accept a connection, returning the stream and the address
+    /// of the peer.
+    pub fn poll_accept(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<(TcpStream, SocketAddr)>> {
+        let this = self.get_mut();
+
+        if this.accept.is_none() {
+            let mut peer = Box::new(Peer {
+                addr: unsafe { mem::zeroed() },
+                len: mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t,
+            });
+            let entry = opcode::Accept::new(
+                types::Fd(this.io.as_raw_fd()),
+                &mut peer.addr as *mut _ as *mut libc::sockaddr,
+                &mut peer.len,
+            )
+            .flags(libc::SOCK_CLOEXEC)
+            .build();
+            this.accept = Some(unsafe { Op::submit(entry, peer)? });
+        }
+
+        let (res, peer) = ready!(this.accept.as_mut().unwrap().poll(cx));
+        this.accept = None;
+
+        let stream = unsafe { net::TcpStream::from_raw_fd(res? as i32) };
+        let addr = socket_addr(&peer.addr).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                "accepted a connection of unknown family",
+            )
+        })?;
+        Poll::Ready(Ok((TcpStream::from_std(stream)?, addr)))
+    }
+
+    /// Returns a stream over the connections being received on this listener.
+    ///
+    /// The stream never returns `None`.
+    pub fn incoming(&mut self) -> Incoming<'_> {
+        Incoming { inner: self }
+    }
+
+    /// Gets the value of the `IP_TTL` option for this socket.
+    ///
+    /// For more information about this option, see [`set_ttl`].
+    ///
+    /// [`set_ttl`]: #method.set_ttl
+    pub fn ttl(&self) -> io::Result<u32> {
+        self.io.ttl()
+    }
+
+    /// Sets the value for the `IP_TTL` option on this socket.
+    ///
+    /// This value sets the time-to-live field that is used in every packet sent
+    /// from this socket.
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        self.io.set_ttl(ttl)
+    }
+}
+
+impl AsRawFd for TcpListener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.io.as_raw_fd()
+    }
+}
+
+impl fmt::Debug for TcpListener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.io.fmt(f)
+    }
+}
+
+/// The future returned by `TcpListener::accept`.
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct Accept<'a> {
+    listener: &'a mut TcpListener,
+}
+
+impl<'a> Future for Accept<'a> {
+    type Output = io::Result<(TcpStream, SocketAddr)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut *self.listener).poll_accept(cx)
+    }
+}
+
+/// A stream of the connections accepted by a `TcpListener`.
+///
+/// This stream is returned by [`TcpListener::incoming`]. Each item is an
+/// accepted socket along with the address of its peer, like
+/// [`TcpListener::poll_accept`] returns.
+///
+/// [`TcpListener::incoming`]: struct.TcpListener.html#method.incoming
+/// [`TcpListener::poll_accept`]: struct.TcpListener.html#method.poll_accept
+#[must_use = "streams do nothing unless polled"]
+#[derive(Debug)]
+pub struct Incoming<'a> {
+    inner: &'a mut TcpListener,
+}
+
+impl<'a> Incoming<'a> {
+    /// Returns a reference to the listener this stream accepts sockets from.
+    pub fn listener(&self) -> &TcpListener {
+        self.inner
+    }
+}
+
+impl<'a> Stream for Incoming<'a> {
+    type Item = io::Result<(TcpStream, SocketAddr)>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let accepted = ready!(Pin::new(&mut *self.inner).poll_accept(cx)?);
+        Poll::Ready(Some(Ok(accepted)))
+    }
+}
+
+impl<'a> FusedStream for Incoming<'a> {
+    /// A listener keeps accepting connections until it is dropped.
+    fn is_terminated(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,57 @@
+//! Sockets backed by `io_uring`.
+//!
+//! The types in this module mirror [`TcpListener`], [`TcpStream`] and
+//! [`UdpSocket`] from the rest of the crate, but rather than waiting for
+//! readiness through the reactor and then making a system call, they submit
+//! their operations to an `io_uring` submission queue and are woken once the
+//! kernel has completed them. At high I/O rates this saves most of the system
+//! calls of the readiness-based sockets.
+//!
+//! The constructors, futures, `poll_*` methods and common socket options have
+//! the same signatures as their readiness-based counterparts, so switching
+//! backends is a matter of changing imports, for example behind a feature flag
+//! of the application:
+//!
+//! ```rust
+//! #[cfg(feature = "io-uring")]
+//! use romio::uring::{TcpListener, TcpStream};
+//! #[cfg(not(feature = "io-uring"))]
+//! use romio::tcp::{TcpListener, TcpStream};
+//! ```
+//!
+//! Only that common surface is mirrored. Methods built on readiness, such as
+//! `readable`, `try_read` or `peek`, have no counterpart here, and neither do
+//! the Linux-specific extensions of the readiness-based sockets, such as
+//! out-of-band data, zerocopy sends, `sendfile`, or most `setsockopt`
+//! wrappers. They can be reached through the raw file descriptor if needed.
+//!
+//! # Buffers
+//!
+//! The kernel accesses the buffers of an operation until it completes, which
+//! may be after the future or socket that started it is dropped. Operations
+//! therefore use buffers owned by the socket, which are copied from and into
+//! the caller's buffers. After a `poll_*` method returned `Poll::Pending`, it
+//! has to be called again with the same data, as is the case for any
+//! `AsyncWrite`: a write that was already submitted goes out as it was.
+//!
+//! # Platform support
+//!
+//! This module requires the `io-uring` feature and is only available on
+//! Linux, version 5.6 or later. All sockets share a single ring, which is
+//! created on first use and driven by a background thread.
+//!
+//! [`TcpListener`]: ../tcp/struct.TcpListener.html
+//! [`TcpStream`]: ../tcp/struct.TcpStream.html
+//! [`UdpSocket`]: ../udp/struct.UdpSocket.html
+
+mod driver;
+mod listener;
+mod stream;
+mod udp;
+
+pub use self::listener::{Accept, Incoming, TcpListener};
+pub use self::stream::{ConnectFuture, TcpStream};
+pub use self::udp::{Recv, RecvFrom, Send, SendTo, UdpSocket};
+
+/// The largest buffer an operation copies data through.
+const MAX_BUF: usize = 64 * 1024;
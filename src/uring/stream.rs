@@ -0,0 +1,378 @@
+use std::fmt;
+use std::io;
+use std::mem;
+use std::net::{self, Shutdown, SocketAddr};
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::pin::Pin;
+use std::task::Context;
+use std::time::Duration;
+
+use futures::io::{AsyncRead, AsyncWrite};
+use futures::{ready, Future, Poll};
+use io_uring::{opcode, types};
+use net2::TcpStreamExt;
+
+use super::driver::Op;
+use super::MAX_BUF;
+use crate::socket::sockaddr;
+
+/// A TCP stream backed by `io_uring`, between a local and a remote socket.
+///
+/// This is the `io_uring` counterpart of [`tcp::TcpStream`]. It can be read
+/// or written to using the `AsyncRead`, `AsyncWrite`, and related extension
+/// traits in `futures::io`.
+///
+/// [`tcp::TcpStream`]: ../tcp/struct.TcpStream.html
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// #![feature(async_await)]
+/// use futures::prelude::*;
+/// use romio::uring::TcpStream;
+///
+/// # async fn run() -> Result<(), Box<dyn std::error::Error + 'static>> {
+/// let mut stream = TcpStream::connect(&"127.0.0.1:7878".parse()?).await?;
+/// stream.write_all(b"I am as constant as the northern star").await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct TcpStream {
+    io: net::TcpStream,
+    read: Option<Op<Vec<u8>>>,
+    /// Data which was received but didn't fit into the caller's buffer.
+    rd: Vec<u8>,
+    rd_pos: usize,
+    write: Option<Op<Vec<u8>>>,
+}
+
+/// The future returned by `TcpStream::connect`, which will resolve to a
+/// `TcpStream` when the stream is connected.
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct ConnectFuture {
+    inner: ConnectFutureState,
+}
+
+#[derive(Debug)]
+enum ConnectFutureState {
+    Waiting(net::TcpStream, Op<Box<libc::sockaddr_storage>>),
+    Error(io::Error),
+    Empty,
+}
+
+impl TcpStream {
+    /// Create a new TCP stream connected to the specified address.
+    ///
+    /// This function will create a new TCP socket and attempt to connect it to
+    /// the `addr` provided. The returned future will be resolved once the
+    /// stream has successfully connected, or it will return an error if one
+    /// occurs.
+    pub fn connect(addr: &SocketAddr) -> ConnectFuture {
+        let inner = match TcpStream::connect_priv(addr) {
+            Ok((stream, op)) => ConnectFutureState::Waiting(stream, op),
+            Err(e) => ConnectFutureState::Error(e),
+        };
+        ConnectFuture { inner }
+    }
+
+    fn connect_priv(
+        addr: &SocketAddr,
+    ) -> io::Result<(net::TcpStream, Op<Box<libc::sockaddr_storage>>)> {
+        let builder = match addr {
+            SocketAddr::V4(..) => net2::TcpBuilder::new_v4()?,
+            SocketAddr::V6(..) => net2::TcpBuilder::new_v6()?,
+        };
+        let stream = builder.to_tcp_stream()?;
+
+        let (storage, len) = sockaddr(addr);
+        let storage = Box::new(storage);
+        let entry = opcode::Connect::new(
+            types::Fd(stream.as_raw_fd()),
+            &*storage as *const _ as *const libc::sockaddr,
+            len,
+        )
+        .build();
+        let op = unsafe { Op::submit(entry, storage)? };
+        Ok((stream, op))
+    }
+
+    /// Creates a new `TcpStream` from a connected `std::net::TcpStream`.
+    ///
+    /// The stream may be in blocking or non-blocking mode.
+    pub fn from_std(stream: net::TcpStream) -> io::Result<TcpStream> {
+        Ok(TcpStream {
+            io: stream,
+            read: None,
+            rd: Vec::new(),
+            rd_pos: 0,
+            write: None,
+        })
+    }
+
+    /// Returns the local address that this stream is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.io.local_addr()
+    }
+
+    /// Returns the remote address that this stream is connected to.
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.io.peer_addr()
+    }
+
+    /// Shuts down the read, write, or both halves of this connection.
+    ///
+    /// This function will cause all pending and future I/O on the specified
+    /// portions to return immediately with an appropriate value (see the
+    /// documentation of `Shutdown`).
+    pub fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        self.io.shutdown(how)
+    }
+
+    /// Gets the value of the `TCP_NODELAY` option on this socket.
+    ///
+    /// For more information about this option, see [`set_nodelay`].
+    ///
+    /// [`set_nodelay`]: #method.set_nodelay
+    pub fn nodelay(&self) -> io::Result<bool> {
+        self.io.nodelay()
+    }
+
+    /// Sets the value of the `TCP_NODELAY` option on this socket.
+    ///
+    /// If set, this option disables the Nagle algorithm. This means that
+    /// segments are always sent as soon as possible, even if there is only a
+    /// small amount of data. When not set, data is buffered until there is a
+    /// sufficient amount to send out, thereby avoiding the frequent sending of
+    /// small packets.
+    pub fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
+        self.io.set_nodelay(nodelay)
+    }
+
+    /// Gets the value of the `SO_RCVBUF` option on this socket.
+    ///
+    /// For more information about this option, see [`set_recv_buffer_size`].
+    ///
+    /// [`set_recv_buffer_size`]: #method.set_recv_buffer_size
+    pub fn recv_buffer_size(&self) -> io::Result<usize> {
+        self.io.recv_buffer_size()
+    }
+
+    /// Sets the value of the `SO_RCVBUF` option on this socket.
+    ///
+    /// Changes the size of the operating system's receive buffer associated
+    /// with the socket.
+    pub fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.io.set_recv_buffer_size(size)
+    }
+
+    /// Gets the value of the `SO_SNDBUF` option on this socket.
+    ///
+    /// For more information about this option, see [`set_send_buffer_size`].
+    ///
+    /// [`set_send_buffer_size`]: #method.set_send_buffer_size
+    pub fn send_buffer_size(&self) -> io::Result<usize> {
+        self.io.send_buffer_size()
+    }
+
+    /// Sets the value of the `SO_SNDBUF` option on this socket.
+    ///
+    /// Changes the size of the operating system's send buffer associated with
+    /// the socket.
+    pub fn set_send_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.io.set_send_buffer_size(size)
+    }
+
+    /// Returns whether keepalive messages are enabled on this socket, and if
+    /// so the duration of time between them.
+    ///
+    /// For more information about this option, see [`set_keepalive`].
+    ///
+    /// [`set_keepalive`]: #method.set_keepalive
+    pub fn keepalive(&self) -> io::Result<Option<Duration>> {
+        TcpStreamExt::keepalive(&self.io)
+    }
+
+    /// Sets whether keepalive messages are enabled to be sent on this socket.
+    ///
+    /// If `None` is specified then keepalive messages are disabled, otherwise
+    /// the duration specified will be the time to remain idle before sending a
+    /// TCP keepalive probe.
+    pub fn set_keepalive(&self, keepalive: Option<Duration>) -> io::Result<()> {
+        TcpStreamExt::set_keepalive(&self.io, keepalive)
+    }
+
+    /// Sets the `FD_CLOEXEC` flag on the stream's file descriptor, so that it
+    /// is closed when the process executes another program.
+    pub fn set_cloexec(&self) -> io::Result<()> {
+        crate::socket::set_cloexec(self.as_raw_fd(), true)
+    }
+
+    /// Clears the `FD_CLOEXEC` flag on the stream's file descriptor, so that
+    /// it is inherited by programs the process executes.
+    pub fn clear_cloexec(&self) -> io::Result<()> {
+        crate::socket::set_cloexec(self.as_raw_fd(), false)
+    }
+
+    /// Gets the value of the `IP_TTL` option for this socket.
+    ///
+    /// For more information about this option, see [`set_ttl`].
+    ///
+    /// [`set_ttl`]: #method.set_ttl
+    pub fn ttl(&self) -> io::Result<u32> {
+        self.io.ttl()
+    }
+
+    /// Sets the value for the `IP_TTL` option on this socket.
+    ///
+    /// This value sets the time-to-live field that is used in every packet sent
+    /// from this socket.
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        self.io.set_ttl(ttl)
+    }
+
+    /// Reads the linger duration for this socket by getting the `SO_LINGER`
+    /// option.
+    ///
+    /// For more information about this option, see [`set_linger`].
+    ///
+    /// [`set_linger`]: #method.set_linger
+    pub fn linger(&self) -> io::Result<Option<Duration>> {
+        TcpStreamExt::linger(&self.io)
+    }
+
+    /// Sets the linger duration of this socket by setting the `SO_LINGER`
+    /// option.
+    ///
+    /// This option controls the action taken when a stream has unsent messages
+    /// and the stream is closed. If `SO_LINGER` is set, the system shall block
+    /// the process until it can transmit the data or until the time expires.
+    ///
+    /// If `SO_LINGER` is not specified, and the stream is closed, the system
+    /// handles the call in a way that allows the process to continue as
+    /// quickly as possible.
+    pub fn set_linger(&self, dur: Option<Duration>) -> io::Result<()> {
+        TcpStreamExt::set_linger(&self.io, dur)
+    }
+
+    /// Gets the value of the `SO_ERROR` option on this socket.
+    ///
+    /// This will retrieve the stored error in the underlying socket, clearing
+    /// the field in the process. This can be useful for checking errors
+    /// between calls.
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        self.io.take_error()
+    }
+}
+
+impl AsyncRead for TcpStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        if this.rd_pos == this.rd.len() {
+            if this.read.is_none() {
+                let mut data = mem::take(&mut this.rd);
+                this.rd_pos = 0;
+                data.resize(buf.len().min(MAX_BUF), 0);
+                let entry = opcode::Recv::new(
+                    types::Fd(this.io.as_raw_fd()),
+                    data.as_mut_ptr(),
+                    data.len() as u32,
+                )
+                .build();
+                this.read = Some(unsafe { Op::submit(entry, data)? });
+            }
+
+            let (res, mut data) = ready!(this.read.as_mut().unwrap().poll(cx));
+            this.read = None;
+            data.truncate(res.as_ref().map_or(0, |&n| n as usize));
+            this.rd = data;
+            this.rd_pos = 0;
+            res?;
+        }
+
+        let rem = &this.rd[this.rd_pos..];
+        let n = rem.len().min(buf.len());
+        buf[..n].copy_from_slice(&rem[..n]);
+        this.rd_pos += n;
+        Poll::Ready(Ok(n))
+    }
+}
+
+impl AsyncWrite for TcpStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        if this.write.is_none() {
+            let data = buf[..buf.len().min(MAX_BUF)].to_vec();
+            let entry = opcode::Send::new(
+                types::Fd(this.io.as_raw_fd()),
+                data.as_ptr(),
+                data.len() as u32,
+            )
+            .flags(libc::MSG_NOSIGNAL)
+            .build();
+            this.write = Some(unsafe { Op::submit(entry, data)? });
+        }
+
+        let (res, _) = ready!(this.write.as_mut().unwrap().poll(cx));
+        this.write = None;
+        Poll::Ready(res.map(|n| n as usize))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        // Writes go straight to the kernel, which takes care of buffering.
+        Poll::Ready(Ok(()))
+    }
+
+    /// Shuts down the writing portion of the stream, which signals the end of
+    /// the data to the peer. The stream can still be read from.
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(self.shutdown(Shutdown::Write))
+    }
+}
+
+impl AsRawFd for TcpStream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.io.as_raw_fd()
+    }
+}
+
+impl fmt::Debug for TcpStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.io.fmt(f)
+    }
+}
+
+impl Future for ConnectFuture {
+    type Output = io::Result<TcpStream>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<TcpStream>> {
+        match mem::replace(&mut self.inner, ConnectFutureState::Empty) {
+            ConnectFutureState::Waiting(stream, mut op) => {
+                if let Poll::Ready((res, _)) = op.poll(cx) {
+                    res?;
+                    return Poll::Ready(TcpStream::from_std(stream));
+                }
+                self.inner = ConnectFutureState::Waiting(stream, op);
+                Poll::Pending
+            }
+            ConnectFutureState::Error(e) => Poll::Ready(Err(e)),
+            ConnectFutureState::Empty => panic!("can't poll TCP stream twice"),
+        }
+    }
+}
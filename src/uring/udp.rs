@@ -0,0 +1,384 @@
+use std::fmt;
+use std::io;
+use std::mem;
+use std::net::{self, SocketAddr};
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::pin::Pin;
+use std::task::Context;
+
+use async_datagram::AsyncDatagram;
+use futures::{ready, Future, Poll};
+use io_uring::{opcode, types};
+
+use super::driver::Op;
+use super::MAX_BUF;
+use crate::socket::{sockaddr, socket_addr};
+
+/// A UDP socket backed by `io_uring`.
+///
+/// This is the `io_uring` counterpart of [`udp::UdpSocket`].
+///
+/// [`udp::UdpSocket`]: ../udp/struct.UdpSocket.html
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// #![feature(async_await)]
+/// use romio::uring::UdpSocket;
+///
+/// # async fn run() -> Result<(), Box<dyn std::error::Error + 'static>> {
+/// let mut socket = UdpSocket::bind(&"127.0.0.1:7878".parse()?)?;
+/// let mut buf = vec![0; 1024];
+///
+/// loop {
+///     let (n, peer) = socket.recv_from(&mut buf).await?;
+///     socket.send_to(&buf[..n], &peer).await?;
+/// }
+/// # }
+/// ```
+pub struct UdpSocket {
+    io: net::UdpSocket,
+    recv: Option<Op<Box<Msg>>>,
+    send: Option<Op<Box<Msg>>>,
+}
+
+/// The message header of a `sendmsg` or `recvmsg`, with everything it points
+/// to.
+struct Msg {
+    hdr: libc::msghdr,
+    iov: libc::iovec,
+    addr: libc::sockaddr_storage,
+    buf: Vec<u8>,
+}
+
+// The pointers in `hdr` and `iov` only point into the `Msg` itself.
+unsafe impl std::marker::Send for Msg {}
+
+impl Msg {
+    fn new(buf: Vec<u8>) -> Box<Msg> {
+        let mut msg = Box::new(Msg {
+            hdr: unsafe { mem::zeroed() },
+            iov: libc::iovec {
+                iov_base: std::ptr::null_mut(),
+                iov_len: buf.len(),
+            },
+            addr: unsafe { mem::zeroed() },
+            buf,
+        });
+        msg.iov.iov_base = msg.buf.as_mut_ptr() as *mut libc::c_void;
+        msg.hdr.msg_iov = &mut msg.iov;
+        msg.hdr.msg_iovlen = 1;
+        msg
+    }
+
+    fn set_addr(&mut self, addr: Option<&SocketAddr>) {
+        match addr {
+            Some(addr) => {
+                let (storage, len) = sockaddr(addr);
+                self.addr = storage;
+                self.hdr.msg_namelen = len;
+            }
+            None => self.hdr.msg_namelen = mem::size_of_val(&self.addr) as libc::socklen_t,
+        }
+        self.hdr.msg_name = &mut self.addr as *mut _ as *mut libc::c_void;
+    }
+}
+
+impl UdpSocket {
+    /// Creates a UDP socket from the given address.
+    pub fn bind(addr: &SocketAddr) -> io::Result<UdpSocket> {
+        UdpSocket::from_std(net::UdpSocket::bind(addr)?)
+    }
+
+    /// Creates a new `UdpSocket` from a bound `std::net::UdpSocket`.
+    ///
+    /// The socket may be in blocking or non-blocking mode.
+    pub fn from_std(socket: net::UdpSocket) -> io::Result<UdpSocket> {
+        Ok(UdpSocket {
+            io: socket,
+            recv: None,
+            send: None,
+        })
+    }
+
+    /// Returns the local address that this socket is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.io.local_addr()
+    }
+
+    /// Returns the address of the peer this socket is connected to.
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.io.peer_addr()
+    }
+
+    /// Connects the socket to a remote address, which [`send`] sends to and
+    /// [`recv`] receives from.
+    ///
+    /// [`send`]: #method.send
+    /// [`recv`]: #method.recv
+    pub fn connect(&self, addr: &SocketAddr) -> io::Result<()> {
+        self.io.connect(addr)
+    }
+
+    /// Gets the value of the `SO_BROADCAST` option for this socket.
+    ///
+    /// For more information about this option, see [`set_broadcast`].
+    ///
+    /// [`set_broadcast`]: #method.set_broadcast
+    pub fn broadcast(&self) -> io::Result<bool> {
+        self.io.broadcast()
+    }
+
+    /// Sets the value of the `SO_BROADCAST` option for this socket.
+    ///
+    /// When enabled, this socket is allowed to send packets to a broadcast
+    /// address.
+    pub fn set_broadcast(&self, on: bool) -> io::Result<()> {
+        self.io.set_broadcast(on)
+    }
+
+    /// Gets the value of the `IP_TTL` option for this socket.
+    ///
+    /// For more information about this option, see [`set_ttl`].
+    ///
+    /// [`set_ttl`]: #method.set_ttl
+    pub fn ttl(&self) -> io::Result<u32> {
+        self.io.ttl()
+    }
+
+    /// Sets the value for the `IP_TTL` option on this socket.
+    ///
+    /// This value sets the time-to-live field that is used in every packet sent
+    /// from this socket.
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        self.io.set_ttl(ttl)
+    }
+
+    /// Gets the value of the `SO_ERROR` option on this socket.
+    ///
+    /// This will retrieve the stored error in the underlying socket, clearing
+    /// the field in the process. This can be useful for checking errors
+    /// between calls.
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        self.io.take_error()
+    }
+
+    /// Sends data on the socket to the given address. On success, returns the
+    /// number of bytes written.
+    pub fn send_to<'a, 'b>(&'a mut self, buf: &'b [u8], target: &'b SocketAddr) -> SendTo<'a, 'b> {
+        SendTo {
+            buf,
+            target,
+            socket: self,
+        }
+    }
+
+    /// Receives data from the socket. On success, returns the number of bytes
+    /// read and the address from whence the data came.
+    pub fn recv_from<'a, 'b>(&'a mut self, buf: &'b mut [u8]) -> RecvFrom<'a, 'b> {
+        RecvFrom { buf, socket: self }
+    }
+
+    /// Sends data on the socket to the peer it is connected to. On success,
+    /// returns the number of bytes written.
+    ///
+    /// The socket must first be connected with [`connect`].
+    ///
+    /// [`connect`]: #method.connect
+    pub fn send<'a>(&'a mut self, buf: &'a [u8]) -> Send<'a> {
+        Send { buf, socket: self }
+    }
+
+    /// Receives data from the peer the socket is connected to. On success,
+    /// returns the number of bytes read.
+    ///
+    /// The socket must first be connected with [`connect`].
+    ///
+    /// [`connect`]: #method.connect
+    pub fn recv<'a>(&'a mut self, buf: &'a mut [u8]) -> Recv<'a> {
+        Recv { buf, socket: self }
+    }
+
+    /// Attempts to send data on the socket to the peer it is connected to.
+    ///
+    /// For more information, see [`send`].
+    ///
+    /// [`send`]: #method.send
+    pub fn poll_send(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().poll_send_priv(cx, buf, None)
+    }
+
+    /// Attempts to receive data from the peer the socket is connected to.
+    ///
+    /// For more information, see [`recv`].
+    ///
+    /// [`recv`]: #method.recv
+    pub fn poll_recv(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let (n, _) = ready!(self.get_mut().poll_recv_priv(cx, buf)?);
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_send_priv(
+        &mut self,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+        target: Option<&SocketAddr>,
+    ) -> Poll<io::Result<usize>> {
+        if self.send.is_none() {
+            let mut msg = Msg::new(buf[..buf.len().min(MAX_BUF)].to_vec());
+            if target.is_some() {
+                msg.set_addr(target);
+            }
+            let entry = opcode::SendMsg::new(types::Fd(self.io.as_raw_fd()), &msg.hdr).build();
+            self.send = Some(unsafe { Op::submit(entry, msg)? });
+        }
+
+        let (res, _) = ready!(self.send.as_mut().unwrap().poll(cx));
+        self.send = None;
+        Poll::Ready(res.map(|n| n as usize))
+    }
+
+    fn poll_recv_priv(
+        &mut self,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<(usize, Option<SocketAddr>)>> {
+        if self.recv.is_none() {
+            let mut msg = Msg::new(vec![0; buf.len().min(MAX_BUF)]);
+            msg.set_addr(None);
+            let entry = opcode::RecvMsg::new(types::Fd(self.io.as_raw_fd()), &mut msg.hdr).build();
+            self.recv = Some(unsafe { Op::submit(entry, msg)? });
+        }
+
+        let (res, msg) = ready!(self.recv.as_mut().unwrap().poll(cx));
+        self.recv = None;
+        // A datagram which doesn't fit into `buf` is truncated, as it would
+        // be by `recv`.
+        let n = (res? as usize).min(buf.len());
+        buf[..n].copy_from_slice(&msg.buf[..n]);
+        Poll::Ready(Ok((n, socket_addr(&msg.addr))))
+    }
+}
+
+impl AsyncDatagram for UdpSocket {
+    type Sender = SocketAddr;
+    type Receiver = SocketAddr;
+    type Err = io::Error;
+
+    fn poll_send_to(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+        receiver: &Self::Receiver,
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().poll_send_priv(cx, buf, Some(receiver))
+    }
+
+    fn poll_recv_from(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<(usize, Self::Sender)>> {
+        let (n, addr) = ready!(self.get_mut().poll_recv_priv(cx, buf)?);
+        let addr = addr.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                "received a datagram of unknown family",
+            )
+        })?;
+        Poll::Ready(Ok((n, addr)))
+    }
+}
+
+impl AsRawFd for UdpSocket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.io.as_raw_fd()
+    }
+}
+
+impl fmt::Debug for UdpSocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.io.fmt(f)
+    }
+}
+
+/// The future returned by `UdpSocket::send_to`
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct SendTo<'a, 'b> {
+    socket: &'a mut UdpSocket,
+    buf: &'b [u8],
+    target: &'b SocketAddr,
+}
+
+impl<'a, 'b> Future for SendTo<'a, 'b> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let SendTo {
+            socket,
+            buf,
+            target,
+        } = &mut *self;
+        Pin::new(&mut **socket).poll_send_to(cx, buf, target)
+    }
+}
+
+/// The future returned by `UdpSocket::recv_from`
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct RecvFrom<'a, 'b> {
+    socket: &'a mut UdpSocket,
+    buf: &'b mut [u8],
+}
+
+impl<'a, 'b> Future for RecvFrom<'a, 'b> {
+    type Output = io::Result<(usize, SocketAddr)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let RecvFrom { socket, buf } = &mut *self;
+        Pin::new(&mut **socket).poll_recv_from(cx, buf)
+    }
+}
+
+/// The future returned by `UdpSocket::send`
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct Send<'a> {
+    socket: &'a mut UdpSocket,
+    buf: &'a [u8],
+}
+
+impl<'a> Future for Send<'a> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let Send { socket, buf } = &mut *self;
+        Pin::new(&mut **socket).poll_send(cx, buf)
+    }
+}
+
+/// The future returned by `UdpSocket::recv`
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct Recv<'a> {
+    socket: &'a mut UdpSocket,
+    buf: &'a mut [u8],
+}
+
+impl<'a> Future for Recv<'a> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let Recv { socket, buf } = &mut *self;
+        Pin::new(&mut **socket).poll_recv(cx, buf)
+    }
+}
@@ -0,0 +1,244 @@
+#![feature(async_await)]
+use std::net::TcpStream;
+use std::sync::{Arc, Barrier};
+use std::thread;
+
+use futures::{executor, future, Poll};
+
+use romio::raw::{Handle, PollEvented};
+
+#[test]
+fn into_inner_deregisters() -> std::io::Result<()> {
+    drop(env_logger::try_init());
+    let listener = mio::net::TcpListener::bind(&"127.0.0.1:0".parse().unwrap())?;
+    let addr = listener.local_addr()?;
+
+    // the resource is registered with the reactor when first polled
+    let mut io = PollEvented::new(listener);
+    executor::block_on(future::poll_fn(|cx| {
+        assert!(io.poll_read_ready(cx).is_pending());
+        Poll::Ready(())
+    }));
+
+    io.get_mut().set_ttl(42)?;
+    assert_eq!(io.get_ref().ttl()?, 42);
+
+    // registering it again only works once it has been deregistered
+    let io = PollEvented::new(io.into_inner()?);
+    let _client = TcpStream::connect(addr)?;
+    let ready = executor::block_on(future::poll_fn(|cx| io.poll_read_ready(cx)))?;
+    assert!(ready.is_readable());
+    Ok(())
+}
+
+#[test]
+fn new_with_interest() -> std::io::Result<()> {
+    drop(env_logger::try_init());
+    let listener = mio::net::TcpListener::bind(&"127.0.0.1:0".parse().unwrap())?;
+    let err = PollEvented::new_with_interest(listener, mio::Ready::empty()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    let listener = mio::net::TcpListener::bind(&"127.0.0.1:0".parse().unwrap())?;
+    let addr = listener.local_addr()?;
+    let io = PollEvented::new_with_interest(listener, mio::Ready::readable())?;
+
+    let _client = TcpStream::connect(addr)?;
+    let ready = executor::block_on(future::poll_fn(|cx| io.poll_read_ready(cx)))?;
+    assert!(ready.is_readable());
+    Ok(())
+}
+
+#[test]
+fn read_with_and_write_with() -> std::io::Result<()> {
+    drop(env_logger::try_init());
+    let any = "127.0.0.1:0".parse().unwrap();
+    let server = PollEvented::new(mio::net::UdpSocket::bind(&any)?);
+    let client = PollEvented::new(mio::net::UdpSocket::bind(&any)?);
+    let addr = server.get_ref().local_addr()?;
+
+    // nothing to receive yet, so the read is retried once readable
+    let mut buf = [0; 8];
+    executor::block_on(future::poll_fn(|cx| {
+        assert!(server
+            .read_with(cx, |io| io.recv_from(&mut buf))
+            .is_pending());
+        Poll::Ready(())
+    }));
+
+    let n = executor::block_on(future::poll_fn(|cx| {
+        client.write_with(cx, |io| io.send_to(b"hello", &addr))
+    }))?;
+    assert_eq!(n, 5);
+
+    let (n, from) = executor::block_on(future::poll_fn(|cx| {
+        server.read_with(cx, |io| io.recv_from(&mut buf))
+    }))?;
+    assert_eq!(&buf[..n], b"hello");
+    assert_eq!(from, client.get_ref().local_addr()?);
+
+    // other errors are passed through untouched
+    let err = executor::block_on(future::poll_fn(|cx| {
+        server.write_with(cx, |_| -> std::io::Result<()> {
+            Err(std::io::ErrorKind::Other.into())
+        })
+    }))
+    .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::Other);
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn error_ready() -> std::io::Result<()> {
+    drop(env_logger::try_init());
+    let any = "127.0.0.1:0".parse().unwrap();
+
+    // nothing listens on the port once the socket is dropped
+    let addr = mio::net::UdpSocket::bind(&any)?.local_addr()?;
+    let socket = mio::net::UdpSocket::bind(&any)?;
+    socket.connect(addr)?;
+    let io = PollEvented::new(socket);
+
+    executor::block_on(future::poll_fn(|cx| {
+        assert!(io.poll_error_ready(cx).is_pending());
+        Poll::Ready(())
+    }));
+
+    // the port unreachable reply queues an error on the connected socket
+    io.get_ref().send(b"hello")?;
+    let ready = executor::block_on(future::poll_fn(|cx| io.poll_error_ready(cx)))?;
+    assert!(mio::unix::UnixReady::from(ready).is_error());
+
+    let err = io.get_ref().take_error()?.unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::ConnectionRefused);
+
+    executor::block_on(future::poll_fn(|cx| {
+        io.clear_error_ready(cx)?;
+        assert!(io.poll_error_ready(cx).is_pending());
+        Poll::Ready(Ok::<_, std::io::Error>(()))
+    }))
+}
+
+#[test]
+fn deregister() -> std::io::Result<()> {
+    drop(env_logger::try_init());
+    let listener = mio::net::TcpListener::bind(&"127.0.0.1:0".parse().unwrap())?;
+    let addr = listener.local_addr()?;
+    let io = PollEvented::new(listener);
+    executor::block_on(future::poll_fn(|cx| {
+        assert!(io.poll_read_ready(cx).is_pending());
+        Poll::Ready(())
+    }));
+
+    io.deregister()?;
+    io.deregister()?;
+
+    let _client = TcpStream::connect(addr)?;
+    let res = executor::block_on(future::poll_fn(|cx| io.poll_read_ready(cx)));
+    assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::Other);
+
+    // the resource can be registered anew once it has been unwrapped
+    let io = PollEvented::new(io.into_inner()?);
+    let ready = executor::block_on(future::poll_fn(|cx| io.poll_read_ready(cx)))?;
+    assert!(ready.is_readable());
+    Ok(())
+}
+
+#[test]
+fn deregister_while_registering() -> std::io::Result<()> {
+    drop(env_logger::try_init());
+    for _ in 0..1000 {
+        let listener = mio::net::TcpListener::bind(&"127.0.0.1:0".parse().unwrap())?;
+        let io = Arc::new(PollEvented::new(listener));
+        let barrier = Arc::new(Barrier::new(2));
+
+        let registering = {
+            let io = io.clone();
+            let barrier = barrier.clone();
+            thread::spawn(move || {
+                barrier.wait();
+                executor::block_on(future::poll_fn(|cx| Poll::Ready(io.poll_read_ready(cx))))
+            })
+        };
+        barrier.wait();
+        io.deregister()?;
+        drop(registering.join().unwrap());
+
+        // no registration may be left behind, whichever call came first
+        let io = Arc::try_unwrap(io).ok().unwrap();
+        let io = PollEvented::new(io.into_inner()?);
+        executor::block_on(future::poll_fn(|cx| {
+            assert!(io.poll_read_ready(cx).is_pending());
+            Poll::Ready(())
+        }));
+    }
+    Ok(())
+}
+
+#[test]
+fn reregister() -> std::io::Result<()> {
+    drop(env_logger::try_init());
+    let socket = mio::net::UdpSocket::bind(&"127.0.0.1:0".parse().unwrap())?;
+    let mut io = PollEvented::new_with_interest(socket, mio::Ready::readable())?;
+    executor::block_on(future::poll_fn(|cx| {
+        assert!(io.poll_write_ready(cx).is_pending());
+        Poll::Ready(())
+    }));
+
+    let err = io
+        .reregister(mio::Ready::empty(), mio::PollOpt::edge())
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    let interest = mio::Ready::readable() | mio::Ready::writable();
+    io.reregister(interest, mio::PollOpt::edge())?;
+    let ready = executor::block_on(future::poll_fn(|cx| io.poll_write_ready(cx)))?;
+    assert!(ready.is_writable());
+    Ok(())
+}
+
+#[test]
+fn new_with_handle() -> std::io::Result<()> {
+    drop(env_logger::try_init());
+    let handle = Handle::current();
+
+    let io = std::thread::spawn(move || -> std::io::Result<_> {
+        let listener = mio::net::TcpListener::bind(&"127.0.0.1:0".parse().unwrap())?;
+        PollEvented::new_with_handle(listener, &handle)
+    })
+    .join()
+    .unwrap()?;
+
+    let _client = TcpStream::connect(io.get_ref().local_addr()?)?;
+    let ready = executor::block_on(future::poll_fn(|cx| io.poll_read_ready(cx)))?;
+    assert!(ready.is_readable());
+
+    // a default handle binds to the current reactor
+    let listener = mio::net::TcpListener::bind(&"127.0.0.1:0".parse().unwrap())?;
+    PollEvented::new_with_handle(listener, &Handle::default())?;
+    Ok(())
+}
+
+#[test]
+fn readiness_through_shared_reference() -> std::io::Result<()> {
+    drop(env_logger::try_init());
+    let listener = mio::net::TcpListener::bind(&"127.0.0.1:0".parse().unwrap())?;
+    let addr = listener.local_addr()?;
+
+    // readiness is polled and cleared without exclusive access, so that
+    // halves of a socket can share one `PollEvented`
+    let io = PollEvented::new(listener);
+    let shared = &io;
+    let _client = TcpStream::connect(addr)?;
+    let ready = executor::block_on(future::poll_fn(|cx| shared.poll_read_ready(cx)))?;
+    assert!(ready.is_readable());
+
+    executor::block_on(future::poll_fn(|cx| {
+        shared.clear_read_ready(cx)?;
+        assert!(shared.poll_read_ready(cx).is_pending());
+        shared.clear_write_ready(cx)?;
+        Poll::Ready(Ok::<_, std::io::Error>(()))
+    }))?;
+    io.get_ref().accept()?;
+    Ok(())
+}
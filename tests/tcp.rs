@@ -1,13 +1,17 @@
 #![feature(async_await)]
 use std::convert::TryFrom;
 use std::io::{Read, Write};
-use std::net::{SocketAddr, TcpStream};
+use std::net::{Shutdown, SocketAddr, TcpStream};
+use std::pin::Pin;
 use std::thread;
 
 use futures::executor;
-use futures::io::{AsyncReadExt, AsyncWriteExt};
+use futures::future;
+use futures::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt};
+use futures::stream::FusedStream;
 use futures::StreamExt;
 
+use romio::tcp::BufTcpStream;
 use romio::TcpListener;
 
 const THE_WINTERS_TALE: &[u8] = b"
@@ -32,7 +36,7 @@ fn listener_reads() {
     executor::block_on(async {
         let mut buf = vec![0; THE_WINTERS_TALE.len()];
         let mut incoming = server.incoming();
-        let mut stream = incoming.next().await.unwrap().unwrap();
+        let (mut stream, _) = incoming.next().await.unwrap().unwrap();
         stream.read_exact(&mut buf).await.unwrap();
         assert_eq!(buf, THE_WINTERS_TALE);
     });
@@ -54,7 +58,7 @@ fn listener_writes() {
 
     executor::block_on(async {
         let mut incoming = server.incoming();
-        let mut stream = incoming.next().await.unwrap().unwrap();
+        let (mut stream, _) = incoming.next().await.unwrap().unwrap();
         stream.write_all(THE_WINTERS_TALE).await.unwrap();
     });
 }
@@ -75,7 +79,7 @@ fn both_sides_async_using_threadpool() {
     pool.run(Box::pin(async {
         let mut buf = vec![0; THE_WINTERS_TALE.len()];
         let mut incoming = server.incoming();
-        let mut stream = incoming.next().await.unwrap().unwrap();
+        let (mut stream, _) = incoming.next().await.unwrap().unwrap();
         stream.read_exact(&mut buf).await.unwrap();
         assert_eq!(buf, THE_WINTERS_TALE);
     }));
@@ -88,3 +92,1258 @@ fn listener_from_std() {
     let std_socket = std::net::TcpListener::bind(&addr).unwrap();
     let _ = TcpListener::try_from(std_socket).unwrap();
 }
+
+#[test]
+fn listener_accept_loop() {
+    drop(env_logger::try_init());
+    let server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    let pool = executor::ThreadPool::new().unwrap();
+    let spawner = pool.clone();
+    pool.spawn_ok(async move {
+        let _ = server
+            .accept_loop(spawner, |mut stream, _| async move {
+                stream.write_all(THE_WINTERS_TALE).await.unwrap();
+            })
+            .await;
+    });
+
+    for _ in 0..2 {
+        let mut buf = vec![0; THE_WINTERS_TALE.len()];
+        let mut client = TcpStream::connect(&addr).unwrap();
+        client.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, THE_WINTERS_TALE);
+    }
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn stream_sets_rto_bounds() {
+    use std::time::Duration;
+
+    drop(env_logger::try_init());
+    let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    let _client = TcpStream::connect(&addr).unwrap();
+    executor::block_on(async {
+        let (stream, _) = server.incoming().next().await.unwrap().unwrap();
+        match stream.set_rto_max(Duration::from_secs(5)) {
+            // the option needs Linux 6.15 or later
+            Err(ref e) if e.raw_os_error() == Some(libc::ENOPROTOOPT) => return,
+            res => res.unwrap(),
+        }
+        assert_eq!(stream.rto_max().unwrap(), Duration::from_secs(5));
+        stream.set_rto_min(Duration::from_millis(5)).unwrap();
+
+        // the cap must stay within the kernel's limits
+        assert!(stream.set_rto_max(Duration::from_secs(121)).is_err());
+    });
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn listener_reuseport_bpf() {
+    use net2::unix::UnixTcpBuilderExt;
+
+    drop(env_logger::try_init());
+    let std_listener = net2::TcpBuilder::new_v4()
+        .unwrap()
+        .reuse_port(true)
+        .unwrap()
+        .bind("127.0.0.1:0")
+        .unwrap()
+        .listen(128)
+        .unwrap();
+    let listener = TcpListener::try_from(std_listener).unwrap();
+
+    // always steer to the first socket of the group
+    let first = [libc::sock_filter {
+        code: (libc::BPF_RET | libc::BPF_K) as u16,
+        jt: 0,
+        jf: 0,
+        k: 0,
+    }];
+    listener.attach_reuseport_bpf(&first).unwrap();
+}
+
+#[test]
+fn stream_cork_vectored_write() {
+    drop(env_logger::try_init());
+    let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    // client thread
+    let client = thread::spawn(move || {
+        let mut buf = vec![];
+        let mut client = TcpStream::connect(&addr).unwrap();
+        client.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, THE_WINTERS_TALE);
+    });
+
+    executor::block_on(async {
+        let mut incoming = server.incoming();
+        let (mut stream, _) = incoming.next().await.unwrap().unwrap();
+        let (head, tail) = THE_WINTERS_TALE.split_at(20);
+        let bufs = [std::io::IoSlice::new(head), std::io::IoSlice::new(tail)];
+        let n = stream.cork_vectored_write(&bufs).await.unwrap();
+        assert_eq!(n, THE_WINTERS_TALE.len());
+    });
+
+    client.join().unwrap();
+}
+
+#[test]
+#[cfg(unix)]
+fn listener_cloexec() {
+    use std::os::unix::io::AsRawFd;
+
+    drop(env_logger::try_init());
+    let listener = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let cloexec = || {
+        let flags = unsafe { libc::fcntl(listener.as_raw_fd(), libc::F_GETFD) };
+        assert_ne!(flags, -1);
+        flags & libc::FD_CLOEXEC != 0
+    };
+
+    listener.clear_cloexec().unwrap();
+    assert!(!cloexec());
+    listener.set_cloexec().unwrap();
+    assert!(cloexec());
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn stream_window_clamp() {
+    drop(env_logger::try_init());
+    let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    let _client = TcpStream::connect(&addr).unwrap();
+    executor::block_on(async {
+        let (stream, _) = server.incoming().next().await.unwrap().unwrap();
+        stream.set_window_clamp(64 * 1024).unwrap();
+        assert_eq!(stream.window_clamp().unwrap(), 64 * 1024);
+    });
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn stream_sends_and_receives_oob() {
+    drop(env_logger::try_init());
+    let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    executor::block_on(async {
+        let mut client = romio::TcpStream::connect(&addr).await.unwrap();
+        let (mut stream, _) = server.incoming().next().await.unwrap().unwrap();
+
+        client.write_all(b"hello").await.unwrap();
+        client.send_oob(b'!').await.unwrap();
+
+        let mut buf = [0; 1];
+        let n = stream.recv_oob(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"!");
+
+        // waiting for urgent data left the regular data readable
+        let mut buf = [0; 5];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"hello");
+    });
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn stream_recv_oob_fails_inline() {
+    use std::os::unix::io::AsRawFd;
+
+    drop(env_logger::try_init());
+    let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    executor::block_on(async {
+        let mut client = romio::TcpStream::connect(&addr).await.unwrap();
+        let (mut stream, _) = server.incoming().next().await.unwrap().unwrap();
+
+        let on: libc::c_int = 1;
+        let ret = unsafe {
+            libc::setsockopt(
+                stream.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_OOBINLINE,
+                &on as *const _ as *const libc::c_void,
+                std::mem::size_of_val(&on) as libc::socklen_t,
+            )
+        };
+        assert_eq!(ret, 0);
+
+        client.send_oob(b'!').await.unwrap();
+
+        let mut buf = [0; 1];
+        let err = stream.recv_oob(&mut buf).await.unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::EINVAL));
+
+        // the urgent byte is read in line instead
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"!");
+    });
+}
+
+#[test]
+#[cfg(all(unix, feature = "socket2"))]
+fn listener_set_socket2_options() {
+    drop(env_logger::try_init());
+    let server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+
+    server
+        .set_socket2_options(|socket| socket.set_ttl(42))
+        .unwrap();
+    assert_eq!(server.ttl().unwrap(), 42);
+
+    // The socket must still be usable once the borrow has ended.
+    server.local_addr().unwrap();
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn stream_sends_zerocopy() {
+    drop(env_logger::try_init());
+    let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    executor::block_on(async {
+        let mut client = romio::TcpStream::connect(&addr).await.unwrap();
+        let (mut stream, _) = server.incoming().next().await.unwrap().unwrap();
+
+        let n = client.send_zerocopy(THE_WINTERS_TALE).await.unwrap();
+        let mut buf = vec![0; n];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf[..], &THE_WINTERS_TALE[..n]);
+
+        // Notifications on loopback are queued once the data is delivered.
+        let notification =
+            future::poll_fn(|cx| Pin::new(&mut client).poll_recv_zerocopy_completion(cx))
+                .await
+                .unwrap();
+        assert_eq!(notification.first, 0);
+        assert_eq!(notification.last, 0);
+
+        // SO_ZEROCOPY stays enabled, so the next send is numbered too.
+        let n = client.send_zerocopy(THE_WINTERS_TALE).await.unwrap();
+        let mut buf = vec![0; n];
+        stream.read_exact(&mut buf).await.unwrap();
+        let notification =
+            future::poll_fn(|cx| Pin::new(&mut client).poll_recv_zerocopy_completion(cx))
+                .await
+                .unwrap();
+        assert_eq!(notification.first, 1);
+        assert_eq!(notification.last, 1);
+    });
+}
+
+#[test]
+#[cfg(unix)]
+fn listeners_share_address_with_reuseport() {
+    drop(env_logger::try_init());
+    let first = TcpListener::bind_reuseport(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = first.local_addr().unwrap();
+    let second = TcpListener::bind_reuseport(&addr).unwrap();
+    assert_eq!(second.local_addr().unwrap(), addr);
+    assert!(first.reuseport().unwrap());
+
+    second.set_reuseport(false).unwrap();
+    assert!(!second.reuseport().unwrap());
+}
+
+#[test]
+#[cfg(unix)]
+fn stream_into_std() {
+    drop(env_logger::try_init());
+    let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    let mut client = executor::block_on(async {
+        let client = romio::TcpStream::connect(&addr).await.unwrap();
+        client.into_std().unwrap()
+    });
+
+    // client thread, blocking on the converted stream
+    let handle = thread::spawn(move || {
+        client.write_all(THE_WINTERS_TALE).unwrap();
+        let mut buf = [0; 3];
+        client.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"ack");
+    });
+
+    executor::block_on(async {
+        let mut incoming = server.incoming();
+        let (mut stream, _) = incoming.next().await.unwrap().unwrap();
+        let mut buf = vec![0; THE_WINTERS_TALE.len()];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(buf, THE_WINTERS_TALE);
+        stream.write_all(b"ack").await.unwrap();
+    });
+    handle.join().unwrap();
+}
+
+#[test]
+fn connect_refused() {
+    drop(env_logger::try_init());
+    let server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+    drop(server);
+
+    executor::block_on(async {
+        let err = romio::TcpStream::connect(&addr).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::ConnectionRefused);
+    });
+}
+
+#[test]
+fn incoming_debug_shows_address() {
+    let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+    let incoming = server.incoming();
+    assert_eq!(
+        format!("{:?}", incoming),
+        format!("Incoming {{ local_addr: {} }}", addr)
+    );
+}
+
+#[test]
+fn incoming_yields_peer_address() {
+    drop(env_logger::try_init());
+    let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    executor::block_on(async {
+        let client = romio::TcpStream::connect(&addr).await.unwrap();
+        let (stream, peer) = server.incoming().next().await.unwrap().unwrap();
+        assert_eq!(peer, client.local_addr().unwrap());
+        assert_eq!(stream.peer_addr().unwrap(), peer);
+    });
+}
+
+#[test]
+fn incoming_is_selectable() {
+    drop(env_logger::try_init());
+    let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    executor::block_on(async {
+        let client = romio::TcpStream::connect(&addr).await.unwrap();
+        // `select_next_some` is what `select!` uses, and needs a `FusedStream`
+        let (_, peer) = server.incoming().select_next_some().await.unwrap();
+        assert_eq!(peer, client.local_addr().unwrap());
+    });
+}
+
+#[test]
+fn split_stream_echoes() {
+    drop(env_logger::try_init());
+    let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    // client thread
+    let handle = thread::spawn(move || {
+        let mut client = TcpStream::connect(&addr).unwrap();
+        client.write_all(THE_WINTERS_TALE).unwrap();
+        let mut buf = vec![0; THE_WINTERS_TALE.len()];
+        client.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, THE_WINTERS_TALE);
+    });
+
+    executor::block_on(async {
+        let (mut stream, _) = server.incoming().next().await.unwrap().unwrap();
+        let (mut reader, mut writer) = stream.split_borrowed();
+        let mut buf = vec![0; THE_WINTERS_TALE.len()];
+        reader.read_exact(&mut buf).await.unwrap();
+        writer.write_all(&buf).await.unwrap();
+    });
+    handle.join().unwrap();
+}
+
+#[test]
+fn into_split_closes_writes_and_reunites() {
+    drop(env_logger::try_init());
+    let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    // client thread
+    let handle = thread::spawn(move || {
+        let mut client = TcpStream::connect(&addr).unwrap();
+        let mut buf = Vec::new();
+        client.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, THE_WINTERS_TALE);
+        client.write_all(b"ack").unwrap();
+    });
+
+    executor::block_on(async {
+        let (stream, _) = server.incoming().next().await.unwrap().unwrap();
+        let (mut reader, mut writer) = stream.into_split();
+        assert_eq!(writer.peer_addr().unwrap(), reader.peer_addr().unwrap());
+
+        writer.write_all(THE_WINTERS_TALE).await.unwrap();
+        writer.close().await.unwrap();
+
+        let mut buf = [0; 3];
+        reader.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"ack");
+
+        let local_addr = reader.local_addr().unwrap();
+        let stream = reader.reunite(writer).unwrap();
+        assert_eq!(stream.local_addr().unwrap(), local_addr);
+    });
+    handle.join().unwrap();
+}
+
+#[test]
+fn close_shuts_down_writes() {
+    drop(env_logger::try_init());
+    let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    // client thread
+    let handle = thread::spawn(move || {
+        let mut client = TcpStream::connect(&addr).unwrap();
+        let mut buf = Vec::new();
+        client.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, THE_WINTERS_TALE);
+        client.write_all(b"ack").unwrap();
+    });
+
+    executor::block_on(async {
+        let (mut stream, _) = server.incoming().next().await.unwrap().unwrap();
+        stream.write_all(THE_WINTERS_TALE).await.unwrap();
+        stream.close().await.unwrap();
+
+        // the reading portion stays open
+        let mut buf = Vec::new();
+        stream.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(buf, b"ack");
+    });
+    handle.join().unwrap();
+}
+
+#[test]
+fn stream_sets_nodelay() {
+    drop(env_logger::try_init());
+    let server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    executor::block_on(async {
+        let stream = romio::TcpStream::connect(&addr).await.unwrap();
+        stream.set_nodelay(true).unwrap();
+        assert!(stream.nodelay().unwrap());
+        stream.set_nodelay(false).unwrap();
+        assert!(!stream.nodelay().unwrap());
+    });
+}
+
+#[test]
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "illumos",
+    target_os = "solaris"
+))]
+fn stream_sets_keepalive_params() {
+    drop(env_logger::try_init());
+    let server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    executor::block_on(async {
+        let stream = romio::TcpStream::connect(&addr).await.unwrap();
+        let idle = std::time::Duration::from_secs(60);
+        stream
+            .set_keepalive_params(idle, std::time::Duration::from_secs(10), 5)
+            .unwrap();
+        assert_eq!(stream.keepalive().unwrap(), Some(idle));
+
+        stream.set_keepalive(None).unwrap();
+        assert_eq!(stream.keepalive().unwrap(), None);
+    });
+}
+
+#[test]
+fn stream_shuts_down_halves() {
+    drop(env_logger::try_init());
+    let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    // client thread
+    let handle = thread::spawn(move || {
+        let mut client = TcpStream::connect(&addr).unwrap();
+        let mut buf = Vec::new();
+        client.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, THE_WINTERS_TALE);
+    });
+
+    executor::block_on(async {
+        let (mut stream, _) = server.incoming().next().await.unwrap().unwrap();
+        stream.shutdown(std::net::Shutdown::Read).unwrap();
+
+        let mut buf = [0; 8];
+        assert_eq!(stream.read(&mut buf).await.unwrap(), 0);
+
+        stream.write_all(THE_WINTERS_TALE).await.unwrap();
+        futures::future::poll_fn(|cx| {
+            std::pin::Pin::new(&mut stream).poll_shutdown(cx, std::net::Shutdown::Write)
+        })
+        .await
+        .unwrap();
+    });
+    handle.join().unwrap();
+}
+
+#[test]
+fn stream_peeks() {
+    drop(env_logger::try_init());
+    let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    // client thread
+    thread::spawn(move || {
+        let mut client = TcpStream::connect(&addr).unwrap();
+        client.write_all(THE_WINTERS_TALE).unwrap();
+    });
+
+    executor::block_on(async {
+        let (mut stream, _) = server.incoming().next().await.unwrap().unwrap();
+        let mut buf = [0; 8];
+        let n = stream.peek(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], &THE_WINTERS_TALE[..n]);
+
+        let mut all = Vec::new();
+        stream.read_to_end(&mut all).await.unwrap();
+        assert_eq!(all, THE_WINTERS_TALE);
+        assert_eq!(stream.peek(&mut buf).await.unwrap(), 0);
+    });
+}
+
+#[test]
+fn stream_try_reads_after_readable() {
+    drop(env_logger::try_init());
+    let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    // client thread
+    thread::spawn(move || {
+        let mut client = TcpStream::connect(&addr).unwrap();
+        client.write_all(THE_WINTERS_TALE).unwrap();
+    });
+
+    executor::block_on(async {
+        let (mut stream, _) = server.incoming().next().await.unwrap().unwrap();
+        let mut all = Vec::new();
+        let mut buf = [0; 16];
+        loop {
+            stream.readable().await.unwrap();
+            loop {
+                match stream.try_read(&mut buf) {
+                    Ok(0) => {
+                        assert_eq!(all, THE_WINTERS_TALE);
+                        return;
+                    }
+                    Ok(n) => all.extend_from_slice(&buf[..n]),
+                    Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+                    Err(e) => panic!("{}", e),
+                }
+            }
+        }
+    });
+}
+
+#[test]
+fn stream_reads_message_larger_than_buffer_after_readable() {
+    drop(env_logger::try_init());
+    let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    // client thread
+    let handle = thread::spawn(move || {
+        let mut client = TcpStream::connect(&addr).unwrap();
+        client.write_all(THE_WINTERS_TALE).unwrap();
+    });
+
+    executor::block_on(async {
+        let (mut stream, _) = server.incoming().next().await.unwrap().unwrap();
+        // everything arrives before the first read, so no further readiness
+        // events are delivered while the buffer is drained
+        handle.join().unwrap();
+
+        let mut all = Vec::new();
+        let mut buf = [0; 16];
+        loop {
+            stream.readable().await.unwrap();
+            match stream.try_read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => all.extend_from_slice(&buf[..n]),
+                Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => continue,
+                Err(e) => panic!("{}", e),
+            }
+        }
+        assert_eq!(all, THE_WINTERS_TALE);
+    });
+}
+
+#[test]
+fn stream_try_writes_after_writable() {
+    drop(env_logger::try_init());
+    let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    // client thread
+    let handle = thread::spawn(move || {
+        let mut client = TcpStream::connect(&addr).unwrap();
+        let mut buf = Vec::new();
+        client.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, THE_WINTERS_TALE);
+    });
+
+    executor::block_on(async {
+        let (mut stream, _) = server.incoming().next().await.unwrap().unwrap();
+        stream.writable().await.unwrap();
+        let n = stream.try_write(THE_WINTERS_TALE).unwrap();
+        assert_eq!(n, THE_WINTERS_TALE.len());
+    });
+    handle.join().unwrap();
+}
+
+#[test]
+fn listener_binds_with_backlog() {
+    drop(env_logger::try_init());
+    let mut server = TcpListener::bind_with_backlog(&"127.0.0.1:0".parse().unwrap(), 1).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    // client thread
+    thread::spawn(move || {
+        let mut client = TcpStream::connect(&addr).unwrap();
+        client.write_all(THE_WINTERS_TALE).unwrap();
+    });
+
+    executor::block_on(async {
+        let (mut stream, _) = server.incoming().next().await.unwrap().unwrap();
+        let mut buf = Vec::new();
+        stream.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(buf, THE_WINTERS_TALE);
+    });
+}
+
+#[test]
+#[cfg(unix)]
+fn accepted_stream_inherits_buffer_sizes() {
+    drop(env_logger::try_init());
+    let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    // Linux reports twice the requested size
+    server.set_recv_buffer_size(64 * 1024).unwrap();
+    let recv = server.recv_buffer_size().unwrap();
+    assert!(recv >= 64 * 1024);
+    server.set_send_buffer_size(64 * 1024).unwrap();
+    let send = server.send_buffer_size().unwrap();
+    assert!(send >= 64 * 1024);
+
+    // client thread
+    thread::spawn(move || {
+        TcpStream::connect(&addr).unwrap();
+    });
+
+    executor::block_on(async {
+        let (stream, _) = server.incoming().next().await.unwrap().unwrap();
+        assert_eq!(stream.recv_buffer_size().unwrap(), recv);
+        assert_eq!(stream.send_buffer_size().unwrap(), send);
+    });
+}
+
+#[test]
+fn connect_any_falls_back() {
+    drop(env_logger::try_init());
+    let closed = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let refused = closed.local_addr().unwrap();
+    drop(closed);
+    let server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    executor::block_on(async {
+        let stream = romio::TcpStream::connect_any(vec![refused, addr])
+            .timeout(std::time::Duration::from_millis(50))
+            .await
+            .unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), addr);
+
+        let err = romio::TcpStream::connect_any(vec![refused])
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::ConnectionRefused);
+
+        let err = romio::TcpStream::connect_any(None).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    });
+}
+
+#[test]
+fn stream_vectored_io() {
+    drop(env_logger::try_init());
+    let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    // client thread
+    let handle = thread::spawn(move || {
+        let mut client = TcpStream::connect(&addr).unwrap();
+        let mut buf = [0; 11];
+        client.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"hello world");
+        client.write_all(b"hello world").unwrap();
+    });
+
+    executor::block_on(async {
+        let (mut stream, _) = server.incoming().next().await.unwrap().unwrap();
+        let bufs = [
+            std::io::IoSlice::new(b"hello"),
+            std::io::IoSlice::new(b" "),
+            std::io::IoSlice::new(b"world"),
+        ];
+        assert_eq!(stream.write_vectored(&bufs).await.unwrap(), 11);
+
+        let mut all: Vec<u8> = Vec::new();
+        while all.len() < 11 {
+            let (mut first, mut second) = ([0; 6], [0; 5]);
+            let n = {
+                let mut bufs = [
+                    std::io::IoSliceMut::new(&mut first),
+                    std::io::IoSliceMut::new(&mut second),
+                ];
+                stream.read_vectored(&mut bufs).await.unwrap()
+            };
+            all.extend(first.iter().chain(second.iter()).take(n));
+        }
+        assert_eq!(all, b"hello world");
+    });
+    handle.join().unwrap();
+}
+
+#[test]
+#[cfg(unix)]
+fn listener_into_std_and_stream_from_std() {
+    drop(env_logger::try_init());
+    let server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+    let server = server.into_std().unwrap();
+
+    // server thread, blocking on the converted listener
+    let handle = thread::spawn(move || {
+        let (mut stream, _) = server.accept().unwrap();
+        stream.write_all(THE_WINTERS_TALE).unwrap();
+    });
+
+    let client = TcpStream::connect(&addr).unwrap();
+    let mut client = romio::TcpStream::from_std(client).unwrap();
+    executor::block_on(async {
+        let mut buf = Vec::new();
+        client.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(buf, THE_WINTERS_TALE);
+    });
+    handle.join().unwrap();
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn listener_sets_fastopen() {
+    drop(env_logger::try_init());
+    let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    server.set_fastopen(16).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    // client thread, which doesn't use fast open itself
+    thread::spawn(move || {
+        let mut client = TcpStream::connect(&addr).unwrap();
+        client.write_all(THE_WINTERS_TALE).unwrap();
+    });
+
+    executor::block_on(async {
+        let (mut stream, _) = server.incoming().next().await.unwrap().unwrap();
+        let mut buf = Vec::new();
+        stream.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(buf, THE_WINTERS_TALE);
+    });
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn features_match_listener_options() {
+    use romio::features::{self, Features};
+
+    drop(env_logger::try_init());
+    let all = Features::all();
+    assert_eq!(all.tcp_fastopen, features::supports_tcp_fastopen());
+    assert_eq!(all.mptcp, features::supports_mptcp());
+    assert_eq!(all.udp_gso, features::supports_udp_gso());
+    assert_eq!(
+        all.timestamping_opt_id,
+        features::supports_timestamping_opt_id()
+    );
+
+    // the probe agrees with what a real listener can do
+    let server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    assert_eq!(server.set_fastopen(16).is_ok(), all.tcp_fastopen);
+}
+
+#[test]
+fn stream_zero_linger_resets() {
+    drop(env_logger::try_init());
+    let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    // client thread
+    let handle = thread::spawn(move || {
+        let mut client = TcpStream::connect(&addr).unwrap();
+        let mut buf = [0; 1];
+        client.read(&mut buf).unwrap_err().kind()
+    });
+
+    executor::block_on(async {
+        let (stream, _) = server.incoming().next().await.unwrap().unwrap();
+        assert_eq!(stream.linger().unwrap(), None);
+        stream
+            .set_linger(Some(std::time::Duration::from_secs(0)))
+            .unwrap();
+        assert_eq!(
+            stream.linger().unwrap(),
+            Some(std::time::Duration::from_secs(0))
+        );
+    });
+    assert_eq!(handle.join().unwrap(), std::io::ErrorKind::ConnectionReset);
+}
+
+#[test]
+fn stream_takes_reset_error() {
+    drop(env_logger::try_init());
+    let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    executor::block_on(async {
+        let mut client = romio::TcpStream::connect(&addr).await.unwrap();
+        assert!(client.take_error().unwrap().is_none());
+
+        let (stream, _) = server.incoming().next().await.unwrap().unwrap();
+        stream
+            .set_linger(Some(std::time::Duration::from_secs(0)))
+            .unwrap();
+        drop(stream);
+
+        client.readable().await.unwrap();
+        let err = client.take_error().unwrap().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::ConnectionReset);
+        assert!(client.take_error().unwrap().is_none());
+    });
+}
+
+#[test]
+fn accepted_stream_inherits_ttl() {
+    drop(env_logger::try_init());
+    let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    server.set_ttl(1).unwrap();
+    assert_eq!(server.ttl().unwrap(), 1);
+    let addr = server.local_addr().unwrap();
+
+    // client thread
+    thread::spawn(move || {
+        TcpStream::connect(&addr).unwrap();
+    });
+
+    executor::block_on(async {
+        let (stream, _) = server.incoming().next().await.unwrap().unwrap();
+        assert_eq!(stream.ttl().unwrap(), 1);
+        stream.set_ttl(64).unwrap();
+        assert_eq!(stream.ttl().unwrap(), 64);
+    });
+}
+
+#[test]
+fn listener_builder_sets_only_v6() {
+    drop(env_logger::try_init());
+    let mut server = match romio::tcp::TcpListenerBuilder::new()
+        .only_v6(false)
+        .bind(&"[::]:0".parse().unwrap())
+    {
+        Ok(server) => server,
+        // no IPv6 support
+        Err(_) => return,
+    };
+    assert!(!server.only_v6().unwrap());
+    let port = server.local_addr().unwrap().port();
+
+    // client thread, connecting over IPv4
+    thread::spawn(move || {
+        let mut client = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        client.write_all(THE_WINTERS_TALE).unwrap();
+    });
+
+    executor::block_on(async {
+        let (mut stream, _) = server.incoming().next().await.unwrap().unwrap();
+        let mut buf = Vec::new();
+        stream.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(buf, THE_WINTERS_TALE);
+    });
+
+    let server = romio::tcp::TcpListenerBuilder::new()
+        .only_v6(true)
+        .bind(&"[::]:0".parse().unwrap())
+        .unwrap();
+    assert!(server.only_v6().unwrap());
+}
+
+#[test]
+fn builders_set_options_before_connecting() {
+    drop(env_logger::try_init());
+    let mut builder = romio::tcp::TcpListenerBuilder::new();
+    builder.backlog(16);
+    #[cfg(unix)]
+    builder.reuseport(true).recv_buffer_size(64 * 1024);
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    builder.fastopen(16);
+    let mut server = builder.bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+    #[cfg(unix)]
+    {
+        assert!(server.reuseport().unwrap());
+        assert!(server.recv_buffer_size().unwrap() >= 64 * 1024);
+        let second = builder.bind(&addr).unwrap();
+        assert_eq!(second.local_addr().unwrap(), addr);
+    }
+
+    executor::block_on(async {
+        let mut client = romio::tcp::TcpStreamBuilder::new()
+            .nodelay(true)
+            .send_buffer_size(64 * 1024)
+            .ttl(42)
+            .connect(&addr)
+            .await
+            .unwrap();
+        assert_eq!(client.peer_addr().unwrap(), addr);
+        assert!(client.nodelay().unwrap());
+        assert!(client.send_buffer_size().unwrap() >= 64 * 1024);
+        assert_eq!(client.ttl().unwrap(), 42);
+        client.write_all(THE_WINTERS_TALE).await.unwrap();
+        client.close().await.unwrap();
+
+        let (mut stream, _) = server.incoming().next().await.unwrap().unwrap();
+        let mut buf = Vec::new();
+        stream.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(buf, THE_WINTERS_TALE);
+    });
+}
+
+#[test]
+fn listener_poll_accept() {
+    drop(env_logger::try_init());
+    let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+    let client = TcpStream::connect(&addr).unwrap();
+
+    let (stream, peer) =
+        executor::block_on(future::poll_fn(|cx| Pin::new(&mut server).poll_accept(cx))).unwrap();
+    assert_eq!(peer, client.local_addr().unwrap());
+    assert_eq!(stream.peer_addr().unwrap(), peer);
+
+    let incoming = server.incoming();
+    assert!(!incoming.is_terminated());
+    assert_eq!(incoming.listener().local_addr().unwrap(), addr);
+}
+
+#[test]
+fn listener_accepts() {
+    drop(env_logger::try_init());
+    let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    // client thread
+    let client = thread::spawn(move || {
+        let mut client = TcpStream::connect(&addr).unwrap();
+        client.write_all(THE_WINTERS_TALE).unwrap();
+        client.local_addr().unwrap()
+    });
+
+    let (stream, peer) = executor::block_on(async {
+        let (mut stream, peer) = server.accept().await.unwrap();
+        let mut buf = Vec::new();
+        stream.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(buf, THE_WINTERS_TALE);
+        (stream, peer)
+    });
+    assert_eq!(peer, client.join().unwrap());
+    assert_eq!(stream.peer_addr().unwrap(), peer);
+}
+
+#[test]
+#[cfg(unix)]
+fn listener_and_stream_from_raw_fd() {
+    use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
+
+    drop(env_logger::try_init());
+    let server = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    server.set_nonblocking(true).unwrap();
+    let addr = server.local_addr().unwrap();
+    let fd = server.into_raw_fd();
+    let mut server = unsafe { TcpListener::from_raw_fd(fd) };
+    assert_eq!(server.as_raw_fd(), fd);
+    assert_eq!(server.local_addr().unwrap(), addr);
+
+    let client = TcpStream::connect(&addr).unwrap();
+    client.set_nonblocking(true).unwrap();
+    let mut client = unsafe { romio::TcpStream::from_raw_fd(client.into_raw_fd()) };
+
+    executor::block_on(async {
+        let (mut stream, _) = server.accept().await.unwrap();
+        stream.write_all(THE_WINTERS_TALE).await.unwrap();
+        drop(stream);
+
+        let mut buf = Vec::new();
+        client.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(buf, THE_WINTERS_TALE);
+    });
+}
+
+#[test]
+fn buf_stream_reads_lines() {
+    drop(env_logger::try_init());
+    let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    // client thread
+    let client = thread::spawn(move || {
+        let mut client = TcpStream::connect(&addr).unwrap();
+        client.write_all(THE_WINTERS_TALE).unwrap();
+        client.shutdown(Shutdown::Write).unwrap();
+        let mut buf = [0; 5];
+        client.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"queen");
+    });
+
+    executor::block_on(async {
+        let (stream, _) = server.incoming().next().await.unwrap().unwrap();
+        let mut stream = BufTcpStream::with_capacity(stream, 16);
+
+        let mut lines = Vec::new();
+        let mut line = String::new();
+        while stream.read_line(&mut line).await.unwrap() > 0 {
+            lines.push(line.clone());
+            line.clear();
+        }
+        assert_eq!(lines.concat().as_bytes(), THE_WINTERS_TALE);
+        assert_eq!(lines[4], "    That all your acts are queens.\n");
+        assert!(stream.buffer().is_empty());
+
+        stream.write_all(b"queen").await.unwrap();
+    });
+    client.join().unwrap();
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "macos"))]
+fn stream_sendfile() {
+    drop(env_logger::try_init());
+    let dir = tempdir::TempDir::new("romio").unwrap();
+    let path = dir.path().join("winters_tale.txt");
+    let contents: Vec<u8> = THE_WINTERS_TALE
+        .iter()
+        .cycle()
+        .take(4 << 20)
+        .cloned()
+        .collect();
+    std::fs::write(&path, &contents).unwrap();
+    let file = std::fs::File::open(&path).unwrap();
+
+    let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    // client thread
+    let client = thread::spawn(move || {
+        let mut client = TcpStream::connect(&addr).unwrap();
+        let mut buf = Vec::new();
+        client.read_to_end(&mut buf).unwrap();
+        buf
+    });
+
+    executor::block_on(async {
+        let (mut stream, _) = server.incoming().next().await.unwrap().unwrap();
+        // the file ends before `count` bytes are sent
+        let n = stream.sendfile(&file, 3, contents.len()).await.unwrap();
+        assert_eq!(n, contents.len() - 3);
+        assert_eq!(stream.sendfile(&file, 0, 0).await.unwrap(), 0);
+    });
+    assert!(client.join().unwrap() == &contents[3..]);
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "macos"))]
+fn stream_sendfile_reports_progress_before_error() {
+    drop(env_logger::try_init());
+    let dir = tempdir::TempDir::new("romio").unwrap();
+    let path = dir.path().join("winters_tale.txt");
+    let contents: Vec<u8> = THE_WINTERS_TALE
+        .iter()
+        .cycle()
+        .take(64 << 20)
+        .cloned()
+        .collect();
+    std::fs::write(&path, &contents).unwrap();
+    let file = std::fs::File::open(&path).unwrap();
+
+    let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    // client thread, which resets the connection with data left unread
+    let client = thread::spawn(move || {
+        let mut client = std::net::TcpStream::connect(&addr).unwrap();
+        let mut buf = [0; 1];
+        std::io::Read::read_exact(&mut client, &mut buf).unwrap();
+    });
+
+    executor::block_on(async {
+        let (mut stream, _) = server.incoming().next().await.unwrap().unwrap();
+        let n = stream.sendfile(&file, 0, contents.len()).await.unwrap();
+        assert!(n > 0 && n < contents.len());
+        let res = stream.sendfile(&file, n as u64, contents.len()).await;
+        assert!(res.is_err());
+    });
+    client.join().unwrap();
+}
+
+#[cfg(feature = "bytes")]
+mod framed {
+    use bytes::{Bytes, BytesMut};
+    use futures::executor;
+    use futures::prelude::*;
+    use romio::codec::{Decoder, Framed, LengthDelimitedCodec, LineCodec};
+    use romio::TcpListener;
+    use std::io::{self, Write};
+    use std::net::{Shutdown, TcpStream};
+    use std::thread;
+
+    #[test]
+    fn frames_lines() {
+        drop(env_logger::try_init());
+        let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        let addr = server.local_addr().unwrap();
+
+        // client thread
+        let client = thread::spawn(move || {
+            let mut client = TcpStream::connect(&addr).unwrap();
+            client.write_all(b"Two households,\r\nboth alike").unwrap();
+            client.write_all(b" in dignity,\nIn fair Verona").unwrap();
+            client.shutdown(Shutdown::Write).unwrap();
+            let mut buf = String::new();
+            io::Read::read_to_string(&mut client, &mut buf).unwrap();
+            buf
+        });
+
+        let lines = executor::block_on(async {
+            let (stream, _) = server.incoming().next().await.unwrap().unwrap();
+            let mut framed = Framed::new(stream, LineCodec::new());
+
+            let mut lines = Vec::new();
+            while let Some(line) = framed.next().await {
+                lines.push(line.unwrap());
+            }
+            framed
+                .send("where we lay our scene".to_string())
+                .await
+                .unwrap();
+            framed.close().await.unwrap();
+            lines
+        });
+        assert_eq!(
+            lines,
+            [
+                "Two households,",
+                "both alike in dignity,",
+                "In fair Verona"
+            ]
+        );
+        assert_eq!(client.join().unwrap(), "where we lay our scene\n");
+    }
+
+    #[test]
+    fn frames_length_delimited() {
+        drop(env_logger::try_init());
+        let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        let addr = server.local_addr().unwrap();
+
+        // client thread, splitting the length field across writes
+        thread::spawn(move || {
+            let mut client = TcpStream::connect(&addr).unwrap();
+            client.set_nodelay(true).unwrap();
+            client.write_all(b"\0\0").unwrap();
+            thread::sleep(std::time::Duration::from_millis(10));
+            client.write_all(b"\0\x05hello\0\0\0\0").unwrap();
+        });
+
+        executor::block_on(async {
+            let (stream, _) = server.incoming().next().await.unwrap().unwrap();
+            let mut framed = Framed::new(stream, LengthDelimitedCodec::new());
+            assert_eq!(&framed.next().await.unwrap().unwrap()[..], b"hello");
+            assert_eq!(&framed.next().await.unwrap().unwrap()[..], b"");
+            assert!(framed.next().await.is_none());
+        });
+    }
+
+    #[test]
+    fn rejects_oversized_frames() {
+        let mut codec = LineCodec::with_max_length(4);
+        let mut buf = BytesMut::from(&b"Tybalt\n"[..]);
+        let err = codec.decode(&mut buf).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let mut buf = BytesMut::from(&b"Nurse\r\nPeter"[..]);
+        assert!(codec.decode(&mut buf).is_err());
+        assert!(codec.decode(&mut buf).is_err());
+        buf.extend_from_slice(b"son\nAbe\n");
+        assert_eq!(codec.decode(&mut buf).unwrap(), Some("Abe".to_string()));
+
+        let mut codec = LengthDelimitedCodec::new();
+        codec.set_max_frame_length(4);
+        let mut buf = BytesMut::from(&b"\0\0\0\x05"[..]);
+        let err = codec.decode(&mut buf).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let err = romio::codec::Encoder::encode(&mut codec, Bytes::from("Paris"), &mut buf);
+        assert_eq!(err.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn length_delimited_builder() {
+        let mut codec = LengthDelimitedCodec::builder()
+            .length_field_offset(2)
+            .length_field_length(8)
+            .little_endian()
+            .max_frame_length(16)
+            .new_codec();
+
+        // The header arrives a byte at a time.
+        let mut buf = BytesMut::new();
+        for &b in b"\xca\xfe\x08\0\0\0\0\0\0" {
+            buf.extend_from_slice(&[b]);
+            assert_eq!(codec.decode(&mut buf).unwrap(), None);
+        }
+        buf.extend_from_slice(b"\0Benvolio\0\0");
+        assert_eq!(&codec.decode(&mut buf).unwrap().unwrap()[..], b"Benvolio");
+        assert_eq!(&buf[..], b"\0\0");
+
+        let mut buf = BytesMut::new();
+        romio::codec::Encoder::encode(&mut codec, Bytes::from("Balthasar"), &mut buf).unwrap();
+        assert_eq!(&buf[..], &b"\0\0\x09\0\0\0\0\0\0\0Balthasar"[..]);
+
+        let mut codec = LengthDelimitedCodec::builder()
+            .length_field_length(1)
+            .new_codec();
+        let err = romio::codec::Encoder::encode(&mut codec, Bytes::from(vec![0; 256]), &mut buf);
+        assert_eq!(err.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    }
+}
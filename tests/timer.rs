@@ -0,0 +1,74 @@
+#![feature(async_await)]
+use std::io;
+use std::time::{Duration, Instant};
+
+use futures::executor;
+use futures::future;
+use futures::prelude::*;
+
+use romio::timer::{Delay, Interval};
+
+#[test]
+fn delay_elapses() -> io::Result<()> {
+    drop(env_logger::try_init());
+    let when = Instant::now() + Duration::from_millis(20);
+    executor::block_on(Delay::new(when)?)?;
+    assert!(Instant::now() >= when);
+
+    // a deadline in the past completes right away
+    executor::block_on(Delay::new(Instant::now() - Duration::from_secs(1))?)
+}
+
+#[test]
+fn delays_elapse_after_others_are_dropped() -> io::Result<()> {
+    drop(env_logger::try_init());
+    let far = Instant::now() + Duration::from_secs(3600);
+    for _ in 0..1000 {
+        drop(Delay::new(far)?);
+    }
+
+    let when = Instant::now() + Duration::from_millis(10);
+    let delays = (0..10)
+        .rev()
+        .map(|i| Delay::new(when + Duration::from_millis(i)))
+        .collect::<io::Result<Vec<_>>>()?;
+    executor::block_on(future::try_join_all(delays))?;
+    assert!(Instant::now() >= when + Duration::from_millis(9));
+    Ok(())
+}
+
+#[test]
+fn interval_ticks() -> io::Result<()> {
+    drop(env_logger::try_init());
+    let period = Duration::from_millis(10);
+    let start = Instant::now();
+    let mut interval = Interval::new(period)?;
+
+    executor::block_on(async {
+        for _ in 0..3 {
+            interval.next().await.unwrap()?;
+        }
+        Ok::<_, io::Error>(())
+    })?;
+    assert!(start.elapsed() >= period * 3);
+
+    let err = Interval::new(Duration::from_secs(0)).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    Ok(())
+}
+
+#[test]
+fn interval_merges_missed_ticks() -> io::Result<()> {
+    use futures::task::{noop_waker_ref, Context};
+
+    drop(env_logger::try_init());
+    let period = Duration::from_millis(100);
+    let mut interval = Interval::new(period)?;
+
+    // miss three ticks, which are yielded as a single one
+    std::thread::sleep(period * 3 + period / 2);
+    executor::block_on(interval.next()).unwrap()?;
+    let mut cx = Context::from_waker(noop_waker_ref());
+    assert!(interval.poll_tick(&mut cx).is_pending());
+    Ok(())
+}
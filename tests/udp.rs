@@ -1,6 +1,6 @@
 #![feature(async_await)]
 use std::convert::TryFrom;
-use std::net::SocketAddr;
+use std::net::{Ipv4Addr, SocketAddr};
 use futures::executor;
 use romio::UdpSocket;
 
@@ -35,4 +35,922 @@ fn socket_from_std() {
     let std_socket = std::net::UdpSocket::bind(&addr).unwrap();
     let socket = UdpSocket::try_from(std_socket).unwrap();
     executor::block_on(exchange(socket));
-}
\ No newline at end of file
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn socket_receives_with_source() {
+    drop(env_logger::try_init());
+    executor::block_on(async {
+        let mut server = UdpSocket::bind(&"0.0.0.0:0".parse().unwrap()).unwrap();
+        server.set_recv_pktinfo(true).unwrap();
+        let port = server.local_addr().unwrap().port();
+        let server_addr: SocketAddr = format!("127.0.0.1:{}", port).parse().unwrap();
+
+        let mut client = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        client.send_to(THE_WINTERS_TALE, &server_addr).await.unwrap();
+
+        let mut buf = vec![0; THE_WINTERS_TALE.len()];
+        let (n, peer, local) = server.recv_from_with_source(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], THE_WINTERS_TALE);
+        assert_eq!(peer, client.local_addr().unwrap());
+        assert_eq!(local, server_addr.ip());
+
+        server.send_from_source(&buf[..n], &peer, &local).await.unwrap();
+        let (n, sender) = client.recv_from(&mut buf).await.unwrap();
+        assert_eq!(sender, server_addr);
+        assert_eq!(&buf[..n], THE_WINTERS_TALE);
+    });
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn socket_receives_with_timestamp() {
+    drop(env_logger::try_init());
+    executor::block_on(async {
+        let mut socket = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        let addr = socket.local_addr().unwrap();
+        let mut buf = vec![0; THE_WINTERS_TALE.len()];
+
+        socket.send_to(THE_WINTERS_TALE, &addr).await.unwrap();
+        let (_, _, time) = socket.recv_from_timestampns(&mut buf).await.unwrap();
+        assert!(time.is_none());
+
+        socket.set_timestampns(true).unwrap();
+        let before = std::time::SystemTime::now();
+        socket.send_to(THE_WINTERS_TALE, &addr).await.unwrap();
+        let (n, sender, time) = socket.recv_from_timestampns(&mut buf).await.unwrap();
+        assert_eq!(sender, addr);
+        assert_eq!(&buf[..n], THE_WINTERS_TALE);
+        assert!(time.unwrap() >= before);
+    });
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn socket_with_bpf_filter() {
+    drop(env_logger::try_init());
+    let socket = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+
+    let accept_all = [libc::sock_filter {
+        code: (libc::BPF_RET | libc::BPF_K) as u16,
+        jt: 0,
+        jf: 0,
+        k: 0xffff_ffff,
+    }];
+    assert!(socket.attach_bpf(&[]).is_err());
+    assert!(socket.attach_ebpf(-1).is_err());
+    socket.attach_bpf(&accept_all).unwrap();
+    socket.detach_bpf().unwrap();
+    socket.attach_bpf(&accept_all).unwrap();
+    executor::block_on(exchange(socket));
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn socket_receives_with_orig_dst() {
+    drop(env_logger::try_init());
+    executor::block_on(async {
+        let mut socket = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        socket.set_recv_orig_dst_addr(true).unwrap();
+        let addr = socket.local_addr().unwrap();
+        let mut buf = vec![0; THE_WINTERS_TALE.len()];
+
+        socket.send_to(THE_WINTERS_TALE, &addr).await.unwrap();
+        let (n, sender, orig_dst) = socket.recv_from_with_orig_dst(&mut buf).await.unwrap();
+        assert_eq!(sender, addr);
+        assert_eq!(orig_dst, addr);
+        assert_eq!(&buf[..n], THE_WINTERS_TALE);
+    });
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn socket_receives_with_ttl() {
+    drop(env_logger::try_init());
+    executor::block_on(async {
+        let mut socket = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        socket.set_recv_ttl(true).unwrap();
+        socket.set_ttl(255).unwrap();
+        let addr = socket.local_addr().unwrap();
+        let mut buf = vec![0; THE_WINTERS_TALE.len()];
+
+        socket.send_to(THE_WINTERS_TALE, &addr).await.unwrap();
+        let (n, sender, ttl) = socket.recv_from_with_ttl(&mut buf).await.unwrap();
+        assert_eq!(sender, addr);
+        assert_eq!(ttl, 255);
+        assert_eq!(&buf[..n], THE_WINTERS_TALE);
+    });
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn socket_sends_with_flow_label() {
+    drop(env_logger::try_init());
+    let socket = UdpSocket::bind(&"[::1]:0".parse().unwrap()).unwrap();
+    assert!(socket.set_flow_label_v6(0x10_0000).is_err());
+    socket.set_flow_label_v6(0x12345).unwrap();
+    assert_eq!(socket.flow_label_v6(), 0x12345);
+    assert!(socket.set_flow_label_v6(0x10_0000).is_err());
+    assert_eq!(socket.flow_label_v6(), 0x12345);
+
+    let mut client = UdpSocket::bind(&"[::1]:0".parse().unwrap()).unwrap();
+    let addr = client.local_addr().unwrap();
+    let source = socket.local_addr().unwrap();
+    executor::block_on(async {
+        let mut socket = socket;
+        let mut buf = vec![0; THE_WINTERS_TALE.len()];
+        socket
+            .send_from_source(THE_WINTERS_TALE, &addr, &source.ip())
+            .await
+            .unwrap();
+        let (n, sender) = client.recv_from(&mut buf).await.unwrap();
+        assert_eq!(sender, source);
+        assert_eq!(&buf[..n], THE_WINTERS_TALE);
+
+        socket.set_flow_label_v6(0).unwrap();
+        assert_eq!(socket.flow_label_v6(), 0);
+        exchange(socket).await;
+    });
+}
+
+#[test]
+fn socket_leaves_all_multicast_groups() {
+    drop(env_logger::try_init());
+    let socket = UdpSocket::bind(&"0.0.0.0:0".parse().unwrap()).unwrap();
+    let interface = Ipv4Addr::LOCALHOST;
+    let groups = [Ipv4Addr::new(224, 0, 0, 123), Ipv4Addr::new(224, 0, 0, 124)];
+    for group in &groups {
+        socket.join_multicast_v4(group, &interface).unwrap();
+    }
+
+    socket.leave_all_multicast_v4().unwrap();
+
+    // Leaving a group that is no longer joined fails.
+    for group in &groups {
+        assert!(socket.leave_multicast_v4(group, &interface).is_err());
+    }
+    socket.leave_all_multicast_v4().unwrap();
+}
+
+#[test]
+#[cfg(unix)]
+fn join_multicast_group_receives() {
+    drop(env_logger::try_init());
+    let group = Ipv4Addr::new(239, 255, 42, 99);
+    let port = std::net::UdpSocket::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut receiver = romio::net::join_multicast_group(group, port, Ipv4Addr::LOCALHOST).unwrap();
+
+    let mut sender = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    sender.set_multicast_if_v4(&Ipv4Addr::LOCALHOST).unwrap();
+    let sender_addr = sender.local_addr().unwrap();
+
+    executor::block_on(async {
+        let target = SocketAddr::new(group.into(), port);
+        sender.send_to(THE_WINTERS_TALE, &target).await.unwrap();
+
+        let mut buf = vec![0; THE_WINTERS_TALE.len()];
+        let (n, from) = receiver.recv_from(&mut buf).await.unwrap();
+        assert_eq!(from, sender_addr);
+        assert_eq!(&buf[..n], THE_WINTERS_TALE);
+    });
+
+    // The group is tracked, so it is left along with all others.
+    receiver.leave_all_multicast_v4().unwrap();
+    assert!(receiver.leave_multicast_v4(&group, &Ipv4Addr::LOCALHOST).is_err());
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn socket_sends_zerocopy() {
+    use futures::future::poll_fn;
+    use std::pin::Pin;
+
+    drop(env_logger::try_init());
+    executor::block_on(async {
+        let mut socket = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        socket.set_zerocopy(true).unwrap();
+        let addr = socket.local_addr().unwrap();
+        let mut buf = vec![0; THE_WINTERS_TALE.len()];
+
+        for _ in 0..2 {
+            let n = poll_fn(|cx| {
+                Pin::new(&mut socket).poll_send_to_zerocopy(cx, THE_WINTERS_TALE, &addr)
+            })
+            .await
+            .unwrap();
+            assert_eq!(n, THE_WINTERS_TALE.len());
+            let (n, _) = socket.recv_from(&mut buf).await.unwrap();
+            assert_eq!(&buf[..n], THE_WINTERS_TALE);
+        }
+
+        let mut completed = 0;
+        while completed < 2 {
+            for range in socket.recv_zerocopy_completions().unwrap() {
+                assert_eq!(range.first, completed);
+                completed = range.last + 1;
+            }
+        }
+    });
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn zerocopy_send_waits_for_completion() {
+    drop(env_logger::try_init());
+    executor::block_on(async {
+        let mut socket = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        let addr = socket.local_addr().unwrap();
+
+        assert!(!socket.zerocopy().unwrap());
+        let err = socket
+            .send_to_zerocopy(THE_WINTERS_TALE, &addr)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+        socket.set_zerocopy(true).unwrap();
+        assert!(socket.zerocopy().unwrap());
+        let buf = THE_WINTERS_TALE.to_vec();
+        for _ in 0..3 {
+            socket.send_to_zerocopy(&buf, &addr).await.unwrap();
+        }
+
+        // every send has completed by the time its future resolves, and
+        // the futures consumed the completions
+        assert!(socket.recv_zerocopy_completions().unwrap().is_empty());
+    });
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn zerocopy_send_keeps_completions_of_polled_sends() {
+    use futures::future::poll_fn;
+    use std::pin::Pin;
+
+    drop(env_logger::try_init());
+    executor::block_on(async {
+        let mut socket = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        socket.set_zerocopy(true).unwrap();
+        let addr = socket.local_addr().unwrap();
+
+        poll_fn(|cx| Pin::new(&mut socket).poll_send_to_zerocopy(cx, THE_WINTERS_TALE, &addr))
+            .await
+            .unwrap();
+        socket.send_to_zerocopy(THE_WINTERS_TALE, &addr).await.unwrap();
+
+        // the completion of the polled send is returned even if the future
+        // read it, but not the one of the future's own send
+        let mut ranges = Vec::new();
+        while ranges.is_empty() {
+            ranges = socket.recv_zerocopy_completions().unwrap();
+        }
+        assert_eq!(ranges.len(), 1);
+        assert_eq!((ranges[0].first, ranges[0].last), (0, 0));
+        assert!(socket.recv_zerocopy_completions().unwrap().is_empty());
+    });
+}
+
+#[test]
+#[cfg(unix)]
+fn connected_sockets_send_and_recv() {
+    drop(env_logger::try_init());
+    executor::block_on(async {
+        let mut a = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        let mut b = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        let mut stranger = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        let a_addr = a.local_addr().unwrap();
+        let b_addr = b.local_addr().unwrap();
+
+        let err = a.peer_addr().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotConnected);
+
+        a.connect(&b_addr).unwrap();
+        b.connect(&a_addr).unwrap();
+        assert_eq!(a.peer_addr().unwrap(), b_addr);
+
+        // datagrams from other addresses are filtered out
+        stranger.send_to(b"ignored", &a_addr).await.unwrap();
+
+        b.send(THE_WINTERS_TALE).await.unwrap();
+        let mut buf = vec![0; THE_WINTERS_TALE.len()];
+        let n = a.recv(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], THE_WINTERS_TALE);
+    });
+}
+
+#[test]
+#[cfg(unix)]
+fn socket_peeks_without_consuming() {
+    drop(env_logger::try_init());
+    executor::block_on(async {
+        let mut socket = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        let addr = socket.local_addr().unwrap();
+        socket.send_to(THE_WINTERS_TALE, &addr).await.unwrap();
+
+        let mut head = [0; 8];
+        for _ in 0..2 {
+            let (n, sender) = socket.peek_from(&mut head).await.unwrap();
+            assert_eq!(sender, addr);
+            assert_eq!(&head[..n], &THE_WINTERS_TALE[..8]);
+        }
+
+        let mut buf = vec![0; THE_WINTERS_TALE.len()];
+        let (n, _) = socket.recv_from(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], THE_WINTERS_TALE);
+    });
+}
+
+#[test]
+#[cfg(unix)]
+fn sockets_share_address_with_reuseport() {
+    drop(env_logger::try_init());
+    let first = UdpSocket::bind_reuseport(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = first.local_addr().unwrap();
+    let second = UdpSocket::bind_reuseport(&addr).unwrap();
+    assert_eq!(second.local_addr().unwrap(), addr);
+    assert!(first.reuseport().unwrap());
+
+    // sockets bound without the option cannot join in
+    assert!(UdpSocket::bind(&addr).is_err());
+
+    second.set_reuseport(false).unwrap();
+    assert!(!second.reuseport().unwrap());
+}
+
+#[test]
+fn builder_sets_options_before_binding() {
+    drop(env_logger::try_init());
+    let mut builder = romio::udp::UdpSocketBuilder::new();
+    builder.ttl(42);
+    #[cfg(unix)]
+    builder.reuseport(true).send_buffer_size(64 * 1024);
+
+    let socket = builder.bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    assert_eq!(socket.ttl().unwrap(), 42);
+    #[cfg(unix)]
+    {
+        let addr = socket.local_addr().unwrap();
+        assert!(socket.reuseport().unwrap());
+        assert!(socket.send_buffer_size().unwrap() >= 64 * 1024);
+        let second = builder.bind(&addr).unwrap();
+        assert_eq!(second.local_addr().unwrap(), addr);
+    }
+    executor::block_on(exchange(socket));
+
+    // an IPv6-only socket can't send to IPv4-mapped addresses
+    let mut socket = match romio::udp::UdpSocketBuilder::new()
+        .only_v6(true)
+        .bind(&"[::]:0".parse().unwrap())
+    {
+        Ok(socket) => socket,
+        // no IPv6 support
+        Err(_) => return,
+    };
+    let target = "[::ffff:127.0.0.1]:9".parse().unwrap();
+    assert!(executor::block_on(socket.send_to(THE_WINTERS_TALE, &target)).is_err());
+}
+
+#[test]
+fn split_socket_sends_and_receives() {
+    drop(env_logger::try_init());
+    let socket = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = socket.local_addr().unwrap();
+    let (mut send, mut recv) = socket.split();
+
+    let sender = std::thread::spawn(move || {
+        executor::block_on(send.send_to(THE_WINTERS_TALE, &addr)).unwrap();
+        send
+    });
+
+    let mut buf = vec![0; THE_WINTERS_TALE.len()];
+    let (n, peer) = executor::block_on(recv.recv_from(&mut buf)).unwrap();
+    assert_eq!(peer, addr);
+    assert_eq!(&buf[..n], THE_WINTERS_TALE);
+
+    let send = sender.join().unwrap();
+    let socket = send.reunite(recv).unwrap();
+    assert_eq!(socket.local_addr().unwrap(), addr);
+}
+
+#[test]
+fn split_halves_are_async_datagrams() {
+    use async_datagram::AsyncDatagram;
+    use futures::future::poll_fn;
+    use std::pin::Pin;
+
+    drop(env_logger::try_init());
+    let socket = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = socket.local_addr().unwrap();
+    let (mut send, mut recv) = socket.split();
+
+    executor::block_on(async {
+        let mut buf = vec![0; THE_WINTERS_TALE.len()];
+        poll_fn(|cx| {
+            AsyncDatagram::poll_send_to(Pin::new(&mut send), cx, THE_WINTERS_TALE, &addr)
+        })
+        .await
+        .unwrap();
+        let (n, peer) = poll_fn(|cx| {
+            AsyncDatagram::poll_recv_from(Pin::new(&mut recv), cx, &mut buf)
+        })
+        .await
+        .unwrap();
+        assert_eq!(peer, addr);
+        assert_eq!(&buf[..n], THE_WINTERS_TALE);
+
+        // each half only works in its own direction
+        let res = poll_fn(|cx| {
+            AsyncDatagram::poll_recv_from(Pin::new(&mut send), cx, &mut buf)
+        })
+        .await;
+        assert!(res.is_err());
+        let res = poll_fn(|cx| {
+            AsyncDatagram::poll_send_to(Pin::new(&mut recv), cx, THE_WINTERS_TALE, &addr)
+        })
+        .await;
+        assert!(res.is_err());
+    });
+}
+
+#[test]
+fn split_halves_of_different_sockets_do_not_reunite() {
+    drop(env_logger::try_init());
+    let a = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let b = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let (a_send, _a_recv) = a.split();
+    let (_b_send, b_recv) = b.split();
+
+    assert!(a_send.reunite(b_recv).is_err());
+}
+
+#[test]
+fn socket_try_sends_and_receives() {
+    drop(env_logger::try_init());
+    let mut socket = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = socket.local_addr().unwrap();
+    let mut buf = vec![0; THE_WINTERS_TALE.len()];
+
+    let err = socket.try_recv_from(&mut buf).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::WouldBlock);
+
+    let n = socket.try_send_to(THE_WINTERS_TALE, &addr).unwrap();
+    assert_eq!(n, THE_WINTERS_TALE.len());
+    let (n, sender) = socket.try_recv_from(&mut buf).unwrap();
+    assert_eq!(sender, addr);
+    assert_eq!(&buf[..n], THE_WINTERS_TALE);
+}
+
+#[test]
+fn socket_drains_after_readable() {
+    drop(env_logger::try_init());
+    executor::block_on(async {
+        let mut socket = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        let addr = socket.local_addr().unwrap();
+
+        socket.writable().await.unwrap();
+        for _ in 0..3 {
+            socket.try_send_to(THE_WINTERS_TALE, &addr).unwrap();
+        }
+
+        socket.readable().await.unwrap();
+        let mut buf = vec![0; THE_WINTERS_TALE.len()];
+        let mut received = 0;
+        loop {
+            match socket.try_recv_from(&mut buf) {
+                Ok((n, _)) => {
+                    assert_eq!(&buf[..n], THE_WINTERS_TALE);
+                    received += 1;
+                }
+                Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+                Err(e) => panic!("{}", e),
+            }
+        }
+        assert_eq!(received, 3);
+    });
+}
+
+#[test]
+fn socket_receives_one_at_a_time_after_readable() {
+    drop(env_logger::try_init());
+    executor::block_on(async {
+        let mut socket = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        let addr = socket.local_addr().unwrap();
+        for _ in 0..3 {
+            socket.send_to(THE_WINTERS_TALE, &addr).await.unwrap();
+        }
+
+        // Readiness is kept until `try_recv_from` would block, so queued
+        // datagrams are not missed.
+        let mut buf = vec![0; THE_WINTERS_TALE.len()];
+        for _ in 0..3 {
+            socket.readable().await.unwrap();
+            let (n, _) = socket.try_recv_from(&mut buf).unwrap();
+            assert_eq!(&buf[..n], THE_WINTERS_TALE);
+        }
+        let err = socket.try_recv_from(&mut buf).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::WouldBlock);
+
+        socket.send_to(THE_WINTERS_TALE, &addr).await.unwrap();
+        socket.readable().await.unwrap();
+        let (n, _) = socket.try_recv_from(&mut buf).unwrap();
+        assert_eq!(&buf[..n], THE_WINTERS_TALE);
+    });
+}
+
+#[test]
+#[cfg(unix)]
+fn socket_sets_multicast_hops_v6() {
+    drop(env_logger::try_init());
+    let socket = UdpSocket::bind(&"[::1]:0".parse().unwrap()).unwrap();
+    assert_eq!(socket.multicast_hops_v6().unwrap(), 1);
+    socket.set_multicast_hops_v6(16).unwrap();
+    assert_eq!(socket.multicast_hops_v6().unwrap(), 16);
+}
+
+#[test]
+#[cfg(unix)]
+fn socket_sets_multicast_if() {
+    drop(env_logger::try_init());
+    let socket = UdpSocket::bind(&"0.0.0.0:0".parse().unwrap()).unwrap();
+    socket.set_multicast_if_v4(&Ipv4Addr::LOCALHOST).unwrap();
+    assert_eq!(socket.multicast_if_v4().unwrap(), Ipv4Addr::LOCALHOST);
+
+    let lo = unsafe { libc::if_nametoindex(b"lo\0".as_ptr() as *const libc::c_char) };
+    let socket = UdpSocket::bind(&"[::]:0".parse().unwrap()).unwrap();
+    assert_eq!(socket.multicast_if_v6().unwrap(), 0);
+    if lo != 0 {
+        socket.set_multicast_if_v6(lo).unwrap();
+        assert_eq!(socket.multicast_if_v6().unwrap(), lo);
+    }
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "macos", target_os = "freebsd"))]
+fn socket_joins_source_specific_multicast() {
+    drop(env_logger::try_init());
+    let socket = UdpSocket::bind(&"0.0.0.0:0".parse().unwrap()).unwrap();
+    let group = Ipv4Addr::new(232, 1, 1, 1);
+    let interface = Ipv4Addr::LOCALHOST;
+    let source = Ipv4Addr::LOCALHOST;
+
+    socket
+        .join_multicast_v4_source(&group, &interface, &source)
+        .unwrap();
+    socket
+        .leave_multicast_v4_source(&group, &interface, &source)
+        .unwrap();
+    assert!(socket
+        .leave_multicast_v4_source(&group, &interface, &source)
+        .is_err());
+}
+
+#[test]
+#[cfg(unix)]
+fn socket_sets_buffer_sizes() {
+    drop(env_logger::try_init());
+    let socket = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+
+    // Linux reports twice the requested size
+    socket.set_recv_buffer_size(16 * 1024).unwrap();
+    assert!(socket.recv_buffer_size().unwrap() >= 16 * 1024);
+    socket.set_send_buffer_size(16 * 1024).unwrap();
+    assert!(socket.send_buffer_size().unwrap() >= 16 * 1024);
+}
+
+#[test]
+#[cfg(unix)]
+fn socket_sets_traffic_class() {
+    drop(env_logger::try_init());
+    let socket = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    socket.set_tos(0xb9).unwrap();
+    assert_eq!(socket.tos().unwrap(), 0xb9);
+
+    let socket = match UdpSocket::bind(&"[::1]:0".parse().unwrap()) {
+        Ok(socket) => socket,
+        Err(_) => return,
+    };
+    socket.set_tclass_v6(0xb9).unwrap();
+    assert_eq!(socket.tclass_v6().unwrap(), 0xb9);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn connected_socket_takes_icmp_error() {
+    drop(env_logger::try_init());
+    executor::block_on(async {
+        let closed = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        let closed_addr = closed.local_addr().unwrap();
+        drop(closed);
+
+        let mut socket = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        assert!(socket.take_error().unwrap().is_none());
+
+        socket.connect(&closed_addr).unwrap();
+        socket.send(b"anyone there?").await.unwrap();
+
+        // the port unreachable notification arrives asynchronously
+        let mut err = None;
+        for _ in 0..100 {
+            err = socket.take_error().unwrap();
+            if err.is_some() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        let err = err.expect("no error was reported");
+        assert_eq!(err.kind(), std::io::ErrorKind::ConnectionRefused);
+        assert!(socket.take_error().unwrap().is_none());
+    });
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn socket_binds_to_device() {
+    drop(env_logger::try_init());
+    let socket = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    assert_eq!(socket.device().unwrap(), None);
+
+    // binding requires CAP_NET_RAW
+    match socket.bind_device(Some(b"lo")) {
+        Ok(()) => {}
+        Err(ref e) if e.kind() == std::io::ErrorKind::PermissionDenied => return,
+        Err(e) => panic!("{}", e),
+    }
+    assert_eq!(socket.device().unwrap(), Some(b"lo".to_vec()));
+
+    socket.bind_device(None).unwrap();
+    assert_eq!(socket.device().unwrap(), None);
+}
+
+#[test]
+#[cfg(feature = "bytes")]
+fn send_and_recv_bytes_buffers() {
+    use bytes::{BufMut, Bytes, BytesMut};
+    use std::io::Cursor;
+
+    drop(env_logger::try_init());
+    executor::block_on(async {
+        let mut a = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        let mut b = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        let b_addr = b.local_addr().unwrap();
+
+        let out = Cursor::new(Bytes::from_static(THE_WINTERS_TALE));
+        let n = a.send_buf(&out, &b_addr).await.unwrap();
+        assert_eq!(n, THE_WINTERS_TALE.len());
+
+        let mut buf = BytesMut::with_capacity(THE_WINTERS_TALE.len() + 4);
+        buf.put_slice(b"hdr:");
+        let (n, addr) = b.recv_buf(&mut buf).await.unwrap();
+        assert_eq!(n, THE_WINTERS_TALE.len());
+        assert_eq!(addr, a.local_addr().unwrap());
+        assert_eq!(&buf[..4], b"hdr:");
+        assert_eq!(&buf[4..], THE_WINTERS_TALE);
+
+        // a full buffer resolves without waiting for a datagram
+        let (n, _) = b.recv_buf(&mut buf).await.unwrap();
+        assert_eq!(n, 0);
+    });
+}
+
+#[cfg(feature = "bytes")]
+mod framed {
+    use bytes::{BufMut, BytesMut};
+    use futures::executor;
+    use futures::prelude::*;
+    use romio::codec::{Decoder, Encoder};
+    use romio::udp::{UdpFramed, UdpSocket};
+    use std::io;
+
+    /// Frames are runs of bytes terminated by `;`.
+    struct SemicolonCodec;
+
+    impl Decoder for SemicolonCodec {
+        type Item = Vec<u8>;
+        type Error = io::Error;
+
+        fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<Vec<u8>>> {
+            match src.iter().position(|&b| b == b';') {
+                Some(end) => {
+                    let frame = src.split_to(end + 1);
+                    Ok(Some(frame[..end].to_vec()))
+                }
+                None => Ok(None),
+            }
+        }
+    }
+
+    impl Encoder for SemicolonCodec {
+        type Item = Vec<u8>;
+        type Error = io::Error;
+
+        fn encode(&mut self, item: Vec<u8>, dst: &mut BytesMut) -> io::Result<()> {
+            dst.reserve(item.len() + 1);
+            dst.put_slice(&item);
+            dst.put_u8(b';');
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn framed_sends_and_receives_frames() {
+        drop(env_logger::try_init());
+        executor::block_on(async {
+            let a = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+            let mut b = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+            let a_addr = a.local_addr().unwrap();
+            let b_addr = b.local_addr().unwrap();
+            let mut framed = UdpFramed::new(a, SemicolonCodec);
+
+            framed.send((b"ping".to_vec(), b_addr)).await.unwrap();
+            let mut buf = [0; 64];
+            let (n, addr) = b.recv_from(&mut buf).await.unwrap();
+            assert_eq!(&buf[..n], b"ping;");
+            assert_eq!(addr, a_addr);
+
+            // every frame in a datagram is yielded, paired with the sender
+            b.send_to(b"one;two;", &a_addr).await.unwrap();
+            let (frame, addr) = framed.next().await.unwrap().unwrap();
+            assert_eq!((frame, addr), (b"one".to_vec(), b_addr));
+            let (frame, _) = framed.next().await.unwrap().unwrap();
+            assert_eq!(frame, b"two".to_vec());
+
+            // a datagram with a trailing partial frame is an error, after which
+            // the stream carries on with the next datagram
+            b.send_to(b"three;fo", &a_addr).await.unwrap();
+            b.send_to(b"five;", &a_addr).await.unwrap();
+            let (frame, _) = framed.next().await.unwrap().unwrap();
+            assert_eq!(frame, b"three".to_vec());
+            let err = framed.next().await.unwrap().unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+            let (frame, _) = framed.next().await.unwrap().unwrap();
+            assert_eq!(frame, b"five".to_vec());
+        });
+    }
+}
+
+#[test]
+fn send_and_recv_vectored() {
+    use std::io::{IoSlice, IoSliceMut};
+
+    drop(env_logger::try_init());
+    executor::block_on(async {
+        let mut a = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        let mut b = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        let b_addr = b.local_addr().unwrap();
+
+        let (head, tail) = THE_WINTERS_TALE.split_at(10);
+        let bufs = [IoSlice::new(head), IoSlice::new(tail)];
+        let n = a.send_to_vectored(&bufs, &b_addr).await.unwrap();
+        assert_eq!(n, THE_WINTERS_TALE.len());
+
+        let mut first = [0; 4];
+        let mut second = vec![0; THE_WINTERS_TALE.len()];
+        let mut bufs = [IoSliceMut::new(&mut first), IoSliceMut::new(&mut second)];
+        let (n, addr) = b.recv_from_vectored(&mut bufs).await.unwrap();
+        assert_eq!(n, THE_WINTERS_TALE.len());
+        assert_eq!(addr, a.local_addr().unwrap());
+        assert_eq!(&first, &THE_WINTERS_TALE[..4]);
+        assert_eq!(&second[..n - 4], &THE_WINTERS_TALE[4..]);
+    });
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn recv_with_software_timestamp() {
+    use romio::udp::TimestampingFlags;
+    use std::time::{Duration, SystemTime};
+
+    drop(env_logger::try_init());
+    executor::block_on(async {
+        let mut a = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        let mut b = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        let b_addr = b.local_addr().unwrap();
+        b.set_timestamping(TimestampingFlags::RX_SOFTWARE | TimestampingFlags::SOFTWARE)
+            .unwrap();
+
+        let before = SystemTime::now();
+        a.send_to(THE_WINTERS_TALE, &b_addr).await.unwrap();
+
+        let mut buf = vec![0; THE_WINTERS_TALE.len()];
+        let (n, _, timestamps) = b.recv_from_with_timestamp(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], THE_WINTERS_TALE);
+
+        let timestamps = timestamps.expect("no timestamps were reported");
+        let software = timestamps.software.expect("no software timestamp");
+        let elapsed = software.duration_since(before).unwrap_or_default();
+        assert!(elapsed < Duration::from_secs(5));
+        assert_eq!(timestamps.hardware, None);
+    });
+}
+
+#[test]
+#[cfg(unix)]
+fn socket_into_std() {
+    drop(env_logger::try_init());
+    let socket = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = socket.local_addr().unwrap();
+    socket.set_ttl(42).unwrap();
+
+    let socket = socket.into_std().unwrap();
+    assert_eq!(socket.local_addr().unwrap(), addr);
+    assert_eq!(socket.ttl().unwrap(), 42);
+
+    // the converted socket blocks again
+    socket.send_to(b"ping", &addr).unwrap();
+    let mut buf = [0; 4];
+    let (n, from) = socket.recv_from(&mut buf).unwrap();
+    assert_eq!((&buf[..n], from), (&b"ping"[..], addr));
+}
+
+#[test]
+fn send_and_recv_batches() {
+    use romio::udp::{RecvMsg, SendMsg};
+
+    drop(env_logger::try_init());
+    executor::block_on(async {
+        let mut a = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        let mut b = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        let a_addr = a.local_addr().unwrap();
+        let b_addr = b.local_addr().unwrap();
+
+        let payloads: [&[u8]; 3] = [b"one", b"two", b"three"];
+        let msgs: Vec<_> = payloads
+            .iter()
+            .map(|buf| SendMsg {
+                buf,
+                target: b_addr,
+            })
+            .collect();
+        let mut sent = 0;
+        while sent < msgs.len() {
+            sent += a.send_mmsg(&msgs[sent..]).await.unwrap();
+        }
+
+        let mut bufs = vec![[0u8; 16]; 4];
+        let mut received = Vec::new();
+        while received.len() < payloads.len() {
+            let mut msgs: Vec<_> = bufs.iter_mut().map(|buf| RecvMsg::new(buf)).collect();
+            let n = b.recv_mmsg(&mut msgs).await.unwrap();
+            assert!(n > 0);
+            for msg in &msgs[..n] {
+                assert_eq!(msg.addr, Some(a_addr));
+                received.push(msg.data().to_vec());
+            }
+        }
+        assert_eq!(received, payloads);
+    });
+}
+
+#[test]
+fn recv_from_and_send_to_time_out() {
+    use std::time::Duration;
+
+    drop(env_logger::try_init());
+
+    let any = SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0);
+    let mut a = UdpSocket::bind(&any).unwrap();
+    let mut b = UdpSocket::bind(&any).unwrap();
+    let b_addr = b.local_addr().unwrap();
+
+    executor::block_on(async {
+        let mut buf = vec![0; THE_WINTERS_TALE.len()];
+        let timeout = Duration::from_millis(10);
+        let err = b.recv_from_timeout(&mut buf, timeout).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+
+        let n = a
+            .send_to_timeout(THE_WINTERS_TALE, &b_addr, timeout)
+            .await
+            .unwrap();
+        assert_eq!(n, THE_WINTERS_TALE.len());
+
+        let (n, sender) = b.recv_from_timeout(&mut buf, timeout).await.unwrap();
+        assert_eq!(sender, a.local_addr().unwrap());
+        assert_eq!(&buf[..n], THE_WINTERS_TALE);
+    });
+}
+
+#[test]
+#[cfg(unix)]
+fn socket_cloexec() {
+    use std::os::unix::io::AsRawFd;
+
+    drop(env_logger::try_init());
+    let socket = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let cloexec = || {
+        let flags = unsafe { libc::fcntl(socket.as_raw_fd(), libc::F_GETFD) };
+        assert_ne!(flags, -1);
+        flags & libc::FD_CLOEXEC != 0
+    };
+
+    assert!(cloexec());
+    socket.clear_cloexec().unwrap();
+    assert!(!cloexec());
+    socket.set_cloexec().unwrap();
+    assert!(cloexec());
+}
@@ -29,7 +29,7 @@ fn listener_reads() -> Result<(), Error> {
     let tmp_dir = TempDir::new("listener_reads")?;
     let file_path = tmp_dir.path().join("sock");
 
-    let listener = UnixListener::bind(&file_path)?;
+    let mut listener = UnixListener::bind(&file_path)?;
     let file_path = listener.local_addr()?;
 
     // client thread
@@ -56,7 +56,7 @@ fn listener_writes() -> Result<(), Error> {
     let tmp_dir = TempDir::new("listener_writes")?;
     let file_path = tmp_dir.path().join("sock");
 
-    let listener = UnixListener::bind(&file_path)?;
+    let mut listener = UnixListener::bind(&file_path)?;
     let file_path = listener.local_addr()?;
 
     // client thread
@@ -83,7 +83,7 @@ fn both_sides_async_using_threadpool() -> Result<(), Error> {
     let tmp_dir = TempDir::new("both_sides_async")?;
     let file_path = tmp_dir.path().join("sock");
 
-    let listener = UnixListener::bind(&file_path)?;
+    let mut listener = UnixListener::bind(&file_path)?;
     let file_path = listener.local_addr()?;
 
     let mut pool = executor::ThreadPool::new().unwrap();
@@ -199,3 +199,611 @@ fn reads_bytes() {
     let expected = "The thrust of a sword will end this surrender";
     assert_eq!(buf, expected.as_bytes());
 }
+
+#[test]
+fn recv_with_ancillary() -> Result<(), Error> {
+    drop(env_logger::try_init());
+    let (mut server, mut client) = UnixStream::pair()?;
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    unsafe {
+        use std::os::unix::io::AsRawFd;
+        let on: libc::c_int = 1;
+        let ret = libc::setsockopt(
+            client.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PASSCRED,
+            &on as *const _ as *const libc::c_void,
+            std::mem::size_of_val(&on) as libc::socklen_t,
+        );
+        assert_eq!(ret, 0);
+    }
+
+    executor::block_on(async {
+        server.write_all(THE_WINTERS_TALE).await?;
+
+        let mut buf = vec![0; THE_WINTERS_TALE.len()];
+        let (n, messages) = client.recv_with_ancillary(&mut buf, 128).await?;
+        assert_eq!(&buf[..n], THE_WINTERS_TALE);
+
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        assert_eq!(
+            messages,
+            vec![romio::uds::AncillaryMessage::Credentials(server.peer_cred()?)]
+        );
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        assert!(messages.is_empty());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn recv_with_ancillary_rights() -> Result<(), Error> {
+    use std::fs::File;
+    use std::os::unix::io::{AsRawFd, FromRawFd};
+
+    drop(env_logger::try_init());
+    let (mut a, mut b) = UnixStream::pair()?;
+    let (mut reader, writer) = StdStream::pair()?;
+
+    executor::block_on(async {
+        a.send_fds(b"fd", &[writer.as_raw_fd()]).await?;
+        drop(writer);
+
+        let mut buf = [0; 16];
+        let (n, mut messages) = b.recv_with_ancillary(&mut buf, 128).await?;
+        assert_eq!(&buf[..n], b"fd");
+        let fd = match messages.pop() {
+            Some(romio::uds::AncillaryMessage::Rights(fds)) => {
+                assert_eq!(fds.len(), 1);
+                fds[0]
+            }
+            message => panic!("unexpected ancillary message {:?}", message),
+        };
+        assert!(messages.is_empty());
+
+        let mut passed = unsafe { File::from_raw_fd(fd) };
+        passed.write_all(b"through the socket")?;
+        Ok::<_, Error>(())
+    })?;
+
+    let mut received = String::new();
+    reader.read_to_string(&mut received)?;
+    assert_eq!(received, "through the socket");
+    Ok(())
+}
+
+#[test]
+fn stream_into_std() -> Result<(), Error> {
+    drop(env_logger::try_init());
+
+    let (server, mut client) = UnixStream::pair()?;
+    let mut server = server.into_std()?;
+
+    // the converted stream blocks again
+    server.write_all(THE_WINTERS_TALE)?;
+
+    executor::block_on(async {
+        let mut buf = vec![0; THE_WINTERS_TALE.len()];
+        client.read_exact(&mut buf).await.unwrap();
+        assert_eq!(buf, THE_WINTERS_TALE);
+        client.write_all(b"ack").await.unwrap();
+    });
+
+    let mut buf = [0; 3];
+    server.read_exact(&mut buf)?;
+    assert_eq!(&buf, b"ack");
+    Ok(())
+}
+
+#[test]
+fn stream_from_raw_fd() -> Result<(), Error> {
+    use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
+
+    drop(env_logger::try_init());
+
+    let (server, mut client) = StdStream::pair()?;
+    server.set_nonblocking(true)?;
+    let fd = server.into_raw_fd();
+    let mut server = unsafe { UnixStream::from_raw_fd(fd) };
+    assert_eq!(server.as_raw_fd(), fd);
+
+    client.write_all(THE_WINTERS_TALE)?;
+    drop(client);
+
+    executor::block_on(async {
+        let mut buf = Vec::new();
+        server.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(buf, THE_WINTERS_TALE);
+    });
+    Ok(())
+}
+
+#[test]
+fn listener_accepts() -> Result<(), Error> {
+    drop(env_logger::try_init());
+    let tmp_dir = TempDir::new("listener_accepts")?;
+    let file_path = tmp_dir.path().join("sock");
+
+    let std_listener = std::os::unix::net::UnixListener::bind(&file_path)?;
+    let mut listener = UnixListener::from_std(std_listener)?;
+
+    // client thread
+    let client_path = file_path.clone();
+    thread::spawn(move || {
+        let mut client = StdStream::connect(&client_path).unwrap();
+        client.write_all(THE_WINTERS_TALE).unwrap();
+    });
+
+    executor::block_on(async {
+        let (mut stream, addr) = listener.accept().await.unwrap();
+        assert!(addr.is_unnamed());
+        let mut buf = vec![0; THE_WINTERS_TALE.len()];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(buf, THE_WINTERS_TALE);
+    });
+
+    // the converted listener blocks again
+    let listener = listener.into_std()?;
+    thread::spawn(move || {
+        StdStream::connect(&file_path).unwrap();
+    });
+    listener.accept()?;
+    Ok(())
+}
+
+#[test]
+fn datagram_connects_and_tries() -> Result<(), Error> {
+    drop(env_logger::try_init());
+    let tmp_dir = TempDir::new("datagram_connects_and_tries")?;
+    let server_path = tmp_dir.path().join("server");
+    let client_path = tmp_dir.path().join("client");
+
+    let mut server = romio::uds::UnixDatagram::bind(&server_path)?;
+    let mut client = romio::uds::UnixDatagram::bind(&client_path)?;
+    client.connect(&server_path)?;
+    assert_eq!(
+        client.peer_addr()?.as_pathname(),
+        Some(server_path.as_path())
+    );
+
+    let mut buf = [0; 32];
+    let err = server.try_recv_from(&mut buf).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::WouldBlock);
+
+    assert_eq!(client.try_send_to(b"ping", &server_path)?, 4);
+    executor::block_on(async {
+        let (n, addr) = server.recv_from(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"ping");
+        assert_eq!(addr.as_pathname(), Some(client_path.as_path()));
+    });
+    Ok(())
+}
+
+#[test]
+fn stream_splits() -> Result<(), Error> {
+    drop(env_logger::try_init());
+    let (mut a, mut b) = UnixStream::pair()?;
+
+    executor::block_on(async {
+        let (mut reader, mut writer) = a.split_borrowed();
+        writer.write_all(b"ping").await.unwrap();
+        writer.close().await.unwrap();
+
+        let mut buf = Vec::new();
+        b.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(buf, b"ping");
+
+        b.write_all(b"pong").await.unwrap();
+        let mut buf = [0; 4];
+        reader.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"pong");
+    });
+    Ok(())
+}
+
+#[test]
+fn stream_into_split_reunites() -> Result<(), Error> {
+    drop(env_logger::try_init());
+    let (a, b) = UnixStream::pair()?;
+    let (a_read, a_write) = a.into_split();
+    let (b_read, b_write) = b.into_split();
+
+    let err = a_read.reunite(b_write).unwrap_err();
+    let (a_read, b_write) = (err.0, err.1);
+    let err = a_write.reunite(b_read).unwrap_err();
+    let (b_read, a_write) = (err.0, err.1);
+
+    let (mut a_read, mut b_write) = (a_read, b_write);
+    executor::block_on(async {
+        b_write.write_all(b"ping").await.unwrap();
+        let mut buf = [0; 4];
+        a_read.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"ping");
+    });
+
+    a_read.reunite(a_write)?;
+    b_write.reunite(b_read)?;
+    Ok(())
+}
+
+#[test]
+fn stream_vectored_io_and_close() -> Result<(), Error> {
+    drop(env_logger::try_init());
+    let (mut a, mut b) = UnixStream::pair()?;
+
+    executor::block_on(async {
+        let bufs = [
+            std::io::IoSlice::new(b"hello"),
+            std::io::IoSlice::new(b" "),
+            std::io::IoSlice::new(b"world"),
+        ];
+        assert_eq!(a.write_vectored(&bufs).await.unwrap(), 11);
+        a.flush().await.unwrap();
+        a.close().await.unwrap();
+
+        let (mut first, mut second) = ([0; 6], [0; 5]);
+        let mut bufs = [
+            std::io::IoSliceMut::new(&mut first),
+            std::io::IoSliceMut::new(&mut second),
+        ];
+        assert_eq!(b.read_vectored(&mut bufs).await.unwrap(), 11);
+        assert_eq!(&first, b"hello ");
+        assert_eq!(&second, b"world");
+
+        // the write half is shut down, but the other direction still works
+        let mut buf = [0; 1];
+        assert_eq!(b.read(&mut buf).await.unwrap(), 0);
+        b.write_all(b"!").await.unwrap();
+        a.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"!");
+    });
+    Ok(())
+}
+
+#[test]
+fn stream_try_reads_and_writes() -> Result<(), Error> {
+    drop(env_logger::try_init());
+    let (mut a, mut b) = UnixStream::pair()?;
+
+    let mut buf = [0; 16];
+    let err = b.try_read(&mut buf).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::WouldBlock);
+
+    executor::block_on(async {
+        a.writable().await.unwrap();
+        assert_eq!(a.try_write(b"ping").unwrap(), 4);
+        a.shutdown(std::net::Shutdown::Write).unwrap();
+
+        let mut all = Vec::new();
+        loop {
+            b.readable().await.unwrap();
+            loop {
+                match b.try_read(&mut buf) {
+                    Ok(0) => {
+                        assert_eq!(all, b"ping");
+                        return;
+                    }
+                    Ok(n) => all.extend_from_slice(&buf[..n]),
+                    Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+                    Err(e) => panic!("{}", e),
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+#[test]
+fn stream_reads_message_larger_than_buffer_after_readable() -> Result<(), Error> {
+    drop(env_logger::try_init());
+    let (mut a, mut b) = UnixStream::pair()?;
+
+    executor::block_on(async {
+        // everything arrives before the first read, so no further readiness
+        // events are delivered while the buffer is drained
+        a.write_all(THE_WINTERS_TALE).await.unwrap();
+        a.shutdown(std::net::Shutdown::Write).unwrap();
+
+        let mut all = Vec::new();
+        let mut buf = [0; 16];
+        loop {
+            b.readable().await.unwrap();
+            match b.try_read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => all.extend_from_slice(&buf[..n]),
+                Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => continue,
+                Err(e) => panic!("{}", e),
+            }
+        }
+        assert_eq!(all, THE_WINTERS_TALE);
+    });
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn binds_abstract() -> Result<(), Error> {
+    use std::os::linux::net::SocketAddrExt;
+    use std::os::unix::net::{SocketAddr, UnixDatagram as StdDatagram};
+
+    drop(env_logger::try_init());
+    let name = format!("romio-binds-abstract-{}", std::process::id());
+
+    let mut listener = UnixListener::bind_abstract(name.as_bytes())?;
+    let addr = listener.local_addr()?;
+    assert_eq!(addr.as_abstract_name(), Some(name.as_bytes()));
+    let mut client = StdStream::connect_addr(&addr)?;
+    client.write_all(b"ping")?;
+    executor::block_on(async {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buf = [0; 4];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"ping");
+    });
+
+    let dgram_name = format!("{}-dgram", name);
+    let mut server = romio::uds::UnixDatagram::bind_abstract(dgram_name.as_bytes())?;
+    let client = StdDatagram::unbound()?;
+    let target = SocketAddr::from_abstract_name(dgram_name.as_bytes())?;
+    client.send_to_addr(b"pong", &target)?;
+    executor::block_on(async {
+        let mut buf = [0; 4];
+        let (n, _) = server.recv_from(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"pong");
+    });
+
+    // the name is released when the socket is closed
+    drop(listener);
+    UnixListener::bind_abstract(name.as_bytes())?;
+    Ok(())
+}
+
+#[test]
+fn stream_passes_fds() -> Result<(), Error> {
+    use std::fs::File;
+    use std::os::unix::io::{AsRawFd, FromRawFd};
+
+    drop(env_logger::try_init());
+    let (mut a, mut b) = UnixStream::pair()?;
+    let (mut reader, writer) = StdStream::pair()?;
+
+    executor::block_on(async {
+        let fds = [writer.as_raw_fd(), writer.as_raw_fd()];
+        assert_eq!(a.send_fds(b"fds", &fds).await.unwrap(), 3);
+        drop(writer);
+
+        let mut buf = [0; 16];
+        let (n, fds) = b.recv_fds(&mut buf, 1).await.unwrap();
+        assert_eq!(&buf[..n], b"fds");
+        assert_eq!(fds.len(), 1);
+
+        let mut passed = unsafe { File::from_raw_fd(fds[0]) };
+        passed.write_all(b"through the socket").unwrap();
+    });
+
+    // every duplicate of the write end is closed, so this sees EOF
+    let mut received = String::new();
+    reader.read_to_string(&mut received)?;
+    assert_eq!(received, "through the socket");
+    Ok(())
+}
+
+#[test]
+fn stream_addrs() -> Result<(), Error> {
+    drop(env_logger::try_init());
+    let tmp_dir = TempDir::new("stream_addrs")?;
+    let path = tmp_dir.path().join("sock");
+    let mut listener = UnixListener::bind(&path)?;
+
+    executor::block_on(async {
+        let client = UnixStream::connect(&path).await.unwrap();
+        let (server, _) = listener.accept().await.unwrap();
+
+        assert_eq!(client.peer_addr().unwrap().as_pathname(), Some(path.as_path()));
+        assert!(client.local_addr().unwrap().is_unnamed());
+        assert_eq!(server.local_addr().unwrap().as_pathname(), Some(path.as_path()));
+        assert!(server.peer_addr().unwrap().is_unnamed());
+    });
+    Ok(())
+}
+
+#[test]
+fn stream_peeks() -> Result<(), Error> {
+    drop(env_logger::try_init());
+    let (mut a, mut b) = UnixStream::pair()?;
+
+    executor::block_on(async {
+        a.write_all(b"{\"id\":1}").await.unwrap();
+        a.close().await.unwrap();
+
+        let mut byte = [0; 1];
+        assert_eq!(b.peek(&mut byte).await.unwrap(), 1);
+        assert_eq!(&byte, b"{");
+        assert_eq!(b.peek(&mut byte).await.unwrap(), 1);
+
+        let mut buf = Vec::new();
+        b.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(buf, b"{\"id\":1}");
+        assert_eq!(b.peek(&mut byte).await.unwrap(), 0);
+    });
+    Ok(())
+}
+
+#[test]
+fn connect_reports_missing_path() -> Result<(), Error> {
+    fn assert_send<T: Send>(_: &T) {}
+
+    drop(env_logger::try_init());
+    let tmp_dir = TempDir::new("connect_reports_missing_path")?;
+    let connect = UnixStream::connect(tmp_dir.path().join("missing"));
+    assert_send(&connect);
+
+    let err = executor::block_on(connect).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    Ok(())
+}
+
+#[test]
+fn datagram_pair() -> Result<(), Error> {
+    use std::mem::ManuallyDrop;
+    use std::os::unix::io::{AsRawFd, FromRawFd};
+    use std::os::unix::net::UnixDatagram as StdDatagram;
+
+    drop(env_logger::try_init());
+    let (mut a, mut b) = romio::uds::UnixDatagram::pair()?;
+
+    // send through std, which borrows the descriptors without closing them
+    let std_a = ManuallyDrop::new(unsafe { StdDatagram::from_raw_fd(a.as_raw_fd()) });
+    let std_b = ManuallyDrop::new(unsafe { StdDatagram::from_raw_fd(b.as_raw_fd()) });
+    std_a.send(b"one")?;
+    std_a.send(b"two")?;
+    std_b.send(b"three")?;
+
+    executor::block_on(async {
+        let mut buf = [0; 16];
+        let (n, addr) = b.recv_from(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"one");
+        assert!(addr.is_unnamed());
+        let (n, _) = b.recv_from(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"two");
+        let (n, _) = a.recv_from(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"three");
+    });
+    Ok(())
+}
+
+#[test]
+fn datagram_sends_and_recvs_connected() -> Result<(), Error> {
+    fn assert_unpin<T: Unpin>(_: &T) {}
+
+    drop(env_logger::try_init());
+    let tmp_dir = TempDir::new("datagram_sends_and_recvs_connected")?;
+    let server_path = tmp_dir.path().join("server");
+    let client_path = tmp_dir.path().join("client");
+
+    let mut server = romio::uds::UnixDatagram::bind(&server_path)?;
+    let mut client = romio::uds::UnixDatagram::bind(&client_path)?;
+
+    executor::block_on(async {
+        // not connected yet
+        assert!(client.send(b"ping").await.is_err());
+
+        client.connect(&server_path).unwrap();
+        server.connect(&client_path).unwrap();
+
+        let send = client.send(b"ping");
+        assert_unpin(&send);
+        assert_eq!(send.await.unwrap(), 4);
+
+        let mut buf = [0; 16];
+        let n = server.recv(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"ping");
+
+        server.send(b"pong").await.unwrap();
+        let n = client.recv(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"pong");
+    });
+    Ok(())
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+fn stream_recvs_with_cred() -> Result<(), Error> {
+    drop(env_logger::try_init());
+    let (mut a, mut b) = UnixStream::pair()?;
+    assert!(!b.passcred()?);
+
+    executor::block_on(async {
+        let mut buf = [0; 16];
+        a.write_all(b"anonymous").await.unwrap();
+        let err = b.recv_with_cred(&mut buf).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+        b.set_passcred(true).unwrap();
+        assert!(b.passcred().unwrap());
+        a.write_all(b"signed").await.unwrap();
+        let (n, cred) = b.recv_with_cred(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"signed");
+        assert_eq!(cred, b.peer_cred().unwrap());
+    });
+    Ok(())
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+#[test]
+fn seqpacket_preserves_message_boundaries() -> Result<(), Error> {
+    use romio::uds::{UnixSeqPacket, UnixSeqPacketListener};
+
+    drop(env_logger::try_init());
+    let tmp_dir = TempDir::new("seqpacket")?;
+    let file_path = tmp_dir.path().join("sock");
+    let mut listener = UnixSeqPacketListener::bind(&file_path)?;
+    assert_eq!(listener.local_addr()?.as_pathname(), Some(file_path.as_path()));
+
+    executor::block_on(async {
+        let mut client = UnixSeqPacket::connect(&file_path).await.unwrap();
+        let (mut server, addr) = listener.accept().await.unwrap();
+        assert!(addr.is_unnamed());
+
+        client.write_all(b"Each your doing,").await.unwrap();
+        client.write_all(b"So singular").await.unwrap();
+        client.close().await.unwrap();
+
+        // every read returns a single message, truncated to fit the buffer
+        let mut buf = [0; 64];
+        let n = server.read(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"Each your doing,");
+        let n = server.read(&mut buf[..2]).await.unwrap();
+        assert_eq!(&buf[..n], b"So");
+        assert_eq!(server.read(&mut buf).await.unwrap(), 0);
+    });
+
+    let (mut a, mut b) = UnixSeqPacket::pair()?;
+    executor::block_on(async {
+        a.write_all(THE_WINTERS_TALE).await.unwrap();
+        let mut buf = vec![0; 1024];
+        let n = b.read(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], THE_WINTERS_TALE);
+    });
+
+    // connecting to a stream socket fails
+    let stream_path = tmp_dir.path().join("stream");
+    let _stream_listener = UnixListener::bind(&stream_path)?;
+    assert!(executor::block_on(UnixSeqPacket::connect(&stream_path)).is_err());
+    Ok(())
+}
+
+#[test]
+fn cloexec() -> Result<(), Error> {
+    use romio::uds::UnixDatagram;
+    use std::os::unix::io::{AsRawFd, RawFd};
+
+    fn cloexec(fd: RawFd) -> bool {
+        let flags = unsafe { libc::fcntl(fd, libc::F_GETFD) };
+        assert_ne!(flags, -1);
+        flags & libc::FD_CLOEXEC != 0
+    }
+
+    drop(env_logger::try_init());
+    let tmp_dir = TempDir::new("cloexec")?;
+    let listener = UnixListener::bind(tmp_dir.path().join("sock"))?;
+    listener.clear_cloexec()?;
+    assert!(!cloexec(listener.as_raw_fd()));
+    listener.set_cloexec()?;
+    assert!(cloexec(listener.as_raw_fd()));
+
+    let (stream, _) = UnixStream::pair()?;
+    stream.clear_cloexec()?;
+    assert!(!cloexec(stream.as_raw_fd()));
+    stream.set_cloexec()?;
+    assert!(cloexec(stream.as_raw_fd()));
+
+    let socket = UnixDatagram::unbound()?;
+    socket.clear_cloexec()?;
+    assert!(!cloexec(socket.as_raw_fd()));
+    socket.set_cloexec()?;
+    assert!(cloexec(socket.as_raw_fd()));
+    Ok(())
+}
@@ -0,0 +1,166 @@
+#![feature(async_await)]
+#![cfg(all(target_os = "linux", feature = "io-uring"))]
+use std::io::{self, Read, Write};
+use std::net;
+use std::pin::Pin;
+use std::task::Context;
+use std::thread;
+use std::time::Duration;
+
+use futures::executor;
+use futures::future;
+use futures::prelude::*;
+use futures::task;
+
+use romio::uring::{TcpListener, TcpStream, UdpSocket};
+
+const THE_TEMPEST: &[u8] = b"
+    We are such stuff
+    As dreams are made on, and our little life
+    Is rounded with a sleep.
+";
+
+#[test]
+fn tcp_echo() {
+    drop(env_logger::try_init());
+    let mut server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    // echo server thread
+    let server = thread::spawn(move || {
+        executor::block_on(async {
+            let (mut stream, _) = server.incoming().next().await.unwrap().unwrap();
+            let mut buf = vec![0; 7];
+            loop {
+                let n = stream.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    break;
+                }
+                stream.write_all(&buf[..n]).await.unwrap();
+            }
+            stream.close().await.unwrap();
+        })
+    });
+
+    executor::block_on(async {
+        let mut stream = TcpStream::connect(&addr).await.unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), addr);
+        stream.write_all(THE_TEMPEST).await.unwrap();
+        stream.close().await.unwrap();
+
+        let mut buf = Vec::new();
+        stream.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(buf, THE_TEMPEST);
+    });
+    server.join().unwrap();
+}
+
+#[test]
+fn pending_accept() {
+    drop(env_logger::try_init());
+    let mut listener = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let waker = task::noop_waker();
+    let mut cx = Context::from_waker(&waker);
+
+    // the accept outlives the `Incoming` that started it
+    let mut incoming = listener.incoming();
+    assert!(incoming.poll_next_unpin(&mut cx).is_pending());
+    let client = net::TcpStream::connect(addr).unwrap();
+    executor::block_on(async {
+        let (stream, peer) = future::poll_fn(|cx| Pin::new(&mut listener).poll_accept(cx))
+            .await
+            .unwrap();
+        assert_eq!(stream.local_addr().unwrap(), addr);
+        assert_eq!(peer, client.local_addr().unwrap());
+    });
+
+    // dropping the listener cancels the accept, which closes the socket
+    assert!(Pin::new(&mut listener).poll_accept(&mut cx).is_pending());
+    drop(listener);
+    thread::sleep(Duration::from_millis(50));
+    let err = net::TcpStream::connect(addr).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::ConnectionRefused);
+}
+
+#[test]
+fn udp_send_to_and_recv_from() {
+    drop(env_logger::try_init());
+    let mut a = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let mut b = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let a_addr = a.local_addr().unwrap();
+    let b_addr = b.local_addr().unwrap();
+
+    executor::block_on(async {
+        let n = a.send_to(THE_TEMPEST, &b_addr).await.unwrap();
+        assert_eq!(n, THE_TEMPEST.len());
+
+        let mut buf = vec![0; 1024];
+        let (n, peer) = b.recv_from(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], THE_TEMPEST);
+        assert_eq!(peer, a_addr);
+
+        // datagrams which don't fit into the buffer are truncated
+        b.connect(&a_addr).unwrap();
+        b.send(b"Ariel").await.unwrap();
+        let mut buf = [0; 3];
+        assert_eq!(a.recv(&mut buf).await.unwrap(), 3);
+        assert_eq!(&buf, b"Ari");
+    });
+}
+
+#[test]
+fn interoperates_with_std() {
+    drop(env_logger::try_init());
+    let std_listener = net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = std_listener.local_addr().unwrap();
+    let mut listener = TcpListener::from_std(std_listener).unwrap();
+
+    let client = thread::spawn(move || {
+        let mut client = net::TcpStream::connect(addr).unwrap();
+        client.write_all(b"Miranda").unwrap();
+        let mut buf = String::new();
+        client.read_to_string(&mut buf).unwrap();
+        buf
+    });
+
+    executor::block_on(async {
+        let (mut stream, peer) = listener.incoming().next().await.unwrap().unwrap();
+        assert_eq!(peer, stream.peer_addr().unwrap());
+        let mut buf = [0; 7];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"Miranda");
+        stream.write_all(b"Ferdinand").await.unwrap();
+    });
+    assert_eq!(client.join().unwrap(), "Ferdinand");
+}
+
+#[test]
+fn accept_and_socket_options() {
+    drop(env_logger::try_init());
+    let mut listener = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = listener.local_addr().unwrap();
+    listener.set_ttl(100).unwrap();
+    assert_eq!(listener.ttl().unwrap(), 100);
+
+    let client = net::TcpStream::connect(addr).unwrap();
+    executor::block_on(async {
+        let (stream, peer) = listener.accept().await.unwrap();
+        assert_eq!(peer, client.local_addr().unwrap());
+
+        stream.set_ttl(42).unwrap();
+        assert_eq!(stream.ttl().unwrap(), 42);
+        stream.set_keepalive(Some(Duration::from_secs(60))).unwrap();
+        assert_eq!(stream.keepalive().unwrap(), Some(Duration::from_secs(60)));
+        stream.set_linger(Some(Duration::from_secs(1))).unwrap();
+        assert_eq!(stream.linger().unwrap(), Some(Duration::from_secs(1)));
+        stream.set_recv_buffer_size(16 * 1024).unwrap();
+        assert!(stream.recv_buffer_size().unwrap() >= 16 * 1024);
+        assert!(stream.take_error().unwrap().is_none());
+    });
+
+    let socket = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    socket.set_broadcast(true).unwrap();
+    assert!(socket.broadcast().unwrap());
+}